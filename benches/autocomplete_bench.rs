@@ -0,0 +1,59 @@
+use {
+    criterion::{BenchmarkId, Criterion, criterion_group, criterion_main},
+    e62rs::data::tags::TagDb,
+    std::{hint::black_box, io::Write, path::Path},
+    tempfile::TempDir,
+};
+
+const TAG_COUNT: u64 = 300_000;
+
+/// write a tags csv of made-up names, and empty alias and implication csvs
+fn write_tag_db(dir: &Path) -> TagDb {
+    let syllables = [
+        "ka", "ni", "ne", "fo", "x", "wo", "lf", "ca", "t", "dra", "gon", "_", "pa", "ws",
+    ];
+    let mut seed: u64 = 42;
+    let mut next = || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        seed >> 33
+    };
+
+    let tags = dir.join("tags.csv");
+    let mut file = std::fs::File::create(&tags).unwrap();
+    writeln!(file, "id,name,category,post_count").unwrap();
+    for id in 0..TAG_COUNT {
+        let name: String = (0..4)
+            .map(|_| syllables[next() as usize % syllables.len()])
+            .collect();
+        writeln!(file, "{},{}{},0,{}", id, name, id, next() % 10_000).unwrap();
+    }
+
+    let empty = dir.join("empty.csv");
+    std::fs::write(
+        &empty,
+        "id,antecedent_name,consequent_name,created_at,status\n",
+    )
+    .unwrap();
+
+    let path = |p: &Path| p.to_string_lossy().into_owned();
+    TagDb::load_from(&path(&tags), &path(&empty), &path(&empty), 0, true, false).unwrap()
+}
+
+fn bench_autocomplete(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+    let db = write_tag_db(dir.path());
+    let mut group = c.benchmark_group("tag_autocomplete");
+
+    for query in ["k", "ka", "kani", "dragon", "zzz"] {
+        group.bench_with_input(BenchmarkId::from_parameter(query), query, |b, query| {
+            b.iter(|| black_box(db.autocomplete(black_box(query), 10)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_autocomplete);
+criterion_main!(benches);
@@ -40,7 +40,20 @@ var CONFIG = Object.freeze({
     SCROLL_THRESHOLD: 300,
     LAZY_LOAD_MARGIN: "100px",
     DEFAULT_ITEMS_PER_PAGE: 20,
+    WATCH_RECONNECT_MS: 5000,
+    POST_URL: "https://e621.net/posts/",
 });
+var TAG_CATEGORIES = [
+    ["artist", "Artists"],
+    ["copyright", "Copyrights"],
+    ["character", "Characters"],
+    ["species", "Species"],
+    ["general", "General"],
+    ["meta", "Meta"],
+    ["lore", "Lore"],
+    ["contributor", "Contributors"],
+    ["invalid", "Invalid"],
+];
 var DOM = {
     gallery: document.getElementById("gallery"),
     modal: document.getElementById("modal"),
@@ -61,6 +74,7 @@ var DOM = {
     items_per_page: document.getElementById("itemsPerPage"),
     sort_by: document.getElementById("sortBy"),
     sort_order: document.getElementById("sortOrder"),
+    rating_filter: document.getElementById("ratingFilter"),
 };
 var state = {
     filtered_media: [],
@@ -97,11 +111,19 @@ function format_fsize(bytes) {
     var i = Math.floor(Math.log(bytes) / Math.log(k));
     return "".concat(Math.round((bytes / Math.pow(k, i)) * 100) / 100, " ").concat(sizes[i]);
 }
+function thumbnail_url(path) {
+    return /\.(jpe?g|png|webp|bmp)$/i.test(path)
+        ? path.replace(/^\/files\//, "/thumbnails/")
+        : path;
+}
 function escape_html(str) {
     var div = document.createElement("div");
     div.textContent = str;
     return div.innerHTML;
 }
+function escape_attr(str) {
+    return escape_html(str).replace(/"/g, "&quot;");
+}
 function show_toast(message, type, duration) {
     if (type === void 0) { type = "info"; }
     if (duration === void 0) { duration = CONFIG.TOAST_DURATION_MS; }
@@ -291,7 +313,7 @@ function create_media_card(item, global_idx, is_selected) {
     var ratingClass = meta ? "rating-".concat(meta.rating) : "";
     card.innerHTML = "\n    <div class=\"media-preview-container\">\n      ".concat(item.media_type === "video"
         ? "<video class=\"media-preview\" src=\"".concat(escape_html(item.path), "\" preload=\"metadata\" loop muted></video>")
-        : "<img class=\"media-preview loading\" data-src=\"".concat(escape_html(item.path), "\" alt=\"").concat(escape_html(item.name), "\">"), "\n      <div class=\"media-overlay\">\n        <div class=\"quick-actions\">\n          <button class=\"quick-action select-btn\" title=\"Select\" aria-label=\"Select item\">\u2611</button>\n          <button class=\"quick-action download-btn\" title=\"Download\" aria-label=\"Download item\">\u2B07</button>\n        </div>\n        <span class=\"media-type ").concat(item.media_type, "\">").concat(item.media_type, "</span>\n      </div>\n    </div>\n    <div class=\"media-info\">\n      <div class=\"media-name\">").concat(escape_html(item.name), "</div>\n      <div class=\"media-meta\">\n        <span>").concat(format_fsize(item.size), "</span>\n      </div>\n      ").concat(meta
+        : "<img class=\"media-preview loading\" data-src=\"".concat(escape_html(thumbnail_url(item.path)), "\" alt=\"").concat(escape_html(item.name), "\">"), "\n      <div class=\"media-overlay\">\n        <div class=\"quick-actions\">\n          <button class=\"quick-action select-btn\" title=\"Select\" aria-label=\"Select item\">\u2611</button>\n          <button class=\"quick-action download-btn\" title=\"Download\" aria-label=\"Download item\">\u2B07</button>\n        </div>\n        <span class=\"media-type ").concat(item.media_type, "\">").concat(item.media_type, "</span>\n      </div>\n    </div>\n    <div class=\"media-info\">\n      <div class=\"media-name\">").concat(escape_html(item.name), "</div>\n      <div class=\"media-meta\">\n        <span>").concat(format_fsize(item.size), "</span>\n      </div>\n      ").concat(meta
         ? "\n        <div class=\"media-metadata\">\n          <div class=\"metadata-row\">\n            <span class=\"metadata-label\">ID:</span>\n            <span class=\"metadata-value\">".concat(meta.id, "</span>\n          </div>\n          <div class=\"metadata-row\">\n            <span class=\"metadata-label\">Artist:</span>\n            <span class=\"metadata-value\">").concat(escape_html(meta.artists.join(", ") || "Unknown"), "</span>\n          </div>\n          <div class=\"metadata-row\">\n            <span class=\"metadata-label\">Rating:</span>\n            <span class=\"rating-badge ").concat(ratingClass, "\">").concat(meta.rating.toUpperCase(), "</span>\n          </div>\n          <div class=\"metadata-row\">\n            <span class=\"metadata-label\">Score:</span>\n            <span class=\"metadata-value\">").concat(meta.score, "</span>\n          </div>\n          ").concat(meta.artists.length > 0
             ? "\n            <div class=\"tag-list\">\n              ".concat(meta.artists
                 .slice(0, 3)
@@ -560,6 +582,7 @@ function close_modal() {
     var _a;
     DOM.modal.close();
     DOM.modal_info.hidden = true;
+    delete DOM.modal_info.dataset.post;
     (_a = document
         .getElementById("modalMetadata")) === null || _a === void 0 ? void 0 : _a.setAttribute("aria-pressed", "false");
     document.body.style.overflow = "";
@@ -587,6 +610,7 @@ function show_modal_media() {
     newMedia.id = "modalMedia";
     DOM.modal_media.replaceWith(newMedia);
     DOM.modal_media = newMedia;
+    delete DOM.modal_info.dataset.post;
     var meta = item.metadata;
     if (meta) {
         DOM.modal_info.innerHTML = "\n      <h3>".concat(escape_html(item.name), "</h3>\n      <p><strong>ID:</strong> ").concat(meta.id, " | <strong>Rating:</strong> ").concat(meta.rating.toUpperCase(), " | <strong>Score:</strong> ").concat(meta.score, " | <strong>Favorites:</strong> ").concat(meta.fav_count, "</p>\n      ").concat(meta.artists.length > 0 ? "<p><strong>Artists:</strong> ".concat(escape_html(meta.artists.join(", ")), "</p>") : "", "\n      ").concat(meta.tags.length > 0 ? "<p><strong>Tags:</strong> ".concat(escape_html(meta.tags.slice(0, 20).join(", "))).concat(meta.tags.length > 20 ? "..." : "", "</p>") : "", "\n      ").concat(meta.character_tags.length > 0 ? "<p><strong>Characters:</strong> ".concat(escape_html(meta.character_tags.join(", ")), "</p>") : "", "\n      ").concat(meta.species_tags.length > 0 ? "<p><strong>Species:</strong> ".concat(escape_html(meta.species_tags.join(", ")), "</p>") : "", "\n      <p><strong>Created:</strong> ").concat(new Date(meta.created_at).toLocaleString(), "</p>\n      <p><strong>Size:</strong> ").concat(format_fsize(item.size), "</p>\n    ");
@@ -594,6 +618,57 @@ function show_modal_media() {
     else {
         DOM.modal_info.innerHTML = "\n      <h3>".concat(escape_html(item.name), "</h3>\n      <p><strong>Size:</strong> ").concat(format_fsize(item.size), "</p>\n      <p>No metadata available</p>\n    ");
     }
+    if (!DOM.modal_info.hidden)
+        load_post_details();
+}
+function render_post_details(item, post) {
+    var tags = TAG_CATEGORIES.filter(function (_a) {
+        var _b;
+        var category = _a[0];
+        return ((_b = post.tags[category]) !== null && _b !== void 0 ? _b : []).length > 0;
+    })
+        .map(function (_a) {
+        var category = _a[0], label = _a[1];
+        var names = post.tags[category]
+            .map(function (tag) {
+            return "<span class=\"post-tag tag-".concat(category, "\">").concat(escape_html(tag), "</span>");
+        })
+            .join(" ");
+        return "<p><strong>".concat(label, ":</strong> ").concat(names, "</p>");
+    })
+        .join("");
+    // only link real urls, sources are free text on e621
+    var sources = post.sources
+        .map(function (source) {
+        return /^https?:\/\//i.test(source)
+            ? "<li><a href=\"".concat(escape_attr(source), "\" target=\"_blank\" rel=\"noopener noreferrer\">").concat(escape_html(source), "</a></li>")
+            : "<li>".concat(escape_html(source), "</li>");
+    })
+        .join("");
+    DOM.modal_info.innerHTML = "\n    <h3>".concat(escape_html(item.name), "</h3>\n    <p><strong>ID:</strong> <a href=\"").concat(CONFIG.POST_URL).concat(post.id, "\" target=\"_blank\" rel=\"noopener noreferrer\">#").concat(post.id, "</a> | <strong>Rating:</strong> <span class=\"rating-badge rating-").concat(escape_attr(post.rating), "\">").concat(escape_html(post.rating.toUpperCase()), "</span> | <strong>Score:</strong> ").concat(post.score.total, " (↑").concat(post.score.up, " ↓").concat(Math.abs(post.score.down), ") | <strong>Favorites:</strong> ").concat(post.fav_count, "</p>\n    ").concat(tags, "\n    ").concat(sources ? "<p><strong>Sources:</strong></p><ul class=\"post-sources\">".concat(sources, "</ul>") : "", "\n    <p><strong>Created:</strong> ").concat(new Date(post.created_at).toLocaleString(), "</p>\n    <p><strong>Size:</strong> ").concat(format_fsize(item.size), "</p>\n  ");
+    DOM.modal_info.dataset.post = String(post.id);
+}
+function load_post_details() {
+    var _a;
+    var item = state.filtered_media[state.current_modal_idx];
+    var id = (_a = item === null || item === void 0 ? void 0 : item.metadata) === null || _a === void 0 ? void 0 : _a.id;
+    if (id === undefined || DOM.modal_info.dataset.post === String(id))
+        return;
+    fetch("/post/".concat(id))
+        .then(function (response) {
+        if (!response.ok)
+            throw new Error("Server error: ".concat(response.status));
+        return response.json();
+    })
+        .then(function (post) {
+        // the modal may have moved on while this was loading
+        if (state.filtered_media[state.current_modal_idx] === item) {
+            render_post_details(item, post);
+        }
+    })
+        .catch(function (error) {
+        console.error("Error loading post details:", error);
+    });
 }
 function nav_modal(direction) {
     var length = state.filtered_media.length;
@@ -607,6 +682,8 @@ function toggle_modal_info() {
     DOM.modal_info.hidden = !isHidden;
     (_a = document
         .getElementById("modalMetadata")) === null || _a === void 0 ? void 0 : _a.setAttribute("aria-pressed", String(isHidden));
+    if (isHidden)
+        load_post_details();
 }
 function toggle_fullscreen() {
     return __awaiter(this, void 0, void 0, function () {
@@ -695,6 +772,11 @@ function setup_evlisteners() {
             show_toast("View changed to ".concat(state.current_view), "info", 1500);
         });
     });
+    DOM.rating_filter.addEventListener("change", function () {
+        state.advanced_filters.rating = DOM.rating_filter.value;
+        state.current_page = 1;
+        load_media();
+    });
     DOM.sort_by.addEventListener("change", function (e) {
         var target = e.target;
         state.current_sort = target.value;
@@ -872,10 +954,55 @@ function handle_key_down(e) {
             break;
     }
 }
+function watch_media() {
+    var protocol = location.protocol === "https:" ? "wss:" : "ws:";
+    var socket = new WebSocket("".concat(protocol, "//").concat(location.host, "/api/events"));
+    var opened = false;
+    socket.addEventListener("open", function () {
+        opened = true;
+    });
+    socket.addEventListener("message", function (event) {
+        var change = JSON.parse(event.data);
+        load_media();
+        var parts = [];
+        if (change.added.length > 0)
+            parts.push("".concat(change.added.length, " new"));
+        if (change.removed.length > 0) {
+            parts.push("".concat(change.removed.length, " removed"));
+        }
+        show_toast("Downloads changed: ".concat(parts.join(", ")), "info", 3000);
+    });
+    socket.addEventListener("close", function () {
+        // the route only exists when `gallery.watch` is on, so don't retry if it never opened
+        if (opened)
+            setTimeout(watch_media, CONFIG.WATCH_RECONNECT_MS);
+    });
+}
+function load_settings() {
+    return fetch("/api/config")
+        .then(function (response) {
+        if (!response.ok)
+            throw new Error("Server error: ".concat(response.status));
+        return response.json();
+    })
+        .then(function (settings) {
+        // ratings come from the metadata, so there's nothing to filter by without it
+        document.getElementById("ratingControl").hidden =
+            !settings.metadata_filtering;
+        if (settings.default_rating) {
+            DOM.rating_filter.value = settings.default_rating;
+            state.advanced_filters.rating = settings.default_rating;
+        }
+    })
+        .catch(function (error) {
+        console.error("Error loading gallery settings:", error);
+    });
+}
 function init() {
     state.items_per_page = parseInt(DOM.items_per_page.value, 10);
     setup_evlisteners();
-    load_media();
+    load_settings().then(load_media);
+    watch_media();
     show_toast("Press ? for keyboard shortcuts", "info", 4000);
 }
 if (document.readyState === "loading") {
@@ -33,6 +33,33 @@ interface AdvancedFilters {
   aspect_ratio?: string;
 }
 
+interface PostTags {
+  general: string[];
+  artist: string[];
+  contributor: string[];
+  copyright: string[];
+  character: string[];
+  species: string[];
+  invalid: string[];
+  meta: string[];
+  lore: string[];
+}
+
+interface PostDetails {
+  id: number;
+  created_at: string;
+  rating: string;
+  fav_count: number;
+  score: { up: number; down: number; total: number };
+  tags: PostTags;
+  sources: string[];
+}
+
+interface GallerySettings {
+  metadata_filtering: boolean;
+  default_rating: string | null;
+}
+
 type MediaFilter = "all" | "image" | "video";
 type ViewMode = "grid" | "list" | "compact";
 type SortOption = "random" | "name" | "size" | "date" | "score" | "fav_count";
@@ -76,6 +103,7 @@ interface DOMElements {
   items_per_page: HTMLSelectElement;
   sort_by: HTMLSelectElement;
   sort_order: HTMLButtonElement;
+  rating_filter: HTMLSelectElement;
 }
 
 interface Config {
@@ -84,6 +112,13 @@ interface Config {
   readonly SCROLL_THRESHOLD: number;
   readonly LAZY_LOAD_MARGIN: string;
   readonly DEFAULT_ITEMS_PER_PAGE: number;
+  readonly WATCH_RECONNECT_MS: number;
+}
+
+interface MediaEvent {
+  added: string[];
+  removed: string[];
+  posts: number;
 }
 
 const CONFIG: Config = Object.freeze({
@@ -92,8 +127,22 @@ const CONFIG: Config = Object.freeze({
   SCROLL_THRESHOLD: 300,
   LAZY_LOAD_MARGIN: "100px",
   DEFAULT_ITEMS_PER_PAGE: 20,
+  WATCH_RECONNECT_MS: 5000,
+  POST_URL: "https://e621.net/posts/",
 });
 
+const TAG_CATEGORIES: ReadonlyArray<[keyof PostTags, string]> = [
+  ["artist", "Artists"],
+  ["copyright", "Copyrights"],
+  ["character", "Characters"],
+  ["species", "Species"],
+  ["general", "General"],
+  ["meta", "Meta"],
+  ["lore", "Lore"],
+  ["contributor", "Contributors"],
+  ["invalid", "Invalid"],
+];
+
 const DOM: DOMElements = {
   gallery: document.getElementById("gallery") as HTMLElement,
   modal: document.getElementById("modal") as HTMLDialogElement,
@@ -116,6 +165,7 @@ const DOM: DOMElements = {
   items_per_page: document.getElementById("itemsPerPage") as HTMLSelectElement,
   sort_by: document.getElementById("sortBy") as HTMLSelectElement,
   sort_order: document.getElementById("sortOrder") as HTMLButtonElement,
+  rating_filter: document.getElementById("ratingFilter") as HTMLSelectElement,
 };
 
 const state: AppState = {
@@ -154,12 +204,22 @@ function format_fsize(bytes: number): string {
   return `${Math.round((bytes / Math.pow(k, i)) * 100) / 100} ${sizes[i]}`;
 }
 
+function thumbnail_url(path: string): string {
+  return /\.(jpe?g|png|webp|bmp)$/i.test(path)
+    ? path.replace(/^\/files\//, "/thumbnails/")
+    : path;
+}
+
 function escape_html(str: string): string {
   const div = document.createElement("div");
   div.textContent = str;
   return div.innerHTML;
 }
 
+function escape_attr(str: string): string {
+  return escape_html(str).replace(/"/g, "&quot;");
+}
+
 function show_toast(
   message: string,
   type: ToastType = "info",
@@ -391,7 +451,7 @@ function create_media_card(
       ${
         item.media_type === "video"
           ? `<video class="media-preview" src="${escape_html(item.path)}" preload="metadata" loop muted></video>`
-          : `<img class="media-preview loading" data-src="${escape_html(item.path)}" alt="${escape_html(item.name)}">`
+          : `<img class="media-preview loading" data-src="${escape_html(thumbnail_url(item.path))}" alt="${escape_html(item.name)}">`
       }
       <div class="media-overlay">
         <div class="quick-actions">
@@ -779,6 +839,7 @@ function open_modal(index: number): void {
 function close_modal(): void {
   DOM.modal.close();
   DOM.modal_info.hidden = true;
+  delete DOM.modal_info.dataset.post;
   document
     .getElementById("modalMetadata")
     ?.setAttribute("aria-pressed", "false");
@@ -812,6 +873,7 @@ function show_modal_media(): void {
   DOM.modal_media.replaceWith(newMedia);
   DOM.modal_media = newMedia;
 
+  delete DOM.modal_info.dataset.post;
   const meta = item.metadata;
   if (meta) {
     DOM.modal_info.innerHTML = `
@@ -831,6 +893,64 @@ function show_modal_media(): void {
       <p>No metadata available</p>
     `;
   }
+
+  if (!DOM.modal_info.hidden) load_post_details();
+}
+
+function render_post_details(item: MediaItem, post: PostDetails): void {
+  const tags = TAG_CATEGORIES.filter(
+    ([category]) => (post.tags[category] ?? []).length > 0,
+  )
+    .map(([category, label]) => {
+      const names = post.tags[category]
+        .map(
+          (tag) =>
+            `<span class="post-tag tag-${category}">${escape_html(tag)}</span>`,
+        )
+        .join(" ");
+      return `<p><strong>${label}:</strong> ${names}</p>`;
+    })
+    .join("");
+
+  // only link real urls, sources are free text on e621
+  const sources = post.sources
+    .map((source) =>
+      /^https?:\/\//i.test(source)
+        ? `<li><a href="${escape_attr(source)}" target="_blank" rel="noopener noreferrer">${escape_html(source)}</a></li>`
+        : `<li>${escape_html(source)}</li>`,
+    )
+    .join("");
+
+  DOM.modal_info.innerHTML = `
+    <h3>${escape_html(item.name)}</h3>
+    <p><strong>ID:</strong> <a href="${CONFIG.POST_URL}${post.id}" target="_blank" rel="noopener noreferrer">#${post.id}</a> | <strong>Rating:</strong> <span class="rating-badge rating-${escape_attr(post.rating)}">${escape_html(post.rating.toUpperCase())}</span> | <strong>Score:</strong> ${post.score.total} (↑${post.score.up} ↓${Math.abs(post.score.down)}) | <strong>Favorites:</strong> ${post.fav_count}</p>
+    ${tags}
+    ${sources ? `<p><strong>Sources:</strong></p><ul class="post-sources">${sources}</ul>` : ""}
+    <p><strong>Created:</strong> ${new Date(post.created_at).toLocaleString()}</p>
+    <p><strong>Size:</strong> ${format_fsize(item.size)}</p>
+  `;
+  DOM.modal_info.dataset.post = String(post.id);
+}
+
+function load_post_details(): void {
+  const item = state.filtered_media[state.current_modal_idx];
+  const id = item?.metadata?.id;
+  if (id === undefined || DOM.modal_info.dataset.post === String(id)) return;
+
+  fetch(`/post/${id}`)
+    .then((response) => {
+      if (!response.ok) throw new Error(`Server error: ${response.status}`);
+      return response.json() as Promise<PostDetails>;
+    })
+    .then((post) => {
+      // the modal may have moved on while this was loading
+      if (state.filtered_media[state.current_modal_idx] === item) {
+        render_post_details(item, post);
+      }
+    })
+    .catch((error) => {
+      console.error("Error loading post details:", error);
+    });
 }
 
 function nav_modal(direction: 1 | -1): void {
@@ -846,6 +966,7 @@ function toggle_modal_info(): void {
   document
     .getElementById("modalMetadata")
     ?.setAttribute("aria-pressed", String(isHidden));
+  if (isHidden) load_post_details();
 }
 
 async function toggle_fullscreen(): Promise<void> {
@@ -925,6 +1046,12 @@ function setup_evlisteners(): void {
     });
   });
 
+  DOM.rating_filter.addEventListener("change", () => {
+    state.advanced_filters.rating = DOM.rating_filter.value;
+    state.current_page = 1;
+    load_media();
+  });
+
   DOM.sort_by.addEventListener("change", (e: Event) => {
     const target = e.target as HTMLSelectElement;
     state.current_sort = target.value as SortOption;
@@ -1137,10 +1264,59 @@ function handle_key_down(e: KeyboardEvent): void {
   }
 }
 
+function watch_media(): void {
+  const protocol = location.protocol === "https:" ? "wss:" : "ws:";
+  const socket = new WebSocket(`${protocol}//${location.host}/api/events`);
+  let opened = false;
+
+  socket.addEventListener("open", () => {
+    opened = true;
+  });
+
+  socket.addEventListener("message", (event: MessageEvent<string>) => {
+    const change = JSON.parse(event.data) as MediaEvent;
+    load_media();
+
+    const parts: string[] = [];
+    if (change.added.length > 0) parts.push(`${change.added.length} new`);
+    if (change.removed.length > 0) {
+      parts.push(`${change.removed.length} removed`);
+    }
+    show_toast(`Downloads changed: ${parts.join(", ")}`, "info", 3000);
+  });
+
+  socket.addEventListener("close", () => {
+    // the route only exists when `gallery.watch` is on, so don't retry if it never opened
+    if (opened) setTimeout(watch_media, CONFIG.WATCH_RECONNECT_MS);
+  });
+}
+
+function load_settings(): Promise<void> {
+  return fetch("/api/config")
+    .then((response) => {
+      if (!response.ok) throw new Error(`Server error: ${response.status}`);
+      return response.json() as Promise<GallerySettings>;
+    })
+    .then((settings) => {
+      // ratings come from the metadata, so there's nothing to filter by without it
+      document.getElementById("ratingControl")!.hidden =
+        !settings.metadata_filtering;
+
+      if (settings.default_rating) {
+        DOM.rating_filter.value = settings.default_rating;
+        state.advanced_filters.rating = settings.default_rating;
+      }
+    })
+    .catch((error) => {
+      console.error("Error loading gallery settings:", error);
+    });
+}
+
 function init(): void {
   state.items_per_page = parseInt(DOM.items_per_page.value, 10);
   setup_evlisteners();
-  load_media();
+  load_settings().then(load_media);
+  watch_media();
   show_toast("Press ? for keyboard shortcuts", "info", 4000);
 }
 
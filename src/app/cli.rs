@@ -1,20 +1,133 @@
 //! cli stuff
 use {
     crate::{
-        config::options::E62Rs,
+        app::{daemon, update::NO_UPDATE},
+        cache::NO_CACHE,
+        client::E6Client,
+        config::{
+            blacklist::active_blacklist,
+            options::{E62Rs, REDACTED},
+        },
+        data::{booru, manifest},
         error::{Report, Result},
+        getopt,
+        ui::menus::{
+            download::{download_dirs, format_post_list_entry, post_list_template},
+            explore::FULL_RESCAN,
+            search::with_always_tags,
+        },
     },
-    clap::Parser,
+    clap::{Parser, ValueEnum},
+    futures::StreamExt,
     schemars::generate::SchemaSettings,
+    serde::Serialize,
     std::{
         fs::OpenOptions,
-        io::{BufWriter, Write},
-        path::Path,
+        io::{BufWriter, ErrorKind, Write},
+        path::{Path, PathBuf},
+        sync::atomic::Ordering,
     },
 };
 
+/// where e62rs keeps its files, as reported by `--info`
+#[derive(Debug, Serialize)]
+pub struct Info {
+    /// the version of e62rs
+    pub version: &'static str,
+    /// the global config file
+    pub global_config: Option<PathBuf>,
+    /// whether the global config file exists
+    pub global_config_exists: bool,
+    /// the local config file that overrides the global one, if any
+    pub local_config: Option<PathBuf>,
+    /// the directory the caches are kept in
+    pub cache_dir: PathBuf,
+    /// the directory downloads are saved to
+    pub download_dir: PathBuf,
+    /// whether requests are logged in
+    pub login: bool,
+    /// the username requests are logged in as
+    pub username: String,
+    /// the api key, masked so the output can be shared
+    pub api_key: String,
+}
+
+impl Info {
+    /// gather info about the running e62rs and its config
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the working directory can't be read
+    pub fn gather() -> Result<Self> {
+        let global_config = E62Rs::global_config_path().ok();
+        let download_dir = PathBuf::from(getopt!(download.path));
+        let api_key = getopt!(login).resolve_api_key().unwrap_or_default();
+
+        Ok(Self {
+            version: env!("CARGO_PKG_VERSION"),
+            global_config_exists: global_config.as_ref().is_some_and(|p| p.exists()),
+            global_config,
+            local_config: E62Rs::find_local_config()?,
+            cache_dir: getopt!(cache).resolved_cache_dir(),
+            download_dir: std::path::absolute(&download_dir).unwrap_or(download_dir),
+            login: getopt!(login.login),
+            username: getopt!(login.username),
+            api_key: if api_key.is_empty() {
+                String::new()
+            } else {
+                REDACTED.to_string()
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for Info {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        /// show a path, or `none` if there isn't one
+        fn path(p: Option<&Path>) -> String {
+            p.map(|p| p.display().to_string())
+                .unwrap_or_else(|| "none".to_string())
+        }
+
+        writeln!(f, "e62rs {}", self.version)?;
+        writeln!(
+            f,
+            "global config: {}{}",
+            path(self.global_config.as_deref()),
+            if self.global_config_exists {
+                ""
+            } else {
+                " (not created yet)"
+            }
+        )?;
+        writeln!(f, "local config:  {}", path(self.local_config.as_deref()))?;
+        writeln!(f, "cache dir:     {}", self.cache_dir.display())?;
+        writeln!(f, "download dir:  {}", self.download_dir.display())?;
+        write!(
+            f,
+            "login:         {}",
+            match (self.login, self.api_key.is_empty()) {
+                (false, _) => "off".to_string(),
+                (true, true) => format!("on as '{}' (no api key set)", self.username),
+                (true, false) => format!("on as '{}' (api key {})", self.username, self.api_key),
+            }
+        )
+    }
+}
+
+/// how `--search` prints its results
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchFormat {
+    /// one line per post, using `ui.post_list_format`
+    #[default]
+    Text,
+    /// one json post per line
+    Ndjson,
+}
+
 /// the CLI
 #[derive(Parser)]
+#[command(version)]
 pub struct Cli {
     /// Save instead of printing
     #[arg(long)]
@@ -35,6 +148,59 @@ pub struct Cli {
     /// Display localization progress
     #[arg(short, long = "localization")]
     pub loc_prog: bool,
+
+    /// Write a checksum manifest (md5, size, path) of the download directory to PATH
+    #[arg(long, value_name = "PATH")]
+    pub export_manifest: Option<PathBuf>,
+
+    /// Check the download directory against a manifest made with --export-manifest
+    #[arg(long, value_name = "MANIFEST")]
+    pub verify: Option<PathBuf>,
+
+    /// Copy downloads with metadata to DIR with a manifest.json of their tags, rating,
+    /// sources, and sha256, for importing into a self-hosted booru
+    #[arg(long, value_name = "DIR")]
+    pub export_booru: Option<PathBuf>,
+
+    /// Print the version, config files, cache and download dirs, and login status
+    #[arg(long)]
+    pub info: bool,
+
+    /// Print --info as JSON
+    #[arg(long, requires = "info")]
+    pub json: bool,
+
+    /// Print the loaded config with the api key redacted
+    #[arg(long)]
+    pub dump_config: bool,
+
+    /// Make the explorer read every file's metadata instead of reusing its last scan
+    #[arg(long)]
+    pub full_rescan: bool,
+
+    /// Turn off the http and post caches for this run, so every request goes to the network
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Skip the startup tag and pool updates for this run
+    #[arg(long)]
+    pub no_update: bool,
+
+    /// Search for TAGS without the menus and print the results as they come in
+    #[arg(long, value_name = "TAGS")]
+    pub search: Option<String>,
+
+    /// The most posts --search prints (defaults to search.results)
+    #[arg(long, requires = "search")]
+    pub limit: Option<u64>,
+
+    /// How --search prints posts
+    #[arg(long, value_enum, default_value_t, requires = "search")]
+    pub format: SearchFormat,
+
+    /// Keep downloading new posts for the saved searches in FILE (one search per line)
+    #[arg(long, value_name = "FILE")]
+    pub daemon: Option<PathBuf>,
 }
 
 impl Cli {
@@ -44,9 +210,25 @@ impl Cli {
     ///
     /// returns an error if it fails to generate and/or save the json schema  
     /// returns an error if it fails to generate and/or save the default config  
+    /// returns an error if it fails to write or verify a manifest  
+    /// returns an error if it fails to export downloads for a booru  
+    /// returns an error if a `--search` request fails  
+    /// returns an error if the daemon can't read its saved searches  
     pub async fn run() -> Result<()> {
         let argv = Self::parse();
 
+        if argv.full_rescan {
+            FULL_RESCAN.store(true, Ordering::Relaxed);
+        }
+
+        if argv.no_cache {
+            NO_CACHE.store(true, Ordering::Relaxed);
+        }
+
+        if argv.no_update {
+            NO_UPDATE.store(true, Ordering::Relaxed);
+        }
+
         if argv.gen_schema || argv.gen_all {
             Self::gen_schema(argv.save)?;
         }
@@ -59,7 +241,57 @@ impl Cli {
             crate::ui::menus::calculate_localization_progress();
         }
 
-        if argv.gen_default || argv.gen_all || argv.gen_schema || argv.save || argv.loc_prog {
+        if argv.info {
+            Self::print_info(argv.json)?;
+        }
+
+        if argv.dump_config {
+            println!(
+                "{}",
+                toml::to_string_pretty(&E62Rs::load()?.redacted(false))?
+            );
+        }
+
+        if let Some(path) = &argv.export_manifest {
+            Self::export_manifest(path)?;
+        }
+
+        if let Some(path) = &argv.verify
+            && !Self::verify_manifest(path)?
+        {
+            std::process::exit(1);
+        }
+
+        if let Some(path) = &argv.export_booru {
+            Self::export_booru(path)?;
+        }
+
+        if let Some(tags) = &argv.search {
+            Self::search(
+                tags,
+                argv.limit.unwrap_or(getopt!(search.results)),
+                argv.format,
+            )
+            .await?;
+        }
+
+        if let Some(path) = &argv.daemon {
+            daemon::run(path).await?;
+        }
+
+        if argv.gen_default
+            || argv.gen_all
+            || argv.gen_schema
+            || argv.save
+            || argv.loc_prog
+            || argv.export_manifest.is_some()
+            || argv.verify.is_some()
+            || argv.export_booru.is_some()
+            || argv.info
+            || argv.dump_config
+            || argv.search.is_some()
+            || argv.daemon.is_some()
+        {
             std::process::exit(0);
         }
 
@@ -89,6 +321,171 @@ impl Cli {
         w.write_all(contents.as_bytes()).map_err(Report::new)
     }
 
+    /// print where e62rs keeps its files
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - print json instead of text
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the info can't be gathered or serialized
+    pub fn print_info(json: bool) -> Result<()> {
+        let info = Info::gather()?;
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+        } else {
+            println!("{}", info);
+        }
+
+        Ok(())
+    }
+
+    /// write a checksum manifest of the download directory
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - where to write the manifest
+    ///
+    /// # Errors
+    ///
+    /// returns an error if a download can't be hashed or the manifest can't be written
+    pub fn export_manifest(path: &Path) -> Result<()> {
+        let root = PathBuf::from(getopt!(download.path));
+        let count = manifest::write_manifest(&root, path)?;
+
+        println!(
+            "Wrote {} files from '{}' to '{}'",
+            count,
+            root.display(),
+            path.display()
+        );
+
+        Ok(())
+    }
+
+    /// check the download directory against a manifest, printing any problems
+    ///
+    /// returns whether every file matched
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the manifest to check against
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the manifest can't be read or a download can't be hashed
+    pub fn verify_manifest(path: &Path) -> Result<bool> {
+        let root = PathBuf::from(getopt!(download.path));
+        let report = manifest::verify_manifest(&root, path)?;
+
+        for (label, files) in [
+            ("MISMATCH", &report.mismatched),
+            ("MISSING", &report.missing),
+            ("EXTRA", &report.extra),
+        ] {
+            for file in files {
+                println!("{:<8} {}", label, file);
+            }
+        }
+
+        println!(
+            "{} ok, {} mismatched, {} missing, {} extra",
+            report.ok,
+            report.mismatched.len(),
+            report.missing.len(),
+            report.extra.len()
+        );
+
+        Ok(report.is_clean())
+    }
+
+    /// search without the menus, printing each post as soon as it's fetched
+    ///
+    /// posts are filtered with the blacklist and stdout is flushed after every post, so the
+    /// output can be piped (`e62rs --search fox --format ndjson | jq .id`). a closed pipe ends
+    /// the search quietly. the always included/excluded tags are added like in the menus, and
+    /// listed on stderr
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - the search, like `canine solo -comic`
+    /// * `limit` - the most posts to print
+    /// * `format` - how to print them
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the client can't be made, a request fails, or stdout can't be
+    /// written to
+    pub async fn search(tags: &str, limit: u64, format: SearchFormat) -> Result<()> {
+        let client = E6Client::new()?;
+        let (tags, always) = with_always_tags(
+            tags.split_whitespace().map(str::to_string).collect(),
+            &getopt!(search.always_include),
+            &getopt!(search.always_exclude),
+        );
+        if !always.is_empty() {
+            eprintln!("Also searching with {}", always.join(" "));
+        }
+
+        let blacklist = active_blacklist();
+        let template = post_list_template();
+        let mut stdout = std::io::stdout().lock();
+
+        let mut posts = std::pin::pin!(client.search_posts_stream(
+            &tags,
+            limit,
+            &blacklist,
+            getopt!(search.max_pages)
+        ));
+
+        while let Some(post) = posts.next().await {
+            let post = post?;
+            let written = match format {
+                SearchFormat::Ndjson => serde_json::to_writer(&mut stdout, &post)
+                    .map_err(std::io::Error::from)
+                    .and_then(|()| writeln!(stdout)),
+                SearchFormat::Text => {
+                    writeln!(stdout, "{}", format_post_list_entry(&template, &post))
+                }
+            }
+            .and_then(|()| stdout.flush());
+
+            match written {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// copy the downloads into a directory with a manifest for importing into a booru
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - the directory to export to
+    ///
+    /// # Errors
+    ///
+    /// returns an error if a download can't be copied or the manifest can't be written
+    pub fn export_booru(out: &Path) -> Result<()> {
+        let export =
+            booru::export_booru(&download_dirs(), out, getopt!(download.metadata_backend))?;
+
+        println!(
+            "Exported {} posts to '{}' ({} files without metadata and {} duplicates left out)",
+            export.entries.len(),
+            out.display(),
+            export.skipped,
+            export.duplicates
+        );
+
+        Ok(())
+    }
+
     /// generate/save the config schema
     ///
     /// # Arguments
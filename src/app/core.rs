@@ -1,6 +1,6 @@
 //! the core app
 use {
-    super::{handlers::Handlers, interrupt::InterruptHandler, logging},
+    super::{handlers::Handlers, interrupt::InterruptHandler, logging, update},
     crate::{
         app::cli::Cli,
         client::E6Client,
@@ -12,7 +12,7 @@ use {
     },
     color_eyre::eyre::Context,
     std::sync::Arc,
-    tracing::info,
+    tracing::{info, warn},
 };
 
 /// the e62rs app
@@ -67,7 +67,7 @@ impl E6App {
         owo_colors::set_override(getopt!(ui.colored_output));
 
         let interrupt = Self::setup_interrupt_handler()?;
-        let ui = Self::setup_ui().await?;
+        let ui = Self::setup_ui(interrupt.clone()).await?;
 
         reload_config()?;
 
@@ -78,10 +78,17 @@ impl E6App {
 
     /// run the main loop
     ///
+    /// offers to resume any downloads left over from a previous run first. failing to resume
+    /// them (like when offline) is only a warning, the queue is kept for next time
+    ///
     /// # Errors
     ///
     /// returns an error if the main loop fails
     pub async fn run(&self) -> Result<()> {
+        if let Err(e) = self.handlers.ui.resume_download_queue().await {
+            warn!("Couldn't resume queued downloads: {}", e);
+        }
+
         self.handlers.run_main_loop().await
     }
 
@@ -107,14 +114,33 @@ impl E6App {
     }
 
     /// setup the UI
-    async fn setup_ui() -> Result<E6Ui> {
-        let client = Arc::new(E6Client::default());
-
-        opt_and!(autoupdate.tags, client.update_tags().await?);
-        opt_and!(autoupdate.pools, client.update_pools().await?);
-
-        let tag_db = Arc::new(TagDb::load().context("failed to load tag db")?);
-        let pool_db = Arc::new(PoolDb::load().context("failed to load pool db")?);
+    ///
+    /// the tags and pools are updated first, unless `autoupdate.background` is on. then they're
+    /// updated after the ui is made, and whatever dbs are already on disk are used until then
+    /// (empty ones if there aren't any yet)
+    async fn setup_ui(interrupt: InterruptHandler) -> Result<E6Ui> {
+        let client = Arc::new(E6Client::new()?);
+        let background = getopt!(autoupdate.background);
+
+        opt_and!(http.warmup, client.warmup());
+
+        let (tag_db, pool_db) = if background {
+            let tag_db = TagDb::load().unwrap_or_else(|e| {
+                warn!("no tag db to load until the update finishes: {}", e);
+                TagDb::default()
+            });
+            let pool_db = PoolDb::load().unwrap_or_else(|e| {
+                warn!("no pool db to load until the update finishes: {}", e);
+                PoolDb::default()
+            });
+            (tag_db, pool_db)
+        } else {
+            update::run_foreground(&client).await?;
+            (
+                TagDb::load().context("failed to load tag db")?,
+                PoolDb::load().context("failed to load pool db")?,
+            )
+        };
 
         info!(
             "Starting {} v{} using {}",
@@ -123,6 +149,16 @@ impl E6App {
             getopt!(http.api)
         );
 
-        Ok(E6Ui::new(client, tag_db, pool_db))
+        let ui = E6Ui::new(
+            client.clone(),
+            Arc::new(tag_db),
+            Arc::new(pool_db),
+            interrupt,
+        );
+        if background {
+            update::spawn_background(client, ui.clone());
+        }
+
+        Ok(ui)
     }
 }
@@ -0,0 +1,377 @@
+//! the download daemon
+//!
+//! watches a file of saved searches (one per line, like `canine solo -comic`) and polls each of
+//! them on a schedule, downloading posts newer than the last one it saw. the newest id seen for
+//! every search is kept in `<file>.state.json` so restarting picks up where it left off
+use {
+    crate::{
+        client::E6Client, config::blacklist::active_blacklist, error::Result, getopt,
+        models::E6Post, ui::menus::download::PostDownloader,
+    },
+    color_eyre::eyre::Context,
+    hashbrown::HashMap,
+    serde::{Deserialize, Serialize},
+    std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::Duration,
+    },
+    tracing::{info, warn},
+};
+
+/// the most posts fetched per request
+const BATCH_SIZE: u64 = 320;
+
+/// how many cycles a post can fail to download before the daemon gives up on it
+const MAX_ATTEMPTS: u32 = 3;
+
+/// read the saved searches from a file
+///
+/// every non-empty line is a search. lines starting with `#` are comments
+///
+/// # Arguments
+///
+/// * `path` - the saved searches file
+///
+/// # Errors
+///
+/// returns an error if the file can't be read
+pub fn read_searches(path: &Path) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read saved searches '{}'", path.display()))?;
+
+    Ok(contents
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect())
+}
+
+/// the newest post the daemon has seen for each saved search
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct DaemonState {
+    /// the id of the newest post seen, keyed by search
+    pub last_seen: HashMap<String, i64>,
+    /// how many cycles each post has failed to download, keyed by post id
+    #[serde(default)]
+    pub attempts: HashMap<i64, u32>,
+}
+
+impl DaemonState {
+    /// get where the state of a saved searches file is kept
+    ///
+    /// # Arguments
+    ///
+    /// * `searches` - the saved searches file
+    pub fn path_for(searches: &Path) -> PathBuf {
+        let mut name = searches.file_name().unwrap_or_default().to_os_string();
+        name.push(".state.json");
+        searches.with_file_name(name)
+    }
+
+    /// load the state, starting fresh if it's missing or unreadable
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the state file
+    pub fn load(path: &Path) -> Self {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!(
+                "ignoring unreadable daemon state '{}': {}",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    /// save the state
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the state file
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the state can't be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write daemon state '{}'", path.display()))?;
+        Ok(())
+    }
+
+    /// remember the newest post downloaded for a search
+    ///
+    /// only posts older than the oldest failed download count, so failed downloads (and
+    /// anything newer) are polled again next cycle. a post that has failed [`MAX_ATTEMPTS`]
+    /// times is given up on and no longer holds the search back
+    ///
+    /// returns the ids of the posts given up on
+    ///
+    /// # Arguments
+    ///
+    /// * `search` - the saved search
+    /// * `ids` - the ids of the posts it found
+    /// * `failed` - the ids of the posts that failed to download
+    pub fn record(&mut self, search: &str, ids: &[i64], failed: &[i64]) -> Vec<i64> {
+        for id in ids.iter().filter(|id| !failed.contains(id)) {
+            self.attempts.remove(id);
+        }
+
+        let mut given_up = Vec::new();
+        let mut retrying = Vec::new();
+        for id in failed {
+            let attempts = self.attempts.entry(*id).or_insert(0);
+            *attempts += 1;
+
+            if *attempts >= MAX_ATTEMPTS {
+                self.attempts.remove(id);
+                given_up.push(*id);
+            } else {
+                retrying.push(*id);
+            }
+        }
+
+        let oldest_failed = retrying.iter().min().copied().unwrap_or(i64::MAX);
+
+        if let Some(newest) = ids.iter().copied().filter(|id| *id < oldest_failed).max() {
+            let seen = self.last_seen.entry(search.to_string()).or_insert(newest);
+            *seen = (*seen).max(newest);
+        }
+
+        given_up
+    }
+}
+
+/// fetch the posts of a saved search that are newer than `last_seen`
+///
+/// a search that has never been polled only fetches its newest page (`search.results` posts).
+/// blacklisted posts are left out, but pages are followed by the unfiltered results so a page
+/// that's entirely blacklisted doesn't end the search early. requests that get rate limited
+/// are retried by the client
+///
+/// # Arguments
+///
+/// * `client` - the client to search with
+/// * `search` - the saved search
+/// * `last_seen` - the id of the newest post already downloaded for this search
+///
+/// # Errors
+///
+/// returns an error if a search request fails
+pub async fn poll_search(
+    client: &E6Client,
+    search: &str,
+    last_seen: Option<i64>,
+) -> Result<Vec<E6Post>> {
+    let mut tags: Vec<String> = search.split_whitespace().map(str::to_string).collect();
+    if let Some(id) = last_seen {
+        tags.push(format!("id:>{}", id));
+    }
+
+    let limit = match last_seen {
+        Some(_) => BATCH_SIZE,
+        None => getopt!(search.results),
+    };
+
+    let blacklist = active_blacklist();
+    let mut posts = Vec::new();
+    let mut before_id = None;
+
+    loop {
+        let results = client
+            .search_posts_with(&tags, Some(limit), before_id, &[])
+            .await
+            .with_context(|| format!("failed to search '{}'", search))?;
+
+        let Some(min_id) = results.posts.iter().map(|p| p.id).min() else {
+            break;
+        };
+        before_id = Some(min_id);
+        posts.extend(results.filter_blacklisted_by(&tags, &blacklist).posts);
+
+        if last_seen.is_none() {
+            break;
+        }
+    }
+
+    Ok(posts)
+}
+
+/// watch a saved searches file, downloading new posts forever
+///
+/// the file is re-read every cycle so searches can be added or removed while it runs. cycles
+/// are `download.watch_interval` minutes apart
+///
+/// # Arguments
+///
+/// * `searches_path` - the saved searches file
+///
+/// # Errors
+///
+/// returns an error if the saved searches file can't be read
+pub async fn run(searches_path: &Path) -> Result<()> {
+    let client = Arc::new(E6Client::new()?);
+    let downloader = Arc::new(
+        PostDownloader::with_download_dir_and_format(
+            getopt!(download.path),
+            Some(getopt!(download.format)),
+        )
+        .non_interactive(),
+    );
+    let state_path = DaemonState::path_for(searches_path);
+    let mut state = DaemonState::load(&state_path);
+    let interval = Duration::from_secs(getopt!(download.watch_interval) * 60);
+
+    println!(
+        "Watching the searches in '{}', checking every {} minutes",
+        searches_path.display(),
+        interval.as_secs() / 60
+    );
+
+    loop {
+        let searches = read_searches(searches_path)?;
+        let mut found = 0;
+        let mut failed_searches = 0;
+        let mut failed_downloads = 0;
+
+        for search in &searches {
+            let last_seen = state.last_seen.get(search).copied();
+            let posts = match poll_search(&client, search, last_seen).await {
+                Ok(posts) => posts,
+                Err(e) => {
+                    warn!("Polling '{}' failed: {}", search, e);
+                    println!("'{}': failed ({})", search, e);
+                    failed_searches += 1;
+                    continue;
+                }
+            };
+
+            info!(search, new = posts.len(), "polled saved search");
+            println!("'{}': {} new posts", search, posts.len());
+
+            if posts.is_empty() {
+                continue;
+            }
+
+            found += posts.len();
+            let ids: Vec<i64> = posts.iter().map(|p| p.id).collect();
+
+            let failed_ids = match downloader.clone().download_posts(posts).await {
+                Ok(failed_ids) => failed_ids,
+                Err(e) => {
+                    warn!("Downloading posts for '{}' failed: {}", search, e);
+                    ids.clone()
+                }
+            };
+            failed_downloads += failed_ids.len();
+
+            let given_up = state.record(search, &ids, &failed_ids);
+            if !given_up.is_empty() {
+                warn!(
+                    search,
+                    ?given_up,
+                    "giving up on posts that keep failing to download"
+                );
+                println!(
+                    "'{}': gave up on {} posts after {} failed attempts",
+                    search,
+                    given_up.len(),
+                    MAX_ATTEMPTS
+                );
+            }
+
+            if let Err(e) = state.save(&state_path) {
+                warn!("{}", e);
+            }
+        }
+
+        let summary = format!(
+            "{} new posts from {} searches ({} searches failed, {} downloads failed)",
+            found,
+            searches.len(),
+            failed_searches,
+            failed_downloads
+        );
+        info!("{}", summary);
+        println!(
+            "[{}] {}, next check in {} minutes",
+            chrono::Local::now().format("%Y-%m-%d %H:%M"),
+            summary,
+            interval.as_secs() / 60
+        );
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_read_searches() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("searches.txt");
+        std::fs::write(
+            &path,
+            "# my searches\ncanine  solo\n\n  -comic   fox \n#wolf\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_searches(&path).unwrap(),
+            vec!["canine solo", "-comic fox"]
+        );
+        assert_eq!(
+            DaemonState::path_for(&path),
+            dir.path().join("searches.txt.state.json")
+        );
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+        let mut state = DaemonState::default();
+        state.record("fox", &[5, 12, 7], &[]);
+        state.record("fox", &[9], &[]);
+        state.record("wolf", &[], &[]);
+        state.record("otter", &[3, 10, 8], &[8]);
+        state.record("bird", &[4], &[4]);
+        assert_eq!(state.last_seen.get("fox"), Some(&12));
+        assert!(!state.last_seen.contains_key("wolf"));
+        assert_eq!(state.last_seen.get("otter"), Some(&3));
+        assert!(!state.last_seen.contains_key("bird"));
+        assert_eq!(state.attempts.get(&4), Some(&1));
+
+        state.save(&path).unwrap();
+        assert_eq!(DaemonState::load(&path), state);
+        assert_eq!(
+            DaemonState::load(&dir.path().join("missing.json")),
+            DaemonState::default()
+        );
+    }
+
+    #[test]
+    fn test_give_up_on_failing_posts() {
+        let mut state = DaemonState::default();
+        for _ in 1..MAX_ATTEMPTS {
+            assert!(state.record("fox", &[4, 6], &[4]).is_empty());
+            assert!(!state.last_seen.contains_key("fox"));
+        }
+
+        assert_eq!(state.record("fox", &[4, 6], &[4]), vec![4]);
+        assert_eq!(state.last_seen.get("fox"), Some(&6));
+        assert!(state.attempts.is_empty());
+
+        state.record("wolf", &[8], &[8]);
+        state.record("wolf", &[8], &[]);
+        assert!(state.attempts.is_empty());
+        assert_eq!(state.last_seen.get("wolf"), Some(&8));
+    }
+}
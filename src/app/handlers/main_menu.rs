@@ -2,6 +2,7 @@
 use {
     super::Handlers,
     crate::{
+        app::update,
         config::instance::reload_config,
         error::Result,
         ui::menus::{
@@ -18,10 +19,13 @@ impl Handlers {
     /// [`MainMenu::ManageBlacklist`] runs the blacklist manager
     /// [`MainMenu::EditConfig`] lets the user edit their config file
     /// [`MainMenu::ViewLatest`] displays the latest uploads on e621
+    /// [`MainMenu::RecentlyViewed`] lets the user revisit posts they've viewed
     /// [`MainMenu::OpenInBrowser`] opens the downloads gallery in the users browser
     /// [`MainMenu::Reorganize`] runs the downloads reorganizer
+    /// [`MainMenu::CleanEmptyFolders`] removes empty folders from the download directories
     /// [`MainMenu::ExploreDownloads`] runs the downloads explorer
     /// [`MainMenu::UpdateDownloads`] runs the downloads updater
+    /// [`MainMenu::WhatsNew`] shows how many new posts each downloaded artist has
     /// [`MainMenu::Search`] runs the search menu (see [`crate::app::handlers::search`])
     /// [`MainMenu::ReloadConfig`] reloads and reapplies the config file
     /// [`MainMenu::Exit`] exits e62rs
@@ -32,7 +36,11 @@ impl Handlers {
     /// returns an error if it fails to run the logic associated with the user selection
     pub async fn run_main_loop(&self) -> Result<()> {
         'main: loop {
-            let selection = match MainMenu::select("What would you like to do?").ask() {
+            let prompt = match update::background_status() {
+                Some(status) => format!("What would you like to do? ({})", status),
+                None => "What would you like to do?".to_string(),
+            };
+            let selection = match MainMenu::select(&prompt).ask() {
                 Ok(sel) => sel,
                 Err(_) if self.was_interrupted() => continue 'main,
                 Err(e) => return Err(e.into()),
@@ -41,14 +49,19 @@ impl Handlers {
             match selection.value {
                 MainMenu::ManageBlacklist => self.ui.manage_blacklist().await.into_diagnostic()?,
                 MainMenu::ViewLatest => self.ui.display_latest_posts().await.into_diagnostic()?,
+                MainMenu::RecentlyViewed => {
+                    self.ui.browse_recently_viewed().await.into_diagnostic()?
+                }
                 MainMenu::OpenInBrowser => self.ui.serve_downloads().await.into_diagnostic()?,
                 MainMenu::Reorganize => self.ui.reorganize_downloads().await.into_diagnostic()?,
+                MainMenu::CleanEmptyFolders => self.ui.clean_empty_folders().into_diagnostic()?,
                 MainMenu::ExploreDownloads => {
                     self.ui.explore_downloads().await.into_diagnostic()?
                 }
                 MainMenu::UpdateDownloads => {
                     self.ui.redownload_by_artists().await.into_diagnostic()?
                 }
+                MainMenu::WhatsNew => self.ui.whats_new().await.into_diagnostic()?,
                 MainMenu::Search => self.handle_search().await?,
                 MainMenu::ManageConfig => {
                     match ConfigMenu::select("What would you like to do?").ask() {
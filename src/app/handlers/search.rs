@@ -3,7 +3,10 @@
 //! See [`Handlers::handle_search`] and [`Handlers::handle_pool_search`]
 use {
     super::Handlers,
-    crate::ui::menus::{PoolSearchModeMenu, SearchMenu, search::SearchMenu as _},
+    crate::ui::menus::{
+        PoolSearchModeMenu, SearchMenu,
+        search::{SearchMenu as _, TagInput},
+    },
     miette::IntoDiagnostic,
 };
 
@@ -14,6 +17,7 @@ impl Handlers {
     /// search for posts or for pools.
     ///
     /// [`SearchMenu::Posts`] searches posts by their tags
+    /// [`SearchMenu::BrowseTags`] searches posts by tags picked from the tag database
     /// [`SearchMenu::Pools`] searches pools (see [`Handlers::handle_pool_search`])
     ///
     /// # Errors
@@ -28,7 +32,16 @@ impl Handlers {
         };
 
         match selection.value {
-            SearchMenu::Posts => self.ui.search_posts().await.into_diagnostic()?,
+            SearchMenu::Posts => self
+                .ui
+                .search_posts(TagInput::Typed)
+                .await
+                .into_diagnostic()?,
+            SearchMenu::BrowseTags => self
+                .ui
+                .search_posts(TagInput::Browse)
+                .await
+                .into_diagnostic()?,
             SearchMenu::Pools => self.handle_pool_search().await?,
             SearchMenu::Back => {}
         }
@@ -1,8 +1,10 @@
 //! the main application
 pub mod cli;
 pub mod core;
+pub mod daemon;
 pub mod handlers;
 pub mod interrupt;
 pub mod logging;
+pub mod update;
 
 pub use core::E6App;
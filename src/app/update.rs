@@ -0,0 +1,221 @@
+//! the tag and pool updates run at startup
+//!
+//! they either run before the main menu shows up, behind a progress bar, or (with
+//! `autoupdate.background`) while it's up. the main menu's prompt shows how far along a
+//! background update is, and the tag and pool dbs are reloaded into the ui once it's done
+use {
+    crate::{
+        client::{E6Client, export::ExportProgress},
+        data::{pools::PoolDb, tags::TagDb},
+        error::Result,
+        getopt,
+        ui::E6Ui,
+        utils::MutableStatic as MutStatic,
+    },
+    indicatif::HumanBytes,
+    std::{
+        fmt,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+    },
+    tracing::{info, warn},
+};
+
+/// skip the startup updates for this run, whatever the config says
+///
+/// set by `--no-update`
+pub static NO_UPDATE: AtomicBool = AtomicBool::new(false);
+
+/// how far along the background update is (`None` when there isn't one running)
+static BACKGROUND_STATUS: MutStatic<Option<UpdateStatus>> = MutStatic::new(None);
+
+/// how far along an export update is
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdateStatus {
+    /// the name of the export
+    pub label: String,
+    /// what it's doing once it's done downloading
+    pub phase: Option<String>,
+    /// bytes downloaded so far
+    pub downloaded: u64,
+    /// the full size of the export (0 if unknown)
+    pub total: u64,
+}
+
+impl UpdateStatus {
+    /// the status of an export that hasn't started downloading yet
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - the name of the export
+    pub fn checking(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            phase: Some("checking".to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for UpdateStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "updating {}: ", self.label)?;
+
+        match (&self.phase, self.total) {
+            (Some(phase), _) => write!(f, "{}", phase.to_lowercase()),
+            (None, 0) => write!(f, "{}", HumanBytes(self.downloaded)),
+            (None, total) => write!(
+                f,
+                "{} of {}",
+                HumanBytes(self.downloaded),
+                HumanBytes(total)
+            ),
+        }
+    }
+}
+
+/// reports export progress to [`background_status`]
+struct BackgroundProgress;
+
+impl ExportProgress for BackgroundProgress {
+    fn begin(&self, label: &str, downloaded: u64, total: u64) {
+        BACKGROUND_STATUS.replace(Some(UpdateStatus {
+            label: label.to_string(),
+            phase: None,
+            downloaded,
+            total,
+        }));
+    }
+
+    fn advance(&self, downloaded: u64) {
+        BACKGROUND_STATUS.update(|status| {
+            if let Some(status) = status {
+                status.downloaded = downloaded;
+            }
+        });
+    }
+
+    fn phase(&self, label: &str, phase: &str) {
+        BACKGROUND_STATUS.update(|status| {
+            let status = status.get_or_insert_with(|| UpdateStatus::checking(label));
+            status.label = label.to_string();
+            status.phase = Some(phase.to_string());
+        });
+    }
+}
+
+/// get how far along the background update is, if one is running
+pub fn background_status() -> Option<UpdateStatus> {
+    BACKGROUND_STATUS.with(Clone::clone)
+}
+
+/// which of the tags and pools to update, going by the config and `--no-update`
+fn wanted() -> (bool, bool) {
+    if NO_UPDATE.load(Ordering::Relaxed) {
+        return (false, false);
+    }
+
+    (getopt!(autoupdate.tags), getopt!(autoupdate.pools))
+}
+
+/// update the tags and pools before going any further, showing a progress bar for each
+///
+/// # Arguments
+///
+/// * `client` - the client to download the exports with
+///
+/// # Errors
+///
+/// returns an error if an export can't be downloaded, is corrupt, or can't replace the old one
+pub async fn run_foreground(client: &E6Client) -> Result<()> {
+    let (tags, pools) = wanted();
+
+    if tags {
+        client.update_tags().await?;
+    }
+
+    if pools {
+        client.update_pools().await?;
+    }
+
+    Ok(())
+}
+
+/// start updating the tags and pools in the background
+///
+/// each db is reloaded into the ui as soon as its update is done. failures are only logged,
+/// since nothing is waiting on them
+///
+/// # Arguments
+///
+/// * `client` - the client to download the exports with
+/// * `ui` - the ui to reload the dbs into
+pub fn spawn_background(client: Arc<E6Client>, ui: E6Ui) {
+    let (tags, pools) = wanted();
+    if !tags && !pools {
+        return;
+    }
+
+    BACKGROUND_STATUS.replace(Some(UpdateStatus::checking(if tags {
+        "tags"
+    } else {
+        "pools"
+    })));
+
+    tokio::spawn(async move {
+        if tags {
+            match client.update_tags_reporting(&BackgroundProgress).await {
+                Ok(()) => match tokio::task::spawn_blocking(TagDb::load).await {
+                    Ok(Ok(tag_db)) => ui.set_tag_db(Arc::new(tag_db)),
+                    Ok(Err(e)) => warn!("failed to reload the tag db: {}", e),
+                    Err(e) => warn!("failed to reload the tag db: {}", e),
+                },
+                Err(e) => warn!("background tag update failed: {}", e),
+            }
+        }
+
+        if pools {
+            BACKGROUND_STATUS.replace(Some(UpdateStatus::checking("pools")));
+            match client.update_pools_reporting(&BackgroundProgress).await {
+                Ok(()) => match tokio::task::spawn_blocking(PoolDb::load).await {
+                    Ok(Ok(pool_db)) => ui.set_pool_db(Arc::new(pool_db)),
+                    Ok(Err(e)) => warn!("failed to reload the pool db: {}", e),
+                    Err(e) => warn!("failed to reload the pool db: {}", e),
+                },
+                Err(e) => warn!("background pool update failed: {}", e),
+            }
+        }
+
+        BACKGROUND_STATUS.replace(None);
+        info!("background tag and pool update finished");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_status_display() {
+        assert_eq!(
+            UpdateStatus::checking("tags").to_string(),
+            "updating tags: checking"
+        );
+
+        let mut status = UpdateStatus {
+            label: "pools".to_string(),
+            phase: None,
+            downloaded: 1024,
+            total: 0,
+        };
+        assert_eq!(status.to_string(), "updating pools: 1.00 KiB");
+
+        status.total = 2048;
+        assert_eq!(status.to_string(), "updating pools: 1.00 KiB of 2.00 KiB");
+
+        status.phase = Some("Unpacking".to_string());
+        assert_eq!(status.to_string(), "updating pools: unpacking");
+    }
+}
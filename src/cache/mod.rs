@@ -1,12 +1,17 @@
 //! post and http cache stuff
 use {
-    crate::{bail, cache::posts::CacheEntry, client::E6Client, error::*},
+    crate::{
+        bail,
+        cache::posts::CacheEntry,
+        client::{E6Client, is_retryable, retry_delay},
+        error::*,
+    },
     color_eyre::eyre::Context,
     flate2::{Compression, read::GzDecoder, write::GzEncoder},
     hashbrown::HashMap,
     std::{
         io::{Read, Write},
-        sync::atomic::Ordering,
+        sync::atomic::{AtomicBool, Ordering},
         time::{Duration, Instant, SystemTime, UNIX_EPOCH},
     },
     tracing::{debug, info, warn},
@@ -15,6 +20,11 @@ use {
 pub mod posts;
 pub mod stats;
 
+/// turn off the http and post caches for this run, whatever the config says
+///
+/// set by `--no-cache`. only clients made after it's set are affected
+pub static NO_CACHE: AtomicBool = AtomicBool::new(false);
+
 impl E6Client {
     /// get an entry from the cache, fetch if no entry found
     ///
@@ -31,6 +41,39 @@ impl E6Client {
     /// * `Err(color_eyre::Report)` - an error describing why it failed
     /// * `Ok(Vec<u8>)` - the data of the cache entry or fetched post
     pub async fn get_cached_or_fetch(&self, url: &str) -> Result<Vec<u8>> {
+        self.fetch_cached(url, self.max_retries).await
+    }
+
+    /// get a file from the cache, fetching it once if no entry is found
+    ///
+    /// unlike [`E6Client::get_cached_or_fetch`], failed requests aren't retried with
+    /// `http.max_retries`, so callers fetching images can retry with their own settings
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - the url of the file to get
+    ///
+    /// # Errors
+    ///
+    /// returns an error if it fails to fetch the file
+    pub async fn get_cached_or_fetch_once(&self, url: &str) -> Result<Vec<u8>> {
+        self.fetch_cached(url, 0).await
+    }
+
+    /// get an entry from the cache, fetching it if no entry is found
+    ///
+    /// connection failures, server errors, and rate limiting are retried up to `max_retries`
+    /// times
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - the url to get
+    /// * `max_retries` - how many times to retry a failed request
+    ///
+    /// # Errors
+    ///
+    /// returns an error if every attempt to fetch it fails
+    async fn fetch_cached(&self, url: &str, max_retries: u32) -> Result<Vec<u8>> {
         let cache_key = url.to_string();
         let cache_enabled = self.cache_config.enabled.unwrap_or(true);
 
@@ -100,12 +143,27 @@ impl E6Client {
         debug!("cache miss, fetching: {}", url);
 
         let start = Instant::now();
-        let response = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .context("failed to fetch")?;
+        let mut attempt = 0;
+        let response = loop {
+            let result = self.client.get(url).send().await;
+            let retryable = match &result {
+                Ok(response) => is_retryable(response.status()),
+                Err(_) => true,
+            };
+
+            if !retryable || attempt >= max_retries {
+                break result;
+            }
+
+            attempt += 1;
+            let delay = retry_delay(attempt);
+            warn!(
+                "request to {} failed, retrying in {:?} ({}/{})",
+                url, delay, attempt, max_retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+        .context("failed to fetch")?;
         let status = response.status();
 
         if !status.is_success() {
@@ -370,18 +428,19 @@ impl E6Client {
 
     /// get the stats for the http and post caches
     ///
-    /// # Errors
-    ///
-    /// returns an error if it fails to get the stats of the post cache
-    pub async fn get_all_cache_stats(&self) -> Result<String> {
+    /// if the post cache can't be read (e.g. another process has it locked) the http stats are
+    /// still returned, with a note about the post cache in place of its stats
+    pub async fn get_all_cache_stats(&self) -> String {
         let http_stats = self.get_detailed_cache_stats().await;
-        let post_stats = self
-            .post_cache
-            .get_stats()
-            .await
-            .context("failed to get post cache stats")?;
+        let post_stats = match self.post_cache.get_stats().await {
+            Ok(stats) => stats.to_string(),
+            Err(e) => {
+                warn!("Failed to get post cache stats: {:#}", e);
+                format!("Post Cache: unavailable ({})", e)
+            }
+        };
 
-        Ok(format!("{}\n\n{}", http_stats, post_stats))
+        format!("{}\n\n{}", http_stats, post_stats)
     }
 
     /// cleanup expired entries from the cache
@@ -1,11 +1,19 @@
 //! post cache management stuff
 use {
-    crate::{bail, cache::stats::PostCacheStats, error::*, models::E6Post},
+    crate::{cache::stats::PostCacheStats, error::*, models::E6Post},
     color_eyre::{Section, eyre::Context},
     postcard::{from_bytes, to_allocvec},
     redb::{Database, ReadableDatabase, ReadableTable, ReadableTableMetadata, TableDefinition},
     serde::{Deserialize, Serialize},
-    std::{fs::create_dir_all, path::PathBuf, sync::Arc},
+    std::{
+        fs::create_dir_all,
+        path::PathBuf,
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::{SystemTime, UNIX_EPOCH},
+    },
     tokio::sync::RwLock,
     tracing::{debug, error, info, warn},
 };
@@ -13,6 +21,17 @@ use {
 /// the table of cached posts
 const POSTS_TABLE: TableDefinition<i64, &[u8]> = TableDefinition::new("posts");
 
+/// when each cached post was stored (seconds since the unix epoch)
+const CACHED_AT_TABLE: TableDefinition<i64, u64> = TableDefinition::new("cached_at");
+
+/// the current time in seconds since the unix epoch
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// an entry in the post-cache
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CacheEntry {
@@ -43,6 +62,12 @@ pub struct PostCache {
     auto_compact: bool,
     /// the threshold at which to compact an entry
     compact_threshold: u8,
+    /// how long a cached post stays fresh for id lookups, in seconds (0 = forever)
+    ttl_secs: u64,
+    /// the number of id lookups answered by the cache
+    id_hits: Arc<AtomicU64>,
+    /// the number of id lookups that had to be fetched
+    id_misses: Arc<AtomicU64>,
 }
 
 impl PostCache {
@@ -56,6 +81,7 @@ impl PostCache {
     /// * `max_posts` - the maximum number of posts to cache
     /// * `auto_compact` - whether to automatically compact the cache
     /// * `compact_threshold` - the threshold at which to compact
+    /// * `ttl_secs` - how long a cached post stays fresh for id lookups (0 = forever)
     ///
     /// # Errors
     ///
@@ -69,6 +95,7 @@ impl PostCache {
         max_posts: usize,
         auto_compact: bool,
         compact_threshold: u8,
+        ttl_secs: u64,
     ) -> Result<Self> {
         let cache_path = PathBuf::from(cache_dir).join("posts.redb");
 
@@ -81,6 +108,9 @@ impl PostCache {
                 max_posts: 0,
                 auto_compact: false,
                 compact_threshold: 0,
+                ttl_secs,
+                id_hits: Arc::new(AtomicU64::new(0)),
+                id_misses: Arc::new(AtomicU64::new(0)),
             });
         }
 
@@ -108,6 +138,9 @@ impl PostCache {
             max_posts,
             auto_compact,
             compact_threshold,
+            ttl_secs,
+            id_hits: Arc::new(AtomicU64::new(0)),
+            id_misses: Arc::new(AtomicU64::new(0)),
         })
     }
 
@@ -116,11 +149,12 @@ impl PostCache {
     pub fn new(cache_dir: &str) -> Result<Self> {
         Self::with_config(
             cache_dir,
-            crate::getopt!(cache.posts.enabled),
+            crate::getopt!(cache.posts.enabled) && !crate::cache::NO_CACHE.load(Ordering::Relaxed),
             crate::getopt!(cache.max_size_mb),
             crate::getopt!(cache.posts.max_posts),
             crate::getopt!(cache.posts.auto_compact),
             crate::getopt!(cache.posts.compact_threshold),
+            crate::getopt!(cache.posts.ttl_secs),
         )
     }
 
@@ -241,12 +275,61 @@ impl PostCache {
         }
     }
 
+    /// get a post in the cache if it was cached within the ttl
+    ///
+    /// posts cached before their timestamps were recorded count as stale
+    ///
+    /// # Arguments
+    ///
+    /// * `post_id` - the id of the post to try retrieving from the cache
+    ///
+    /// # Errors
+    ///
+    /// returns an error if it fails to start reading the post cache db
+    pub async fn get_fresh(&self, post_id: i64) -> Result<Option<E6Post>> {
+        if self.ttl_secs > 0 {
+            let cached_at = {
+                let db_guard = self.db.read().await;
+                let Some(db) = db_guard.as_ref() else {
+                    return Ok(None);
+                };
+
+                let read_txn = db
+                    .begin_read()
+                    .context("failed to begin read transaction")?;
+                match read_txn.open_table(CACHED_AT_TABLE) {
+                    Ok(table) => table.get(post_id).ok().flatten().map(|t| t.value()),
+                    Err(_) => None,
+                }
+            };
+
+            if cached_at.is_none_or(|t| now_secs().saturating_sub(t) >= self.ttl_secs) {
+                debug!("Post cache entry for {} is stale", post_id);
+                return Ok(None);
+            }
+        }
+
+        self.get(post_id).await
+    }
+
+    /// count an id lookup as a cache hit or miss
+    ///
+    /// # Arguments
+    ///
+    /// * `hit` - whether the post was found in the cache
+    pub fn record_id_lookup(&self, hit: bool) {
+        let counter = if hit { &self.id_hits } else { &self.id_misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// insert a post into the cache
+    ///
+    /// does nothing if the cache is disabled
     pub async fn insert(&self, post: &E6Post) -> Result<()> {
         let db_guard = self.db.read().await;
         let db = match db_guard.as_ref() {
             Some(db) => db,
-            None => bail!("Database not initialized"),
+            None => return Ok(()),
         };
 
         let serialized = to_allocvec(post).context("Failed to serialize post")?;
@@ -262,6 +345,12 @@ impl PostCache {
             table
                 .insert(post.id, serialized.as_slice())
                 .context("Failed to insert post into cache")?;
+
+            write_txn
+                .open_table(CACHED_AT_TABLE)
+                .context("Failed to open cached_at table")?
+                .insert(post.id, now_secs())
+                .context("Failed to record when post was cached")?;
         }
 
         write_txn.commit().context("Failed to commit transaction")?;
@@ -275,6 +364,8 @@ impl PostCache {
     }
 
     /// insert multiple posts into the cache
+    ///
+    /// does nothing if the cache is disabled
     pub async fn insert_batch(&self, posts: &[E6Post]) -> Result<()> {
         if posts.is_empty() {
             return Ok(());
@@ -283,7 +374,7 @@ impl PostCache {
         let db_guard = self.db.read().await;
         let db = match db_guard.as_ref() {
             Some(db) => db,
-            None => bail!("Database not initialized"),
+            None => return Ok(()),
         };
 
         let write_txn = db
@@ -294,6 +385,10 @@ impl PostCache {
             let mut table = write_txn
                 .open_table(POSTS_TABLE)
                 .context("Failed to open posts table")?;
+            let mut cached_at = write_txn
+                .open_table(CACHED_AT_TABLE)
+                .context("Failed to open cached_at table")?;
+            let now = now_secs();
 
             for post in posts {
                 let serialized = to_allocvec(post).context("Failed to serialize post")?;
@@ -301,6 +396,9 @@ impl PostCache {
                 table
                     .insert(post.id, serialized.as_slice())
                     .with_context(|| format!("Failed to insert post {} into cache", post.id))?;
+                cached_at.insert(post.id, now).with_context(|| {
+                    format!("Failed to record when post {} was cached", post.id)
+                })?;
             }
         }
 
@@ -355,8 +453,10 @@ impl PostCache {
         let write_txn = db.begin_write()?;
         {
             let mut table = write_txn.open_table(POSTS_TABLE)?;
+            let mut cached_at = write_txn.open_table(CACHED_AT_TABLE)?;
             for key in &keys_to_remove {
                 table.remove(*key)?;
+                cached_at.remove(*key)?;
             }
         }
         write_txn.commit()?;
@@ -486,12 +586,17 @@ impl PostCache {
     }
 
     /// clear the post cache
+    ///
+    /// a disabled cache only has its file removed, it isn't recreated
     pub async fn clear(&self) -> Result<()> {
         let mut db_guard = self.db.write().await;
-        *db_guard = None;
+        let enabled = db_guard.take().is_some();
         std::fs::remove_file(&self.cache_path).ok();
-        let new = Database::create(&self.cache_path).context("failed to recreate post cache db")?;
-        *db_guard = Some(new);
+        if enabled {
+            let new =
+                Database::create(&self.cache_path).context("failed to recreate post cache db")?;
+            *db_guard = Some(new);
+        }
         info!("Post cache cleared");
         Ok(())
     }
@@ -524,6 +629,8 @@ impl PostCache {
             file_size_bytes: file_size,
             max_entries: self.max_posts,
             auto_compact_enabled: self.auto_compact,
+            id_hits: self.id_hits.load(Ordering::Relaxed),
+            id_misses: self.id_misses.load(Ordering::Relaxed),
         })
     }
 
@@ -537,6 +644,7 @@ impl PostCache {
 
         let write_txn = db.begin_write()?;
         let removed = {
+            write_txn.open_table(CACHED_AT_TABLE)?.remove(post_id)?;
             let mut table = write_txn.open_table(POSTS_TABLE)?;
             table.remove(post_id)?.is_some()
         };
@@ -562,7 +670,9 @@ impl PostCache {
 
         {
             let mut table = write_txn.open_table(POSTS_TABLE)?;
+            let mut cached_at = write_txn.open_table(CACHED_AT_TABLE)?;
             for &post_id in post_ids {
+                cached_at.remove(post_id)?;
                 if table.remove(post_id)?.is_some() {
                     removed_count += 1;
                 }
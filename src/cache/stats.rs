@@ -13,6 +13,10 @@ pub struct PostCacheStats {
     pub max_entries: usize,
     /// whether auto-compact is enabled
     pub auto_compact_enabled: bool,
+    /// the number of id lookups answered by the cache this session
+    pub id_hits: u64,
+    /// the number of id lookups that had to be fetched this session
+    pub id_misses: u64,
 }
 
 impl PostCacheStats {
@@ -45,7 +49,7 @@ impl std::fmt::Display for PostCacheStats {
         write!(
             f,
             "Post Cache Statistics:\n- Entries: {} / {} ({:.1}% full)\n- File Size: {:.2} MB\n- \
-             Avg Entry Size: {:.2} KB\n- Auto-Compact: {}",
+             Avg Entry Size: {:.2} KB\n- Auto-Compact: {}\n- ID Lookup Hits: {} / {}",
             self.entry_count,
             self.max_entries,
             self.usage_percent(),
@@ -55,7 +59,9 @@ impl std::fmt::Display for PostCacheStats {
                 "enabled"
             } else {
                 "disabled"
-            }
+            },
+            self.id_hits,
+            self.id_hits + self.id_misses
         )
     }
 }
@@ -79,11 +85,9 @@ impl CacheStats {
     pub fn hit_rate(&self) -> f64 {
         let hits = self.hits.load(Ordering::Relaxed);
         let total = hits + self.misses.load(Ordering::Relaxed);
-        if total == 0 {
-            0.0
-        } else {
-            (hits / total) as f64
-        }
+        hits.checked_div(total)
+            .map(|rate| rate as f64)
+            .unwrap_or(0.0)
     }
 
     /// reset stats
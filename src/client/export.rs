@@ -0,0 +1,402 @@
+//! resumable downloading of e6 db exports
+use {
+    crate::{client::E6Client, data::manifest::sha256_file},
+    color_eyre::eyre::{Context, Result, bail},
+    flate2::read::GzDecoder,
+    futures::StreamExt,
+    reqwest::{StatusCode, header::RANGE},
+    std::{
+        fs::File,
+        io::{BufReader, BufWriter, Write},
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    },
+    tokio::{
+        fs::{self, OpenOptions},
+        io::AsyncWriteExt,
+    },
+    tracing::{debug, info, warn},
+};
+
+/// how many decompressed bytes are held in memory at once while decompressing an export
+const DECOMPRESS_BUFFER_SIZE: usize = 64 * 1024;
+
+/// receives byte progress while a db export is downloading
+pub trait ExportProgress: Send + Sync {
+    /// called when an export starts (or resumes) downloading
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - the name of the export
+    /// * `downloaded` - bytes already on disk from a previous attempt
+    /// * `total` - the full size of the export (0 if unknown)
+    fn begin(&self, label: &str, downloaded: u64, total: u64);
+
+    /// called whenever more bytes have been written to disk
+    ///
+    /// # Arguments
+    ///
+    /// * `downloaded` - total bytes on disk so far
+    fn advance(&self, downloaded: u64);
+
+    /// called when an export is done downloading and moves on to another step
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - the name of the export
+    /// * `phase` - what it's doing now, like `Unpacking`
+    fn phase(&self, _label: &str, _phase: &str) {}
+}
+
+impl ExportProgress for () {
+    fn begin(&self, _label: &str, _downloaded: u64, _total: u64) {}
+    fn advance(&self, _downloaded: u64) {}
+}
+
+#[cfg(feature = "cli")]
+impl ExportProgress for indicatif::ProgressBar {
+    fn begin(&self, label: &str, downloaded: u64, total: u64) {
+        self.set_length(total);
+        self.set_position(downloaded);
+        self.set_message(format!("Downloading {}", label));
+    }
+
+    fn advance(&self, downloaded: u64) {
+        self.set_position(downloaded);
+    }
+
+    fn phase(&self, label: &str, phase: &str) {
+        self.set_message(format!("{} {}", phase, label));
+    }
+}
+
+/// get the path of the partial download for an export url
+///
+/// the remote filename (which includes the export date) is part of the path so a partial
+/// download is never resumed against a different day's export
+fn part_path(local_file: &str, url: &str) -> PathBuf {
+    let remote_name = url.rsplit('/').next().unwrap_or("export.csv.gz");
+    PathBuf::from(format!("{}.{}.part", local_file, remote_name))
+}
+
+/// remove partial downloads left behind by older exports
+async fn remove_stale_parts(local_file: &str, current: &Path) {
+    let local = Path::new(local_file);
+    let Some(prefix) = local.file_name().and_then(|n| n.to_str()) else {
+        return;
+    };
+    let dir = match local.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return;
+    };
+
+    let prefix = format!("{}.", prefix);
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let is_stale = path.file_name() != current.file_name()
+            && path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".part"));
+
+        if is_stale && let Err(e) = fs::remove_file(&path).await {
+            warn!(
+                "failed to remove stale partial download '{}': {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// stream-decompress a gzipped csv to `dest` and make sure every row parses
+///
+/// the csv is decompressed [`DECOMPRESS_BUFFER_SIZE`] bytes at a time, so memory use doesn't
+/// grow with the size of the export
+///
+/// returns the number of data rows in the csv
+fn decompress_and_verify(src: &Path, dest: &Path) -> Result<u64> {
+    let mut decoder = GzDecoder::new(BufReader::new(File::open(src)?));
+    let mut out = BufWriter::with_capacity(DECOMPRESS_BUFFER_SIZE, File::create(dest)?);
+    let written = std::io::copy(&mut decoder, &mut out)
+        .with_context(|| format!("failed to decompress export to '{}'", dest.display()))?;
+
+    debug!(bytes = written, path = %dest.display(), "decompressed export");
+    out.flush()?;
+    out.get_ref().sync_all()?;
+    drop(out);
+
+    let mut rdr = csv::Reader::from_path(dest).context("failed to open decompressed csv")?;
+    if rdr.headers()?.is_empty() {
+        bail!("csv has no header row");
+    }
+
+    let mut rows = 0u64;
+    for record in rdr.records() {
+        record.with_context(|| format!("row {} failed to parse", rows + 1))?;
+        rows += 1;
+    }
+
+    if rows == 0 {
+        bail!("csv has no rows");
+    }
+
+    Ok(rows)
+}
+
+impl E6Client {
+    /// download a db export and replace `local_file` with its decompressed contents
+    ///
+    /// the compressed export is streamed to a `.part` file next to `local_file`, so an
+    /// interrupted download picks up where it left off on the next run. the csv is
+    /// decompressed to a temp file and fully parsed before it replaces the existing one, so a
+    /// truncated download can't corrupt the db
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - the url of the gzipped export
+    /// * `local_file` - where the decompressed csv lives
+    /// * `label` - a name for the export, used for logging and progress
+    /// * `rate_limit` - max download speed in KiB/s (0 = unlimited)
+    /// * `progress` - receives byte progress while downloading
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the export can't be downloaded, the downloaded export is corrupt, or
+    /// the csv can't be replaced. the existing file is left untouched in every case
+    pub async fn update_db_export(
+        &self,
+        url: &str,
+        local_file: &str,
+        label: &str,
+        rate_limit: u64,
+        progress: &impl ExportProgress,
+    ) -> Result<()> {
+        let hash_file = format!("{}.hash", local_file);
+        self.update_db_export_hashed(url, local_file, &hash_file, label, rate_limit, progress)
+            .await
+    }
+
+    /// download a db export and replace `local_file` with its decompressed contents, without
+    /// progress or a rate limit
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - the url of the gzipped export
+    /// * `local_file` - where the decompressed csv lives
+    /// * `hash_file` - where the hash of the last downloaded export is kept
+    /// * `file_type` - a name for the export, used for logging
+    #[deprecated(note = "use `E6Client::update_db_export`, which keeps the hash next to the csv")]
+    pub async fn download_and_update_file(
+        &self,
+        url: &str,
+        local_file: &str,
+        hash_file: &str,
+        file_type: &str,
+    ) -> Result<()> {
+        self.update_db_export_hashed(url, local_file, hash_file, file_type, 0, &())
+            .await
+    }
+
+    /// the body of [`E6Client::update_db_export`], with the hash file given explicitly
+    async fn update_db_export_hashed(
+        &self,
+        url: &str,
+        local_file: &str,
+        hash_file: &str,
+        label: &str,
+        rate_limit: u64,
+        progress: &impl ExportProgress,
+    ) -> Result<()> {
+        let part_file = part_path(local_file, url);
+
+        if let Some(parent) = Path::new(local_file).parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).await?;
+        }
+
+        remove_stale_parts(local_file, &part_file).await;
+
+        info!(label, "checking for updates");
+
+        self.download_export_part(url, &part_file, label, rate_limit, progress)
+            .await
+            .with_context(|| format!("couldn't download the {} export", label))?;
+
+        progress.phase(label, "Verifying");
+        let part = part_file.clone();
+        let remote_hash_hex = tokio::task::spawn_blocking(move || sha256_file(&part)).await??;
+
+        let update_needed = match fs::read_to_string(hash_file).await {
+            Ok(local_hash) => local_hash.trim() != remote_hash_hex,
+            Err(_) => true,
+        };
+
+        if !update_needed {
+            let _ = fs::remove_file(&part_file).await;
+            info!(label, "✓ Already up to date");
+            return Ok(());
+        }
+
+        info!(label, "Updating local snapshot");
+        progress.phase(label, "Unpacking");
+
+        let temp_file = PathBuf::from(format!("{}.tmp", local_file));
+        let (part, temp) = (part_file.clone(), temp_file.clone());
+        let rows =
+            match tokio::task::spawn_blocking(move || decompress_and_verify(&part, &temp)).await? {
+                Ok(rows) => rows,
+                Err(e) => {
+                    let _ = fs::remove_file(&temp_file).await;
+                    let _ = fs::remove_file(&part_file).await;
+                    return Err(e.wrap_err(format!(
+                        "{} export is corrupt, keeping the existing file",
+                        label
+                    )));
+                }
+            };
+
+        fs::rename(&temp_file, local_file).await?;
+        fs::write(hash_file, remote_hash_hex.as_bytes()).await?;
+        let _ = fs::remove_file(&part_file).await;
+
+        info!(label, rows, path = %local_file, "✓ Updated");
+        Ok(())
+    }
+
+    /// download an export to `part_file`, resuming from its current length if it exists
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - the url of the gzipped export
+    /// * `part_file` - where the compressed bytes are written
+    /// * `label` - a name for the export, used for logging and progress
+    /// * `rate_limit` - max download speed in KiB/s (0 = unlimited)
+    /// * `progress` - receives byte progress while downloading
+    async fn download_export_part(
+        &self,
+        url: &str,
+        part_file: &Path,
+        label: &str,
+        rate_limit: u64,
+        progress: &impl ExportProgress,
+    ) -> Result<()> {
+        let mut resume_from = fs::metadata(part_file).await.map(|m| m.len()).unwrap_or(0);
+
+        let mut request = self.client.get(url);
+        if resume_from > 0 {
+            debug!(label, resume_from, "resuming partial download");
+            request = request.header(RANGE, format!("bytes={}-", resume_from));
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("failed to fetch {}", label))?;
+        let status = response.status();
+
+        if status == StatusCode::RANGE_NOT_SATISFIABLE {
+            debug!(label, "partial download is already complete");
+            return Ok(());
+        }
+
+        if !status.is_success() {
+            bail!("failed to download {}: http {}", label, status);
+        }
+
+        let append = resume_from > 0 && status == StatusCode::PARTIAL_CONTENT;
+        if !append {
+            resume_from = 0;
+        }
+
+        let total = response
+            .content_length()
+            .map(|len| len + resume_from)
+            .unwrap_or(0);
+        progress.begin(label, resume_from, total);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(part_file)
+            .await
+            .with_context(|| format!("failed to open '{}'", part_file.display()))?;
+
+        let mut stream = response.bytes_stream();
+        let mut downloaded = resume_from;
+        let mut session_bytes = 0u64;
+        let started = Instant::now();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.with_context(|| format!("error reading {} export", label))?;
+
+            file.write_all(&chunk)
+                .await
+                .with_context(|| format!("error writing to '{}'", part_file.display()))?;
+
+            downloaded += chunk.len() as u64;
+            session_bytes += chunk.len() as u64;
+            progress.advance(downloaded);
+
+            if rate_limit > 0 {
+                let expected =
+                    Duration::from_secs_f64(session_bytes as f64 / (rate_limit * 1024) as f64);
+                let elapsed = started.elapsed();
+
+                if expected > elapsed {
+                    tokio::time::sleep(expected - elapsed).await;
+                }
+            }
+        }
+
+        file.flush().await?;
+        file.sync_all().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        flate2::{Compression, write::GzEncoder},
+        tempfile::TempDir,
+    };
+
+    /// gzip `contents` into a file
+    fn write_gz(path: &Path, contents: &[u8]) {
+        let mut encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        encoder.write_all(contents).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    #[test]
+    fn test_decompress_and_verify() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("tags.csv.gz");
+        let dest = dir.path().join("tags.csv");
+
+        let mut csv = String::from("id,name,category,post_count\n");
+        for i in 0..10_000 {
+            csv.push_str(&format!("{},tag_{},0,{}\n", i, i, i * 3));
+        }
+        write_gz(&src, csv.as_bytes());
+
+        assert_eq!(decompress_and_verify(&src, &dest).unwrap(), 10_000);
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), csv);
+
+        let bytes = std::fs::read(&src).unwrap();
+        std::fs::write(&src, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(decompress_and_verify(&src, &dest).is_err());
+
+        write_gz(&src, b"id,name,category,post_count\n");
+        assert!(decompress_and_verify(&src, &dest).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! client extensions for flagging posts on the e6 api
+use {
+    crate::{client::E6Client, models::E6PostFlag},
+    color_eyre::eyre::{Context, Result, bail},
+    serde_json::Value,
+    tracing::{info, instrument},
+};
+
+/// why a post is being flagged
+///
+/// these are the reasons the site accepts, anything else is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagReason {
+    /// the artist is on the avoid posting list
+    DnpArtist,
+    /// paysite, commercial, or subscription content
+    PayContent,
+    /// a trace of another artist's work
+    Trace,
+    /// the post was deleted before
+    PreviouslyDeleted,
+    /// real-life pornography
+    RealPorn,
+    /// the file is corrupted or doesn't work
+    Corrupt,
+    /// a duplicate or inferior version of another post
+    Inferior {
+        /// the better post
+        parent_id: i64,
+    },
+    /// breaks the uploading guidelines
+    UploadingGuidelines,
+}
+
+impl FlagReason {
+    /// every reason, with `0` standing in for the parent of [`FlagReason::Inferior`]
+    pub const ALL: [FlagReason; 8] = [
+        FlagReason::DnpArtist,
+        FlagReason::PayContent,
+        FlagReason::Trace,
+        FlagReason::PreviouslyDeleted,
+        FlagReason::RealPorn,
+        FlagReason::Corrupt,
+        FlagReason::Inferior { parent_id: 0 },
+        FlagReason::UploadingGuidelines,
+    ];
+
+    /// the name the api knows the reason by
+    pub fn name(&self) -> &'static str {
+        match self {
+            FlagReason::DnpArtist => "dnp_artist",
+            FlagReason::PayContent => "pay_content",
+            FlagReason::Trace => "trace",
+            FlagReason::PreviouslyDeleted => "previously_deleted",
+            FlagReason::RealPorn => "real_porn",
+            FlagReason::Corrupt => "corrupt",
+            FlagReason::Inferior { .. } => "inferior",
+            FlagReason::UploadingGuidelines => "uploading_guidelines",
+        }
+    }
+
+    /// a short description of the reason for menus
+    pub fn describe(&self) -> &'static str {
+        match self {
+            FlagReason::DnpArtist => "The artist is on the avoid posting list",
+            FlagReason::PayContent => "Paysite, commercial, or subscription content",
+            FlagReason::Trace => "Trace of another artist's work",
+            FlagReason::PreviouslyDeleted => "Previously deleted",
+            FlagReason::RealPorn => "Real-life pornography",
+            FlagReason::Corrupt => "The file is corrupted, broken, or doesn't work",
+            FlagReason::Inferior { .. } => "Duplicate or inferior version of another post",
+            FlagReason::UploadingGuidelines => "Doesn't meet the uploading guidelines",
+        }
+    }
+}
+
+/// pull a readable message out of an api error response
+///
+/// the api reports errors as `{"message": ...}`, `{"reason": ...}`, or
+/// `{"errors": {"field": ["..."]}}` depending on the endpoint
+///
+/// # Arguments
+///
+/// * `body` - the body of the failed response
+pub fn api_error_message(body: &[u8]) -> Option<String> {
+    let value: Value = serde_json::from_slice(body).ok()?;
+
+    for key in ["message", "reason"] {
+        if let Some(message) = value.get(key).and_then(Value::as_str)
+            && !message.is_empty()
+        {
+            return Some(message.to_string());
+        }
+    }
+
+    let messages: Vec<String> = match value.get("errors")? {
+        Value::Object(fields) => fields
+            .iter()
+            .flat_map(|(field, errors)| {
+                let errors = match errors {
+                    Value::Array(errors) => errors.iter().filter_map(Value::as_str).collect(),
+                    Value::String(error) => vec![error.as_str()],
+                    _ => Vec::new(),
+                };
+                errors
+                    .into_iter()
+                    .map(move |error| format!("{} {}", field.replace('_', " "), error))
+            })
+            .collect(),
+        Value::Array(errors) => errors
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Value::String(error) => vec![error.clone()],
+        _ => Vec::new(),
+    };
+
+    (!messages.is_empty()).then(|| messages.join(", "))
+}
+
+impl E6Client {
+    #[instrument(skip(self))]
+    /// flag a post for the moderators
+    ///
+    /// needs to be logged in
+    ///
+    /// # Arguments
+    ///
+    /// * `post_id` - the post to flag
+    /// * `reason` - why it's being flagged
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the post is flagged as inferior to itself, the request fails, or the
+    /// api rejects the flag (with the api's reason)
+    pub async fn flag_post(&self, post_id: i64, reason: FlagReason) -> Result<E6PostFlag> {
+        let mut form = vec![
+            ("post_flag[post_id]", post_id.to_string()),
+            ("post_flag[reason_name]", reason.name().to_string()),
+        ];
+
+        if let FlagReason::Inferior { parent_id } = reason {
+            if parent_id <= 0 || parent_id == post_id {
+                bail!("a post can only be flagged as inferior to a different post");
+            }
+            form.push(("post_flag[parent_id]", parent_id.to_string()));
+        }
+
+        let url = format!("{}/post_flags.json", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .form(&form)
+            .send()
+            .await
+            .with_context(|| format!("failed to flag post {}", post_id))?;
+        let status = response.status();
+        let body = response
+            .bytes()
+            .await
+            .with_context(|| format!("failed to read the response to flagging post {}", post_id))?;
+
+        if !status.is_success() {
+            match api_error_message(&body) {
+                Some(message) => bail!("failed to flag post {}: {}", post_id, message),
+                None => bail!("failed to flag post {}: http {}", post_id, status),
+            }
+        }
+
+        let flag: E6PostFlag = serde_json::from_slice(&body)
+            .with_context(|| format!("failed to deserialize the flag on post {}", post_id))?;
+        info!(
+            post_id,
+            flag_id = flag.id,
+            reason = reason.name(),
+            "flagged post"
+        );
+
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_message() {
+        assert_eq!(
+            api_error_message(br#"{"success":false,"reason":"Access Denied"}"#).as_deref(),
+            Some("Access Denied")
+        );
+        assert_eq!(
+            api_error_message(br#"{"errors":{"post_id":["has already been flagged"]}}"#).as_deref(),
+            Some("post id has already been flagged")
+        );
+        assert_eq!(
+            api_error_message(br#"{"errors":["Parent must exist"]}"#).as_deref(),
+            Some("Parent must exist")
+        );
+        assert_eq!(api_error_message(b"<html>bad gateway</html>"), None);
+        assert_eq!(api_error_message(br#"{"errors":{}}"#), None);
+    }
+
+    #[tokio::test]
+    async fn test_flag_post_sends_auth_header() {
+        use {
+            crate::client::E6ClientConfig,
+            tokio::{
+                io::{AsyncReadExt, AsyncWriteExt},
+                net::TcpListener,
+            },
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            let body = r#"{"id":7,"post_id":1,"reason":"corrupt","is_resolved":false}"#;
+            let response = format!(
+                "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_lowercase()
+        });
+
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let mut config = E6ClientConfig {
+            base_url: format!("http://{}", addr),
+            login: Some(("user".to_string(), "key".to_string())),
+            ..Default::default()
+        };
+        config.cache_config.enabled = Some(false);
+        config.cache_config.cache_dir = Some(cache_dir.path().to_string_lossy().to_string());
+        config.cache_config.posts.get_or_insert_default().enabled = Some(false);
+
+        let client = E6Client::with_config(config).unwrap();
+        let flag = client.flag_post(1, FlagReason::Corrupt).await.unwrap();
+        assert_eq!(flag.id, 7);
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("post /post_flags.json"));
+        assert!(request.contains("authorization: basic dxnlcjprzxk="));
+    }
+}
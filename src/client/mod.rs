@@ -2,6 +2,7 @@
 use {
     crate::{
         cache::{
+            NO_CACHE,
             posts::{CacheEntry, PostCache},
             stats::CacheStats,
         },
@@ -10,15 +11,42 @@ use {
     },
     color_eyre::eyre::Context,
     hashbrown::HashMap,
-    reqwest::Client,
-    std::{sync::Arc, time::Duration},
+    reqwest::{Client, StatusCode},
+    std::{
+        sync::{Arc, atomic::Ordering},
+        time::Duration,
+    },
     tokio::sync::RwLock,
     tracing::{debug, info, warn},
 };
 
+pub mod export;
+pub mod flags;
 pub mod pools;
 pub mod posts;
 
+/// how long to wait before the given retry of a failed request
+///
+/// doubles every attempt, starting at 400ms and topping out at 6.4s
+///
+/// # Arguments
+///
+/// * `attempt` - which retry this is, starting at 1
+pub fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_millis(200 * 2_u64.pow(attempt.min(5)))
+}
+
+/// whether a response status is worth retrying the request for
+///
+/// server errors and rate limiting usually go away on their own, anything else won't
+///
+/// # Arguments
+///
+/// * `status` - the status of the response
+pub fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
 /// configuration for constructing an [`E6Client`]
 #[derive(Clone, Debug)]
 pub struct E6ClientConfig {
@@ -30,6 +58,8 @@ pub struct E6ClientConfig {
     pub timeout: u64,
     /// connection timeout in seconds
     pub connect_timeout: u64,
+    /// how many times to retry an api request that fails to connect or gets a server error
+    pub max_retries: u32,
     /// max idle connections per host
     pub pool_max_idle_per_host: usize,
     /// pool idle timeout in seconds
@@ -59,6 +89,7 @@ impl Default for E6ClientConfig {
             ),
             timeout: 30,
             connect_timeout: 10,
+            max_retries: 2,
             pool_max_idle_per_host: 32,
             pool_idle_timeout: 90,
             http2: false,
@@ -77,6 +108,8 @@ pub struct E6Client {
     pub client: Client,
     /// the base url for api requests
     pub base_url: String,
+    /// how many times to retry an api request that fails to connect or gets a server error
+    pub max_retries: u32,
     /// the http cache
     pub cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
     /// the cache configuration
@@ -95,11 +128,17 @@ impl Default for E6Client {
 
 impl E6Client {
     /// make a new e621 api client from explicit configuration
-    pub fn with_config(config: E6ClientConfig) -> Result<Self> {
+    ///
+    /// both caches are turned off if [`NO_CACHE`] is set
+    pub fn with_config(mut config: E6ClientConfig) -> Result<Self> {
+        if NO_CACHE.load(Ordering::Relaxed) {
+            config.cache_config.enabled = Some(false);
+            config.cache_config.posts.get_or_insert_default().enabled = Some(false);
+        }
+
         let client = Self::build_http_client_with(&config)?;
 
-        let cache_dir = config.cache_config.cache_dir.clone()
-            .unwrap_or_else(|| ".cache".to_string());
+        let cache_dir = config.cache_config.resolved_cache_dir();
         let posts_enabled = config.cache_config.posts.as_ref()
             .and_then(|p| p.enabled)
             .unwrap_or(true);
@@ -113,14 +152,18 @@ impl E6Client {
         let compact_threshold = config.cache_config.posts.as_ref()
             .and_then(|p| p.compact_threshold)
             .unwrap_or(25);
+        let post_ttl = config.cache_config.posts.as_ref()
+            .and_then(|p| p.ttl_secs)
+            .unwrap_or(86400);
 
         let post_cache = PostCache::with_config(
-            &cache_dir,
+            &cache_dir.to_string_lossy(),
             posts_enabled,
             max_size_mb,
             max_posts,
             auto_compact,
             compact_threshold,
+            post_ttl,
         )?;
 
         info!("initialized http client");
@@ -131,6 +174,7 @@ impl E6Client {
         let client = Self {
             client,
             base_url: config.base_url,
+            max_retries: config.max_retries,
             cache: Arc::new(RwLock::new(HashMap::new())),
             cache_config: config.cache_config,
             cache_stats: Arc::new(CacheStats::default()),
@@ -156,22 +200,25 @@ impl E6Client {
     }
 
     /// make a new e621 api client from the loaded configuration
+    ///
+    /// requests go to `http.api`, so the site matches the blacklist additions that are used
     #[cfg(feature = "cli")]
     pub fn new() -> Result<Self> {
         use crate::getopt;
 
         let config = E6ClientConfig {
-            base_url: "https://e621.net".to_string(),
+            base_url: getopt!(http.api).trim_end_matches('/').to_string(),
             user_agent: getopt!(http.user_agent),
             timeout: getopt!(http.timeout),
             connect_timeout: getopt!(http.connect_timeout),
+            max_retries: getopt!(http.max_retries),
             pool_max_idle_per_host: getopt!(http.pool_max_idle_per_host),
             pool_idle_timeout: getopt!(http.pool_idle_timeout),
             http2: getopt!(http.http2),
             tcp_keepalive: getopt!(http.tcp_keepalive),
             tcp_keepalive_secs: getopt!(http.tcp_keepalive_secs),
             login: if getopt!(login.login) {
-                Some((getopt!(login.username), getopt!(login.api_key)))
+                Some((getopt!(login.username), getopt!(login).resolve_api_key()?))
             } else {
                 None
             },
@@ -181,6 +228,22 @@ impl E6Client {
         Self::with_config(config)
     }
 
+    /// open a connection to the api in the background
+    ///
+    /// sends a `HEAD` request to `base_url` so the tls handshake is done and the connection is
+    /// in the pool before the first real request. failures are only logged
+    pub fn warmup(&self) {
+        let client = self.client.clone();
+        let url = self.base_url.clone();
+
+        tokio::spawn(async move {
+            match client.head(&url).send().await {
+                Ok(response) => debug!(status = %response.status(), "warmed up connection"),
+                Err(e) => debug!(error = %e, "connection warmup failed"),
+            }
+        });
+    }
+
     /// build an http client from explicit configuration
     fn build_http_client_with(config: &E6ClientConfig) -> Result<Client> {
         let mut client_builder = Client::builder()
@@ -231,7 +294,7 @@ impl E6Client {
                     attempts += 1;
 
                     if attempts <= max_retries {
-                        let delay = Duration::from_millis(200 * 2_u64.pow(attempts.min(5)));
+                        let delay = retry_delay(attempts);
                         debug!("retry attempt {} after {:?}", attempts, delay);
                         tokio::time::sleep(delay).await;
                     }
@@ -1,25 +1,40 @@
 //! client extensions for pool operations on the e6 api
 use {
     crate::{
-        client::E6Client,
+        client::{E6Client, export::ExportProgress},
         error::{Report, Result},
-        models::{E6PoolResponse, E6PoolsResponse, E6PostsResponse},
+        models::{E6PoolResponse, E6PoolsResponse, E6Post, E6PostsResponse},
     },
     chrono::{Datelike, Days, Local},
     color_eyre::eyre::Context,
-    flate2::read::GzDecoder,
-    sha2::{Digest, Sha256},
-    std::{io::Read, path::Path},
-    tokio::fs,
-    tracing::{debug, info, instrument, warn},
+    hashbrown::HashSet,
+    tracing::{debug, instrument},
 };
 
+/// how many posts to request per page when fetching a whole pool
+const POOL_PAGE_SIZE: u64 = 320;
+
 impl E6Client {
     #[instrument(skip(self), name = "update_pools_with")]
     /// update the local pool database using an explicit file path
     pub async fn update_pools_with(&self, local_file: &str) -> Result<()> {
-        let local_hash_file = format!("{}.hash", local_file);
+        self.update_pools_with_progress(local_file, 0, &()).await
+    }
 
+    #[instrument(skip(self, progress), name = "update_pools_with_progress")]
+    /// update the local pool database using an explicit file path, a rate limit, and progress
+    ///
+    /// # Arguments
+    ///
+    /// * `local_file` - path to the pools csv
+    /// * `rate_limit` - max download speed in KiB/s (0 = unlimited)
+    /// * `progress` - receives byte progress while downloading
+    pub async fn update_pools_with_progress(
+        &self,
+        local_file: &str,
+        rate_limit: u64,
+        progress: &impl ExportProgress,
+    ) -> Result<()> {
         let now = Local::now()
             .checked_sub_days(Days::new(1))
             .unwrap_or(Local::now());
@@ -30,75 +45,40 @@ impl E6Client {
             now.day()
         );
 
-        self.download_and_update_file(&url, local_file, &local_hash_file, "pools")
-            .await
+        self.update_db_export(&url, local_file, "pools", rate_limit, progress)
+            .await?;
+
+        Ok(())
     }
 
     /// update the local pool database using the configured path
     #[cfg(feature = "cli")]
     #[instrument(skip(self), name = "update_pools")]
     pub async fn update_pools(&self) -> Result<()> {
-        self.update_pools_with(&crate::getopt!(completion.pools)).await
+        let progress = crate::ui::progress::ProgressManager::new();
+        let pb = progress
+            .mk_dl_bar("pool_exports", 0, "Checking pools")
+            .await?;
+        let res = self.update_pools_reporting(&pb).await;
+
+        progress.remove_bar("pool_exports").await;
+        res
     }
 
-    /// generic file download with hash-based update check
-    pub async fn download_and_update_file(
-        &self,
-        url: &str,
-        local_file: &str,
-        hash_file: &str,
-        file_type: &str,
-    ) -> Result<()> {
-        let response = match self
-            .client
-            .get(url)
-            .send()
-            .await
-            .context(format!("failed to fetch {}", file_type))
-        {
-            Ok(r) => r,
-            Err(e) => {
-                warn!("couldn't download the update: {}", e);
-                return Ok(());
-            }
-        };
-
-        let remote_bytes = response.bytes().await?;
-        let remote_hash_hex = {
-            let mut hasher = Sha256::new();
-            hasher.update(&remote_bytes);
-            hex::encode(hasher.finalize())
-        };
-
-        let update_needed = match fs::read_to_string(hash_file).await {
-            Ok(local_hash) => local_hash.trim() != remote_hash_hex,
-            Err(_) => true,
-        };
-
-        if !update_needed {
-            info!(file_type, "Local snapshot is up to date");
-            return Ok(());
-        }
-
-        info!(file_type, "Updating local snapshot");
-
-        let mut gz = GzDecoder::new(&remote_bytes[..]);
-        let mut decompressed = Vec::new();
-        gz.read_to_end(&mut decompressed)
-            .with_context(|| format!("Failed to decompress {}", file_type))?;
-
-        if let Some(parent) = Path::new(local_file).parent() {
-            fs::create_dir_all(parent).await?;
-        }
-
-        let temp_file = format!("{}.tmp", local_file);
-
-        fs::write(&temp_file, &decompressed).await?;
-        fs::rename(&temp_file, local_file).await?;
-        fs::write(hash_file, remote_hash_hex.as_bytes()).await?;
-
-        info!(file_type, path = local_file, "Updated local snapshot");
-        Ok(())
+    /// update the local pool database using the configured path, reporting progress somewhere
+    /// other than a progress bar
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - receives byte progress while downloading
+    #[cfg(feature = "cli")]
+    pub async fn update_pools_reporting(&self, progress: &impl ExportProgress) -> Result<()> {
+        self.update_pools_with_progress(
+            &crate::getopt!(completion.pools),
+            crate::getopt!(autoupdate.rate_limit),
+            progress,
+        )
+        .await
     }
 
     #[instrument(skip(self), fields(limit))]
@@ -137,6 +117,68 @@ impl E6Client {
         Ok(posts)
     }
 
+    /// get every post in a pool, paging through the results with `before_id`
+    ///
+    /// `is_cancelled` is checked before each page, so a large pool can be aborted part way
+    /// through. the posts loaded so far are returned along with whether the fetch was cancelled
+    ///
+    /// # Arguments
+    ///
+    /// * `pool_id` - the id of the pool
+    /// * `on_page` - called with the number of posts loaded so far after each page
+    /// * `is_cancelled` - returns true if the fetch should stop early
+    ///
+    /// # Errors
+    ///
+    /// returns an error if a page can't be fetched or deserialized
+    #[instrument(skip(self, on_page, is_cancelled))]
+    pub async fn get_all_pool_posts(
+        &self,
+        pool_id: i64,
+        on_page: impl Fn(usize),
+        is_cancelled: impl Fn() -> bool,
+    ) -> Result<(Vec<E6Post>, bool)> {
+        let mut posts: Vec<E6Post> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut before_id: Option<i64> = None;
+
+        loop {
+            if is_cancelled() {
+                debug!(pool_id, count = posts.len(), "Pool post fetch cancelled");
+                return Ok((posts, true));
+            }
+
+            let mut url = format!(
+                "{}/posts.json?tags=pool:{}&limit={}",
+                self.base_url, pool_id, POOL_PAGE_SIZE
+            );
+            if let Some(before_id) = before_id {
+                url.push_str(&format!("&page=b{}", before_id));
+            }
+
+            let bytes = self.get_cached_or_fetch(&url).await?;
+            let page: E6PostsResponse = serde_json::from_slice(&bytes)
+                .with_context(|| format!("Failed to deserialize pool {} posts", pool_id))?;
+
+            let page_len = page.posts.len();
+            let Some(min_id) = page.posts.iter().map(|p| p.id).min() else {
+                break;
+            };
+
+            posts.extend(page.posts.into_iter().filter(|p| seen.insert(p.id)));
+            on_page(posts.len());
+
+            if page_len < POOL_PAGE_SIZE as usize || before_id == Some(min_id) {
+                break;
+            }
+
+            before_id = Some(min_id);
+        }
+
+        debug!(pool_id, count = posts.len(), "Fetched all pool posts");
+        Ok((posts, false))
+    }
+
     #[instrument(skip(self))]
     /// search for pools
     pub async fn search_pools(&self, query: &str, limit: Option<u64>) -> Result<E6PoolsResponse> {
@@ -1,18 +1,26 @@
 //! client extensions for post operations on the e6 api
 use {
     crate::{
-        client::E6Client,
-        models::{E6PostResponse, E6PostsResponse},
+        client::{E6Client, export::ExportProgress},
+        models::{E6Post, E6PostResponse, E6PostsResponse, PostTree},
     },
     chrono::{Datelike, Days, Local},
     color_eyre::eyre::{Context, Result, bail},
-    flate2::read::GzDecoder,
-    sha2::{Digest, Sha256},
-    std::{io::Read, path::Path},
-    tokio::{fs, sync::Semaphore},
+    futures::{Stream, StreamExt, stream},
+    hashbrown::{HashMap, HashSet},
+    tokio::sync::Semaphore,
     tracing::{debug, info, instrument, warn},
 };
 
+/// the most parents walked up, or levels of children walked down, when building a post tree
+const MAX_TREE_DEPTH: usize = 16;
+
+/// the most children fetched for a single post when building a post tree
+const MAX_TREE_CHILDREN: u64 = 100;
+
+/// the most posts in a post tree
+const MAX_TREE_POSTS: usize = 500;
+
 impl E6Client {
     /// try to get the latest posts, filtering with the given blacklist
     ///
@@ -47,7 +55,8 @@ impl E6Client {
     /// try to get the latest posts using the configured blacklist
     #[cfg(feature = "cli")]
     pub async fn get_latest_posts(&self) -> Result<E6PostsResponse> {
-        self.get_latest_posts_with(&crate::getopt!(search.blacklist)).await
+        self.get_latest_posts_with(&crate::config::blacklist::active_blacklist())
+            .await
     }
 
     #[instrument(skip(self, tags, blacklist))]
@@ -115,22 +124,107 @@ impl E6Client {
         limit: Option<u64>,
         page_before_id: Option<i64>,
     ) -> Result<E6PostsResponse> {
-        self.search_posts_with(tags, limit, page_before_id, &crate::getopt!(search.blacklist))
-            .await
+        self.search_posts_with(
+            tags,
+            limit,
+            page_before_id,
+            &crate::config::blacklist::active_blacklist(),
+        )
+        .await
+    }
+
+    /// stream the posts of a search one at a time, filtering with the given blacklist
+    ///
+    /// pages of up to 320 posts are fetched as the stream is polled, so the first posts can be
+    /// used before the rest are fetched. the stream ends after `limit` posts, `max_pages`
+    /// requests, the last page, or the first failed request (which is yielded as an error)
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - the search tags
+    /// * `limit` - the most posts to yield
+    /// * `blacklist` - the blacklist rules to filter against
+    /// * `max_pages` - the most requests to make
+    pub fn search_posts_stream<'a>(
+        &'a self,
+        tags: &'a [String],
+        limit: u64,
+        blacklist: &'a [String],
+        max_pages: u64,
+    ) -> impl Stream<Item = Result<E6Post>> + 'a {
+        /// how far into the search the stream is
+        struct Cursor {
+            /// only fetch posts older than this one
+            before_id: Option<i64>,
+            /// the number of requests made
+            pages: u64,
+            /// whether there's nothing left to fetch
+            done: bool,
+        }
+
+        const PAGE_SIZE: u64 = 320;
+
+        let cursor = Cursor {
+            before_id: None,
+            pages: 0,
+            done: limit == 0,
+        };
+
+        stream::unfold(cursor, move |mut cursor| async move {
+            if cursor.done || cursor.pages >= max_pages {
+                return None;
+            }
+            cursor.pages += 1;
+
+            let page = match self
+                .search_posts_with(tags, Some(PAGE_SIZE), cursor.before_id, &[])
+                .await
+            {
+                Ok(page) => page,
+                Err(e) => {
+                    cursor.done = true;
+                    return Some((vec![Err(e)], cursor));
+                }
+            };
+
+            cursor.done = (page.posts.len() as u64) < PAGE_SIZE;
+            cursor.before_id = page.posts.iter().map(|p| p.id).min();
+            if cursor.before_id.is_none() {
+                cursor.done = true;
+            }
+
+            let posts = page.filter_blacklisted_by(tags, blacklist).posts;
+            Some((posts.into_iter().map(Ok).collect::<Vec<_>>(), cursor))
+        })
+        .flat_map(stream::iter)
+        .take(limit as usize)
     }
 
     #[instrument(skip(self))]
     /// get a post by its id
+    ///
+    /// posts cached within `cache.posts.ttl_secs` are returned without a request. if the request
+    /// fails, an older cached copy is returned instead when there is one
     pub async fn get_post_by_id(&self, id: i64) -> Result<E6PostResponse> {
-        if let Ok(Some(cached_post)) = self.post_cache.get(id).await {
+        if let Ok(Some(cached_post)) = self.post_cache.get_fresh(id).await {
             debug!(id, "Post retrieved from cache");
+            self.post_cache.record_id_lookup(true);
             return Ok(E6PostResponse { post: cached_post });
         }
+        self.post_cache.record_id_lookup(false);
 
         let url = format!("{}/posts/{}.json", self.base_url, id);
-        let bytes = self.get_cached_or_fetch(&url).await?;
-        let post: E6PostResponse =
-            serde_json::from_slice(&bytes).context(format!("Failed to deserialize post {}", id))?;
+        let post: E6PostResponse = match self.get_cached_or_fetch(&url).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .context(format!("Failed to deserialize post {}", id))?,
+            Err(e) => match self.post_cache.get(id).await {
+                Ok(Some(stale)) => {
+                    warn!(id, error = %e, "Failed to fetch post, using cached copy");
+                    return Ok(E6PostResponse { post: stale });
+                }
+                _ => return Err(e.into()),
+            },
+        };
         let cache = self.post_cache.clone();
         let post_clone = post.post.clone();
 
@@ -143,7 +237,140 @@ impl E6Client {
         Ok(post)
     }
 
+    #[instrument(skip(self))]
+    /// get the whole parent/child tree a post is in
+    ///
+    /// walks up to the root parent with `id:` lookups, then down through every child with
+    /// `parent:` searches. returns the tree from the root and whether it was cut short by the
+    /// depth, children, or total post caps. posts aren't blacklist filtered, since the tree is
+    /// an exact lookup
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - any post in the tree
+    pub async fn get_post_tree(&self, id: i64) -> Result<(PostTree, bool)> {
+        let mut truncated = false;
+        let mut root = self.get_post_by_id(id).await?.post;
+        let mut ancestors: HashSet<i64> = HashSet::from([root.id]);
+
+        while let Some(parent_id) = root.relationships.parent_id {
+            if ancestors.len() > MAX_TREE_DEPTH {
+                truncated = true;
+                break;
+            }
+            if !ancestors.insert(parent_id) {
+                warn!(id, parent_id, "post parents loop back on themselves");
+                break;
+            }
+
+            root = self.get_post_by_id(parent_id).await?.post;
+        }
+
+        let mut found: HashSet<i64> = HashSet::from([root.id]);
+        let mut children: HashMap<i64, Vec<E6Post>> = HashMap::new();
+        let mut level: Vec<i64> = root
+            .relationships
+            .has_children
+            .then_some(root.id)
+            .into_iter()
+            .collect();
+
+        let mut depth = 0;
+        while !level.is_empty() {
+            if depth >= MAX_TREE_DEPTH {
+                truncated = true;
+                break;
+            }
+            depth += 1;
+
+            let mut next = Vec::new();
+            for parent in level {
+                if found.len() >= MAX_TREE_POSTS {
+                    truncated = true;
+                    next.clear();
+                    break;
+                }
+
+                let tags = [format!("parent:{}", parent)];
+                let page = self
+                    .search_posts_with(&tags, Some(MAX_TREE_CHILDREN), None, &[])
+                    .await
+                    .with_context(|| format!("failed to get the children of post {}", parent))?;
+                truncated |= page.posts.len() as u64 >= MAX_TREE_CHILDREN;
+
+                let mut kids: Vec<E6Post> = page
+                    .posts
+                    .into_iter()
+                    .filter(|post| found.insert(post.id))
+                    .collect();
+                kids.sort_by_key(|post| post.id);
+
+                next.extend(
+                    kids.iter()
+                        .filter(|post| post.relationships.has_children)
+                        .map(|post| post.id),
+                );
+                children.insert(parent, kids);
+            }
+
+            level = next;
+        }
+
+        debug!(
+            id,
+            root = root.id,
+            posts = found.len(),
+            truncated,
+            "built post tree"
+        );
+        Ok((PostTree::build(root, &mut children), truncated))
+    }
+
+    #[instrument(skip(self))]
+    /// get a post by the md5 hash of its file
+    ///
+    /// returns `None` if no post has a file with that hash. blacklisted posts are not filtered
+    /// out, since this is an exact lookup
+    ///
+    /// # Arguments
+    ///
+    /// * `md5` - the md5 hash of the post's file (32 hex chars)
+    pub async fn get_post_by_md5(&self, md5: &str) -> Result<Option<E6Post>> {
+        let md5 = md5.trim().to_lowercase();
+        if md5.len() != 32 || !md5.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("'{}' is not a valid md5 hash", md5);
+        }
+
+        let url = format!(
+            "{}/posts.json?tags={}&limit=1",
+            self.base_url,
+            urlencoding::encode(&format!("md5:{}", md5))
+        );
+        let bytes = self.get_cached_or_fetch(&url).await?;
+        let response: E6PostsResponse = serde_json::from_slice(&bytes)
+            .context(format!("Failed to deserialize md5 lookup for {}", md5))?;
+
+        let Some(post) = response.posts.into_iter().next() else {
+            debug!(md5, "No post found for md5");
+            return Ok(None);
+        };
+
+        let cache = self.post_cache.clone();
+        let post_clone = post.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = cache.insert(&post_clone).await {
+                warn!(id = post_clone.id, error = %e, "Failed to cache post");
+            }
+        });
+
+        Ok(Some(post))
+    }
+
     /// get posts by their ids with explicit concurrency limit and blacklist
+    ///
+    /// posts are returned in the order of `ids`, and ids that appear more than once are only
+    /// fetched and returned once
     #[instrument(skip(self, ids, blacklist), fields(count = ids.len()))]
     pub async fn get_posts_by_ids_with(
         &self,
@@ -151,18 +378,21 @@ impl E6Client {
         concurrent_limit: usize,
         blacklist: &[String],
     ) -> Result<Vec<E6PostResponse>> {
+        let mut seen = HashSet::new();
+        let ids: Vec<i64> = ids.iter().copied().filter(|id| seen.insert(*id)).collect();
+
         if ids.is_empty() {
             return Ok(Vec::new());
         }
 
-        let cached_results = self.post_cache.get_batch(ids).await?;
-        let mut posts = Vec::with_capacity(ids.len());
+        let cached_results = self.post_cache.get_batch(&ids).await?;
+        let mut posts = HashMap::with_capacity(ids.len());
         let mut missing_ids = Vec::new();
 
         for (i, cached_post) in cached_results.into_iter().enumerate() {
             match cached_post {
                 Some(post) if !post.is_blacklisted_by(blacklist) => {
-                    posts.push(E6PostResponse { post });
+                    posts.insert(post.id, E6PostResponse { post });
                 }
                 Some(_) => {}
                 None => missing_ids.push(ids[i]),
@@ -175,10 +405,6 @@ impl E6Client {
             "Cache lookup complete"
         );
 
-        if missing_ids.is_empty() {
-            return Ok(posts);
-        }
-
         let semaphore = std::sync::Arc::new(Semaphore::new(concurrent_limit));
         let fetch_futures: Vec<_> = missing_ids
             .into_iter()
@@ -197,11 +423,11 @@ impl E6Client {
 
         for result in results.into_iter().flatten() {
             if !result.post.is_blacklisted_by(blacklist) {
-                posts.push(result);
+                posts.insert(result.post.id, result);
             }
         }
 
-        Ok(posts)
+        Ok(ids.iter().filter_map(|id| posts.remove(id)).collect())
     }
 
     /// get posts by their ids using the configured concurrency limit and blacklist
@@ -211,7 +437,7 @@ impl E6Client {
         self.get_posts_by_ids_with(
             ids,
             crate::getopt!(download.threads),
-            &crate::getopt!(search.blacklist),
+            &crate::config::blacklist::active_blacklist(),
         )
         .await
     }
@@ -223,6 +449,28 @@ impl E6Client {
         tags_path: &str,
         aliases_path: &str,
         implications_path: &str,
+    ) -> Result<()> {
+        self.update_tags_with_progress(tags_path, aliases_path, implications_path, 0, &())
+            .await
+    }
+
+    #[instrument(skip(self, progress), name = "update_tags_with_progress")]
+    /// update the local tag databases with explicit file paths, a rate limit, and progress
+    ///
+    /// # Arguments
+    ///
+    /// * `tags_path` - path to the tags csv
+    /// * `aliases_path` - path to the tag aliases csv
+    /// * `implications_path` - path to the tag implications csv
+    /// * `rate_limit` - max download speed in KiB/s (0 = unlimited)
+    /// * `progress` - receives byte progress while downloading
+    pub async fn update_tags_with_progress(
+        &self,
+        tags_path: &str,
+        aliases_path: &str,
+        implications_path: &str,
+        rate_limit: u64,
+        progress: &impl ExportProgress,
     ) -> Result<()> {
         let now = Local::now()
             .checked_sub_days(Days::new(1))
@@ -235,57 +483,10 @@ impl E6Client {
         ];
 
         for (ty, local_file) in files {
-            let hash_file = format!("{}.hash", local_file);
             let url = format!("https://e621.net/db_export/{}-{}.csv.gz", ty, date_str);
 
-            info!(ty, "checking for updates");
-
-            let response = match self.client.get(&url).send().await {
-                Ok(r) => r,
-                Err(e) => {
-                    warn!("couldn't download the update: {}", e);
-                    return Ok(());
-                }
-            };
-
-            if !response.status().is_success() {
-                bail!("failed to download {}: http {}", ty, response.status());
-            }
-
-            let remote_bytes = response.bytes().await?;
-            let remote_hash_hex = {
-                let mut hasher = Sha256::new();
-                hasher.update(&remote_bytes);
-                hex::encode(hasher.finalize())
-            };
-
-            let update_needed = match fs::read_to_string(&hash_file).await {
-                Ok(local_hash) => local_hash.trim() != remote_hash_hex,
-                Err(_) => true,
-            };
-
-            if !update_needed {
-                info!(ty, "✓ Already up to date");
-                continue;
-            }
-
-            info!(ty, "Updating local snapshot");
-
-            let mut gz = GzDecoder::new(&remote_bytes[..]);
-            let mut decompressed = Vec::new();
-            gz.read_to_end(&mut decompressed)
-                .context(format!("failed to decompress {}", ty))?;
-
-            if let Some(parent) = Path::new(local_file).parent() {
-                fs::create_dir_all(parent).await?;
-            }
-
-            let temp_file = format!("{}.tmp", local_file);
-            fs::write(&temp_file, &decompressed).await?;
-            fs::rename(&temp_file, local_file).await?;
-            fs::write(&hash_file, remote_hash_hex.as_bytes()).await?;
-
-            info!(ty, path = %local_file, "✓ Updated");
+            self.update_db_export(&url, local_file, ty, rate_limit, progress)
+                .await?;
         }
 
         info!("all tag dbs are up to date");
@@ -296,10 +497,30 @@ impl E6Client {
     #[cfg(feature = "cli")]
     #[instrument(skip(self), name = "update_tags")]
     pub async fn update_tags(&self) -> Result<()> {
-        self.update_tags_with(
+        let progress = crate::ui::progress::ProgressManager::new();
+        let pb = progress
+            .mk_dl_bar("tag_exports", 0, "Checking tags")
+            .await?;
+        let res = self.update_tags_reporting(&pb).await;
+
+        progress.remove_bar("tag_exports").await;
+        res
+    }
+
+    /// update the local tag databases using the configured paths, reporting progress somewhere
+    /// other than a progress bar
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - receives byte progress while downloading
+    #[cfg(feature = "cli")]
+    pub async fn update_tags_reporting(&self, progress: &impl ExportProgress) -> Result<()> {
+        self.update_tags_with_progress(
             &crate::getopt!(completion.tags),
             &crate::getopt!(completion.aliases),
             &crate::getopt!(completion.implications),
+            crate::getopt!(autoupdate.rate_limit),
+            progress,
         )
         .await
     }
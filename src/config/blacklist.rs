@@ -1,9 +1,90 @@
 //! blacklist management stuff
+//!
+//! the blacklist is `search.blacklist` plus the additions for the site `http.api` points at
+//! (`search.blacklist_e621` or `search.blacklist_e926`). the add/remove/clear functions only
+//! touch the base list
 use {
-    crate::config::instance::{config, *},
+    crate::{
+        config::instance::*,
+        getopt,
+    },
     color_eyre::{Result, eyre::Context},
 };
 
+/// the site searches are made on, which picks the blacklist additions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlacklistSite {
+    /// e621, the full site
+    E621,
+    /// e926, the safe-only site
+    E926,
+}
+
+impl BlacklistSite {
+    /// work out the site from an api url
+    ///
+    /// anything that isn't e926 is treated as e621
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - the api url (like `https://e926.net`)
+    pub fn from_url(url: &str) -> Self {
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_lowercase))
+            .unwrap_or_else(|| url.to_lowercase());
+
+        if host == "e926.net" || host.ends_with(".e926.net") {
+            Self::E926
+        } else {
+            Self::E621
+        }
+    }
+
+    /// the site `http.api` points at
+    pub fn active() -> Self {
+        Self::from_url(&getopt!(http.api))
+    }
+}
+
+/// merge a base blacklist with site-specific additions
+///
+/// keeps the order of the base list, then the additions, and leaves out repeats
+///
+/// # Arguments
+///
+/// * `base` - the blacklist used on every site
+/// * `additions` - the extra rules for one site
+pub fn merge_blacklists(base: &[String], additions: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = Vec::with_capacity(base.len() + additions.len());
+    for rule in base.iter().chain(additions) {
+        if !merged.contains(rule) {
+            merged.push(rule.clone());
+        }
+    }
+
+    merged
+}
+
+/// get the blacklist for a site, the base list merged with that site's additions
+///
+/// # Arguments
+///
+/// * `site` - the site to get the blacklist of
+pub fn blacklist_for(site: BlacklistSite) -> Vec<String> {
+    let additions = match site {
+        BlacklistSite::E621 => getopt!(search.blacklist_e621),
+        BlacklistSite::E926 => getopt!(search.blacklist_e926),
+    };
+
+    merge_blacklists(&getopt!(search.blacklist), &additions)
+}
+
+/// get the blacklist for the site `http.api` points at
+pub fn active_blacklist() -> Vec<String> {
+    blacklist_for(BlacklistSite::active())
+}
+
 /// add a tag to the blacklist
 pub fn add_to_blacklist(tag: String) -> Result<()> {
     let mut cfg = config_mut().wrap_err("failed to get write lock for config")?;
@@ -51,16 +132,9 @@ pub fn clear_blacklist() -> Result<()> {
     Ok(())
 }
 
-/// get a copy of the current blacklist
+/// get a copy of the current blacklist, with the additions for the active site
 pub fn get_blacklist() -> Result<Vec<String>> {
-    let cfg = config().wrap_err("failed to get read lock for config")?;
-    if let Some(ref search_cfg) = cfg.search
-        && let Some(blacklist) = search_cfg.blacklist.clone()
-    {
-        return Ok(blacklist);
-    }
-
-    Ok(Vec::new())
+    Ok(active_blacklist())
 }
 
 /// check if a tag is blacklisted
@@ -70,3 +144,36 @@ pub fn is_blacklisted(tag: &str) -> bool {
         .map(|b| b.iter().any(|t| t == tag))
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_site_from_url() {
+        assert_eq!(
+            BlacklistSite::from_url("https://e926.net"),
+            BlacklistSite::E926
+        );
+        assert_eq!(
+            BlacklistSite::from_url("https://E926.net/posts"),
+            BlacklistSite::E926
+        );
+        assert_eq!(
+            BlacklistSite::from_url("https://e621.net"),
+            BlacklistSite::E621
+        );
+        assert_eq!(BlacklistSite::from_url("not a url"), BlacklistSite::E621);
+    }
+
+    #[test]
+    fn test_merge_blacklists() {
+        let list = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            merge_blacklists(&list(&["gore", "scat"]), &list(&["feral", "gore"])),
+            list(&["gore", "scat", "feral"])
+        );
+        assert_eq!(merge_blacklists(&list(&["gore"]), &[]), list(&["gore"]));
+    }
+}
@@ -6,10 +6,19 @@ use {
 };
 
 /// global config instance
+#[cfg(not(test))]
 static CONFIG: LazyLock<RwLock<E62Rs>> = LazyLock::new(|| {
     RwLock::new(E62Rs::load().expect("!!!Failed to load configuration, this should NOT happen!!!"))
 });
 
+/// global config instance, kept at the defaults in tests so they don't read or write the user's
+/// config
+#[cfg(test)]
+static CONFIG: LazyLock<RwLock<E62Rs>> = LazyLock::new(|| RwLock::new(E62Rs::default()));
+
+/// the default config, built once and shared by every `getopt!` fallback
+static DEFAULTS: LazyLock<E62Rs> = LazyLock::new(E62Rs::default);
+
 /// init the config explicitly
 pub fn init_config() -> Result<()> {
     let _l = config()?;
@@ -30,6 +39,11 @@ pub fn config_mut() -> Result<RwLockWriteGuard<'static, E62Rs>> {
         .map_err(|e| color_eyre::eyre::eyre!("Configuration lock poisoned: {}", e))
 }
 
+/// get a ref to the default config
+pub fn defaults() -> &'static E62Rs {
+    &DEFAULTS
+}
+
 /// reload cfg from disk
 pub fn reload_config() -> Result<()> {
     let new_config = E62Rs::load().wrap_err("Failed to reload config from disk")?;
@@ -51,3 +65,15 @@ where
         .and_then(|cfg| getter(&cfg))
         .unwrap_or(default)
 }
+
+/// get a specific config value, only computing the fallback if the value isn't set
+pub fn get_or_else<T, F, D>(getter: F, default: D) -> T
+where
+    F: FnOnce(&E62Rs) -> Option<T>,
+    D: FnOnce() -> T,
+{
+    config()
+        .ok()
+        .and_then(|cfg| getter(&cfg))
+        .unwrap_or_else(default)
+}
@@ -8,6 +8,11 @@
 //! image-when-info = true
 //! sixel-quality = 100
 //! resize-method = "lanczos3"
+//! fetch-timeout = 15
+//! fetch-retries = 2
+//! animate = true
+//! max-frames = 300
+//! max-megapixels = 8.0
 //!
 //! [http]
 //! api = "https://e621.net"
@@ -15,15 +20,17 @@
 //! pool-idle-timeout = 90
 //! timeout = 30
 //! connect-timeout = 10
+//! max-retries = 2
 //! max-connections = 15
 //! http2 = false
 //! tcp-keepalive = true
 //! tcp-keepalive-secs = 60
+//! warmup = true
 //! user-agent = "e62rs/v1.0.2 (by bearodactyl on e621)"
 //!
 //! [cache]
 //! enabled = true
-//! cache-dir = ".cache"
+//! cache-dir = "e62rs"
 //! ttl-secs = 3600
 //! tti-secs = 1800
 //! max-size-mb = 500
@@ -41,6 +48,7 @@
 //! page-size-kb = 4
 //! auto-compact = true
 //! compact-threshold = 25
+//! ttl-secs = 86400
 //!
 //! [performance]
 //! prefetch-enabled = true
@@ -54,6 +62,11 @@
 //! colored-output = true
 //! tag-guide = true
 //! language = "english"
+//! hide-below-score = -9223372036854775808
+//! post-list-format = "ID: $id | Score: $score | Rating: $rating_first"
+//! relationship-markers = true
+//! max-browser-tabs = 20
+//! summary-list-limit = 15
 //!
 //! [ui.progress]
 //! refresh-rate = 20
@@ -63,12 +76,15 @@
 //!
 //! [search]
 //! results = 320
+//! max-pages = 50
 //! blacklist = [
 //!     "young",
 //!     "rape",
 //!     "feral",
 //!     "bestiality",
 //! ]
+//! blacklist-e621 = []
+//! blacklist-e926 = []
 //! min-posts-on-tag = 2
 //! min-posts-on-pool = 2
 //! show-inactive-pools = true
@@ -79,11 +95,19 @@
 //! reverse-tags-order = false
 //! fetch-threads = 8
 //! search-history = false
+//! verbose-blacklist = false
+//! min-width = 0
+//! min-height = 0
+//! min-fav-count = 0
+//! always-include = []
+//! always-exclude = []
 //!
 //! [login]
 //! login = true
 //! username = ""
 //! api-key = ""
+//! api-key-file = ""
+//! use-keyring = false
 //!
 //! [completion]
 //! tag-similarity-threshold = 0.8
@@ -91,16 +115,35 @@
 //! aliases = "data/tag_aliases.csv"
 //! implications = "data/tag_implications.csv"
 //! pools = "data/pools.csv"
+//! name-weight = 1.0
+//! desc-weight = 0.8
+//! strict-tags = false
 //!
 //! [autoupdate]
 //! tags = true
 //! pools = true
+//! rate-limit = 0
+//! background = true
 //!
 //! [download]
 //! path = "downloads"
 //! pools-path = "downloads/pools"
 //! threads = 15
+//! timeout = 300
+//! max-retries = 3
+//! write-nomedia = false
 //! save-metadata = true
+//! metadata-backend = "auto"
+//! metadata-pretty = true
+//! sidecar-pattern = "{stem}.{ext}.json"
+//! separate-by-rating = false
+//! on-conflict = "skip"
+//! on-dnp = "ask"
+//! watch-interval = 30
+//! confirm-over-posts = 500
+//! confirm-over-mb = 2048
+//! pinned-artists = []
+//! routes = []
 //! format = "$artists[3]/$rating/$tags[3] - $id - $date $time - $score.$ext"
 //!
 //! [explorer]
@@ -112,15 +155,29 @@
 //! cache-metadata = true
 //! auto-display-image = true
 //! slideshow-delay = 5
+//! scan-threads = 8
+//! incremental-scan = true
+//! contact-sheet-columns = 6
+//! contact-sheet-max-size = 4096
+//! video-player = "mpv"
+//! video-player-loop-args = "--loop-playlist=inf"
+//! permanent-delete = false
+//! staging-dir = "staging"
 //!
 //! [gallery]
 //! enabled = true
 //! metadata-filtering = true
+//! bind-host = "127.0.0.1"
 //! port = 23794
 //! cache-metadata = true
 //! auto-open = false
 //! load-threads = 8
+//! watch = false
+//! username = ""
+//! password = ""
+//! token = ""
 //! theme = "catppuccin-frappe"
+//! default-rating = ""
 //!
 //! [logging]
 //! enable = true
@@ -139,7 +196,7 @@ use {
 #[cfg(feature = "cli")]
 use {
     crate::{
-        config::validate::{Validate, format_validation_errors},
+        config::validate::{Validate, format_validation_errors, is_valid_user_agent},
         utils::FileWriter,
     },
     color_eyre::{
@@ -151,7 +208,7 @@ use {
         io::Write,
         path::{Path, PathBuf},
     },
-    tracing::info,
+    tracing::{info, warn},
 };
 
 /// Configuration options for making HTTP requests
@@ -223,6 +280,12 @@ pub struct HttpConfig {
     #[default(Some(10))]
     pub connect_timeout: Option<u64>,
 
+    /// How many times to retry an API request that fails to connect or gets a server error
+    ///
+    /// File downloads have their own setting, `download.max-retries`
+    #[default(Some(2))]
+    pub max_retries: Option<u32>,
+
     #[schemars(range(min = 1, max = 15))]
     /// Max concurrent connections
     #[default(Some(15))]
@@ -240,6 +303,11 @@ pub struct HttpConfig {
     #[default(Some(60))]
     pub tcp_keepalive_secs: Option<u64>,
 
+    /// Open a connection to the API in the background on startup, so the first search doesn't
+    /// wait on the TLS handshake
+    #[default(Some(true))]
+    pub warmup: Option<bool>,
+
     /// User agent string in the format:
     /// `<project name>/<project version> (by <valid e6 username> on <e621/e926>)`
     ///
@@ -247,6 +315,9 @@ pub struct HttpConfig {
     /// - `my-project/1.2.3 (by username123 on e621)`
     /// - `another/2.0.0-beta.1 (by user7890 on e926)`
     /// - `test-proj/0.1.0+build.123 (by myuser12345 on e621)`
+    ///
+    /// An unfilled template (like `<project name>` or `by username`) stops the config from
+    /// loading, anything else that doesn't match the format logs a warning
     #[default(Some(format!(
         "{}/v{} (by {} on e621)",
         env!("CARGO_PKG_NAME"),
@@ -267,7 +338,10 @@ pub struct CacheConfig {
     pub enabled: Option<bool>,
 
     /// Cache directory
-    #[default(Some(".cache".to_owned()))]
+    ///
+    /// Relative paths are resolved against the platform cache directory (see
+    /// [`CacheConfig::resolved_cache_dir`])
+    #[default(Some("e62rs".to_owned()))]
     pub cache_dir: Option<String>,
 
     /// Cache TTL in seconds
@@ -311,6 +385,38 @@ pub struct CacheConfig {
     pub posts: Option<PostCacheConfig>,
 }
 
+/// the cache dir every config used before caches moved to the platform cache directory
+const LEGACY_CACHE_DIR: &str = ".cache";
+
+impl CacheConfig {
+    /// get the directory the caches are stored in
+    ///
+    /// absolute paths are used as-is and paths starting with `./` or `../` stay relative to the
+    /// working directory. any other relative path is resolved against the platform cache
+    /// directory (`$XDG_CACHE_HOME` or `~/.cache` on linux, `~/Library/Caches` on macos,
+    /// `%LOCALAPPDATA%` on windows), the same way the config file lives in the platform config
+    /// directory. the old default, `.cache`, still means `.cache` in the working directory so
+    /// existing configs keep using their caches
+    pub fn resolved_cache_dir(&self) -> PathBuf {
+        let dir = PathBuf::from(self.cache_dir.as_deref().unwrap_or("e62rs"));
+
+        if dir.is_absolute()
+            || dir.starts_with(".")
+            || dir.starts_with("..")
+            || dir == Path::new(LEGACY_CACHE_DIR)
+        {
+            return dir;
+        }
+
+        #[cfg(feature = "cli")]
+        if let Some(base) = dirs::cache_dir() {
+            return base.join(dir);
+        }
+
+        dir
+    }
+}
+
 /// Configuration options for post-specific caching
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, SmartDefault)]
 #[schemars(bound = "T: JsonSchema + Default")]
@@ -340,6 +446,12 @@ pub struct PostCacheConfig {
     /// Compaction threshold (compact when wasted space exceeds this percentage)
     #[default(Some(25))]
     pub compact_threshold: Option<u8>,
+
+    /// How long a cached post is used when looking a post up by id, in seconds (0 = forever)
+    ///
+    /// Older entries are fetched again, but are still used when the fetch fails
+    #[default(Some(86400))]
+    pub ttl_secs: Option<u64>,
 }
 
 /// Configuration options for performance
@@ -391,6 +503,36 @@ pub struct UiConfig {
     #[default(Some(Language::default()))]
     pub language: Option<Language>,
 
+    /// Hide posts scoring below this when browsing (online and local)
+    #[default(Some(i64::MIN))]
+    pub hide_below_score: Option<i64>,
+
+    /// Format of each post in post selection lists
+    ///
+    /// Uses the download format placeholders, so fields like `$fav_count` and `$artist` can be
+    /// added to each entry
+    #[default(Some("ID: $id | Score: $score | Rating: $rating_first".to_string()))]
+    pub post_list_format: Option<String>,
+
+    /// Mark posts with a parent or children in post selection lists
+    ///
+    /// Children get `[child]`, parents get `[parent]`, and posts that are both get `[set]`
+    #[default(Some(true))]
+    pub relationship_markers: Option<bool>,
+
+    /// Ask before opening more than this many posts in the browser at once (0 = never ask)
+    ///
+    /// Over the limit, the posts can be opened as a single e621 search instead of a tab each
+    #[default(Some(20))]
+    pub max_browser_tabs: Option<usize>,
+
+    /// How many entries summary lists show before cutting off with `... and N more` (0 = all)
+    ///
+    /// Used by the artist updater, the what's new listing, and the reorganizer's list of removed
+    /// empty folders. Errors are always listed in full
+    #[default(Some(15))]
+    pub summary_list_limit: Option<usize>,
+
     /// Progress configuration
     #[default(Some(ProgressCfg::default()))]
     pub progress: Option<ProgressCfg>,
@@ -488,6 +630,27 @@ pub struct ImageDisplay {
     /// Resize method (nearest, linear, cubic, gaussian, lanczos3)
     #[default(Some("lanczos3".to_string()))]
     pub resize_method: Option<String>,
+
+    /// How many seconds to wait for an image to download before showing a placeholder instead
+    #[default(Some(15))]
+    pub fetch_timeout: Option<u64>,
+
+    /// How many times to retry a failed image download (within the timeout)
+    #[default(Some(2))]
+    pub fetch_retries: Option<u32>,
+
+    /// Play animated GIFs and WebPs in the terminal (only the first frame is shown when off)
+    #[default(Some(true))]
+    pub animate: Option<bool>,
+
+    /// The most frames of an animation to load, later frames are dropped
+    #[default(Some(300))]
+    pub max_frames: Option<usize>,
+
+    /// The most megapixels an image can have when it's shown, bigger images are scaled down
+    /// (keeping their aspect ratio) before they're encoded
+    #[default(Some(8.0))]
+    pub max_megapixels: Option<f64>,
 }
 
 /// Configuration options for searching posts/pools
@@ -500,10 +663,23 @@ pub struct SearchCfg {
     #[default(Some(320))]
     pub results: Option<u64>,
 
+    /// The most requests a single search makes before stopping, so a broad search with a
+    /// high post limit can't keep fetching for minutes
+    #[default(Some(50))]
+    pub max_pages: Option<u64>,
+
     /// Blacklisted tags to filter out from all operations
     #[default(Some(vec!["young".to_string(), "rape".to_string(), "feral".to_string(), "bestiality".to_string()]))]
     pub blacklist: Option<Vec<String>>,
 
+    /// Extra blacklisted tags only used when `http.api` points at e621
+    #[default(Some(Vec::new()))]
+    pub blacklist_e621: Option<Vec<String>>,
+
+    /// Extra blacklisted tags only used when `http.api` points at e926
+    #[default(Some(Vec::new()))]
+    pub blacklist_e926: Option<Vec<String>>,
+
     /// The minimum amount of posts on a tag for it to show up in tag selection
     #[default(Some(2))]
     pub min_posts_on_tag: Option<u64>,
@@ -543,6 +719,31 @@ pub struct SearchCfg {
     /// Enable persistent search history (opt-in)
     #[default(Some(false))]
     pub search_history: Option<bool>,
+
+    /// Show which blacklist rules hid posts for every fetched batch, not just in the summary
+    #[default(Some(false))]
+    pub verbose_blacklist: Option<bool>,
+
+    /// The minimum width (in pixels) a post should have to show up in search, 0 to disable
+    #[default(Some(0))]
+    pub min_width: Option<u64>,
+
+    /// The minimum height (in pixels) a post should have to show up in search, 0 to disable
+    #[default(Some(0))]
+    pub min_height: Option<u64>,
+
+    /// The minimum number of favorites a post should have to show up in search (and in the
+    /// explorer), 0 to disable
+    #[default(Some(0))]
+    pub min_fav_count: Option<u64>,
+
+    /// Tags added to every post search (skipped for a search with `!raw` in it)
+    #[default(Some(Vec::new()))]
+    pub always_include: Option<Vec<String>>,
+
+    /// Tags excluded from every post search (skipped for a search with `!raw` in it)
+    #[default(Some(Vec::new()))]
+    pub always_exclude: Option<Vec<String>>,
 }
 
 /// Configuration options for completion in menus
@@ -570,6 +771,23 @@ pub struct CompletionCfg {
     /// The path to `pools.csv` that's used for pool searching/autocompletion
     #[default(Some("data/pools.csv".to_string()))]
     pub pools: Option<String>,
+
+    /// How much a name match counts when searching pools
+    #[default(Some(1.0))]
+    pub name_weight: Option<f64>,
+
+    /// How much a description match counts when searching pools (below `name_weight` ranks
+    /// name matches first)
+    #[default(Some(0.8))]
+    pub desc_weight: Option<f64>,
+
+    /// Keep entered tags as typed unless they're directly aliased
+    ///
+    /// Off, every tag is replaced with the end of its alias chain.
+    /// On, tags that exist under their own name are never replaced,
+    /// and other tags only follow a single exact alias
+    #[default(Some(false))]
+    pub strict_tags: Option<bool>,
 }
 
 /// Your login credentials
@@ -589,6 +807,73 @@ pub struct LoginCfg {
     #[default(Some(String::new()))]
     /// Your API key
     pub api_key: Option<String>,
+
+    #[default(Some(String::new()))]
+    /// A file to read your API key from, used when `api_key` is empty
+    pub api_key_file: Option<String>,
+
+    #[default(Some(false))]
+    /// Whether to read your API key from the OS keyring (service `e62rs`, account `username`),
+    /// used when `api_key` and `api_key_file` are empty
+    ///
+    /// On Linux and BSD this needs e62rs built with the `secret-service` feature (which needs
+    /// libdbus)
+    pub use_keyring: Option<bool>,
+}
+
+/// the service e62rs stores api keys under in the OS keyring
+#[cfg(feature = "cli")]
+pub const KEYRING_SERVICE: &str = "e62rs";
+
+#[cfg(feature = "cli")]
+impl LoginCfg {
+    /// get the api key from wherever it's configured
+    ///
+    /// an inline `api_key` wins over `api_key_file`, which wins over the keyring. an empty key
+    /// file counts as not set. returns an empty string if none of them are set
+    ///
+    /// # Errors
+    ///
+    /// returns an error if `api_key_file` can't be read, or the keyring is enabled but has no
+    /// key for `username`
+    pub fn resolve_api_key(&self) -> Result<String> {
+        fn set(v: &Option<String>) -> Option<&str> {
+            v.as_deref().map(str::trim).filter(|v| !v.is_empty())
+        }
+
+        if let Some(key) = set(&self.api_key) {
+            return Ok(key.to_string());
+        }
+
+        if let Some(file) = set(&self.api_key_file) {
+            let key = std::fs::read_to_string(file)
+                .wrap_err_with(|| format!("Failed to read api key file '{}'", file))
+                .suggestion("Check that `login.api_key_file` points to a readable file")?;
+            if !key.trim().is_empty() {
+                return Ok(key.trim().to_string());
+            }
+        }
+
+        if self.use_keyring.unwrap_or(false) {
+            let username = set(&self.username)
+                .ok_or_eyre("A username is needed to look up the api key in the keyring")?;
+            let key = keyring::Entry::new(KEYRING_SERVICE, username)
+                .and_then(|entry| entry.get_password())
+                .wrap_err_with(|| {
+                    format!(
+                        "Failed to get the api key for '{}' from the keyring",
+                        username
+                    )
+                })
+                .suggestion(format!(
+                    "Store it under the service '{}' with '{}' as the account",
+                    KEYRING_SERVICE, username
+                ))?;
+            return Ok(key.trim().to_string());
+        }
+
+        Ok(String::new())
+    }
 }
 
 /// Settings for automatically updating data snapshots
@@ -604,6 +889,17 @@ pub struct AutoUpdateCfg {
     /// Whether or not to auto-update pools
     #[default(Some(true))]
     pub pools: Option<bool>,
+
+    /// Max download speed for data snapshots in KiB/s (0 = unlimited)
+    #[default(Some(0))]
+    pub rate_limit: Option<u64>,
+
+    /// Update tags and pools in the background instead of before the main menu shows up
+    ///
+    /// The main menu shows how far along the update is. Tag completion and pool search use the
+    /// old data (or none at all, on the first run) until it's done
+    #[default(Some(true))]
+    pub background: Option<bool>,
 }
 
 /// Settings for the downloads explorer
@@ -643,6 +939,48 @@ pub struct ExplorerCfg {
     /// The amount of time to wait between slideshow images
     #[default(Some(5))]
     pub slideshow_delay: Option<u64>,
+
+    /// Number of metadata files to read concurrently when scanning
+    #[default(Some(8))]
+    pub scan_threads: Option<usize>,
+
+    /// Only read the metadata of files that changed since the last scan
+    ///
+    /// Everything else is loaded from a record of the last scan in the cache dir. Run with
+    /// `--full-rescan` to read everything again
+    #[default(Some(true))]
+    pub incremental_scan: Option<bool>,
+
+    /// The default number of columns of a contact sheet
+    #[default(Some(6))]
+    pub contact_sheet_columns: Option<u32>,
+
+    /// The largest a contact sheet can be on either side, in pixels
+    ///
+    /// Thumbnails are shrunk to make every shown post fit, and posts that still don't fit go on
+    /// more sheets
+    #[default(Some(4096))]
+    pub contact_sheet_max_size: Option<u32>,
+
+    /// The program videos are played with, with any extra arguments (e.g. `mpv --fs`)
+    ///
+    /// It's given the path to an m3u playlist
+    #[default(Some("mpv".to_string()))]
+    pub video_player: Option<String>,
+
+    /// The arguments that make `video_player` loop its playlist
+    #[default(Some("--loop-playlist=inf".to_string()))]
+    pub video_player_loop_args: Option<String>,
+
+    /// Delete downloads for good instead of moving them to the trash
+    #[default(Some(false))]
+    pub permanent_delete: Option<bool>,
+
+    /// The folder "Copy to staging" copies posts into
+    ///
+    /// Copies keep their file names and metadata, the originals aren't touched
+    #[default(Some("staging".to_string()))]
+    pub staging_dir: Option<String>,
 }
 
 /// Settings for post downloading
@@ -664,6 +1002,24 @@ pub struct DownloadCfg {
     #[schemars(range(min = 1, max = 15))]
     pub threads: Option<usize>,
 
+    /// How many seconds a single file download can take before it's given up on
+    ///
+    /// Separate from `http.timeout` so big videos on a slow connection don't need a long
+    /// timeout for every API request too
+    #[default(Some(300))]
+    pub timeout: Option<u64>,
+
+    /// How many times to retry a file download that fails (each retry starts the file over)
+    #[default(Some(3))]
+    pub max_retries: Option<u32>,
+
+    /// Put an empty `.nomedia` file in every download folder e62rs creates
+    ///
+    /// Android's media scanner, and gallery apps that follow it, skip folders that have one.
+    /// Folders that already exist are left alone
+    #[default(Some(false))]
+    pub write_nomedia: Option<bool>,
+
     /// Save the data of downloaded posts
     ///
     /// This is used in the auto-reorganizer to enable
@@ -677,6 +1033,98 @@ pub struct DownloadCfg {
     #[default(Some(true))]
     pub save_metadata: Option<bool>,
 
+    /// Where to store the metadata of downloaded posts
+    ///
+    /// - auto: ADS on Windows, sidecar everywhere else (the previous behavior)
+    /// - sidecar: a json file next to each download (named by `sidecar_pattern`)
+    /// - embedded: an NTFS alternate data stream on each download (Windows only)
+    /// - db: one `.e62rs-metadata.redb` database per directory
+    ///
+    /// Metadata saved with a different backend is still read
+    #[default(Some(MetadataBackend::default()))]
+    pub metadata_backend: Option<MetadataBackend>,
+
+    /// Indent the JSON of metadata sidecars
+    ///
+    /// Turning this off writes each sidecar on a single line, which makes them roughly half the
+    /// size. Both are read the same way, so existing sidecars don't need rewriting
+    #[default(Some(true))]
+    pub metadata_pretty: Option<bool>,
+
+    /// How metadata sidecars are named
+    ///
+    /// `{stem}` is the download's name without its extension and `{ext}` is its extension,
+    /// so `.{stem}.{ext}.json` saves `123.png`'s metadata to `.123.png.json`.
+    /// Both have to be used, and the pattern has to end with a fixed suffix (like `.json`)
+    /// so sidecars can be told apart from downloads.
+    /// Sidecars named with the default pattern are still read after changing this,
+    /// and the reorganizer renames them to the new pattern as it moves files
+    #[default(Some("{stem}.{ext}.json".to_string()))]
+    pub sidecar_pattern: Option<String>,
+
+    /// Put downloads in a `safe`, `questionable`, or `explicit` folder before applying `format`
+    ///
+    /// Also offered by the reorganizer so existing downloads can be split.
+    /// If `format` already starts with `$rating/` the folder isn't added twice,
+    /// but a `$rating` further into the format is kept
+    /// (`$artist/$rating/$id.$ext` → `explicit/artist/explicit/123.png`)
+    #[default(Some(false))]
+    pub separate_by_rating: Option<bool>,
+
+    /// What to do when a download's file already exists
+    ///
+    /// - skip: leave the existing file alone and move on
+    /// - overwrite: replace the existing file
+    /// - rename: save as `<name>_1.<ext>`, `<name>_2.<ext>`, ...
+    #[default(Some(OnConflict::default()))]
+    pub on_conflict: Option<OnConflict>,
+
+    /// What to do with posts by artists on the avoid posting list
+    ///
+    /// Posts are flagged by their `avoid_posting` or `conditional_dnp` artist tags.
+    /// Downloads with no one to ask (like `--daemon`) treat `ask` as `skip`
+    ///
+    /// - ask: list them and ask whether to download them anyway
+    /// - skip: leave them out with a notice
+    /// - download: download them like any other post
+    #[default(Some(OnDnp::default()))]
+    pub on_dnp: Option<OnDnp>,
+
+    /// How many minutes `--daemon` waits between checks of its saved searches
+    #[default(Some(30))]
+    pub watch_interval: Option<u64>,
+
+    /// Ask before downloading more than this many posts at once (0 = never ask)
+    #[default(Some(500))]
+    pub confirm_over_posts: Option<usize>,
+
+    /// Ask before downloading more than this many megabytes at once (0 = never ask)
+    ///
+    /// The size is estimated from the file sizes the API reports
+    #[default(Some(2048))]
+    pub confirm_over_mb: Option<u64>,
+
+    /// Artists listed first in the artist updater, whatever their download count
+    #[default(Some(Vec::new()))]
+    pub pinned_artists: Option<Vec<String>>,
+
+    /// Rules that send matching posts to a different directory than `path`
+    ///
+    /// The first matching rule wins, posts that match none go to `path`.
+    /// The explorer and reorganizer look through every directory listed here
+    ///
+    /// ```toml
+    /// [[download.routes]]
+    /// rating = "s"
+    /// dir = "D:/sfw"
+    ///
+    /// [[download.routes]]
+    /// tags = "canine -feral"
+    /// dir = "E:/canines"
+    /// ```
+    #[default(Some(Vec::new()))]
+    pub routes: Option<Vec<DownloadRoute>>,
+
     /// ## Filename Formatting
     ///
     /// The `format` setting controls how filenames are generated when saving posts. Forward slashes denote subfolders.
@@ -837,6 +1285,14 @@ pub struct GalleryCfg {
     #[default(Some(true))]
     pub metadata_filtering: Option<bool>,
 
+    /// The IP address to run the gallery server on
+    ///
+    /// Use `0.0.0.0` to reach the gallery from other devices on your network.
+    /// Anything other than a loopback address exposes your downloads to that network,
+    /// so consider setting `username`/`password` or `token` too
+    #[default(Some("127.0.0.1".to_string()))]
+    pub bind_host: Option<String>,
+
     /// Port to run the gallery server on
     #[default(Some(23794))]
     pub port: Option<u16>,
@@ -853,6 +1309,25 @@ pub struct GalleryCfg {
     #[default(Some(8))]
     pub load_threads: Option<usize>,
 
+    /// Watch the download directory and live-reload the gallery when files are added or removed
+    #[default(Some(false))]
+    pub watch: Option<bool>,
+
+    /// Username required to use the gallery (needs `password`, empty to disable)
+    #[default(Some(String::new()))]
+    pub username: Option<String>,
+
+    /// Password required to use the gallery (needs `username`, empty to disable)
+    #[default(Some(String::new()))]
+    pub password: Option<String>,
+
+    /// Token required to use the gallery (empty to disable)
+    ///
+    /// Sent as `Authorization: Bearer <token>`, or typed into the browser's login
+    /// prompt as the password with any username
+    #[default(Some(String::new()))]
+    pub token: Option<String>,
+
     /// The colorscheme to use for the gallery
     ///
     /// Possible values:
@@ -865,6 +1340,92 @@ pub struct GalleryCfg {
     /// - catppuccin-mocha
     #[default(Some("catppuccin-frappe".to_string()))]
     pub theme: Option<String>,
+
+    /// The rating the gallery shows when it's opened (s/q/e, empty to show everything)
+    ///
+    /// Only applies with `metadata_filtering` on, and can still be changed from the gallery
+    #[default(Some(String::new()))]
+    pub default_rating: Option<String>,
+}
+
+/// Where the metadata of downloaded posts is stored
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetadataBackend {
+    #[default]
+    /// ADS on Windows, sidecar everywhere else
+    Auto,
+
+    /// A JSON file next to each download
+    Sidecar,
+
+    /// An NTFS alternate data stream on each download
+    Embedded,
+
+    /// One database per directory
+    Db,
+}
+
+impl MetadataBackend {
+    /// get the backend that's actually used on this platform
+    ///
+    /// `Auto` becomes `Embedded` on Windows and `Sidecar` elsewhere, and `Embedded` falls back to
+    /// `Sidecar` outside of Windows
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto | Self::Embedded if cfg!(target_os = "windows") => Self::Embedded,
+            Self::Auto | Self::Embedded => Self::Sidecar,
+            other => other,
+        }
+    }
+}
+
+/// A rule that sends matching downloads to a different directory
+///
+/// A rule with both `rating` and `tags` only matches posts that match both
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct DownloadRoute {
+    /// Only match posts with this rating (`s`, `q`, `e`, or the full name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<String>,
+
+    /// Only match posts matching this tag expression (like `canine -feral` or `cat | dog`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<String>,
+
+    /// The directory matching posts are downloaded to
+    pub dir: String,
+}
+
+/// What to do when a download's file already exists
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnConflict {
+    #[default]
+    /// Leave the existing file alone
+    Skip,
+
+    /// Replace the existing file
+    Overwrite,
+
+    /// Save under a numbered name next to the existing file
+    Rename,
+}
+
+/// What to do with posts by artists on the avoid posting list
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnDnp {
+    #[default]
+    /// Ask whether to download them anyway
+    Ask,
+
+    /// Leave them out
+    Skip,
+
+    /// Download them like any other post
+    Download,
 }
 
 /// The language the app uses
@@ -940,14 +1501,88 @@ pub struct E62Rs {
     pub logging: Option<LoggingConfig>,
 }
 
+/// what secrets are replaced with in a [redacted](E62Rs::redacted) config
+pub const REDACTED: &str = "<redacted>";
+
+impl E62Rs {
+    /// get a copy of the config that's safe to show or share
+    ///
+    /// the api key is replaced with [`REDACTED`] (unless it's empty), and so is the username if
+    /// `hide_username` is set
+    pub fn redacted(&self, hide_username: bool) -> Self {
+        let mut cfg = self.clone();
+
+        if let Some(login) = cfg.login.as_mut() {
+            let redact = |value: &mut Option<String>| {
+                if value.as_deref().is_some_and(|v| !v.is_empty()) {
+                    *value = Some(REDACTED.to_string());
+                }
+            };
+
+            redact(&mut login.api_key);
+            if hide_username {
+                redact(&mut login.username);
+            }
+        }
+
+        cfg
+    }
+
+    /// put back secrets that are still [`REDACTED`] after editing a redacted config
+    ///
+    /// # Arguments
+    ///
+    /// * `original` - the config the redacted copy was made from
+    pub fn restore_secrets(&mut self, original: &Self) {
+        let (Some(login), Some(original)) = (self.login.as_mut(), original.login.as_ref()) else {
+            return;
+        };
+
+        if login.api_key.as_deref() == Some(REDACTED) {
+            login.api_key = original.api_key.clone();
+        }
+        if login.username.as_deref() == Some(REDACTED) {
+            login.username = original.username.clone();
+        }
+    }
+}
+
+/// the environment variable that stops e62rs from writing a default config file
+///
+/// any value other than an empty string, `0`, or `false` turns writing off
+pub const NO_WRITE_CONFIG_ENV: &str = "E62RS_NO_WRITE_CONFIG";
+
 #[cfg(feature = "cli")]
 impl E62Rs {
+    /// whether [`NO_WRITE_CONFIG_ENV`] is set
+    pub fn config_writes_disabled() -> bool {
+        std::env::var(NO_WRITE_CONFIG_ENV).is_ok_and(|v| {
+            let v = v.trim();
+            !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false")
+        })
+    }
+
     /// load configuration
+    ///
+    /// sources are layered, with later ones overriding earlier ones:
+    /// 1. the embedded defaults (`resources/e62rs.default.toml`)
+    /// 2. the global config (`e62rs.toml` in the platform config dir)
+    /// 3. a local `e62rs.toml` found from the working directory
+    /// 4. `E62RS_*` environment variables
+    ///
+    /// if the global config doesn't exist, a copy of the defaults is written there first, unless
+    /// [`NO_WRITE_CONFIG_ENV`] is set. then nothing is written and the missing file is skipped,
+    /// so e62rs runs from the defaults, a local config, and the environment alone
     pub fn load() -> Result<Self> {
         let global_path = Self::global_config_path()?;
         let defaults = Self::load_defaults()?;
 
-        if !global_path.exists() {
+        if !global_path.exists() && Self::config_writes_disabled() {
+            info!(
+                "No global configuration found, not creating one since {} is set",
+                NO_WRITE_CONFIG_ENV
+            );
+        } else if !global_path.exists() {
             info!("No global configuration found. Creating default config...");
             Self::make_default_config(&global_path, &defaults)
                 .wrap_err("Failed to create default configuration file")
@@ -997,6 +1632,7 @@ impl E62Rs {
             )?;
 
         Self::run_validation(&cfg)?;
+        Self::warn_about_user_agent(&cfg);
 
         print!("\x1B[2J\x1B[3J\x1B[H");
         std::io::stdout()
@@ -1065,8 +1701,30 @@ impl E62Rs {
             .suggestion("Try deleting the config file to regenerate defaults")
     }
 
+    /// warn if the user agent doesn't follow e621's format
+    ///
+    /// e621 soft-bans clients with malformed user agents, but a slightly different one may still
+    /// work, so this doesn't stop the config from loading
+    fn warn_about_user_agent(cfg: &Self) {
+        let user_agent = cfg
+            .http
+            .as_ref()
+            .and_then(|http| http.user_agent.as_deref())
+            .unwrap_or_default();
+
+        if !is_valid_user_agent(user_agent) {
+            warn!(
+                "http.user-agent '{}' doesn't match `<project>/<version> (by <username> on \
+                 e621)`, e621 may block requests made with it",
+                user_agent
+            );
+        }
+    }
+
     /// find the local config file
-    fn find_local_config() -> Result<Option<PathBuf>> {
+    ///
+    /// looks for an `e62rs.toml` in the working directory and each of its parents
+    pub fn find_local_config() -> Result<Option<PathBuf>> {
         let curr_dir = std::env::current_dir()
             .wrap_err("Failed to get current working directory")
             .suggestion("Ensure the current directory exists and is accessible")?;
@@ -1131,3 +1789,113 @@ impl E62Rs {
         self.save_to_file(&path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_resolve_api_key() {
+        let dir = TempDir::new().unwrap();
+        let key_file = dir.path().join("key");
+        let empty_file = dir.path().join("empty");
+        std::fs::write(&key_file, "from-file\n").unwrap();
+        std::fs::write(&empty_file, " \n").unwrap();
+        let path = |p: &std::path::Path| Some(p.to_string_lossy().to_string());
+
+        let login = LoginCfg {
+            api_key: Some(" inline ".to_string()),
+            api_key_file: path(&dir.path().join("missing")),
+            use_keyring: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(login.resolve_api_key().unwrap(), "inline");
+
+        let login = LoginCfg {
+            api_key: Some(String::new()),
+            api_key_file: path(&key_file),
+            use_keyring: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(login.resolve_api_key().unwrap(), "from-file");
+
+        let login = LoginCfg {
+            api_key_file: path(&dir.path().join("missing")),
+            ..Default::default()
+        };
+        assert!(login.resolve_api_key().is_err());
+
+        let login = LoginCfg {
+            api_key_file: path(&empty_file),
+            use_keyring: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(login.resolve_api_key().unwrap(), "");
+
+        // an empty file falls through to the keyring, which needs a username
+        let login = LoginCfg {
+            username: None,
+            api_key_file: path(&empty_file),
+            use_keyring: Some(true),
+            ..Default::default()
+        };
+        assert!(login.resolve_api_key().is_err());
+    }
+
+    #[test]
+    fn test_resolved_cache_dir() {
+        let resolve = |dir: &str| {
+            CacheConfig {
+                cache_dir: Some(dir.to_string()),
+                ..Default::default()
+            }
+            .resolved_cache_dir()
+        };
+
+        assert_eq!(resolve(".cache"), PathBuf::from(".cache"));
+        assert_eq!(resolve("./cache"), PathBuf::from("./cache"));
+        assert_eq!(resolve("../cache"), PathBuf::from("../cache"));
+
+        let absolute = std::env::temp_dir().join("e62rs-cache");
+        assert_eq!(resolve(&absolute.to_string_lossy()), absolute);
+
+        let expected = dirs::cache_dir()
+            .map(|base| base.join("e62rs"))
+            .unwrap_or_else(|| PathBuf::from("e62rs"));
+        assert_eq!(resolve("e62rs"), expected);
+        assert_eq!(CacheConfig::default().resolved_cache_dir(), expected);
+    }
+
+    #[test]
+    fn test_redact_round_trip() {
+        let original = E62Rs {
+            login: Some(LoginCfg {
+                username: Some("user".to_string()),
+                api_key: Some("secret".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let redacted = original.redacted(true);
+        let login = redacted.login.as_ref().expect("login section");
+        assert_eq!(login.username.as_deref(), Some(REDACTED));
+        assert_eq!(login.api_key.as_deref(), Some(REDACTED));
+
+        let text = toml::to_string_pretty(&redacted).unwrap();
+        assert!(!text.contains("secret"));
+
+        let mut restored: E62Rs = toml::from_str(&text).unwrap();
+        restored.restore_secrets(&original);
+        let login = restored.login.as_ref().expect("login section");
+        assert_eq!(login.username.as_deref(), Some("user"));
+        assert_eq!(login.api_key.as_deref(), Some("secret"));
+
+        let mut edited = original.redacted(false);
+        edited.login.as_mut().expect("login section").api_key = Some("new".to_string());
+        edited.restore_secrets(&original);
+        let login = edited.login.as_ref().expect("login section");
+        assert_eq!(login.username.as_deref(), Some("user"));
+        assert_eq!(login.api_key.as_deref(), Some("new"));
+    }
+}
@@ -1,9 +1,88 @@
 //! configuration validation stuff
 use {
-    crate::{config::options::*, validator, validator_nested},
+    crate::{
+        config::{format::FormatTemplate, options::*},
+        ui::menus::explore::expr::TagExpr,
+        validator, validator_nested,
+    },
     color_eyre::Result,
+    regex::Regex,
+    std::sync::LazyLock,
 };
 
+/// ratings a download route can match, or the gallery can show by default
+const VALID_RATINGS: &[&str] = &["s", "q", "e", "safe", "questionable", "explicit"];
+
+/// check that a download route has a directory, a known rating, and a valid tag expression
+fn is_valid_route(route: &DownloadRoute) -> bool {
+    !route.dir.trim().is_empty()
+        && route
+            .rating
+            .as_deref()
+            .is_none_or(|r| VALID_RATINGS.contains(&r.to_lowercase().as_str()))
+        && route
+            .tags
+            .as_deref()
+            .is_none_or(|t| TagExpr::parse(t).is_ok())
+}
+
+/// check that a sidecar pattern names one sidecar per download that can't be mistaken for one
+///
+/// it needs `{stem}` and `{ext}` (so `123.png` and `123.jpg` get different sidecars), no path
+/// separators, and a fixed suffix after the last placeholder, since that suffix is how
+/// sidecars are told apart from downloads
+fn is_valid_sidecar_pattern(pattern: &str) -> bool {
+    let suffix = pattern.rsplit('}').next().unwrap_or_default();
+
+    pattern.contains("{stem}")
+        && pattern.contains("{ext}")
+        && !pattern.contains(['/', '\\'])
+        && !suffix.contains('{')
+        && !suffix.trim_matches('.').is_empty()
+}
+
+/// the user agent format e621 asks for: `<project>/<version> (by <username> on <e621/e926>)`
+static USER_AGENT_FORMAT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Za-z0-9._-]+/v?[0-9][0-9A-Za-z.+-]* \(by [A-Za-z0-9_~.-]+ on e(621|926)\)$")
+        .expect("the user agent regex is valid")
+});
+
+/// usernames that only show up in user agents copied from an example without filling them in
+const PLACEHOLDER_USERNAMES: &[&str] = &[
+    "username",
+    "user",
+    "your-username",
+    "your_username",
+    "yourusername",
+    "yourname",
+];
+
+/// check whether a user agent matches the format e621 asks for
+///
+/// # Arguments
+///
+/// * `user_agent` - the user agent to check
+pub fn is_valid_user_agent(user_agent: &str) -> bool {
+    USER_AGENT_FORMAT.is_match(user_agent.trim())
+}
+
+/// check whether a user agent is an unfilled template, like
+/// `<project name>/<project version> (by <username> on e621)`
+///
+/// # Arguments
+///
+/// * `user_agent` - the user agent to check
+pub fn is_placeholder_user_agent(user_agent: &str) -> bool {
+    if user_agent.contains('<') || user_agent.contains('>') {
+        return true;
+    }
+
+    user_agent
+        .split_once("(by ")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .is_some_and(|name| PLACEHOLDER_USERNAMES.contains(&name.to_lowercase().as_str()))
+}
+
 /// trait for validating config structs
 pub trait Validate {
     /// validate the config
@@ -28,8 +107,8 @@ validator! { HttpConfig,
         "must be between 1 and 15";
     tcp_keepalive_secs => |v: &u64| *v > 0,
         "must be greater than 0";
-    user_agent => |v: &String| !v.trim().is_empty(),
-        "must not be empty";
+    user_agent => |v: &String| !v.trim().is_empty() && !is_placeholder_user_agent(v),
+        "must be filled in with your project and e621 username, like `my-project/1.0.0 (by username123 on e621)`";
     api => |v: &String| v.starts_with("http://") || v.starts_with("https://"),
         "must be a valid url and not link to e6ai";
 }
@@ -78,6 +157,8 @@ const VALID_PROGRESS_MESSAGE_MODES: &[&str] = &["id", "filename"];
 validator! { UiConfig,
     pagination_size => |v: &usize| *v > 0 && *v <= 1000,
         "must be between 1 and 1000";
+    post_list_format => |v: &String| FormatTemplate::parse(v).is_ok(),
+        "must be a valid format template";
 }
 
 validator! { ProgressCfg,
@@ -107,6 +188,12 @@ validator! { ImageDisplay,
         "must be between 1 and 100";
     resize_method => |v: &String| VALID_RESIZE_METHODS.contains(&v.to_lowercase().as_str()),
         "must be one of: nearest, linear, cubic, gaussian, lanczos3";
+    fetch_timeout => |v: &u64| *v > 0,
+        "must be greater than 0";
+    max_frames => |v: &usize| *v > 0,
+        "must be greater than 0";
+    max_megapixels => |v: &f64| *v > 0.0,
+        "must be greater than 0";
 }
 
 impl Validate for SearchCfg {
@@ -119,10 +206,22 @@ impl Validate for SearchCfg {
             errors.push("results_limit: must be greater than 0".to_string());
         }
 
-        if let Some(ref v) = self.blacklist
-            && v.iter().any(|tag| tag.trim().is_empty())
+        if let Some(v) = self.max_pages
+            && v == 0
         {
-            errors.push("blacklist: tags must not be empty strings".to_string());
+            errors.push("max_pages: must be greater than 0".to_string());
+        }
+
+        for (name, list) in [
+            ("blacklist", &self.blacklist),
+            ("blacklist_e621", &self.blacklist_e621),
+            ("blacklist_e926", &self.blacklist_e926),
+        ] {
+            if let Some(v) = list
+                && v.iter().any(|tag| tag.trim().is_empty())
+            {
+                errors.push(format!("{}: tags must not be empty strings", name));
+            }
         }
 
         if let Some(v) = self.fetch_threads
@@ -149,6 +248,10 @@ impl Validate for SearchCfg {
 validator! { CompletionCfg,
     tag_similarity_threshold => |v: &f64| *v >= 0.0 && *v <= 1.0,
         "must be between 0.0 and 1.0";
+    name_weight => |v: &f64| *v >= 0.0,
+        "must not be negative";
+    desc_weight => |v: &f64| *v >= 0.0,
+        "must not be negative";
     tags => |v: &String| !v.trim().is_empty(),
         "must not be empty";
     aliases => |v: &String| !v.trim().is_empty(),
@@ -168,8 +271,12 @@ impl Validate for LoginCfg {
                 errors.push("username: required when login is enabled".to_string());
             }
 
-            if self.api_key.as_ref().is_none_or(|s| s.trim().is_empty()) {
-                errors.push("api_key: required when login is enabled".to_string());
+            let blank = |v: &Option<String>| v.as_ref().is_none_or(|s| s.trim().is_empty());
+            if blank(&self.api_key) && blank(&self.api_key_file) && self.use_keyring != Some(true) {
+                errors.push(
+                    "api_key: required when login is enabled (or set api_key_file or use_keyring)"
+                        .to_string(),
+                );
             }
         }
 
@@ -197,15 +304,27 @@ const VALID_SORT_OPTIONS: &[&str] = &[
     "size_smallest",
     "name_asc",
     "name_desc",
+    "downloaded_newest",
+    "downloaded_oldest",
 ];
 
 validator! { ExplorerCfg,
     default_sort => |v: &String| VALID_SORT_OPTIONS.contains(&v.to_lowercase().as_str()),
-        "must be one of: date_newest, date_oldest, score_highest, score_lowest, size_largest, size_smallest, name_asc, name_desc";
+        "must be one of: date_newest, date_oldest, score_highest, score_lowest, size_largest, size_smallest, name_asc, name_desc, downloaded_newest, downloaded_oldest";
     posts_per_page => |v: &usize| *v > 0 && *v <= 1000,
         "must be between 1 and 1000";
     slideshow_delay => |v: &u64| *v > 0,
         "must be greater than 0";
+    scan_threads => |v: &usize| *v > 0,
+        "must be greater than 0";
+    contact_sheet_columns => |v: &u32| *v > 0,
+        "must be greater than 0";
+    contact_sheet_max_size => |v: &u32| *v >= 256,
+        "must be at least 256";
+    video_player => |v: &String| !v.trim().is_empty(),
+        "must not be empty";
+    staging_dir => |v: &String| !v.trim().is_empty(),
+        "must not be empty";
 }
 
 validator! { DownloadCfg,
@@ -215,8 +334,16 @@ validator! { DownloadCfg,
         "must not be empty";
     threads => |v: &usize| *v >= 1 && *v <= 15,
         "must be between 1 and 15";
+    timeout => |v: &u64| *v > 0,
+        "must be greater than 0";
+    watch_interval => |v: &u64| *v > 0,
+        "must be greater than 0";
     format => |v: &String| !v.trim().is_empty() && v.contains("$id"),
         "must not be empty and must contain $id placeholder";
+    sidecar_pattern => |v: &String| is_valid_sidecar_pattern(v),
+        "must contain {stem} and {ext}, end with a fixed suffix (like `.json`), and can't contain path separators";
+    routes => |v: &Vec<DownloadRoute>| v.iter().all(is_valid_route),
+        "every route needs a `dir`, and its `rating` and `tags` must be a rating (s/q/e) and a valid tag expression";
 }
 
 /// valid themes for the gallery
@@ -231,12 +358,16 @@ const VALID_THEMES: &[&str] = &[
 ];
 
 validator! { GalleryCfg,
+    bind_host => |v: &String| v.trim().parse::<std::net::IpAddr>().is_ok(),
+        "must be an IP address (e.g. 127.0.0.1 or 0.0.0.0)";
     port => |v: &u16| *v > 0,
         "must be a valid port (1-65535)";
     load_threads => |v: &usize| *v > 0,
         "must be greater than 0";
     theme => |v: &String| VALID_THEMES.contains(&v.to_lowercase().as_str()),
         "must be one of: rose-pine, rose-pine-moon, rose-pine-dawn, catppuccin-latte, catppuccin-frappe, catppuccin-macchiato, catppuccin-mocha";
+    default_rating => |v: &String| v.is_empty() || VALID_RATINGS.contains(&v.to_lowercase().as_str()),
+        "must be a rating (s/q/e) or empty";
 }
 
 impl Validate for E62Rs {
@@ -289,3 +420,63 @@ pub fn format_validation_errors(errors: &[String]) -> String {
     }
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_agent_examples() {
+        for ua in [
+            "my-project/1.2.3 (by username123 on e621)",
+            "another/2.0.0-beta.1 (by user7890 on e926)",
+            "test-proj/0.1.0+build.123 (by myuser12345 on e621)",
+            "e62rs/v1.0.2 (by bearodactyl on e621)",
+        ] {
+            assert!(is_valid_user_agent(ua), "{ua}");
+            assert!(!is_placeholder_user_agent(ua), "{ua}");
+        }
+    }
+
+    #[test]
+    fn test_sidecar_patterns() {
+        for pattern in [
+            "{stem}.{ext}.json",
+            ".{stem}.{ext}.meta",
+            "{ext}-{stem}.txt",
+        ] {
+            assert!(is_valid_sidecar_pattern(pattern), "{pattern}");
+        }
+
+        for pattern in [
+            "{stem}.json",
+            "{stem}.{ext}",
+            "{stem}_meta.{ext}",
+            "{stem}.{ext}.",
+            "meta/{stem}.{ext}.json",
+        ] {
+            assert!(!is_valid_sidecar_pattern(pattern), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn test_bad_user_agents() {
+        for ua in [
+            "",
+            "Mozilla/5.0",
+            "my-project (by username123 on e621)",
+            "my-project/1.2.3 (by username123)",
+            "my-project/1.2.3 (by username123 on e6ai)",
+        ] {
+            assert!(!is_valid_user_agent(ua), "{ua}");
+        }
+
+        for ua in [
+            "<project name>/<project version> (by <valid e6 username> on <e621/e926>)",
+            "my-project/1.2.3 (by username on e621)",
+            "my-project/1.2.3 (by YOUR_USERNAME on e621)",
+        ] {
+            assert!(is_placeholder_user_agent(ua), "{ua}");
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! exports of downloads for importing into a self-hosted booru (szurubooru, philomena, ...)
+//!
+//! every download with stored metadata is copied into the export directory as `<id>.<ext>`,
+//! and a [`BOORU_MANIFEST_FILE`] next to them lists each file's tags (by category), rating,
+//! sources, and sha256
+use {
+    crate::{
+        bail,
+        config::options::MetadataBackend,
+        data::{manifest::sha256_file, metadata},
+        error::Result,
+        models::{E6Post, TagCategory},
+    },
+    color_eyre::eyre::Context,
+    hashbrown::HashSet,
+    jwalk::WalkDir,
+    rayon::prelude::*,
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::BTreeMap,
+        path::{Path, PathBuf},
+    },
+};
+
+/// the name of the manifest written next to the exported files
+pub const BOORU_MANIFEST_FILE: &str = "manifest.json";
+
+/// a single exported file in the manifest
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BooruEntry {
+    /// the name of the file in the export directory
+    pub file: String,
+    /// the id of the post on e621
+    pub id: i64,
+    /// the post's tags, keyed by category (categories without tags are left out)
+    pub tags: BTreeMap<String, Vec<String>>,
+    /// the rating (`safe`, `questionable`, or `explicit`)
+    pub rating: String,
+    /// the post's sources
+    pub source: Vec<String>,
+    /// the sha256 of the file (lowercase hex)
+    pub sha256: String,
+}
+
+impl BooruEntry {
+    /// make the manifest entry of an exported post
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - the name of the exported file
+    /// * `post` - the post's stored metadata
+    /// * `sha256` - the sha256 of the file
+    pub fn new(file: String, post: &E6Post, sha256: String) -> Self {
+        let tags = TagCategory::ALL
+            .iter()
+            .filter(|category| **category != TagCategory::Invalid)
+            .map(|category| (category, post.tags.category(*category)))
+            .filter(|(_, tags)| !tags.is_empty())
+            .map(|(category, tags)| (category.name().to_string(), tags.to_vec()))
+            .collect();
+
+        Self {
+            file,
+            id: post.id,
+            tags,
+            rating: post.content_rating().name().to_string(),
+            source: post.sources.clone(),
+            sha256,
+        }
+    }
+}
+
+/// the result of an export
+#[derive(Debug, Default)]
+pub struct BooruExport {
+    /// the exported files, sorted by post id
+    pub entries: Vec<BooruEntry>,
+    /// files that were left out because they have no stored metadata
+    pub skipped: usize,
+    /// files that were left out because another file of the same post was already exported
+    pub duplicates: usize,
+}
+
+/// copy every download with stored metadata into a directory and write its manifest
+///
+/// # Arguments
+///
+/// * `roots` - the download directories to walk
+/// * `out` - the directory to export to
+/// * `backend` - the metadata backend to check first
+///
+/// # Errors
+///
+/// returns an error if the export directory can't be made, a file can't be copied or hashed,
+/// or the manifest can't be written
+pub fn export_booru(
+    roots: &[PathBuf],
+    out: &Path,
+    backend: MetadataBackend,
+) -> Result<BooruExport> {
+    if out.is_file() {
+        bail!("'{}' is a file, not a directory", out.display());
+    }
+
+    std::fs::create_dir_all(out)
+        .with_context(|| format!("failed to create '{}'", out.display()))?;
+    let out_abs = std::path::absolute(out).unwrap_or(out.to_path_buf());
+
+    let files: Vec<PathBuf> = roots
+        .iter()
+        .filter(|root| root.is_dir())
+        .flat_map(|root| WalkDir::new(root).follow_links(false))
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && !metadata::is_metadata_file(p))
+        .filter(|p| !std::path::absolute(p).is_ok_and(|p| p.starts_with(&out_abs)))
+        .collect();
+
+    let found: Vec<(PathBuf, Option<E6Post>)> = files
+        .into_par_iter()
+        .map(|path| {
+            let post = metadata::read_metadata(&path, backend).ok();
+            (path, post)
+        })
+        .collect();
+
+    let mut export = BooruExport::default();
+    let mut seen = HashSet::new();
+    let mut posts = Vec::new();
+    for (path, post) in found {
+        match post {
+            None => export.skipped += 1,
+            Some(post) if !seen.insert(post.id) => export.duplicates += 1,
+            Some(post) => posts.push((path, post)),
+        }
+    }
+
+    export.entries = posts
+        .par_iter()
+        .map(|(path, post)| -> Result<BooruEntry> {
+            let ext = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_else(|| post.file.ext.clone());
+            let file = format!("{}.{}", post.id, ext);
+            let dest = out.join(&file);
+
+            std::fs::copy(path, &dest).with_context(|| {
+                format!(
+                    "failed to copy '{}' to '{}'",
+                    path.display(),
+                    dest.display()
+                )
+            })?;
+
+            Ok(BooruEntry::new(file, post, sha256_file(&dest)?))
+        })
+        .collect::<Result<_>>()?;
+    export.entries.sort_by_key(|entry| entry.id);
+
+    let manifest = out.join(BOORU_MANIFEST_FILE);
+    std::fs::write(&manifest, serde_json::to_string_pretty(&export.entries)?)
+        .with_context(|| format!("failed to write '{}'", manifest.display()))?;
+
+    Ok(export)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_export_booru() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("downloads");
+        std::fs::create_dir_all(root.join("artist")).unwrap();
+
+        let mut post = E6Post {
+            id: 7,
+            rating: "q".to_string(),
+            sources: vec!["https://example.com/art".to_string()],
+            ..Default::default()
+        };
+        post.tags.artist = vec!["someone".to_string()];
+        post.tags.general = vec!["solo".to_string(), "smile".to_string()];
+
+        let file = root.join("artist").join("7 - solo.png");
+        std::fs::write(&file, b"one").unwrap();
+        metadata::save_metadata(&file, &post, MetadataBackend::Sidecar, true).unwrap();
+
+        let copy = root.join("7 again.png");
+        std::fs::write(&copy, b"one").unwrap();
+        metadata::save_metadata(&copy, &post, MetadataBackend::Sidecar, true).unwrap();
+
+        std::fs::write(root.join("no metadata.png"), b"two").unwrap();
+
+        let out = dir.path().join("export");
+        let export = export_booru(&[root], &out, MetadataBackend::Sidecar).unwrap();
+        assert_eq!(export.skipped, 1);
+        assert_eq!(export.duplicates, 1);
+        assert_eq!(export.entries.len(), 1);
+
+        let entry = &export.entries[0];
+        assert_eq!(entry.file, "7.png");
+        assert_eq!(entry.rating, "questionable");
+        assert_eq!(entry.tags["artist"], vec!["someone"]);
+        assert_eq!(entry.tags["general"], vec!["solo", "smile"]);
+        assert!(!entry.tags.contains_key("species"));
+        assert_eq!(entry.source, vec!["https://example.com/art"]);
+        assert_eq!(
+            entry.sha256,
+            "7692c3ad3540bb803c020b3aee66cd8887123234ea0c6e7143c0add73ff431ed"
+        );
+
+        assert_eq!(std::fs::read(out.join("7.png")).unwrap(), b"one");
+        let manifest: Vec<BooruEntry> =
+            serde_json::from_str(&std::fs::read_to_string(out.join(BOORU_MANIFEST_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(&manifest, &export.entries);
+    }
+}
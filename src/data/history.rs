@@ -7,6 +7,7 @@ use {
     },
 };
 
+/// the most entries kept in the history
 const MAX_ENTRIES: usize = 100;
 
 /// persisted search history
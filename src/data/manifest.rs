@@ -0,0 +1,340 @@
+//! checksum manifests of the download directory
+//!
+//! a manifest has one line per file: `<md5> <size> <relative path>`. the path comes last so it
+//! can contain spaces, and always uses `/` as the separator so a manifest written on one os can
+//! verify a copy on another
+use {
+    crate::{
+        bail,
+        error::{E6Error, Result},
+    },
+    color_eyre::eyre::Context,
+    hashbrown::{HashMap, HashSet},
+    jwalk::WalkDir,
+    md5::{Digest, Md5},
+    rayon::prelude::*,
+    sha2::Sha256,
+    std::{
+        fs::File,
+        io::{BufRead, BufReader, BufWriter, Write},
+        path::{Path, PathBuf},
+    },
+};
+
+/// a single file in a manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// the md5 of the file (lowercase hex)
+    pub md5: String,
+    /// the size of the file in bytes
+    pub size: u64,
+    /// the path of the file, relative to the manifest root
+    pub path: String,
+}
+
+impl std::fmt::Display for ManifestEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.md5, self.size, self.path)
+    }
+}
+
+impl std::str::FromStr for ManifestEntry {
+    type Err = E6Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let mut parts = line.splitn(3, ' ');
+        let (Some(md5), Some(size), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            bail!("expected `<md5> <size> <path>`, got '{}'", line);
+        };
+
+        if md5.len() != 32 || !md5.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("'{}' is not an md5 hash", md5);
+        }
+
+        Ok(Self {
+            md5: md5.to_lowercase(),
+            size: size
+                .parse()
+                .with_context(|| format!("'{}' is not a file size", size))?,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// the result of checking a directory against a manifest
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// how many files matched their manifest entry
+    pub ok: usize,
+    /// files whose size or md5 doesn't match the manifest
+    pub mismatched: Vec<String>,
+    /// files in the manifest that don't exist on disk
+    pub missing: Vec<String>,
+    /// files on disk that aren't in the manifest
+    pub extra: Vec<String>,
+}
+
+impl VerifyReport {
+    /// whether every file matched and nothing is missing or extra
+    pub fn is_clean(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// md5 a file without reading it all into memory
+///
+/// # Arguments
+///
+/// * `path` - the file to hash
+///
+/// # Errors
+///
+/// returns an error if the file can't be read
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut reader = BufReader::new(
+        File::open(path).with_context(|| format!("failed to open '{}'", path.display()))?,
+    );
+    let mut hasher = Md5::new();
+    std::io::copy(&mut reader, &mut hasher)
+        .with_context(|| format!("failed to read '{}'", path.display()))?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// sha256 a file without reading it all into memory
+///
+/// # Arguments
+///
+/// * `path` - the file to hash
+///
+/// # Errors
+///
+/// returns an error if the file can't be read
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut reader = BufReader::new(
+        File::open(path).with_context(|| format!("failed to open '{}'", path.display()))?,
+    );
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut reader, &mut hasher)
+        .with_context(|| format!("failed to read '{}'", path.display()))?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// get the manifest path of a file relative to `root`
+fn relative_path(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// list every file under `root`, skipping `exclude` (usually the manifest itself)
+fn list_files(root: &Path, exclude: Option<&Path>) -> Vec<PathBuf> {
+    let exclude = exclude.and_then(|p| p.canonicalize().ok());
+
+    let mut files: Vec<PathBuf> = WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| exclude.is_none() || p.canonicalize().ok() != exclude)
+        .collect();
+
+    files.sort();
+    files
+}
+
+/// hash every file under a directory
+///
+/// # Arguments
+///
+/// * `root` - the directory to walk
+/// * `exclude` - a file to leave out, like the manifest being written
+///
+/// # Errors
+///
+/// returns an error if any file can't be read
+pub fn build_manifest(root: &Path, exclude: Option<&Path>) -> Result<Vec<ManifestEntry>> {
+    if !root.is_dir() {
+        bail!("'{}' is not a directory", root.display());
+    }
+
+    list_files(root, exclude)
+        .par_iter()
+        .map(|path| -> Result<ManifestEntry> {
+            Ok(ManifestEntry {
+                md5: hash_file(path)?,
+                size: path.metadata()?.len(),
+                path: relative_path(root, path),
+            })
+        })
+        .collect()
+}
+
+/// write a manifest of every file under a directory
+///
+/// # Arguments
+///
+/// * `root` - the directory to walk
+/// * `manifest` - where to write the manifest
+///
+/// # Errors
+///
+/// returns an error if a file can't be hashed or the manifest can't be written
+pub fn write_manifest(root: &Path, manifest: &Path) -> Result<usize> {
+    let entries = build_manifest(root, Some(manifest))?;
+    let mut out = BufWriter::new(
+        File::create(manifest)
+            .with_context(|| format!("failed to create '{}'", manifest.display()))?,
+    );
+
+    for entry in &entries {
+        writeln!(out, "{}", entry)?;
+    }
+    out.flush()?;
+
+    Ok(entries.len())
+}
+
+/// read the entries of a manifest
+///
+/// blank lines and lines starting with `#` are ignored
+///
+/// # Arguments
+///
+/// * `manifest` - the manifest to read
+///
+/// # Errors
+///
+/// returns an error if the manifest can't be read or a line is malformed
+pub fn read_manifest(manifest: &Path) -> Result<Vec<ManifestEntry>> {
+    let reader = BufReader::new(
+        File::open(manifest).with_context(|| format!("failed to open '{}'", manifest.display()))?,
+    );
+
+    let mut entries = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.parse() {
+            Ok(entry) => entries.push(entry),
+            Err(e) => bail!("line {} of '{}': {}", i + 1, manifest.display(), e),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// re-hash a directory and compare it against a manifest
+///
+/// sizes are compared first so files with the wrong length aren't hashed
+///
+/// # Arguments
+///
+/// * `root` - the directory to check
+/// * `manifest` - the manifest to check against
+///
+/// # Errors
+///
+/// returns an error if the manifest can't be read or a file can't be hashed
+pub fn verify_manifest(root: &Path, manifest: &Path) -> Result<VerifyReport> {
+    let entries = read_manifest(manifest)?;
+    let expected: HashMap<&str, &ManifestEntry> =
+        entries.iter().map(|e| (e.path.as_str(), e)).collect();
+
+    let on_disk: HashSet<String> = list_files(root, Some(manifest))
+        .iter()
+        .map(|p| relative_path(root, p))
+        .collect();
+
+    let checked: Vec<(String, bool)> = entries
+        .par_iter()
+        .filter(|e| on_disk.contains(&e.path))
+        .map(|e| -> Result<(String, bool)> {
+            let path = root.join(&e.path);
+            let matches = path.metadata()?.len() == e.size && hash_file(&path)? == e.md5;
+            Ok((e.path.clone(), matches))
+        })
+        .collect::<Result<_>>()?;
+
+    let mut report = VerifyReport::default();
+    for (path, matches) in checked {
+        if matches {
+            report.ok += 1;
+        } else {
+            report.mismatched.push(path);
+        }
+    }
+
+    report.missing = entries
+        .iter()
+        .filter(|e| !on_disk.contains(&e.path))
+        .map(|e| e.path.clone())
+        .collect();
+
+    report.extra = on_disk
+        .into_iter()
+        .filter(|p| !expected.contains_key(p.as_str()))
+        .collect();
+    report.extra.sort();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_write_and_verify() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("downloads");
+        std::fs::create_dir_all(root.join("artist name")).unwrap();
+        std::fs::write(root.join("1.png"), b"one").unwrap();
+        std::fs::write(root.join("artist name").join("2 copy.webm"), b"two").unwrap();
+        std::fs::write(root.join("3.jpg"), b"three").unwrap();
+
+        let manifest = dir.path().join("manifest.txt");
+        assert_eq!(write_manifest(&root, &manifest).unwrap(), 3);
+
+        let entries = read_manifest(&manifest).unwrap();
+        assert_eq!(entries[0].md5, "f97c5d29941bfb1b2fdab0874906ab82");
+        assert_eq!(entries[0].size, 3);
+        assert!(entries.iter().any(|e| e.path == "artist name/2 copy.webm"));
+        assert!(verify_manifest(&root, &manifest).unwrap().is_clean());
+
+        std::fs::write(root.join("1.png"), b"uno").unwrap();
+        std::fs::remove_file(root.join("3.jpg")).unwrap();
+        std::fs::write(root.join("4.gif"), b"four").unwrap();
+
+        let report = verify_manifest(&root, &manifest).unwrap();
+        assert_eq!(report.ok, 1);
+        assert_eq!(report.mismatched, vec!["1.png"]);
+        assert_eq!(report.missing, vec!["3.jpg"]);
+        assert_eq!(report.extra, vec!["4.gif"]);
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        let entry: ManifestEntry = "F97C5D29941BFB1B2FDAB0874906AB82 3 a b/c.png"
+            .parse()
+            .unwrap();
+        assert_eq!(entry.md5, "f97c5d29941bfb1b2fdab0874906ab82");
+        assert_eq!(entry.path, "a b/c.png");
+        assert_eq!(
+            entry.to_string(),
+            "f97c5d29941bfb1b2fdab0874906ab82 3 a b/c.png"
+        );
+
+        for line in ["", "abc 3 x", "f97c5d29941bfb1b2fdab0874906ab82 big x"] {
+            assert!(line.parse::<ManifestEntry>().is_err(), "{line}");
+        }
+    }
+}
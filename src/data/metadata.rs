@@ -0,0 +1,553 @@
+//! storage of downloaded posts' metadata
+//!
+//! metadata can live in one of three places (see [`MetadataBackend`]):
+//! * a sidecar next to each download, named by `download.sidecar_pattern` (`<file>.<ext>.json`
+//!   by default)
+//! * an NTFS alternate data stream named `metadata` on each download (windows only)
+//! * a single redb database per directory ([`METADATA_DB_FILE`]), keyed by file name
+//!
+//! reads check the configured backend first and then fall back to the others, so switching
+//! backends doesn't hide metadata saved before the switch
+use {
+    crate::{
+        bail, config::options::MetadataBackend, error::Result, models::E6Post, utils::FileWriter,
+    },
+    color_eyre::eyre::Context,
+    hashbrown::HashMap,
+    postcard::{from_bytes, to_allocvec},
+    redb::{Database, ReadableDatabase, ReadableTableMetadata, TableDefinition},
+    std::{
+        path::{Path, PathBuf},
+        sync::{Arc, LazyLock, Mutex},
+    },
+};
+
+/// the name of the per-directory metadata database
+pub const METADATA_DB_FILE: &str = ".e62rs-metadata.redb";
+
+/// the empty file that tells android's media scanner (and gallery apps that honor it) to skip a
+/// download directory, see `download.write_nomedia`
+pub const NOMEDIA_FILE: &str = ".nomedia";
+
+/// the table of metadata, keyed by file name
+const METADATA_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("metadata");
+
+/// metadata databases that are already open, keyed by directory
+///
+/// redb only allows a database to be opened once per process, so every reader and writer
+/// shares the same handle
+static OPEN_DBS: LazyLock<Mutex<HashMap<PathBuf, Arc<Database>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// the default name of a sidecar, `<file>.<ext>.json`
+pub const DEFAULT_SIDECAR_PATTERN: &str = "{stem}.{ext}.json";
+
+/// get the configured sidecar naming pattern
+fn sidecar_pattern() -> String {
+    #[cfg(feature = "cli")]
+    {
+        crate::getopt!(download.sidecar_pattern)
+    }
+
+    #[cfg(not(feature = "cli"))]
+    {
+        DEFAULT_SIDECAR_PATTERN.to_string()
+    }
+}
+
+/// get the sidecar path of a downloaded file with a given naming pattern
+///
+/// `{stem}` is replaced with the file's name without its extension, and `{ext}` with its
+/// extension. the sidecar is always in the same directory as the file
+///
+/// # Arguments
+///
+/// * `file_path` - the path to the downloaded file
+/// * `pattern` - the sidecar naming pattern
+pub fn sidecar_path_with(file_path: &Path, pattern: &str) -> PathBuf {
+    let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = file_path.extension().unwrap_or_default().to_string_lossy();
+
+    file_path.with_file_name(pattern.replace("{stem}", &stem).replace("{ext}", &ext))
+}
+
+/// get the sidecar path of a downloaded file with the configured naming pattern
+///
+/// # Arguments
+///
+/// * `file_path` - the path to the downloaded file
+pub fn sidecar_path(file_path: &Path) -> PathBuf {
+    sidecar_path_with(file_path, &sidecar_pattern())
+}
+
+/// find the sidecar a downloaded file already has
+///
+/// checks the configured naming pattern first, then [`DEFAULT_SIDECAR_PATTERN`], so sidecars
+/// saved before the pattern was changed are still found
+///
+/// # Arguments
+///
+/// * `file_path` - the path to the downloaded file
+pub fn find_sidecar(file_path: &Path) -> Option<PathBuf> {
+    [
+        sidecar_path(file_path),
+        sidecar_path_with(file_path, DEFAULT_SIDECAR_PATTERN),
+    ]
+    .into_iter()
+    .find(|path| path.exists())
+}
+
+/// check whether a path is a metadata file (or a [`NOMEDIA_FILE`]) rather than a download
+///
+/// # Arguments
+///
+/// * `path` - the path to check
+pub fn is_metadata_file(path: &Path) -> bool {
+    let pattern = sidecar_pattern();
+    let suffix = pattern.rsplit('}').next().unwrap_or_default();
+
+    path.file_name().is_some_and(|n| {
+        n == METADATA_DB_FILE
+            || n == NOMEDIA_FILE
+            || (!suffix.is_empty() && n.to_string_lossy().ends_with(suffix))
+    }) || path.extension().is_some_and(|e| e == "json")
+}
+
+/// split a file path into its directory and file name
+fn db_key(file_path: &Path) -> Result<(PathBuf, String)> {
+    let Some(name) = file_path.file_name() else {
+        bail!("'{}' has no file name", file_path.display());
+    };
+
+    let dir = match file_path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let dir = std::path::absolute(&dir).unwrap_or(dir);
+
+    Ok((dir, name.to_string_lossy().to_string()))
+}
+
+/// get the metadata db of a directory
+///
+/// returns `None` if the directory has no db and `create` is false
+fn open_db(dir: &Path, create: bool) -> Result<Option<Arc<Database>>> {
+    let mut dbs = OPEN_DBS
+        .lock()
+        .map_err(|e| color_eyre::eyre::eyre!("metadata db lock poisoned: {}", e))?;
+
+    if let Some(db) = dbs.get(dir) {
+        return Ok(Some(Arc::clone(db)));
+    }
+
+    let db_path = dir.join(METADATA_DB_FILE);
+    if !create && !db_path.exists() {
+        return Ok(None);
+    }
+
+    let db = Arc::new(
+        Database::create(&db_path)
+            .with_context(|| format!("failed to open metadata db '{}'", db_path.display()))?,
+    );
+    dbs.insert(dir.to_path_buf(), Arc::clone(&db));
+
+    Ok(Some(db))
+}
+
+/// write metadata into the db of the file's directory
+fn save_to_db(file_path: &Path, post: &E6Post) -> Result<()> {
+    let (dir, name) = db_key(file_path)?;
+    let Some(db) = open_db(&dir, true)? else {
+        bail!("failed to open metadata db in '{}'", dir.display());
+    };
+
+    let serialized = to_allocvec(post).context("failed to serialize metadata")?;
+    let write_txn = db.begin_write()?;
+    {
+        let mut table = write_txn.open_table(METADATA_TABLE)?;
+        table.insert(name.as_str(), serialized.as_slice())?;
+    }
+    write_txn.commit()?;
+
+    Ok(())
+}
+
+/// read metadata from the db of the file's directory
+fn read_from_db(file_path: &Path) -> Result<Option<E6Post>> {
+    let (dir, name) = db_key(file_path)?;
+    let Some(db) = open_db(&dir, false)? else {
+        return Ok(None);
+    };
+
+    let read_txn = db.begin_read()?;
+    let table = match read_txn.open_table(METADATA_TABLE) {
+        Ok(table) => table,
+        Err(_) => return Ok(None),
+    };
+
+    match table.get(name.as_str())? {
+        Some(data) => Ok(Some(from_bytes(data.value()).with_context(|| {
+            format!("failed to parse metadata for {}", file_path.display())
+        })?)),
+        None => Ok(None),
+    }
+}
+
+/// remove a file's entry from the db of its directory
+///
+/// # Arguments
+///
+/// * `file_path` - the downloaded file
+///
+/// # Errors
+///
+/// returns an error if the db can't be opened or written
+pub fn remove_from_db(file_path: &Path) -> Result<()> {
+    let (dir, name) = db_key(file_path)?;
+    let Some(db) = open_db(&dir, false)? else {
+        return Ok(());
+    };
+
+    let write_txn = db.begin_write()?;
+    {
+        let mut table = write_txn.open_table(METADATA_TABLE)?;
+        table.remove(name.as_str())?;
+    }
+    write_txn.commit()?;
+
+    Ok(())
+}
+
+/// check whether a directory's metadata db has no entries left
+///
+/// a directory without a db counts as empty
+///
+/// # Arguments
+///
+/// * `dir` - the directory to check
+///
+/// # Errors
+///
+/// returns an error if the db can't be opened or read
+pub fn db_is_empty(dir: &Path) -> Result<bool> {
+    let dir = std::path::absolute(dir).unwrap_or(dir.to_path_buf());
+    let Some(db) = open_db(&dir, false)? else {
+        return Ok(true);
+    };
+
+    let read_txn = db.begin_read()?;
+    match read_txn.open_table(METADATA_TABLE) {
+        Ok(table) => Ok(table.is_empty()?),
+        Err(_) => Ok(true),
+    }
+}
+
+/// close and delete a directory's metadata db
+///
+/// # Arguments
+///
+/// * `dir` - the directory whose db to delete
+///
+/// # Errors
+///
+/// returns an error if the db file can't be deleted
+pub fn remove_db(dir: &Path) -> Result<()> {
+    let dir = std::path::absolute(dir).unwrap_or(dir.to_path_buf());
+    OPEN_DBS
+        .lock()
+        .map_err(|e| color_eyre::eyre::eyre!("metadata db lock poisoned: {}", e))?
+        .remove(&dir);
+
+    let db_path = dir.join(METADATA_DB_FILE);
+    if db_path.exists() {
+        std::fs::remove_file(&db_path)
+            .with_context(|| format!("failed to delete '{}'", db_path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// read metadata from a json sidecar
+fn read_from_sidecar(file_path: &Path) -> Result<Option<E6Post>> {
+    let Some(json_path) = find_sidecar(file_path) else {
+        return Ok(None);
+    };
+
+    let contents = std::fs::read_to_string(&json_path)
+        .with_context(|| format!("Failed to read metadata file {}", json_path.display()))?;
+
+    Ok(Some(serde_json::from_str(&contents).with_context(
+        || format!("Failed to parse metadata for {}", file_path.display()),
+    )?))
+}
+
+/// read metadata from an alternate data stream
+#[cfg(target_os = "windows")]
+fn read_from_ads(file_path: &Path) -> Result<Option<E6Post>> {
+    use std::io::Read;
+
+    let ads_path = format!("{}:metadata", file_path.display());
+    let Ok(mut file) = std::fs::OpenOptions::new().read(true).open(&ads_path) else {
+        return Ok(None);
+    };
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .with_context(|| format!("Failed to read ADS metadata for {}", file_path.display()))?;
+
+    Ok(Some(serde_json::from_str(&contents).with_context(
+        || format!("Failed to parse metadata for {}", file_path.display()),
+    )?))
+}
+
+/// read metadata from an alternate data stream (never present outside of windows)
+#[cfg(not(target_os = "windows"))]
+fn read_from_ads(_file_path: &Path) -> Result<Option<E6Post>> {
+    Ok(None)
+}
+
+/// read metadata from a single backend
+fn read_from(file_path: &Path, backend: MetadataBackend) -> Result<Option<E6Post>> {
+    match backend.resolve() {
+        MetadataBackend::Db => read_from_db(file_path),
+        MetadataBackend::Embedded => read_from_ads(file_path),
+        _ => read_from_sidecar(file_path),
+    }
+}
+
+/// the backends to check when reading, starting with the configured one
+fn read_order(backend: MetadataBackend) -> [MetadataBackend; 3] {
+    match backend.resolve() {
+        MetadataBackend::Db => [
+            MetadataBackend::Db,
+            MetadataBackend::Sidecar,
+            MetadataBackend::Embedded,
+        ],
+        MetadataBackend::Embedded => [
+            MetadataBackend::Embedded,
+            MetadataBackend::Sidecar,
+            MetadataBackend::Db,
+        ],
+        _ => [
+            MetadataBackend::Sidecar,
+            MetadataBackend::Embedded,
+            MetadataBackend::Db,
+        ],
+    }
+}
+
+/// save a downloaded post's metadata
+///
+/// # Arguments
+///
+/// * `file_path` - the path to the downloaded file
+/// * `post` - the post's metadata
+/// * `backend` - where to store the metadata
+/// * `pretty` - indent sidecar json instead of writing it on one line
+///
+/// # Errors
+///
+/// returns an error if the metadata can't be written
+pub fn save_metadata(
+    file_path: &Path,
+    post: &E6Post,
+    backend: MetadataBackend,
+    pretty: bool,
+) -> Result<()> {
+    match backend.resolve() {
+        MetadataBackend::Db => save_to_db(file_path, post),
+        MetadataBackend::Embedded => {
+            let mut writer = FileWriter::ads(file_path, "metadata", false)?;
+            writer.write(post)?;
+            writer.flush()?;
+            Ok(())
+        }
+        _ => {
+            let mut writer = FileWriter::json(sidecar_path(file_path), pretty)?;
+            writer.write(post)?;
+            Ok(())
+        }
+    }
+}
+
+/// read a downloaded post's metadata
+///
+/// # Arguments
+///
+/// * `file_path` - the path to the downloaded file
+/// * `backend` - the backend to check first
+///
+/// # Errors
+///
+/// returns an error if no backend has metadata for the file, or it can't be parsed
+pub fn read_metadata(file_path: &Path, backend: MetadataBackend) -> Result<E6Post> {
+    for backend in read_order(backend) {
+        if let Some(post) = read_from(file_path, backend)? {
+            return Ok(post);
+        }
+    }
+
+    bail!("No metadata found for {}", file_path.display())
+}
+
+/// check whether a downloaded file has metadata in any backend
+///
+/// # Arguments
+///
+/// * `file_path` - the path to the downloaded file
+/// * `backend` - the backend to check first
+pub fn has_metadata(file_path: &Path, backend: MetadataBackend) -> bool {
+    read_order(backend)
+        .into_iter()
+        .any(|backend| match backend {
+            MetadataBackend::Db => read_from_db(file_path).is_ok_and(|p| p.is_some()),
+            MetadataBackend::Embedded => {
+                cfg!(target_os = "windows")
+                    && std::fs::OpenOptions::new()
+                        .read(true)
+                        .open(format!("{}:metadata", file_path.display()))
+                        .is_ok()
+            }
+            _ => find_sidecar(file_path).is_some(),
+        })
+}
+
+/// move a file's metadata along with the file
+///
+/// call this after the file itself has been moved. ADS metadata moves with the file, sidecars
+/// are renamed (to the configured naming pattern), and db entries are moved into the db of the
+/// new directory
+///
+/// # Arguments
+///
+/// * `old_path` - where the file used to be
+/// * `new_path` - where the file is now
+///
+/// # Errors
+///
+/// returns an error if the metadata can't be moved
+pub fn move_metadata(old_path: &Path, new_path: &Path) -> Result<()> {
+    if let Some(old_json) = find_sidecar(old_path) {
+        let new_json = sidecar_path(new_path);
+        if std::fs::rename(&old_json, &new_json).is_err() {
+            std::fs::copy(&old_json, &new_json)?;
+            std::fs::remove_file(&old_json)?;
+        }
+    }
+
+    if let Some(post) = read_from_db(old_path)? {
+        save_to_db(new_path, &post)?;
+        remove_from_db(old_path)?;
+    }
+
+    Ok(())
+}
+
+/// copy a file's metadata along with the file
+///
+/// call this after the file itself has been copied. ADS metadata is copied with the file by
+/// the os, sidecars are copied, and db entries are added to the db of the new directory. the
+/// original's metadata is left alone
+///
+/// # Arguments
+///
+/// * `old_path` - the original file
+/// * `new_path` - the copy
+///
+/// # Errors
+///
+/// returns an error if the metadata can't be copied
+pub fn copy_metadata(old_path: &Path, new_path: &Path) -> Result<()> {
+    if let Some(old_json) = find_sidecar(old_path) {
+        std::fs::copy(&old_json, sidecar_path(new_path))?;
+    }
+
+    if let Some(post) = read_from_db(old_path)? {
+        save_to_db(new_path, &post)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_db_round_trip_and_move() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("1.png");
+        let moved = dir.path().join("sub").join("renamed.png");
+        std::fs::create_dir_all(dir.path().join("sub")).unwrap();
+
+        let post = E6Post {
+            id: 1,
+            ..Default::default()
+        };
+
+        save_metadata(&file, &post, MetadataBackend::Db, true).unwrap();
+        assert!(dir.path().join(METADATA_DB_FILE).exists());
+        assert!(!sidecar_path(&file).exists());
+        assert!(has_metadata(&file, MetadataBackend::Db));
+        assert!(has_metadata(&file, MetadataBackend::Sidecar));
+        assert_eq!(
+            read_metadata(&file, MetadataBackend::Sidecar).unwrap().id,
+            1
+        );
+
+        let copied = dir.path().join("copy").join("1.png");
+        std::fs::create_dir_all(dir.path().join("copy")).unwrap();
+        copy_metadata(&file, &copied).unwrap();
+        assert_eq!(read_metadata(&copied, MetadataBackend::Db).unwrap().id, 1);
+        assert!(has_metadata(&file, MetadataBackend::Db));
+
+        move_metadata(&file, &moved).unwrap();
+        assert!(!has_metadata(&file, MetadataBackend::Db));
+        assert_eq!(read_metadata(&moved, MetadataBackend::Db).unwrap().id, 1);
+    }
+
+    #[test]
+    fn test_sidecar_patterns() {
+        let file = Path::new("downloads/artist/1 - fox.png");
+
+        assert_eq!(
+            sidecar_path_with(file, DEFAULT_SIDECAR_PATTERN),
+            Path::new("downloads/artist/1 - fox.png.json")
+        );
+        assert_eq!(
+            sidecar_path_with(file, "{stem}.json"),
+            Path::new("downloads/artist/1 - fox.json")
+        );
+        assert_eq!(
+            sidecar_path_with(file, "{stem}.{ext}.txt"),
+            Path::new("downloads/artist/1 - fox.png.txt")
+        );
+    }
+
+    #[test]
+    fn test_sidecar_is_next_to_file() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("2.webm");
+        std::fs::write(&file, b"video").unwrap();
+
+        let post = E6Post {
+            id: 2,
+            ..Default::default()
+        };
+
+        save_metadata(&file, &post, MetadataBackend::Sidecar, true).unwrap();
+        assert_eq!(std::fs::read(&file).unwrap(), b"video");
+        assert!(dir.path().join("2.webm.json").exists());
+        assert_eq!(read_metadata(&file, MetadataBackend::Db).unwrap().id, 2);
+        let pretty = std::fs::read_to_string(dir.path().join("2.webm.json")).unwrap();
+
+        save_metadata(&file, &post, MetadataBackend::Sidecar, false).unwrap();
+        let compact = std::fs::read_to_string(dir.path().join("2.webm.json")).unwrap();
+        assert!(!compact.trim_end().contains('\n'));
+        assert!(compact.len() < pretty.len());
+        assert_eq!(
+            read_metadata(&file, MetadataBackend::Sidecar).unwrap().id,
+            2
+        );
+    }
+}
@@ -5,10 +5,76 @@ use {
     std::{fs::File, slice, sync::Arc},
 };
 
+#[cfg(feature = "cli")]
+pub mod booru;
 #[cfg(feature = "cli")]
 pub mod history;
+pub mod manifest;
+pub mod metadata;
 pub mod pools;
+#[cfg(feature = "cli")]
+pub mod queue;
+#[cfg(feature = "cli")]
+pub mod recent;
+pub mod scan;
 pub mod tags;
+pub mod thumbs;
+#[cfg(feature = "cli")]
+pub mod trash;
+
+/// how much a name match counts against a description match when searching
+///
+/// a result's score is the bigger of its weighted name and description similarity, and ties go
+/// to the better name match
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldWeights {
+    /// the weight of a name match
+    pub name: f64,
+    /// the weight of a description match
+    pub desc: f64,
+}
+
+impl Default for FieldWeights {
+    fn default() -> Self {
+        Self {
+            name: 1.0,
+            desc: 0.8,
+        }
+    }
+}
+
+impl FieldWeights {
+    /// the weights set in `completion.name_weight` and `completion.desc_weight`
+    ///
+    /// without the `cli` feature there's no config, so this is [`FieldWeights::default`]
+    pub fn configured() -> Self {
+        #[cfg(feature = "cli")]
+        {
+            Self {
+                name: crate::getopt!(completion.name_weight),
+                desc: crate::getopt!(completion.desc_weight),
+            }
+        }
+
+        #[cfg(not(feature = "cli"))]
+        {
+            Self::default()
+        }
+    }
+
+    /// score a result from its name and description similarity
+    ///
+    /// returns the weighted score and the name similarity to break ties with
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - how similar the name is to the query
+    /// * `desc` - how similar the description is to the query, if it has one
+    pub fn score(&self, name: f64, desc: Option<f64>) -> (f64, f64) {
+        let weighted = (name * self.name).max(desc.unwrap_or(0.0) * self.desc);
+        (weighted, name)
+    }
+}
 
 /// a database entry
 pub trait Entry: Clone + Send + Sync + for<'de> serde::Deserialize<'de> {
@@ -81,6 +147,8 @@ impl<T: Entry> Buffer<T> {
 pub struct Db<T: Entry> {
     /// the db buffer
     pub buf: Arc<Buffer<T>>,
+    /// the lowercase name and buffer position of every entry, sorted by name
+    index: Arc<Vec<(String, usize)>>,
 }
 
 impl<T: Entry> Default for Db<T> {
@@ -90,6 +158,7 @@ impl<T: Entry> Default for Db<T> {
                 ptr: std::ptr::null(),
                 len: 0,
             }),
+            index: Arc::new(Vec::new()),
         }
     }
 }
@@ -110,6 +179,13 @@ impl<T: Entry> Db<T> {
             entries.push(entry);
         }
 
+        let mut index: Vec<(String, usize)> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (Self::lowercase(entry.name()), i))
+            .collect();
+        index.sort_unstable();
+
         let boxed: Box<[T]> = entries.into_boxed_slice();
         let len = boxed.len();
         let ptr = boxed.as_ptr();
@@ -118,9 +194,35 @@ impl<T: Entry> Db<T> {
 
         Ok(Self {
             buf: Arc::new(Buffer { ptr, len }),
+            index: Arc::new(index),
         })
     }
 
+    /// returns the names of entries starting with a prefix (case-insensitive), in name order
+    ///
+    /// uses the name index built at load, so only the matching range is looked at
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - the prefix to match
+    /// * `limit` - the max amount of names to return
+    pub fn prefix_matches(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let prefix = Self::lowercase(prefix);
+        let start = self
+            .index
+            .partition_point(|(name, _)| name.as_str() < prefix.as_str());
+
+        unsafe {
+            let entries = slice::from_raw_parts(self.buf.ptr, self.buf.len);
+            self.index[start..]
+                .iter()
+                .take_while(|(name, _)| name.starts_with(&prefix))
+                .take(limit)
+                .map(|(_, i)| entries[*i].name().to_string())
+                .collect()
+        }
+    }
+
     #[inline(always)]
     /// make a string lowercase
     ///
@@ -152,31 +254,49 @@ impl<T: Entry> Db<T> {
 
     /// searches for entries matching the given query (fuzz: name and desc apply)
     ///
+    /// results are ranked with the weights in [`FieldWeights::configured`]
+    ///
     /// # Arguments
     ///
     /// * `query` - the search query
     /// * `limit` - the max amount of entries to return
     /// * `sim_threshold` - the similarity threshold for matching an entry against a search
     pub fn search(&self, query: &str, limit: usize, sim_threshold: f64) -> Vec<String> {
+        self.search_weighted(query, limit, sim_threshold, FieldWeights::configured())
+    }
+
+    /// searches for entries matching the given query, ranking name and desc matches by weight
+    ///
+    /// an entry matches if either its name or desc is more similar than `sim_threshold`
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - the search query
+    /// * `limit` - the max amount of entries to return
+    /// * `sim_threshold` - the similarity threshold for matching an entry against a search
+    /// * `weights` - how much name and desc matches count
+    pub fn search_weighted(
+        &self,
+        query: &str,
+        limit: usize,
+        sim_threshold: f64,
+        weights: FieldWeights,
+    ) -> Vec<String> {
         let query_lower = Self::lowercase(query);
-        let mut matches: Vec<(f64, String)> = Vec::new();
+        let mut matches: Vec<((f64, f64), String)> = Vec::new();
 
         unsafe {
             for entry in self.buf.iter() {
                 let name_lower = Self::lowercase(entry.name());
                 let name_sim = fuzz::ratio(name_lower.chars(), query_lower.chars()) / 100.0;
 
-                let max_sim = if let Some(desc) = entry.desc() {
+                let desc_sim = entry.desc().map(|desc| {
                     let desc_lower = Self::lowercase(desc);
-                    let desc_sim = fuzz::ratio(desc_lower.chars(), query_lower.chars()) / 100.0;
+                    fuzz::ratio(desc_lower.chars(), query_lower.chars()) / 100.0
+                });
 
-                    name_sim.max(desc_sim)
-                } else {
-                    name_sim
-                };
-
-                if max_sim > sim_threshold {
-                    matches.push((max_sim, entry.name().to_string()));
+                if name_sim.max(desc_sim.unwrap_or(0.0)) > sim_threshold {
+                    matches.push((weights.score(name_sim, desc_sim), entry.name().to_string()));
                 }
             }
         }
@@ -191,6 +311,9 @@ impl<T: Entry> Db<T> {
 
     /// returns autocompletions for the query with a given threshold
     ///
+    /// prefix matches come first (see [`Db::prefix_matches`]). the whole db is only fuzzy matched
+    /// if there aren't `limit` of them
+    ///
     /// # Arguments
     ///
     /// * `query` - the search query
@@ -198,11 +321,19 @@ impl<T: Entry> Db<T> {
     /// * `threshold` - the similarity threshold for matching
     pub fn autocomplete_with(&self, query: &str, limit: usize, threshold: f64) -> Vec<String> {
         let query_lower = Self::lowercase(query);
-        let mut results = Vec::new();
+        let mut results = self.prefix_matches(&query_lower, limit);
+
+        if results.len() >= limit {
+            return results;
+        }
 
         unsafe {
             for entry in self.buf.iter() {
                 let name_lower = Self::lowercase(entry.name());
+                if name_lower.starts_with(&query_lower) {
+                    continue;
+                }
+
                 let name_sim = fuzz::ratio(name_lower.chars(), query_lower.chars()) / 100.0;
 
                 if name_sim > threshold {
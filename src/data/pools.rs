@@ -1,6 +1,9 @@
 //! pool db
 use {
-    crate::{data::Entry, models::PoolEntry},
+    crate::{
+        data::{Entry, FieldWeights},
+        models::PoolEntry,
+    },
     color_eyre::Result,
     hashbrown::HashSet,
     nucleo_matcher::{
@@ -97,8 +100,22 @@ impl PoolDb {
         self.sorted_pools.iter().map(|a| a.as_ref())
     }
 
-    /// searches for pools matching the given query (uses 0.5 sim threshold)
+    /// searches for pools matching the given query in their names and descriptions
+    ///
+    /// results are ranked with the weights in [`FieldWeights::configured`]
     pub fn search(&self, query: &str, limit: usize) -> Vec<String> {
+        self.search_weighted(query, limit, FieldWeights::configured())
+    }
+
+    /// searches for pools matching the given query, ranking name and description matches by
+    /// weight
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - the search query
+    /// * `limit` - the max amount of pools to return
+    /// * `weights` - how much name and description matches count
+    pub fn search_weighted(&self, query: &str, limit: usize, weights: FieldWeights) -> Vec<String> {
         if query.is_empty() {
             return Vec::new();
         }
@@ -106,7 +123,7 @@ impl PoolDb {
         let mut matcher = Matcher::new(Config::DEFAULT);
         let pattern = Pattern::parse(query, CaseMatching::Ignore, Normalization::Smart);
 
-        let mut scored: Vec<(u32, &str)> = self
+        let mut scored: Vec<((f64, f64), &str)> = self
             .sorted_pools
             .iter()
             .filter_map(|pool| {
@@ -119,11 +136,17 @@ impl PoolDb {
                     nucleo_matcher::Utf32Str::new(&pool.description, &mut buf),
                     &mut matcher,
                 );
-                name_score.max(desc_score).map(|s| (s, pool.name.as_str()))
+                if name_score.is_none() && desc_score.is_none() {
+                    return None;
+                }
+
+                let score =
+                    weights.score(name_score.unwrap_or(0) as f64, desc_score.map(|s| s as f64));
+                Some((score, pool.name.as_str()))
             })
             .collect();
 
-        scored.sort_by_key(|b| std::cmp::Reverse(b.0));
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
         scored
             .into_iter()
             .take(limit)
@@ -187,4 +210,132 @@ impl PoolDb {
     pub fn list(&self) -> Vec<PoolEntry> {
         self.sorted_pools.iter().map(|a| (**a).clone()).collect()
     }
+
+    /// returns the distinct categories of the loaded pools, sorted by name
+    pub fn categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = self
+            .sorted_pools
+            .iter()
+            .map(|p| p.category.to_lowercase())
+            .filter(|c| !c.is_empty())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        categories.sort();
+        categories
+    }
+
+    /// returns pools in a category ("series", "collection", etc.)
+    ///
+    /// pools keep the order they were loaded in, so `search.sort_pools_by_post_count` still
+    /// applies. the category is matched case-insensitively
+    pub fn by_category<'a>(&'a self, category: &'a str) -> impl Iterator<Item = &'a PoolEntry> {
+        self.iter_pools()
+            .filter(move |p| p.category.eq_ignore_ascii_case(category))
+    }
+
+    /// returns pools whose post count is within `min..=max`
+    ///
+    /// pools at or below `search.min_posts_on_pool` were already dropped when the db was
+    /// loaded, so they never show up here even if `min` is lower
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - the minimum number of posts (inclusive)
+    /// * `max` - the maximum number of posts (inclusive, `None` for no upper bound)
+    pub fn by_post_count(
+        &self,
+        min: usize,
+        max: Option<usize>,
+    ) -> impl Iterator<Item = &PoolEntry> {
+        self.iter_pools().filter(move |p| {
+            let count = p.post_ids.len();
+            count >= min && max.is_none_or(|max| count <= max)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Write, tempfile::NamedTempFile};
+
+    /// write a pools csv with the given (name, category, post count) rows
+    fn pools_csv(rows: &[(&str, &str, usize)]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "id,name,created_at,updated_at,creator_id,description,is_active,category,post_ids"
+        )
+        .unwrap();
+
+        for (id, (name, category, count)) in rows.iter().enumerate() {
+            let ids: Vec<String> = (0..*count).map(|i| i.to_string()).collect();
+            writeln!(
+                file,
+                "{},{},,,1,,t,{},\"{{{}}}\"",
+                id,
+                name,
+                category,
+                ids.join(",")
+            )
+            .unwrap();
+        }
+
+        file
+    }
+
+    #[test]
+    fn test_filter_by_category_and_post_count() {
+        let csv = pools_csv(&[
+            ("tiny", "series", 1),
+            ("short", "series", 3),
+            ("long", "series", 20),
+            ("gallery", "collection", 8),
+        ]);
+        let db = PoolDb::load_from(&csv.path().to_string_lossy(), 2, true, true).unwrap();
+
+        assert_eq!(db.categories(), vec!["collection", "series"]);
+
+        let series: Vec<&str> = db.by_category("Series").map(|p| p.name.as_str()).collect();
+        assert_eq!(series, vec!["long", "short"]);
+
+        let mid: Vec<&str> = db
+            .by_post_count(0, Some(10))
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(mid, vec!["gallery", "short"]);
+
+        let big: Vec<&str> = db.by_post_count(5, None).map(|p| p.name.as_str()).collect();
+        assert_eq!(big, vec!["long", "gallery"]);
+    }
+
+    #[test]
+    fn test_search_ranks_names_over_descriptions() {
+        let mut csv = NamedTempFile::new().unwrap();
+        writeln!(
+            csv,
+            "id,name,created_at,updated_at,creator_id,description,is_active,category,post_ids"
+        )
+        .unwrap();
+        writeln!(csv, "1,misc_comics,,,1,fox_tales,t,series,\"{{1}}\"").unwrap();
+        writeln!(csv, "2,fox_tales,,,1,,t,series,\"{{1}}\"").unwrap();
+        let db = PoolDb::load_from(&csv.path().to_string_lossy(), 0, true, false).unwrap();
+
+        assert_eq!(
+            db.search_weighted("fox_tales", 2, FieldWeights::default()),
+            vec!["fox_tales", "misc_comics"]
+        );
+        assert_eq!(
+            db.search_weighted(
+                "fox_tales",
+                2,
+                FieldWeights {
+                    name: 0.5,
+                    desc: 1.0
+                }
+            ),
+            vec!["misc_comics", "fox_tales"]
+        );
+    }
 }
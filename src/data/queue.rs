@@ -0,0 +1,143 @@
+//! persistent download queue
+use {
+    color_eyre::Result,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        path::PathBuf,
+        time::{Duration, Instant},
+    },
+};
+
+/// a post waiting to be downloaded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QueuedDownload {
+    /// the id of the post
+    pub id: i64,
+    /// where the post will be saved
+    pub path: PathBuf,
+}
+
+/// downloads that have been started but not finished, persisted so they survive a crash
+#[derive(Clone, Debug)]
+pub struct DownloadQueue {
+    /// the queued downloads (in the order they were queued)
+    entries: Vec<QueuedDownload>,
+    /// the path to the queue file
+    path: PathBuf,
+    /// whether there are changes that haven't been saved yet
+    dirty: bool,
+    /// when the queue was last saved
+    last_saved: Option<Instant>,
+}
+
+impl Default for DownloadQueue {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            path: Self::default_path(),
+            dirty: false,
+            last_saved: None,
+        }
+    }
+}
+
+impl DownloadQueue {
+    /// the default path of the queue file
+    fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("e62rs_queue.json")
+    }
+
+    /// load the download queue from the config dir
+    pub fn load() -> Result<Self> {
+        let path = Self::default_path();
+
+        let entries = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            entries,
+            path,
+            dirty: false,
+            last_saved: None,
+        })
+    }
+
+    /// add downloads to the end of the queue, skipping posts that are already queued
+    ///
+    /// # Arguments
+    ///
+    /// * `downloads` - the downloads to queue
+    pub fn enqueue(&mut self, downloads: impl IntoIterator<Item = QueuedDownload>) {
+        for download in downloads {
+            if !self.entries.iter().any(|e| e.id == download.id) {
+                self.entries.push(download);
+            }
+        }
+    }
+
+    /// remove a post from the queue
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the id of the post to remove
+    pub fn dequeue(&mut self, id: i64) {
+        self.entries.retain(|e| e.id != id);
+    }
+
+    /// get all queued downloads
+    pub fn entries(&self) -> &[QueuedDownload] {
+        &self.entries
+    }
+
+    /// check whether the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// remove all queued downloads
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// save the queue to disk
+    pub fn save(&mut self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&self.entries)?)?;
+        self.dirty = false;
+        self.last_saved = Some(Instant::now());
+        Ok(())
+    }
+
+    /// save the queue to disk, unless it was already saved in the last `interval`
+    ///
+    /// skipped changes are saved by the next save or [`DownloadQueue::flush`], so a batch of
+    /// updates doesn't rewrite the file once per update
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - the least time between two saves
+    pub fn save_debounced(&mut self, interval: Duration) -> Result<()> {
+        self.dirty = true;
+        if self
+            .last_saved
+            .is_some_and(|saved| saved.elapsed() < interval)
+        {
+            return Ok(());
+        }
+
+        self.save()
+    }
+
+    /// save the queue to disk if it has changes that haven't been saved yet
+    pub fn flush(&mut self) -> Result<()> {
+        if self.dirty { self.save() } else { Ok(()) }
+    }
+}
@@ -0,0 +1,156 @@
+//! recently viewed posts
+use {
+    crate::models::E6Post,
+    color_eyre::Result,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        path::PathBuf,
+        sync::{LazyLock, Mutex},
+    },
+    tracing::warn,
+};
+
+/// the max number of viewed posts to remember
+const MAX_ENTRIES: usize = 200;
+
+/// the list [`RecentlyViewed::record`] adds to, loaded from disk the first time it's needed
+static RECENT: LazyLock<Mutex<Option<RecentlyViewed>>> = LazyLock::new(|| Mutex::new(None));
+
+/// a post that was viewed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ViewedPost {
+    /// the id of the post
+    pub id: i64,
+    /// the url of the post's thumbnail (if it has one)
+    pub preview_url: Option<String>,
+    /// when the post was last viewed (rfc3339)
+    pub viewed_at: String,
+}
+
+/// persisted list of recently viewed posts
+#[derive(Clone, Debug)]
+pub struct RecentlyViewed {
+    /// the entries (most recent first)
+    entries: Vec<ViewedPost>,
+    /// the path to the recently viewed file
+    path: PathBuf,
+}
+
+impl Default for RecentlyViewed {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            path: Self::default_path(),
+        }
+    }
+}
+
+impl RecentlyViewed {
+    /// the default path of the recently viewed file
+    fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("e62rs_recent.json")
+    }
+
+    /// load recently viewed posts from the config dir
+    pub fn load() -> Result<Self> {
+        let path = Self::default_path();
+
+        let entries = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { entries, path })
+    }
+
+    /// add a post to the list (deduped, most recent first)
+    pub fn add(&mut self, post: &E6Post) {
+        if post.id <= 0 {
+            return;
+        }
+
+        self.entries.retain(|e| e.id != post.id);
+        self.entries.insert(
+            0,
+            ViewedPost {
+                id: post.id,
+                preview_url: post.preview.url.clone(),
+                viewed_at: chrono::Local::now().to_rfc3339(),
+            },
+        );
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    /// get all entries (most recent first)
+    pub fn entries(&self) -> &[ViewedPost] {
+        &self.entries
+    }
+
+    /// remove all entries
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// save the list to disk
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.entries)?)?;
+        Ok(())
+    }
+
+    /// run something on the shared list, loading it first if it hasn't been yet
+    fn with_shared<T>(f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let mut shared = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+        if shared.is_none() {
+            *shared = Some(Self::load()?);
+        }
+
+        f(shared.get_or_insert_with(Self::default))
+    }
+
+    /// get the recently viewed posts, including views that are still being saved
+    pub fn current() -> Result<Self> {
+        Self::with_shared(|recent| Ok(recent.clone()))
+    }
+
+    /// forget every recently viewed post
+    pub fn clear_all() -> Result<()> {
+        Self::with_shared(|recent| {
+            recent.clear();
+            recent.save()
+        })
+    }
+
+    /// record a view of a post
+    ///
+    /// the list is saved on a blocking task, so viewing a post never waits on the disk. errors
+    /// are logged rather than returned
+    pub fn record(post: &E6Post) {
+        if post.id <= 0 {
+            return;
+        }
+
+        let post = E6Post {
+            id: post.id,
+            preview: post.preview.clone(),
+            ..Default::default()
+        };
+
+        tokio::task::spawn_blocking(move || {
+            let res = Self::with_shared(|recent| {
+                recent.add(&post);
+                recent.save()
+            });
+
+            if let Err(e) = res {
+                warn!(id = post.id, error = %e, "failed to record recently viewed post");
+            }
+        });
+    }
+}
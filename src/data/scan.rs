@@ -0,0 +1,191 @@
+//! an on-disk record of the explorer's last scan of each directory
+//!
+//! files whose metadata hasn't changed since a directory was last scanned are loaded from here
+//! instead of being read again, so re-opening the explorer on a big library only reads what's
+//! new
+use {
+    crate::{
+        data::metadata::{METADATA_DB_FILE, find_sidecar},
+        error::Result,
+        models::E6Post,
+    },
+    color_eyre::eyre::Context,
+    postcard::{from_bytes, to_allocvec},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    tracing::warn,
+};
+
+/// the name of the scan record in the cache dir
+pub const SCAN_CACHE_FILE: &str = "explorer-scan.postcard";
+
+/// what a directory looked like the last time it was scanned
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DirScan {
+    /// when the scan started (seconds since the unix epoch)
+    pub scanned_at: u64,
+    /// the posts that were found, keyed by file path
+    pub posts: HashMap<PathBuf, E6Post>,
+}
+
+impl DirScan {
+    /// start recording a new scan
+    pub fn start() -> Self {
+        Self {
+            scanned_at: now(),
+            posts: HashMap::new(),
+        }
+    }
+
+    /// get the post of a file that hasn't changed since this scan
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the downloaded file
+    pub fn unchanged(&self, path: &Path) -> Option<&E6Post> {
+        let post = self.posts.get(path)?;
+        last_changed(path)
+            .is_some_and(|changed| changed < self.scanned_at)
+            .then_some(post)
+    }
+}
+
+/// the last scan of every directory the explorer has scanned
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ScanCache {
+    /// the scans, keyed by directory
+    dirs: HashMap<PathBuf, DirScan>,
+}
+
+impl ScanCache {
+    /// load the scan record, starting fresh if it's missing or unreadable
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the scan record file
+    pub fn load(path: &Path) -> Self {
+        let Ok(bytes) = std::fs::read(path) else {
+            return Self::default();
+        };
+
+        from_bytes(&bytes).unwrap_or_else(|e| {
+            warn!("ignoring unreadable scan cache '{}': {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// save the scan record
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the scan record file
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the record can't be serialized or written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create '{}'", parent.display()))?;
+        }
+
+        let bytes = to_allocvec(self).context("failed to serialize scan cache")?;
+        std::fs::write(path, bytes)
+            .with_context(|| format!("failed to write scan cache '{}'", path.display()))?;
+
+        Ok(())
+    }
+
+    /// get the last scan of a directory
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - the scanned directory
+    pub fn get(&self, dir: &Path) -> Option<&DirScan> {
+        self.dirs.get(dir)
+    }
+
+    /// replace the last scan of a directory
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - the scanned directory
+    /// * `scan` - the new scan
+    pub fn insert(&mut self, dir: PathBuf, scan: DirScan) {
+        self.dirs.insert(dir, scan);
+    }
+}
+
+/// the current time in seconds since the unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// get when a download or any of its metadata last changed (seconds since the unix epoch)
+///
+/// looks at the file, its sidecar, and the metadata db of its directory
+///
+/// # Arguments
+///
+/// * `path` - the downloaded file
+pub fn last_changed(path: &Path) -> Option<u64> {
+    let mtime = |p: &Path| -> Option<u64> {
+        let modified = std::fs::metadata(p).ok()?.modified().ok()?;
+        Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+    };
+
+    let file = mtime(path)?;
+    let sidecar = find_sidecar(path).and_then(|p| mtime(&p));
+    let db = path
+        .parent()
+        .and_then(|dir| mtime(&dir.join(METADATA_DB_FILE)));
+
+    Some(file.max(sidecar.unwrap_or(0)).max(db.unwrap_or(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_unchanged_and_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("1.png");
+        std::fs::write(&file, b"one").unwrap();
+
+        let mut scan = DirScan {
+            scanned_at: last_changed(&file).expect("file mtime") + 1,
+            posts: HashMap::new(),
+        };
+        scan.posts.insert(
+            file.clone(),
+            E6Post {
+                id: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(scan.unchanged(&file).map(|p| p.id), Some(1));
+        assert!(scan.unchanged(&dir.path().join("2.png")).is_none());
+
+        let mut cache = ScanCache::default();
+        cache.insert(dir.path().to_path_buf(), scan.clone());
+        let record = dir.path().join("cache").join(SCAN_CACHE_FILE);
+        cache.save(&record).unwrap();
+
+        let loaded = ScanCache::load(&record);
+        assert_eq!(
+            loaded.get(dir.path()).map(|s| s.scanned_at),
+            Some(scan.scanned_at)
+        );
+
+        scan.scanned_at -= 1;
+        assert!(scan.unchanged(&file).is_none());
+    }
+}
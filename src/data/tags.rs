@@ -3,7 +3,7 @@ use {
     crate::{
         data::Entry,
         error::Result,
-        models::{TagAliasEntry, TagEntry, TagImplicationEntry},
+        models::{TagAliasEntry, TagCategory, TagEntry, TagImplicationEntry},
     },
     hashbrown::{HashMap, HashSet},
     nucleo_matcher::{
@@ -39,6 +39,8 @@ pub struct TagDb {
     tag_trie: Trie<String, Arc<TagEntry>>,
     /// indexed aliases for fast prefix lookup
     alias_trie: Trie<String, String>,
+    /// every tag sorted by name, so the tags with a prefix are one contiguous range
+    name_index: Vec<Arc<TagEntry>>,
     /// sorted tags by post count for iteration
     sorted_tags: Vec<Arc<TagEntry>>,
     /// alias -> canonical tag translation map
@@ -54,6 +56,7 @@ impl Default for TagDb {
         Self {
             tag_trie: Trie::new(),
             alias_trie: Trie::new(),
+            name_index: Vec::new(),
             sorted_tags: Vec::new(),
             alias_map: HashMap::new(),
             impl_map: HashMap::new(),
@@ -124,11 +127,14 @@ impl TagDb {
 
         let mut tag_trie = Trie::new();
         let mut tag_names = HashSet::with_capacity(tags.len());
+        let mut name_index = Vec::with_capacity(tags.len());
         for tag in &tags {
             let arc_tag = Arc::new(tag.clone());
+            name_index.push(arc_tag.clone());
             tag_trie.insert(tag.name.clone(), arc_tag);
             tag_names.insert(tag.name.clone());
         }
+        name_index.sort_unstable_by(|a, b| a.name.cmp(&b.name));
 
         let mut alias_trie = Trie::new();
         for alias in &aliases {
@@ -154,6 +160,7 @@ impl TagDb {
         Ok(Self {
             tag_trie,
             alias_trie,
+            name_index,
             sorted_tags,
             alias_map,
             impl_map,
@@ -296,11 +303,93 @@ impl TagDb {
             .collect()
     }
 
+    /// fuzzy-searches the tags of a single category (aliases aren't included)
+    ///
+    /// an empty query returns the category's most used tags. matches with the same score are
+    /// ordered by post count
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - the category to search in
+    /// * `query` - the fuzzy query, or an empty string for the most used tags
+    /// * `limit` - the max number of tags to return
+    pub fn search_category(
+        &self,
+        category: TagCategory,
+        query: &str,
+        limit: usize,
+    ) -> Vec<&TagEntry> {
+        let in_category = self
+            .sorted_tags
+            .iter()
+            .map(|tag| tag.as_ref())
+            .filter(|tag| tag.category == category.id());
+
+        let mut scored: Vec<(u32, &TagEntry)> = if query.trim().is_empty() {
+            in_category.map(|tag| (0, tag)).collect()
+        } else {
+            let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+            let pattern = Pattern::parse(query, CaseMatching::Ignore, Normalization::Smart);
+
+            in_category
+                .filter_map(|tag| {
+                    let mut buf = Vec::new();
+                    pattern
+                        .score(
+                            nucleo_matcher::Utf32Str::new(&tag.name, &mut buf),
+                            &mut matcher,
+                        )
+                        .map(|score| (score, tag))
+                })
+                .collect()
+        };
+
+        scored.sort_by_key(|(score, tag)| std::cmp::Reverse((*score, tag.post_count)));
+        scored.into_iter().take(limit).map(|(_, tag)| tag).collect()
+    }
+
+    /// returns the most used tags whose names start with a prefix, most used first
+    ///
+    /// only looks at the tags in the prefix's range of the name index, so it doesn't scan the
+    /// whole db
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - the lowercase prefix
+    /// * `limit` - the max number of tags to return
+    pub fn prefix_matches(&self, prefix: &str, limit: usize) -> Vec<&TagEntry> {
+        let start = self
+            .name_index
+            .partition_point(|tag| tag.name.as_str() < prefix);
+        let mut matches: Vec<&TagEntry> = self.name_index[start..]
+            .iter()
+            .take_while(|tag| tag.name.starts_with(prefix))
+            .map(|tag| tag.as_ref())
+            .collect();
+
+        let by_count = |a: &&TagEntry, b: &&TagEntry| {
+            b.post_count
+                .cmp(&a.post_count)
+                .then_with(|| a.name.cmp(&b.name))
+        };
+
+        if matches.len() > limit {
+            matches.select_nth_unstable_by(limit, by_count);
+            matches.truncate(limit);
+        }
+        matches.sort_by(by_count);
+        matches
+    }
+
     /// returns autocompletion possibilities for tag names
-    /// (includes: tags and aliases, resolves to: canonical names)
+    /// (includes: tags and aliases)
+    ///
+    /// aliases are returned by their own name so callers can show what they resolve to (see
+    /// [`TagDb::resolve_alias`]). results are deduped by canonical name
     ///
-    /// uses fuzzy matching via nucleo for typo-tolerant completions,
-    /// with prefix matches prioritized
+    /// prefix matches come first, most used first (see [`TagDb::prefix_matches`]). only if there
+    /// aren't `limit` of them is the whole db fuzzy matched via nucleo for typo-tolerant
+    /// completions
     pub fn autocomplete(&self, query: &str, limit: usize) -> Vec<String> {
         if query.is_empty() {
             return Vec::new();
@@ -310,23 +399,24 @@ impl TagDb {
         let mut seen = HashSet::new();
         let mut scored: Vec<(u32, String)> = Vec::new();
 
-        if let Some(subtrie) = self.tag_trie.get_raw_descendant(&query_lower) {
-            for (key, _) in subtrie.iter().take(limit * 4) {
-                if seen.insert(key.clone()) {
-                    scored.push((u32::MAX, key.clone()));
-                }
+        for tag in self.prefix_matches(&query_lower, limit) {
+            if seen.insert(tag.name.clone()) {
+                scored.push((u32::MAX, tag.name.clone()));
             }
         }
 
         if let Some(subtrie) = self.alias_trie.get_raw_descendant(&query_lower) {
-            for (_, canonical) in subtrie.iter().take(limit * 2) {
-                let resolved = self.resolve_alias(canonical);
-                if seen.insert(resolved.clone()) {
-                    scored.push((u32::MAX - 1, resolved));
+            for (alias, canonical) in subtrie.iter().take(limit * 2) {
+                if seen.insert(self.resolve_alias(canonical)) {
+                    scored.push((u32::MAX - 1, alias.clone()));
                 }
             }
         }
 
+        if scored.len() >= limit {
+            return scored.into_iter().take(limit).map(|(_, n)| n).collect();
+        }
+
         let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
         let pattern = Pattern::parse(query, CaseMatching::Ignore, Normalization::Smart);
 
@@ -353,8 +443,8 @@ impl TagDb {
             if let Some(score) =
                 pattern.score(nucleo_matcher::Utf32Str::new(alias, &mut buf), &mut matcher)
             {
-                seen.insert(resolved.clone());
-                scored.push((score, resolved));
+                seen.insert(resolved);
+                scored.push((score, alias.clone()));
             }
         }
 
@@ -371,4 +461,117 @@ impl TagDb {
     pub fn get_canon_name(&self, tag: &str) -> String {
         self.resolve_alias(tag)
     }
+
+    /// returns the name to search for an entered tag
+    ///
+    /// normally the tag's whole alias chain is followed (see [`TagDb::resolve_alias`]). in strict
+    /// mode, a tag that exists under its own name is kept as typed, and anything else is only
+    /// replaced if it's directly aliased to another tag
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - the entered tag
+    /// * `strict` - only resolve exact alias matches
+    pub fn canonicalize(&self, tag: &str, strict: bool) -> String {
+        if !strict {
+            return self.resolve_alias(tag);
+        }
+
+        if self.tag_names.contains(tag) {
+            return tag.to_string();
+        }
+
+        self.alias_map
+            .get(tag)
+            .cloned()
+            .unwrap_or_else(|| tag.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Write, tempfile::NamedTempFile};
+
+    /// write a csv with the given header and rows
+    fn csv(header: &str, rows: &[String]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{}", header).unwrap();
+        for row in rows {
+            writeln!(file, "{}", row).unwrap();
+        }
+
+        file
+    }
+
+    #[test]
+    fn test_autocomplete_prefers_popular_prefix_matches() {
+        let tags = csv(
+            "id,name,category,post_count",
+            &[
+                ("canine", 900),
+                ("canis", 400),
+                ("cat", 700),
+                ("canon_character", 5),
+                ("scanner", 50),
+            ]
+            .iter()
+            .enumerate()
+            .map(|(id, (name, count))| format!("{},{},0,{}", id, name, count))
+            .collect::<Vec<_>>(),
+        );
+        let aliases = csv(
+            "id,antecedent_name,consequent_name,created_at,status",
+            &["1,canid,canine,,active".to_string()],
+        );
+        let impls = csv("id,antecedent_name,consequent_name,created_at,status", &[]);
+        let path = |f: &NamedTempFile| f.path().to_string_lossy().into_owned();
+        let db =
+            TagDb::load_from(&path(&tags), &path(&aliases), &path(&impls), 0, true, false).unwrap();
+
+        let prefix: Vec<&str> = db
+            .prefix_matches("can", 2)
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(prefix, vec!["canine", "canis"]);
+
+        assert_eq!(db.autocomplete("can", 2), vec!["canine", "canis"]);
+        assert_eq!(
+            db.autocomplete("CAN", 3),
+            vec!["canine", "canis", "canon_character"]
+        );
+
+        let fuzzy = db.autocomplete("can", 10);
+        assert_eq!(&fuzzy[..3], &["canine", "canis", "canon_character"]);
+        assert!(fuzzy.contains(&"scanner".to_string()));
+        assert!(!fuzzy.contains(&"canid".to_string()));
+    }
+
+    #[test]
+    fn test_strict_canonicalize_keeps_deliberate_tags() {
+        let tags = csv(
+            "id,name,category,post_count",
+            &["1,tiger,5,9000".to_string(), "2,tigress,5,12".to_string()],
+        );
+        let aliases = csv(
+            "id,antecedent_name,consequent_name,created_at,status",
+            &[
+                "1,tigress,tiger,,active".to_string(),
+                "2,tigre,tigress,,active".to_string(),
+                "3,tigr,tiger,,pending".to_string(),
+            ],
+        );
+        let impls = csv("id,antecedent_name,consequent_name,created_at,status", &[]);
+        let path = |f: &NamedTempFile| f.path().to_string_lossy().into_owned();
+        let db =
+            TagDb::load_from(&path(&tags), &path(&aliases), &path(&impls), 0, true, false).unwrap();
+
+        assert_eq!(db.canonicalize("tigress", false), "tiger");
+        assert_eq!(db.canonicalize("tigre", false), "tiger");
+
+        assert_eq!(db.canonicalize("tigress", true), "tigress");
+        assert_eq!(db.canonicalize("tigre", true), "tigress");
+        assert_eq!(db.canonicalize("tigr", true), "tigr");
+        assert_eq!(db.canonicalize("my_own_tag", true), "my_own_tag");
+    }
 }
@@ -0,0 +1,227 @@
+//! an on-disk cache of downsized images
+//!
+//! thumbnails are kept as `<md5>-<size>.png` in a directory under the cache dir, so contact
+//! sheets and the gallery's thumbnail endpoint only decode a full image the first time it's shown
+//! at a given size. a thumbnail older than the file it was made from is made again
+use {
+    crate::{data::manifest::hash_file, error::Result},
+    color_eyre::eyre::Context,
+    image::{DynamicImage, ImageFormat, ImageReader},
+    std::{
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicUsize, Ordering},
+        time::SystemTime,
+    },
+    tracing::warn,
+};
+
+/// the name of the thumbnail directory in the cache dir
+pub const THUMBNAIL_CACHE_DIR: &str = "thumbnails";
+
+/// tells apart the temp files of thumbnails being saved at the same time
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// a directory of cached thumbnails
+#[derive(Debug, Clone)]
+pub struct ThumbnailCache {
+    /// where the thumbnails are kept
+    dir: PathBuf,
+}
+
+impl ThumbnailCache {
+    /// use a directory as a thumbnail cache (it's made when the first thumbnail is saved)
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - where the thumbnails are kept
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// use [`THUMBNAIL_CACHE_DIR`] in the configured cache dir
+    #[cfg(feature = "cli")]
+    pub fn configured() -> Self {
+        Self::new(
+            crate::getopt!(cache)
+                .resolved_cache_dir()
+                .join(THUMBNAIL_CACHE_DIR),
+        )
+    }
+
+    /// get where the thumbnail of a file is kept
+    ///
+    /// # Arguments
+    ///
+    /// * `md5` - the md5 of the file
+    /// * `size` - the most pixels the thumbnail has on either side
+    pub fn path_for(&self, md5: &str, size: u32) -> PathBuf {
+        self.dir
+            .join(format!("{}-{}.png", md5.to_lowercase(), size))
+    }
+
+    /// get the cached thumbnail of a file, making it first if it's missing or out of date
+    ///
+    /// returns the path of the thumbnail
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - the full size file
+    /// * `md5` - the md5 of the file, if it's known (the file is hashed otherwise)
+    /// * `size` - the most pixels the thumbnail has on either side
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the file can't be read or decoded, or the thumbnail can't be saved
+    pub fn get_or_create(&self, source: &Path, md5: Option<&str>, size: u32) -> Result<PathBuf> {
+        let md5 = source_md5(source, md5)?;
+        let path = self.path_for(&md5, size);
+
+        if !is_fresh(&path, source) {
+            self.save(&path, &decode_thumbnail(source, size)?)?;
+        }
+
+        Ok(path)
+    }
+
+    /// load the thumbnail of a file, making and caching it first if it's missing or out of date
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - the full size file
+    /// * `md5` - the md5 of the file, if it's known (the file is hashed otherwise)
+    /// * `size` - the most pixels the thumbnail has on either side
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the file can't be read or decoded. failing to save the thumbnail
+    /// isn't an error, it just won't be cached
+    pub fn load(&self, source: &Path, md5: Option<&str>, size: u32) -> Result<DynamicImage> {
+        let md5 = source_md5(source, md5)?;
+        let path = self.path_for(&md5, size);
+
+        if is_fresh(&path, source)
+            && let Ok(thumb) = image::open(&path)
+        {
+            return Ok(thumb);
+        }
+
+        let thumb = decode_thumbnail(source, size)?;
+        if let Err(e) = self.save(&path, &thumb) {
+            warn!("{}", e);
+        }
+
+        Ok(thumb)
+    }
+
+    /// write a thumbnail, going through a temp file so a half-written one is never read
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - where the thumbnail goes
+    /// * `thumb` - the thumbnail
+    fn save(&self, path: &Path, thumb: &DynamicImage) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create '{}'", self.dir.display()))?;
+
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(format!(
+            ".{}-{}.tmp",
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let tmp = path.with_file_name(tmp_name);
+
+        thumb
+            .save_with_format(&tmp, ImageFormat::Png)
+            .with_context(|| format!("failed to save thumbnail '{}'", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("failed to save thumbnail '{}'", path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// get the md5 of a file, hashing it if it isn't known
+///
+/// the file's header is read first, so a file that isn't an image fails before the whole thing
+/// gets hashed
+fn source_md5(source: &Path, md5: Option<&str>) -> Result<String> {
+    if let Some(md5) = md5 {
+        return Ok(md5.to_string());
+    }
+
+    ImageReader::open(source)
+        .with_context(|| format!("failed to open '{}'", source.display()))?
+        .with_guessed_format()?
+        .into_dimensions()
+        .with_context(|| format!("failed to decode '{}'", source.display()))?;
+
+    hash_file(source)
+}
+
+/// decode a file and downsize it to fit in a `size` by `size` square
+fn decode_thumbnail(source: &Path, size: u32) -> Result<DynamicImage> {
+    let img =
+        image::open(source).with_context(|| format!("failed to decode '{}'", source.display()))?;
+    Ok(img.thumbnail(size, size))
+}
+
+/// whether a thumbnail exists and is at least as new as the file it was made from
+fn is_fresh(thumb: &Path, source: &Path) -> bool {
+    let modified = |p: &Path| -> Option<SystemTime> { std::fs::metadata(p).ok()?.modified().ok() };
+
+    match (modified(thumb), modified(source)) {
+        (Some(thumb), Some(source)) => thumb >= source,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        image::{Rgba, RgbaImage},
+        std::time::Duration,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn test_thumbnail_cache() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("1.png");
+        RgbaImage::from_pixel(400, 200, Rgba([255, 0, 0, 255]))
+            .save(&source)
+            .unwrap();
+
+        let cache = ThumbnailCache::new(dir.path().join(THUMBNAIL_CACHE_DIR));
+        let thumb = cache.get_or_create(&source, Some("ABC"), 100).unwrap();
+        assert_eq!(thumb, cache.path_for("abc", 100));
+        assert_eq!(image::open(&thumb).unwrap().width(), 100);
+
+        let loaded = cache.load(&source, None, 50).unwrap();
+        assert_eq!((loaded.width(), loaded.height()), (50, 25));
+        let md5 = hash_file(&source).unwrap();
+        assert!(cache.path_for(&md5, 50).is_file());
+
+        RgbaImage::from_pixel(200, 400, Rgba([0, 255, 0, 255]))
+            .save(&source)
+            .unwrap();
+        let newer = SystemTime::now() + Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(newer)
+            .unwrap();
+
+        let remade = cache.load(&source, Some(&md5), 50).unwrap();
+        assert_eq!((remade.width(), remade.height()), (25, 50));
+
+        let missing = dir.path().join("2.png");
+        assert!(cache.get_or_create(&missing, Some("def"), 100).is_err());
+
+        let text = dir.path().join("3.png");
+        std::fs::write(&text, "not an image").unwrap();
+        assert!(cache.load(&text, None, 50).is_err());
+    }
+}
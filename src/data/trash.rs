@@ -0,0 +1,97 @@
+//! deleting downloads
+//!
+//! deleted files go to the os trash unless `explorer.permanent_delete` is on, so a delete that
+//! picked the wrong files can be undone
+use {
+    crate::{
+        data::metadata::{find_sidecar, remove_from_db},
+        error::Result,
+    },
+    color_eyre::eyre::Context,
+    std::path::Path,
+};
+
+/// delete a single file
+///
+/// # Arguments
+///
+/// * `path` - the file to delete
+/// * `permanent` - delete it for good instead of moving it to the trash
+///
+/// # Errors
+///
+/// returns an error if the file can't be deleted or moved to the trash
+fn discard(path: &Path, permanent: bool) -> Result<()> {
+    if permanent {
+        std::fs::remove_file(path)
+            .with_context(|| format!("failed to delete '{}'", path.display()))?;
+    } else {
+        trash::delete(path)
+            .with_context(|| format!("failed to move '{}' to the trash", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// delete a download along with its sidecar
+///
+/// the download's entry in its directory's metadata db is only removed when deleting for good,
+/// so a download restored from the trash keeps its metadata
+///
+/// # Arguments
+///
+/// * `path` - the downloaded file
+/// * `permanent` - delete it for good instead of moving it to the trash
+///
+/// # Errors
+///
+/// returns an error if the file or its sidecar can't be deleted
+pub fn delete_download(path: &Path, permanent: bool) -> Result<()> {
+    discard(path, permanent)?;
+
+    if let Some(sidecar) = find_sidecar(path) {
+        discard(&sidecar, permanent)?;
+    }
+
+    if permanent {
+        remove_from_db(path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            config::options::MetadataBackend,
+            data::metadata::{has_metadata, save_metadata, sidecar_path},
+            models::E6Post,
+        },
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn test_permanent_delete_download() {
+        let dir = TempDir::new().unwrap();
+        let post = E6Post {
+            id: 3,
+            ..Default::default()
+        };
+
+        let file = dir.path().join("3.png");
+        std::fs::write(&file, b"three").unwrap();
+        save_metadata(&file, &post, MetadataBackend::Sidecar, true).unwrap();
+        save_metadata(&file, &post, MetadataBackend::Db, true).unwrap();
+        let keep = dir.path().join("4.png");
+        std::fs::write(&keep, b"four").unwrap();
+        save_metadata(&keep, &post, MetadataBackend::Sidecar, true).unwrap();
+
+        delete_download(&file, true).unwrap();
+        assert!(!file.exists());
+        assert!(!sidecar_path(&file).exists());
+        assert!(!has_metadata(&file, MetadataBackend::Db));
+        assert!(keep.exists() && sidecar_path(&keep).exists());
+    }
+}
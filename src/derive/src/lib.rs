@@ -38,13 +38,7 @@ pub fn argdoc(_attr: TokenStream, item: TokenStream) -> TokenStream {
                     method.sig.generics = new_generics;
                     method.attrs = filtered_attrs;
 
-                    if !doc_sections.is_empty() {
-                        let doc_attr: syn::Attribute = syn::parse_quote! {
-                            #[doc = #doc_sections]
-                        };
-
-                        method.attrs.insert(0, doc_attr);
-                    }
+                    insert_doc_sections(&mut method.attrs, &doc_sections);
                 }
             }
 
@@ -69,13 +63,7 @@ pub fn argdoc(_attr: TokenStream, item: TokenStream) -> TokenStream {
                     method.sig.generics = new_generics;
                     method.attrs = filtered_attrs;
 
-                    if !doc_sections.is_empty() {
-                        let doc_attr: syn::Attribute = syn::parse_quote! {
-                            #[doc = #doc_sections]
-                        };
-
-                        method.attrs.insert(0, doc_attr);
-                    }
+                    insert_doc_sections(&mut method.attrs, &doc_sections);
                 }
             }
 
@@ -101,20 +89,39 @@ fn process_function(
 ) -> proc_macro2::TokenStream {
     let doc_sections = build_doc_sections(attrs, &sig.generics, &sig.inputs, &sig.output);
     let new_inputs = clean_param_attrs(&sig.inputs);
-    let filtered_attrs = filter_attrs(attrs);
+    let mut filtered_attrs = filter_attrs(attrs);
+    insert_doc_sections(&mut filtered_attrs, &doc_sections);
 
     let mut new_sig = sig.clone();
     new_sig.inputs = new_inputs;
 
     quote! {
         #(#filtered_attrs)*
-        #[doc = #doc_sections]
         #vis #new_sig {
             #block
         }
     }
 }
 
+/// add the generated doc sections right after the last hand-written doc comment, so the summary
+/// stays first in rustdoc
+fn insert_doc_sections(attrs: &mut Vec<syn::Attribute>, doc_sections: &str) {
+    if doc_sections.is_empty() {
+        return;
+    }
+
+    let doc_attr: syn::Attribute = syn::parse_quote! {
+        #[doc = #doc_sections]
+    };
+    let index = attrs
+        .iter()
+        .rposition(|attr| attr.path().is_ident("doc"))
+        .map(|pos| pos + 1)
+        .unwrap_or(0);
+
+    attrs.insert(index, doc_attr);
+}
+
 fn build_doc_sections(
     attrs: &[syn::Attribute],
     generics: &Generics,
@@ -126,6 +133,8 @@ fn build_doc_sections(
     let error_docs = extract_error_docs(attrs);
     let generic_docs = extract_generic_docs(generics);
     let panic_docs = extract_panic_docs(attrs);
+    let returns_docs = extract_returns_docs(attrs);
+    let example_docs = extract_example_docs(attrs);
     let ret_result = matches!(output, ReturnType::Type(_, ty) if is_result_type(ty));
 
     if !generic_docs.is_empty() {
@@ -139,6 +148,10 @@ fn build_doc_sections(
         doc_sections.push_str(&format!("\n\n# Parameters\n\n{}", param_docs.join("\n")));
     }
 
+    if !returns_docs.is_empty() {
+        doc_sections.push_str(&format!("\n\n# Returns\n\n{}", returns_docs.join("\n")));
+    }
+
     if ret_result && !error_docs.is_empty() {
         doc_sections.push_str(&format!(
             "\n\n# Errors\n\nReturns an error if:\n{}",
@@ -150,6 +163,13 @@ fn build_doc_sections(
         doc_sections.push_str(&format!("\n\n# Panics\n\n{}", panic_docs.join("\n")));
     }
 
+    if !example_docs.is_empty() {
+        doc_sections.push_str(&format!(
+            "\n\n# Examples\n\n```rust\n{}\n```",
+            example_docs.join("\n")
+        ));
+    }
+
     doc_sections
 }
 
@@ -260,6 +280,38 @@ fn extract_panic_docs(attrs: &[syn::Attribute]) -> Vec<String> {
     panic_docs
 }
 
+fn extract_returns_docs(attrs: &[syn::Attribute]) -> Vec<String> {
+    let mut returns_docs = Vec::new();
+
+    for attr in attrs {
+        if attr.path().is_ident("returns")
+            && let Meta::NameValue(meta) = &attr.meta
+            && let syn::Expr::Lit(expr_lit) = &meta.value
+            && let syn::Lit::Str(lit_str) = &expr_lit.lit
+        {
+            returns_docs.push(lit_str.value());
+        }
+    }
+
+    returns_docs
+}
+
+fn extract_example_docs(attrs: &[syn::Attribute]) -> Vec<String> {
+    let mut example_docs = Vec::new();
+
+    for attr in attrs {
+        if attr.path().is_ident("example")
+            && let Meta::NameValue(meta) = &attr.meta
+            && let syn::Expr::Lit(expr_lit) = &meta.value
+            && let syn::Lit::Str(lit_str) = &expr_lit.lit
+        {
+            example_docs.push(lit_str.value());
+        }
+    }
+
+    example_docs
+}
+
 fn clean_param_attrs(inputs: &Punctuated<FnArg, Comma>) -> Punctuated<FnArg, Comma> {
     inputs
         .iter()
@@ -294,10 +346,13 @@ fn clean_generic_attrs(generics: &Generics) -> Generics {
     new_generics
 }
 
+/// attributes that only exist for argdoc and have to be removed before the compiler sees them
+const ARGDOC_ATTRS: &[&str] = &["error", "err", "panic", "panics", "returns", "example"];
+
 fn filter_attrs(attrs: &[syn::Attribute]) -> Vec<syn::Attribute> {
     attrs
         .iter()
-        .filter(|attr| !attr.path().is_ident("error") && !attr.path().is_ident("panic"))
+        .filter(|attr| !ARGDOC_ATTRS.iter().any(|name| attr.path().is_ident(name)))
         .cloned()
         .collect()
 }
@@ -319,3 +374,105 @@ fn is_result_type(ty: &Type) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, syn::ItemFn};
+
+    /// get the doc sections argdoc would generate for a function
+    fn doc_sections_for(item_fn: &ItemFn) -> String {
+        build_doc_sections(
+            &item_fn.attrs,
+            &item_fn.sig.generics,
+            &item_fn.sig.inputs,
+            &item_fn.sig.output,
+        )
+    }
+
+    #[test]
+    fn test_returns_section() {
+        let item_fn: ItemFn = syn::parse_quote! {
+            /// count things
+            #[returns = "how many things there are"]
+            #[error = "the things can't be counted"]
+            fn count(
+                /// the things to count
+                things: &[u8],
+            ) -> Result<usize> {
+                Ok(things.len())
+            }
+        };
+
+        let docs = doc_sections_for(&item_fn);
+        let params = docs.find("# Parameters").expect("parameters section");
+        let returns = docs
+            .find("# Returns\n\nhow many things there are")
+            .expect("returns section");
+        let errors = docs.find("# Errors").expect("errors section");
+        assert!(params < returns && returns < errors);
+
+        let attrs = filter_attrs(&item_fn.attrs);
+        assert_eq!(attrs.len(), 1);
+        assert!(attrs[0].path().is_ident("doc"));
+    }
+
+    #[test]
+    fn test_examples_section() {
+        let item_fn: ItemFn = syn::parse_quote! {
+            /// add one
+            #[example = "let two = add_one(1);"]
+            #[example = "assert_eq!(two, 2);"]
+            fn add_one(n: u8) -> u8 {
+                n + 1
+            }
+        };
+
+        let docs = doc_sections_for(&item_fn);
+        assert!(
+            docs.ends_with(
+                "# Examples\n\n```rust\nlet two = add_one(1);\nassert_eq!(two, 2);\n```"
+            )
+        );
+        assert_eq!(filter_attrs(&item_fn.attrs).len(), 1);
+    }
+
+    #[test]
+    fn test_summary_stays_first() {
+        let item_fn: ItemFn = syn::parse_quote! {
+            #[inline]
+            /// the summary
+            ///
+            /// more details
+            #[must_use]
+            fn double(
+                /// the number to double
+                n: u8,
+            ) -> u8 {
+                n * 2
+            }
+        };
+
+        let mut attrs = filter_attrs(&item_fn.attrs);
+        insert_doc_sections(&mut attrs, &doc_sections_for(&item_fn));
+
+        let docs: Vec<String> = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .filter_map(|attr| match &attr.meta {
+                Meta::NameValue(meta) => match &meta.value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit_str),
+                        ..
+                    }) => Some(lit_str.value()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(docs.len(), 4);
+        assert_eq!(docs[0], " the summary");
+        assert!(docs[3].contains("# Parameters"));
+        assert!(attrs[4].path().is_ident("doc") && attrs[5].path().is_ident("must_use"));
+    }
+}
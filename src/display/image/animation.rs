@@ -8,7 +8,7 @@ use {
     },
     std::{
         fs::File,
-        io::{BufRead, BufReader, Cursor, Seek},
+        io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom},
         path::Path,
         thread,
         time::Duration,
@@ -33,22 +33,22 @@ pub struct AnimatedImage {
     pub width: u32,
     /// original height
     pub height: u32,
-    /// number of times to loop
+    /// number of times to play the animation (0 = forever)
     pub loop_count: u16,
 }
 
 impl AnimatedImage {
-    /// load an animated GIF from a file path
-    pub fn from_gif_path(path: &Path) -> Result<Self> {
+    /// load an animated GIF from a file path, decoding at most `max_frames` frames
+    pub fn from_gif_path(path: &Path, max_frames: usize) -> Result<Self> {
         let file = File::open(path).context(format!("Failed to open gif: {}", path.display()))?;
         let reader = BufReader::new(file);
-        Self::from_gif_reader(reader)
+        Self::from_gif_reader(reader, max_frames)
     }
 
-    /// load an animated GIF from bytes
-    pub fn from_gif_bytes(bytes: &[u8]) -> Result<Self> {
+    /// load an animated GIF from bytes, decoding at most `max_frames` frames
+    pub fn from_gif_bytes(bytes: &[u8], max_frames: usize) -> Result<Self> {
         let cursor = Cursor::new(bytes);
-        Self::from_gif_reader(cursor)
+        Self::from_gif_reader(cursor, max_frames)
     }
 
     /// calculate the number of rows the animation takes up
@@ -83,15 +83,18 @@ impl AnimatedImage {
     }
 
     /// load an animated gif from any reader
-    fn from_gif_reader<R>(reader: R) -> Result<Self>
+    ///
+    /// frames past `max_frames` are never decoded, so long gifs don't eat all the memory
+    fn from_gif_reader<R>(mut reader: R, max_frames: usize) -> Result<Self>
     where
         R: std::io::Read + BufRead + Seek,
     {
+        let loop_count = gif_play_count(&mut reader)?;
         let decoder = GifDecoder::new(reader).context("failed to decode gif")?;
         let (width, height) = decoder.dimensions();
-        let loop_count = 0;
         let frames: Vec<_> = decoder
             .into_frames()
+            .take(max_frames)
             .collect::<Result<Vec<_>, _>>()
             .context("failed to decode gif frames")?;
 
@@ -127,22 +130,24 @@ impl AnimatedImage {
         })
     }
 
-    /// load an animated webp from a file path
-    pub fn from_webp_path(path: &Path) -> Result<Self> {
+    /// load an animated webp from a file path, decoding at most `max_frames` frames
+    pub fn from_webp_path(path: &Path, max_frames: usize) -> Result<Self> {
         let file =
             File::open(path).with_context(|| format!("Failed to open WebP: {}", path.display()))?;
         let reader = BufReader::new(file);
-        Self::from_webp_reader(reader)
+        Self::from_webp_reader(reader, max_frames)
     }
 
-    /// load an animated webp from bytes
-    pub fn from_webp_bytes(bytes: &[u8]) -> Result<Self> {
+    /// load an animated webp from bytes, decoding at most `max_frames` frames
+    pub fn from_webp_bytes(bytes: &[u8], max_frames: usize) -> Result<Self> {
         let cursor = Cursor::new(bytes);
-        Self::from_webp_reader(cursor)
+        Self::from_webp_reader(cursor, max_frames)
     }
 
     /// load an animated webp from any reader
-    fn from_webp_reader<R>(reader: R) -> Result<Self>
+    ///
+    /// frames past `max_frames` are never decoded
+    fn from_webp_reader<R>(reader: R, max_frames: usize) -> Result<Self>
     where
         R: std::io::Read + Seek + BufRead,
     {
@@ -151,6 +156,7 @@ impl AnimatedImage {
         let loop_count = 0;
         let frames: Vec<_> = decoder
             .into_frames()
+            .take(max_frames)
             .collect::<Result<Vec<_>, _>>()
             .context("failed to decode frames")?;
 
@@ -227,8 +233,28 @@ pub fn is_animated_format(path: &Path) -> bool {
     }
 }
 
-/// try to load an animation from a path
-pub fn load_animated(path: &Path) -> Result<AnimatedImage> {
+/// read how many times a gif asks to be played (0 = forever), from its loop extension
+///
+/// a gif without one is played once. the reader is left where it started
+fn gif_play_count<R: Read + Seek>(reader: &mut R) -> Result<u16> {
+    let start = reader.stream_position()?;
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+
+    let repeat = options
+        .read_info(&mut *reader)
+        .context("failed to read gif header")?
+        .repeat();
+    reader.seek(SeekFrom::Start(start))?;
+
+    Ok(match repeat {
+        gif::Repeat::Infinite => 0,
+        gif::Repeat::Finite(n) => n.saturating_add(1),
+    })
+}
+
+/// try to load an animation from a path, decoding at most `max_frames` frames
+pub fn load_animated(path: &Path, max_frames: usize) -> Result<AnimatedImage> {
     if !path.exists() {
         bail!("File does not exist: {}", path.display());
     }
@@ -239,9 +265,51 @@ pub fn load_animated(path: &Path) -> Result<AnimatedImage> {
         .map(|s| s.to_lowercase());
 
     match ext.as_deref() {
-        Some("gif") => AnimatedImage::from_gif_path(path),
-        Some("webp") => AnimatedImage::from_webp_path(path),
+        Some("gif") => AnimatedImage::from_gif_path(path, max_frames),
+        Some("webp") => AnimatedImage::from_webp_path(path, max_frames),
         Some(ext) => bail!("Unsupported animation format: {}", ext),
         None => bail!("Could not determine file format: {}", path.display()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// encode a 1x1 two frame gif with the given loop extension
+    fn gif_with(repeat: Option<gif::Repeat>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder =
+                gif::Encoder::new(&mut bytes, 1, 1, &[0, 0, 0, 255, 255, 255]).unwrap();
+            if let Some(repeat) = repeat {
+                encoder.set_repeat(repeat).unwrap();
+            }
+            for pixel in [0, 1] {
+                let mut frame = gif::Frame::from_indexed_pixels(1, 1, vec![pixel], None);
+                frame.delay = 5;
+                encoder.write_frame(&frame).unwrap();
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_gif_loop_count() {
+        let count = |repeat| {
+            let gif = AnimatedImage::from_gif_bytes(&gif_with(repeat), 10).unwrap();
+            assert_eq!(gif.frame_count(), 2);
+            gif.loop_count
+        };
+
+        assert_eq!(count(Some(gif::Repeat::Infinite)), 0);
+        assert_eq!(count(Some(gif::Repeat::Finite(2))), 3);
+        assert_eq!(count(None), 1);
+        assert_eq!(
+            AnimatedImage::from_gif_bytes(&gif_with(None), 1)
+                .unwrap()
+                .frame_count(),
+            1
+        );
+    }
+}
@@ -2,6 +2,7 @@
 use {
     crate::getopt,
     color_eyre::eyre::{Result, bail},
+    tracing::info,
 };
 
 /// max allowed dimension to prevent mem exhaustion
@@ -15,6 +16,9 @@ pub struct ImageDimensions {
 
     /// target height (optional)
     height: Option<u32>,
+
+    /// the most pixels the target can have (optional)
+    max_pixels: Option<u64>,
 }
 
 impl ImageDimensions {
@@ -32,7 +36,21 @@ impl ImageDimensions {
             bail!("Height {} exceeds maximum {}", h, MAX_IMAGE_DIMENSION);
         }
 
-        Ok(Self { width, height })
+        Ok(Self {
+            width,
+            height,
+            max_pixels: None,
+        })
+    }
+
+    /// cap the total number of pixels of the target
+    ///
+    /// # Arguments
+    ///
+    /// * `megapixels` - the most megapixels the target can have
+    pub fn with_max_megapixels(mut self, megapixels: f64) -> Self {
+        self.max_pixels = Some((megapixels * 1_000_000.0).max(1.0) as u64);
+        self
     }
 
     /// create dimensions from the loaded config
@@ -41,7 +59,11 @@ impl ImageDimensions {
         let width = display.width.map(|w| w as u32);
         let height = display.height.map(|h| h as u32);
 
-        Self::new(width, height)
+        let dimensions = Self::new(width, height)?;
+        Ok(match display.max_megapixels {
+            Some(megapixels) => dimensions.with_max_megapixels(megapixels),
+            None => dimensions,
+        })
     }
 
     /// get the target width if specified
@@ -54,8 +76,46 @@ impl ImageDimensions {
         self.height
     }
 
+    /// get the most pixels the target can have if specified
+    pub fn max_pixels(&self) -> Option<u64> {
+        self.max_pixels
+    }
+
     /// compute target dimensions (preserves aspect ratio)
+    ///
+    /// a target with more pixels than the max is scaled down until it fits
     pub fn compute_target(&self, original: (u32, u32)) -> (u32, u32) {
+        let target = self.compute_unbounded(original);
+
+        let Some(max_pixels) = self.max_pixels else {
+            return target;
+        };
+
+        let pixels = target.0 as u64 * target.1 as u64;
+        if pixels <= max_pixels {
+            return target;
+        }
+
+        let scale = (max_pixels as f64 / pixels as f64).sqrt();
+        let capped = (
+            ((target.0 as f64 * scale).floor() as u32).max(1),
+            ((target.1 as f64 * scale).floor() as u32).max(1),
+        );
+
+        info!(
+            "downscaling a {}x{} image to {}x{} to stay under {:.1} megapixels",
+            original.0,
+            original.1,
+            capped.0,
+            capped.1,
+            max_pixels as f64 / 1_000_000.0
+        );
+
+        capped
+    }
+
+    /// compute target dimensions from the width and height alone
+    fn compute_unbounded(&self, original: (u32, u32)) -> (u32, u32) {
         let (orig_width, orig_height) = original;
 
         match (self.width, self.height) {
@@ -85,3 +145,26 @@ impl ImageDimensions {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_megapixels() {
+        let unbounded = ImageDimensions::new(None, None).unwrap();
+        assert_eq!(unbounded.compute_target((7680, 4320)), (7680, 4320));
+
+        let capped = unbounded.with_max_megapixels(8.0);
+        let (width, height) = capped.compute_target((7680, 4320));
+        assert!(width as u64 * height as u64 <= 8_000_000);
+        assert_eq!((width, height), (3771, 2121));
+
+        assert_eq!(capped.compute_target((800, 600)), (800, 600));
+
+        let sized = ImageDimensions::new(Some(800), None)
+            .unwrap()
+            .with_max_megapixels(8.0);
+        assert_eq!(sized.compute_target((7680, 4320)), (800, 450));
+    }
+}
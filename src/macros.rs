@@ -92,7 +92,7 @@ macro_rules! mkvec {
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// use e62rs::opt_and;
 ///
 /// fn save_metadata() {
@@ -444,49 +444,58 @@ macro_rules! getopt {
     };
 
     ($field:ident) => {{
-        $crate::config::instance::get_or_default(
+        $crate::config::instance::get_or_else(
             |c| c.$field.clone(),
-            $crate::config::options::E62Rs::default()
-                .$field
-                .expect(concat!("Default value missing for: ", stringify!($field))),
+            || {
+                $crate::config::instance::defaults()
+                    .$field
+                    .clone()
+                    .expect(concat!("Default value missing for: ", stringify!($field)))
+            },
         )
     }};
 
     ($lvl1:ident . $field:ident) => {{
-        $crate::config::instance::get_or_default(
+        $crate::config::instance::get_or_else(
             |c| c.$lvl1.as_ref().and_then(|sub| sub.$field.clone()),
-            $crate::config::options::E62Rs::default()
-                .$lvl1
-                .and_then(|sub| sub.$field)
-                .expect(concat!(
-                    "Default value missing for: ",
-                    stringify!($lvl1),
-                    ".",
-                    stringify!($field)
-                )),
+            || {
+                $crate::config::instance::defaults()
+                    .$lvl1
+                    .as_ref()
+                    .and_then(|sub| sub.$field.clone())
+                    .expect(concat!(
+                        "Default value missing for: ",
+                        stringify!($lvl1),
+                        ".",
+                        stringify!($field)
+                    ))
+            },
         )
     }};
 
     ($lvl1:ident . $lvl2:ident . $field:ident) => {{
-        $crate::config::instance::get_or_default(
+        $crate::config::instance::get_or_else(
             |c| {
                 c.$lvl1
                     .as_ref()
                     .and_then(|sub| sub.$lvl2.as_ref())
                     .and_then(|sub| sub.$field.clone())
             },
-            $crate::config::options::E62Rs::default()
-                .$lvl1
-                .and_then(|sub| sub.$lvl2)
-                .and_then(|sub| sub.$field)
-                .expect(concat!(
-                    "Default value missing for: ",
-                    stringify!($lvl1),
-                    ".",
-                    stringify!($lvl2),
-                    ".",
-                    stringify!($field)
-                )),
+            || {
+                $crate::config::instance::defaults()
+                    .$lvl1
+                    .as_ref()
+                    .and_then(|sub| sub.$lvl2.as_ref())
+                    .and_then(|sub| sub.$field.clone())
+                    .expect(concat!(
+                        "Default value missing for: ",
+                        stringify!($lvl1),
+                        ".",
+                        stringify!($lvl2),
+                        ".",
+                        stringify!($field)
+                    ))
+            },
         )
     }};
 
@@ -12,6 +12,19 @@ pub struct E6PostsResponse {
     /// List of posts returned by the API.
     #[serde(default)]
     pub posts: Vec<E6Post>,
+
+    /// Which blacklist rules hid posts from this response (filled in locally, never sent by the API).
+    #[serde(skip)]
+    pub blacklisted: BlacklistReport,
+}
+
+/// A summary of which blacklist rules hid posts from a set of results.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlacklistReport {
+    /// How many posts were hidden in total.
+    pub hidden: usize,
+    /// How many posts each rule hid. A post only counts toward the first rule it matched.
+    pub by_rule: HashMap<String, usize>,
 }
 
 /// Response from e621 API containing a single post.
@@ -308,6 +321,29 @@ pub struct Tags {
     pub lore: Vec<String>,
 }
 
+/// A tag category, matching the fields of [`Tags`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TagCategory {
+    /// General descriptive tags.
+    General,
+    /// Artist tags.
+    Artist,
+    /// Contributor tags.
+    Contributor,
+    /// Copyright tags.
+    Copyright,
+    /// Character tags.
+    Character,
+    /// Species tags.
+    Species,
+    /// Invalid tags.
+    Invalid,
+    /// Meta tags.
+    Meta,
+    /// Lore tags.
+    Lore,
+}
+
 /// Contains various status flags for a post.
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Flags {
@@ -348,6 +384,40 @@ pub struct Relationships {
     pub children: Option<Vec<i64>>,
 }
 
+/// A post and every post under it in its parent/child tree (built locally, never sent by the API).
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct PostTree {
+    /// The post at this point in the tree.
+    pub post: E6Post,
+    /// The post's children, oldest first.
+    pub children: Vec<PostTree>,
+}
+
+impl PostTree {
+    /// Build the tree under a post from the children found for each parent.
+    ///
+    /// # Arguments
+    ///
+    /// * `post` - the post at the top of the tree
+    /// * `children` - the children of each post, keyed by parent ID (emptied as it's used)
+    pub fn build(post: E6Post, children: &mut HashMap<i64, Vec<E6Post>>) -> Self {
+        let kids = children.remove(&post.id).unwrap_or_default();
+
+        Self {
+            children: kids
+                .into_iter()
+                .map(|child| Self::build(child, children))
+                .collect(),
+            post,
+        }
+    }
+
+    /// The number of posts in the tree.
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(PostTree::count).sum::<usize>()
+    }
+}
+
 /// Represents a tag entry from e621's tag database.
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TagEntry {
@@ -373,6 +443,26 @@ pub struct E6PoolsResponse {
     pub pools: Vec<E6Pool>,
 }
 
+/// A flag on a post, as returned by the API after flagging it.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct E6PostFlag {
+    /// Unique identifier for the flag.
+    #[serde(default)]
+    pub id: i64,
+
+    /// ID of the flagged post.
+    #[serde(default)]
+    pub post_id: i64,
+
+    /// The reason given for the flag, as the site words it.
+    #[serde(default)]
+    pub reason: String,
+
+    /// Whether a moderator has already resolved the flag.
+    #[serde(default)]
+    pub is_resolved: bool,
+}
+
 /// Response from e621 API containing a single pool.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct E6PoolResponse {
@@ -496,6 +586,11 @@ pub struct TagImplicationEntry {
 }
 
 impl E6Post {
+    /// parses the post's rating
+    pub fn content_rating(&self) -> Rating {
+        Rating::from(self.rating.as_str())
+    }
+
     /// parses a blacklist rule string into included, excluded, and wildcard tags
     ///
     /// # Arguments
@@ -555,23 +650,25 @@ impl E6Post {
     ///
     /// * `blacklist` - the blacklist rules to check against
     pub fn is_blacklisted_by(&self, blacklist: &[String]) -> bool {
-        if blacklist.is_empty() {
-            return false;
-        }
-
-        for rule in blacklist {
-            if self.matches_blacklist_rule(rule) {
-                return true;
-            }
-        }
+        self.matching_blacklist_rule(blacklist).is_some()
+    }
 
-        false
+    /// returns the first blacklist rule this post matches, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `blacklist` - the blacklist rules to check against
+    pub fn matching_blacklist_rule<'a>(&self, blacklist: &'a [String]) -> Option<&'a str> {
+        blacklist
+            .iter()
+            .find(|rule| self.matches_blacklist_rule(rule))
+            .map(String::as_str)
     }
 
     /// checks if this post is blacklisted using the configured blacklist
     #[cfg(feature = "cli")]
     pub fn is_blacklisted(&self) -> bool {
-        self.is_blacklisted_by(&crate::getopt!(search.blacklist))
+        self.is_blacklisted_by(&crate::config::blacklist::active_blacklist())
     }
 
     /// checks if any of the search tags are blacklisted
@@ -599,7 +696,30 @@ impl E6Post {
     /// checks if any of the search tags are blacklisted using the configured blacklist
     #[cfg(feature = "cli")]
     pub fn search_includes_blacklisted(search_tags: &[String]) -> bool {
-        Self::search_includes_blacklisted_by(search_tags, &crate::getopt!(search.blacklist))
+        Self::search_includes_blacklisted_by(
+            search_tags,
+            &crate::config::blacklist::active_blacklist(),
+        )
+    }
+
+    /// returns the post's md5 followed by its tags from the given categories, space separated
+    ///
+    /// this is the `md5 tag1 tag2 ...` line format used for bulk tag imports
+    ///
+    /// # Arguments
+    ///
+    /// * `categories` - the tag categories to include, in output order
+    pub fn tag_line(&self, categories: &[TagCategory]) -> String {
+        let mut line = self.file.md5.clone();
+
+        for category in categories {
+            for tag in self.tags.category(*category) {
+                line.push(' ');
+                line.push_str(tag);
+            }
+        }
+
+        line
     }
 
     /// returns whether the post meets the given score requirements
@@ -620,6 +740,54 @@ impl E6Post {
             crate::getopt!(search.max_post_score),
         )
     }
+
+    /// returns whether the post's file is at least the given size
+    ///
+    /// a minimum of 0 doesn't filter that dimension
+    ///
+    /// # Arguments
+    ///
+    /// * `min_width` - the minimum width in pixels
+    /// * `min_height` - the minimum height in pixels
+    pub fn meets_resolution_with(&self, min_width: u64, min_height: u64) -> bool {
+        let size = |px: i64| u64::try_from(px).unwrap_or(0);
+        size(self.file.width) >= min_width && size(self.file.height) >= min_height
+    }
+
+    /// returns whether the post's file meets the configured minimum resolution
+    #[cfg(feature = "cli")]
+    pub fn meets_resolution(&self) -> bool {
+        self.meets_resolution_with(
+            crate::getopt!(search.min_width),
+            crate::getopt!(search.min_height),
+        )
+    }
+
+    /// removes posts scoring below `min_score` from a list of posts
+    ///
+    /// returns the number of posts that were hidden
+    ///
+    /// # Arguments
+    ///
+    /// * `posts` - the posts to filter
+    /// * `min_score` - the lowest total score a post can have and still be shown
+    pub fn hide_below_score_with(posts: &mut Vec<E6Post>, min_score: i64) -> usize {
+        let before = posts.len();
+        posts.retain(|post| post.score.total >= min_score);
+        before - posts.len()
+    }
+
+    /// removes posts scoring below the configured `hide_below_score` from a list of posts
+    ///
+    /// returns the number of posts that were hidden
+    ///
+    /// # Arguments
+    ///
+    /// * `posts` - the posts to filter
+    #[cfg(feature = "cli")]
+    pub fn hide_below_score(posts: &mut Vec<E6Post>) -> usize {
+        Self::hide_below_score_with(posts, crate::getopt!(ui.hide_below_score))
+    }
 }
 
 impl E6PostsResponse {
@@ -634,14 +802,24 @@ impl E6PostsResponse {
             return self;
         }
 
-        self.posts.retain(|post| !post.is_blacklisted_by(blacklist));
+        let mut report = BlacklistReport::default();
+        self.posts
+            .retain(|post| match post.matching_blacklist_rule(blacklist) {
+                Some(rule) => {
+                    report.record(rule);
+                    false
+                }
+                None => true,
+            });
+
+        self.blacklisted.merge(&report);
         self
     }
 
     /// filter blacklisted posts using the configured blacklist
     #[cfg(feature = "cli")]
     pub fn filter_blacklisted(self, search_tags: &[String]) -> Self {
-        self.filter_blacklisted_by(search_tags, &crate::getopt!(search.blacklist))
+        self.filter_blacklisted_by(search_tags, &crate::config::blacklist::active_blacklist())
     }
 
     /// filters out posts that don't meet the given score requirements
@@ -664,3 +842,253 @@ impl E6PostsResponse {
         )
     }
 }
+
+impl BlacklistReport {
+    /// Records a post hidden by a rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - the blacklist rule that hid the post
+    pub fn record(&mut self, rule: &str) {
+        self.hidden += 1;
+        *self.by_rule.entry(rule.to_string()).or_insert(0) += 1;
+    }
+
+    /// Adds the counts from another report to this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - the report to add
+    pub fn merge(&mut self, other: &BlacklistReport) {
+        self.hidden += other.hidden;
+        for (rule, count) in &other.by_rule {
+            *self.by_rule.entry(rule.clone()).or_insert(0) += count;
+        }
+    }
+
+    /// Returns true if no posts were hidden.
+    pub fn is_empty(&self) -> bool {
+        self.hidden == 0
+    }
+}
+
+impl std::fmt::Display for BlacklistReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rules: Vec<(&String, &usize)> = self.by_rule.iter().collect();
+        rules.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+        let rules: Vec<String> = rules
+            .into_iter()
+            .map(|(rule, count)| format!("{} ×{}", rule, count))
+            .collect();
+
+        write!(f, "hidden: {}", self.hidden)?;
+        if !rules.is_empty() {
+            write!(f, " ({})", rules.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TagCategory {
+    /// All tag categories, in the order e621 lists them.
+    pub const ALL: [TagCategory; 9] = [
+        Self::Artist,
+        Self::Contributor,
+        Self::Copyright,
+        Self::Character,
+        Self::Species,
+        Self::General,
+        Self::Meta,
+        Self::Lore,
+        Self::Invalid,
+    ];
+
+    /// Returns the numeric id e621 uses for this category in its tag exports.
+    pub fn id(&self) -> i64 {
+        match self {
+            Self::General => 0,
+            Self::Artist => 1,
+            Self::Contributor => 2,
+            Self::Copyright => 3,
+            Self::Character => 4,
+            Self::Species => 5,
+            Self::Invalid => 6,
+            Self::Meta => 7,
+            Self::Lore => 8,
+        }
+    }
+
+    /// Returns the lowercase name of this category, as used by e621.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::General => "general",
+            Self::Artist => "artist",
+            Self::Contributor => "contributor",
+            Self::Copyright => "copyright",
+            Self::Character => "character",
+            Self::Species => "species",
+            Self::Invalid => "invalid",
+            Self::Meta => "meta",
+            Self::Lore => "lore",
+        }
+    }
+}
+
+impl std::fmt::Display for TagCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A post's content rating, parsed from the single letter e621 sends.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Rating {
+    /// Rated `s`.
+    Safe,
+    /// Rated `q`.
+    Questionable,
+    /// Rated `e`.
+    Explicit,
+    /// Any other rating, kept as it was sent (trimmed and lowercased). Empty if the post has no
+    /// rating at all.
+    Unknown(String),
+}
+
+impl Rating {
+    /// Returns the full lowercase name of the rating (`safe`, `questionable`, `explicit`), or the
+    /// raw value of an unknown rating (`unknown` if it's empty).
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Safe => "safe",
+            Self::Questionable => "questionable",
+            Self::Explicit => "explicit",
+            Self::Unknown(raw) if raw.is_empty() => "unknown",
+            Self::Unknown(raw) => raw,
+        }
+    }
+
+    /// Returns the letter e621 uses for the rating (`s`, `q`, `e`), or the raw value of an
+    /// unknown rating (`unknown` if it's empty).
+    pub fn code(&self) -> &str {
+        match self {
+            Self::Safe => "s",
+            Self::Questionable => "q",
+            Self::Explicit => "e",
+            Self::Unknown(_) => self.name(),
+        }
+    }
+}
+
+impl std::str::FromStr for Rating {
+    type Err = std::convert::Infallible;
+
+    /// Parses a rating from its letter or full name (case-insensitive). Anything else becomes
+    /// [`Rating::Unknown`], with `unknown` itself standing for an empty rating.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+
+        Ok(match s.as_str() {
+            "s" | "safe" => Self::Safe,
+            "q" | "questionable" => Self::Questionable,
+            "e" | "explicit" => Self::Explicit,
+            "unknown" => Self::Unknown(String::new()),
+            _ => Self::Unknown(s),
+        })
+    }
+}
+
+impl From<&str> for Rating {
+    fn from(s: &str) -> Self {
+        let Ok(rating) = s.parse();
+        rating
+    }
+}
+
+impl std::fmt::Display for Rating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Tags {
+    /// Returns the tags in a single category.
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - the category to get the tags of
+    pub fn category(&self, category: TagCategory) -> &[String] {
+        match category {
+            TagCategory::General => &self.general,
+            TagCategory::Artist => &self.artist,
+            TagCategory::Contributor => &self.contributor,
+            TagCategory::Copyright => &self.copyright,
+            TagCategory::Character => &self.character,
+            TagCategory::Species => &self.species,
+            TagCategory::Invalid => &self.invalid,
+            TagCategory::Meta => &self.meta,
+            TagCategory::Lore => &self.lore,
+        }
+    }
+
+    /// Returns how many tags are in each category, like `general:42 artist:2 character:3 ...`.
+    pub fn count_summary(&self) -> String {
+        [
+            TagCategory::General,
+            TagCategory::Artist,
+            TagCategory::Character,
+            TagCategory::Species,
+            TagCategory::Copyright,
+            TagCategory::Meta,
+            TagCategory::Lore,
+        ]
+        .iter()
+        .map(|category| format!("{}:{}", category.name(), self.category(*category).len()))
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(id: i64) -> E6Post {
+        E6Post {
+            id,
+            ..Default::default()
+        }
+    }
+
+    fn ids(tree: &PostTree) -> Vec<i64> {
+        std::iter::once(tree.post.id)
+            .chain(tree.children.iter().flat_map(ids))
+            .collect()
+    }
+
+    #[test]
+    fn test_post_tree_build() {
+        let mut children: HashMap<i64, Vec<E6Post>> = HashMap::new();
+        children.insert(1, vec![post(2), post(3)]);
+        children.insert(2, vec![post(4)]);
+
+        let tree = PostTree::build(post(1), &mut children);
+        assert_eq!(tree.count(), 4);
+        assert_eq!(ids(&tree), vec![1, 2, 4, 3]);
+        assert_eq!(tree.children[0].children[0].post.id, 4);
+        assert!(tree.children[1].children.is_empty());
+        assert!(children.is_empty());
+    }
+
+    #[test]
+    fn test_post_tree_build_loop() {
+        let mut children: HashMap<i64, Vec<E6Post>> = HashMap::new();
+        children.insert(1, vec![post(2)]);
+        children.insert(2, vec![post(1)]);
+
+        let tree = PostTree::build(post(1), &mut children);
+        assert_eq!(ids(&tree), vec![1, 2, 1]);
+        assert!(children.is_empty());
+    }
+}
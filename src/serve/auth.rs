@@ -0,0 +1,165 @@
+//! optional authentication for the gallery
+//!
+//! when a username/password or a token is configured every route requires an `Authorization`
+//! header. the token can be sent as `Bearer <token>`, or as the password of basic auth (with
+//! any username) so it can be typed into the browser's login prompt
+use {
+    base64::{Engine, engine::general_purpose::STANDARD},
+    rocket::{
+        Request, Responder, catch,
+        http::{Header, Status},
+        request::{FromRequest, Outcome},
+    },
+};
+
+/// the credentials the gallery accepts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerAuth {
+    /// the username and password for basic auth
+    pub basic: Option<(String, String)>,
+    /// the bearer token
+    pub token: Option<String>,
+}
+
+impl ServerAuth {
+    /// make the gallery's credentials, ignoring empty values
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - the basic auth username
+    /// * `password` - the basic auth password
+    /// * `token` - the bearer token
+    pub fn new(username: &str, password: &str, token: &str) -> Self {
+        Self {
+            basic: (!username.is_empty() && !password.is_empty())
+                .then(|| (username.to_string(), password.to_string())),
+            token: (!token.is_empty()).then(|| token.to_string()),
+        }
+    }
+
+    /// whether any credentials are configured
+    pub fn is_enabled(&self) -> bool {
+        self.basic.is_some() || self.token.is_some()
+    }
+
+    /// check the value of an `Authorization` header
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - the header value, like `Basic dXNlcjpwYXNz` or `Bearer abc123`
+    pub fn check(&self, header: &str) -> bool {
+        let Some((scheme, value)) = header.trim().split_once(' ') else {
+            return false;
+        };
+        let value = value.trim();
+
+        if scheme.eq_ignore_ascii_case("bearer") {
+            return self.token.as_deref().is_some_and(|t| constant_eq(t, value));
+        }
+
+        if !scheme.eq_ignore_ascii_case("basic") {
+            return false;
+        }
+
+        let Some(decoded) = STANDARD
+            .decode(value)
+            .ok()
+            .and_then(|d| String::from_utf8(d).ok())
+        else {
+            return false;
+        };
+        let Some((user, pass)) = decoded.split_once(':') else {
+            return false;
+        };
+
+        let basic_ok = self
+            .basic
+            .as_ref()
+            .is_some_and(|(u, p)| constant_eq(u, user) & constant_eq(p, pass));
+        let token_ok = self.token.as_deref().is_some_and(|t| constant_eq(t, pass));
+
+        basic_ok || token_ok
+    }
+}
+
+/// compare two strings without bailing out at the first difference
+fn constant_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+/// a request guard that only passes requests with valid credentials
+///
+/// always passes when no credentials are configured
+pub struct Authorized;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Authorized {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(auth) = request.rocket().state::<ServerAuth>() else {
+            return Outcome::Success(Self);
+        };
+
+        if !auth.is_enabled() {
+            return Outcome::Success(Self);
+        }
+
+        match request.headers().get_one("Authorization") {
+            Some(header) if auth.check(header) => Outcome::Success(Self),
+            _ => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+/// the response to an unauthenticated request
+#[derive(Responder)]
+#[response(status = 401, content_type = "plain")]
+pub struct Unauthorized {
+    /// the response body
+    body: &'static str,
+    /// tells the browser to ask for credentials
+    challenge: Header<'static>,
+}
+
+#[catch(401)]
+/// ask the client to log in
+pub fn unauthorized_catcher() -> Unauthorized {
+    Unauthorized {
+        body: "401 Unauthorized",
+        challenge: Header::new("WWW-Authenticate", r#"Basic realm="e62rs gallery""#),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let auth = ServerAuth::new("bear", "hunter2", "t0ken");
+        let basic = |creds: &str| format!("Basic {}", STANDARD.encode(creds));
+
+        assert!(auth.check(&basic("bear:hunter2")));
+        assert!(auth.check(&basic("anyone:t0ken")));
+        assert!(auth.check("Bearer t0ken"));
+        assert!(auth.check("bearer  t0ken "));
+
+        assert!(!auth.check(&basic("bear:hunter3")));
+        assert!(!auth.check(&basic("bearhunter2")));
+        assert!(!auth.check("Bearer t0ke"));
+        assert!(!auth.check("Digest t0ken"));
+        assert!(!auth.check(""));
+    }
+
+    #[test]
+    fn test_disabled_when_empty() {
+        assert!(!ServerAuth::new("", "", "").is_enabled());
+        assert!(!ServerAuth::new("bear", "", "").is_enabled());
+        assert!(ServerAuth::new("", "", "t0ken").is_enabled());
+    }
+}
@@ -1,5 +1,6 @@
 //! server configuration stuff
 use {
+    crate::{config::options::MetadataBackend, models::Rating, serve::auth::ServerAuth},
     color_eyre::eyre::Result,
     std::{net::SocketAddr, path::PathBuf},
 };
@@ -19,6 +20,14 @@ pub struct ServerConfig {
     pub cache_metadata: bool,
     /// the number of threads to use when loading
     pub num_threads: usize,
+    /// whether to watch the media directory and live-reload the gallery
+    pub watch: bool,
+    /// the credentials required to use the gallery (none by default)
+    pub auth: ServerAuth,
+    /// the metadata backend to read first
+    pub metadata_backend: MetadataBackend,
+    /// the rating code the gallery shows when it's opened (`None` shows everything)
+    pub default_rating: Option<String>,
 }
 
 impl ServerConfig {
@@ -53,6 +62,14 @@ pub struct ServerConfigBuilder {
     cache_metadata: bool,
     /// the number of threads to use when loading
     num_threads: usize,
+    /// whether to watch the media directory and live-reload the gallery
+    watch: bool,
+    /// the credentials required to use the gallery
+    auth: ServerAuth,
+    /// the metadata backend to read first
+    metadata_backend: MetadataBackend,
+    /// the rating the gallery shows when it's opened
+    default_rating: Option<String>,
 }
 
 impl ServerConfigBuilder {
@@ -91,12 +108,39 @@ impl ServerConfigBuilder {
         self
     }
 
-    /// set the number of load threads
+    /// set the number of load threads (0 = pick based on the cpu count)
     pub fn num_threads(mut self, threads: usize) -> Self {
         self.num_threads = threads;
         self
     }
 
+    /// set whether to watch the media directory for changes
+    pub fn watch(mut self, enabled: bool) -> Self {
+        self.watch = enabled;
+        self
+    }
+
+    /// set the credentials required to use the gallery
+    pub fn auth(mut self, auth: ServerAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// set the metadata backend to read first (the others are still tried)
+    pub fn metadata_backend(mut self, backend: MetadataBackend) -> Self {
+        self.metadata_backend = backend;
+        self
+    }
+
+    /// set the rating the gallery shows when it's opened (empty to show everything)
+    ///
+    /// ignored unless metadata filtering is enabled, since ratings come from the metadata
+    pub fn default_rating(mut self, rating: &str) -> Self {
+        let rating = rating.trim();
+        self.default_rating = (!rating.is_empty()).then(|| Rating::from(rating).code().to_string());
+        self
+    }
+
     /// build the ServerConfigBuilder into a ServerConfig
     pub fn build(self) -> Result<ServerConfig, String> {
         let media_directory = self
@@ -120,7 +164,17 @@ impl ServerConfigBuilder {
             max_file_size: self.max_file_size,
             enable_metadata_filtering: self.enable_metadata_filtering,
             cache_metadata: self.cache_metadata,
-            num_threads: self.num_threads,
+            num_threads: if self.num_threads == 0 {
+                num_cpus::get().max(4)
+            } else {
+                self.num_threads
+            },
+            watch: self.watch,
+            auth: self.auth,
+            metadata_backend: self.metadata_backend,
+            default_rating: self
+                .default_rating
+                .filter(|_| self.enable_metadata_filtering),
         })
     }
 }
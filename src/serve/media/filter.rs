@@ -1,5 +1,9 @@
 //! media filtering stuff
-use {crate::serve::media::item::MediaItem, rocket::FromForm, serde::Deserialize};
+use {
+    crate::{models::Rating, serve::media::item::MediaItem},
+    rocket::FromForm,
+    serde::Deserialize,
+};
 
 /// a media filter
 #[derive(Debug, Clone, Deserialize, FromForm, Default)]
@@ -62,7 +66,7 @@ impl MediaFilter {
 
         if let Some(metadata) = item.metadata() {
             if let Some(ref rating_filter) = self.rating
-                && &metadata.rating != rating_filter
+                && Rating::from(metadata.rating.as_str()) != Rating::from(rating_filter.as_str())
             {
                 return false;
             }
@@ -1,7 +1,7 @@
 //! media gallery stuff
 use {
     crate::{
-        getopt,
+        config::options::MetadataBackend,
         serve::media::{
             filter::MediaFilter,
             item::MediaItem,
@@ -10,6 +10,7 @@ use {
             types::MediaType,
         },
     },
+    hashbrown::HashMap,
     std::path::{Path, PathBuf},
 };
 
@@ -21,18 +22,32 @@ pub struct MediaGallery {
     directory: PathBuf,
     /// the cached items
     cached_items: Option<Vec<MediaItem>>,
+    /// the index of each cached item by its url
+    item_index: HashMap<String, usize>,
 }
 
 impl MediaGallery {
     /// initialize the media gallery
-    pub fn new(directory: PathBuf, load_metadata: bool) -> Self {
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - the directory to make a gallery from
+    /// * `load_metadata` - whether to load post metadata
+    /// * `num_threads` - the max number of threads to index with
+    /// * `backend` - the metadata backend to read first
+    pub fn new(
+        directory: PathBuf,
+        load_metadata: bool,
+        num_threads: usize,
+        backend: MetadataBackend,
+    ) -> Self {
         Self {
-            scanner: Box::new(FsScanner::with_threads(
-                load_metadata,
-                getopt!(gallery.load_threads),
-            )),
+            scanner: Box::new(
+                FsScanner::with_threads(load_metadata, num_threads).with_backend(backend),
+            ),
             directory,
             cached_items: None,
+            item_index: HashMap::new(),
         }
     }
 
@@ -42,14 +57,25 @@ impl MediaGallery {
             scanner,
             directory,
             cached_items: None,
+            item_index: HashMap::new(),
         }
     }
 
+    /// replace the cached items and index them by url
+    fn set_items(&mut self, items: Vec<MediaItem>) -> &[MediaItem] {
+        self.item_index = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (item.path().to_string(), i))
+            .collect();
+        self.cached_items.insert(items)
+    }
+
     /// get all media items
     pub async fn get_items(&mut self) -> Result<&[MediaItem], std::io::Error> {
         if self.cached_items.is_none() {
             let items = self.scanner.scan(&self.directory).await?;
-            self.cached_items = Some(items);
+            return Ok(self.set_items(items));
         }
 
         Ok(self.cached_items.as_ref().expect("cached_items is None"))
@@ -71,8 +97,8 @@ impl MediaGallery {
 
     /// refresh the gallery
     pub async fn refresh(&mut self) -> Result<&[MediaItem], std::io::Error> {
-        self.cached_items = Some(self.scanner.scan(&self.directory).await?);
-        Ok(self.cached_items.as_ref().expect("cached_items is None"))
+        let items = self.scanner.scan(&self.directory).await?;
+        Ok(self.set_items(items))
     }
 
     /// filter media items by type
@@ -103,6 +129,42 @@ impl MediaGallery {
             .unwrap_or_default()
     }
 
+    /// get a loaded item by its `/files/...` url
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the url of the item
+    pub fn get_item(&self, path: &str) -> Option<&MediaItem> {
+        self.cached_items.as_ref()?.get(*self.item_index.get(path)?)
+    }
+
+    /// get a loaded item by the id or md5 of its post
+    ///
+    /// items without metadata are never found
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the post id, or the md5 of the file
+    pub fn find_post(&self, key: &str) -> Option<&MediaItem> {
+        let id = key.parse::<i64>().ok();
+
+        self.cached_items.as_ref()?.iter().find(|item| {
+            item.metadata().is_some_and(|meta| {
+                Some(meta.id) == id || (!meta.md5.is_empty() && meta.md5.eq_ignore_ascii_case(key))
+            })
+        })
+    }
+
+    /// get the number of loaded items (0 if nothing has been scanned yet)
+    pub fn len(&self) -> usize {
+        self.cached_items.as_ref().map(Vec::len).unwrap_or_default()
+    }
+
+    /// whether no items are loaded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// get the directory being used for the gallery
     pub fn directory(&self) -> &Path {
         &self.directory
@@ -113,3 +175,63 @@ impl MediaGallery {
         FilterStats::from_items(self.cached_items.as_deref().unwrap_or(&[]))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{models::E6Post, serve::media::metadata::PostMetadata},
+    };
+
+    /// a scanner that always finds the same items
+    struct FixedScanner(Vec<MediaItem>);
+
+    #[async_trait::async_trait]
+    impl MediaScanner for FixedScanner {
+        async fn scan(&self, _dir: &Path) -> Result<Vec<MediaItem>, std::io::Error> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_post() {
+        let mut post = E6Post {
+            id: 42,
+            ..Default::default()
+        };
+        post.file.md5 = "0123456789abcdef0123456789abcdef".to_string();
+
+        let item = |name: &str| {
+            MediaItem::new(
+                format!("/files/{}", name),
+                name.to_string(),
+                MediaType::Image,
+                1,
+            )
+        };
+        let mut gallery = MediaGallery::with_scanner(
+            PathBuf::from("downloads"),
+            Box::new(FixedScanner(vec![
+                item("bare.png"),
+                item("42.png").with_metadata(PostMetadata::from(post)),
+            ])),
+        );
+
+        assert!(gallery.find_post("42").is_none());
+        gallery.get_items().await.unwrap();
+
+        let name = |key: &str| gallery.find_post(key).map(|item| item.name().to_string());
+        assert_eq!(name("42").as_deref(), Some("42.png"));
+        assert_eq!(
+            name("0123456789ABCDEF0123456789ABCDEF").as_deref(),
+            Some("42.png")
+        );
+        assert!(name("43").is_none());
+        assert!(name("").is_none());
+
+        let path = |url: &str| gallery.get_item(url).map(|item| item.name().to_string());
+        assert_eq!(path("/files/bare.png").as_deref(), Some("bare.png"));
+        assert_eq!(path("/files/42.png").as_deref(), Some("42.png"));
+        assert!(path("/files/43.png").is_none());
+    }
+}
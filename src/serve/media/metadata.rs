@@ -1,5 +1,8 @@
 //! media metadata stuffuse serde::{Deserialize, Serialize};
-use serde::{Deserialize, Serialize};
+use {
+    crate::models::E6Post,
+    serde::{Deserialize, Serialize},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// a posts metadata
@@ -24,4 +27,25 @@ pub struct PostMetadata {
     pub created_at: String,
     /// the pools a post is in
     pub pools: Vec<i64>,
+    /// the md5 of the post's file
+    #[serde(default)]
+    pub md5: String,
+}
+
+impl From<E6Post> for PostMetadata {
+    fn from(post: E6Post) -> Self {
+        Self {
+            id: post.id,
+            rating: post.rating,
+            score: post.score.total,
+            fav_count: post.fav_count,
+            artists: post.tags.artist,
+            tags: post.tags.general,
+            character_tags: post.tags.character,
+            species_tags: post.tags.species,
+            created_at: post.created_at,
+            pools: post.pools,
+            md5: post.file.md5,
+        }
+    }
 }
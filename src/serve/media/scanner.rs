@@ -1,9 +1,16 @@
 //! media scanning stuff
 use {
-    crate::serve::media::{item::MediaItem, metadata::PostMetadata, types::MediaType},
+    crate::{
+        config::options::MetadataBackend,
+        data::metadata::read_metadata,
+        serve::media::{item::MediaItem, metadata::PostMetadata, types::MediaType},
+    },
     jwalk::WalkDir,
-    rayon::iter::{IntoParallelRefIterator, ParallelIterator},
-    std::{fs::OpenOptions, io::Read, path::Path},
+    rayon::{
+        ThreadPoolBuilder,
+        iter::{IntoParallelRefIterator, ParallelIterator},
+    },
+    std::path::Path,
     tracing::info,
 };
 
@@ -24,6 +31,8 @@ pub struct FsScanner {
     load_metadata: bool,
     /// how many threads to load with
     num_threads: usize,
+    /// the metadata backend to read first
+    backend: MetadataBackend,
 }
 
 impl FsScanner {
@@ -36,11 +45,14 @@ impl FsScanner {
         Self {
             load_metadata,
             num_threads: num_cpus::get().max(4),
+            backend: MetadataBackend::default(),
         }
     }
 
     /// make a new filesystem scanner with a custom thread count
     ///
+    /// both walking the directory and reading metadata are capped to `num_threads`
+    ///
     /// # Arguments
     ///
     /// * `load_metadata` - whether to load metadata
@@ -49,64 +61,21 @@ impl FsScanner {
         Self {
             load_metadata,
             num_threads,
+            backend: MetadataBackend::default(),
         }
     }
 
-    /// read a files metadata from JSON
+    /// set the metadata backend to read first
     ///
-    /// # Arguments
-    ///
-    /// * `file_path` - the path to the file being read
-    fn read_metadata_from_json(&self, file_path: &Path) -> Option<PostMetadata> {
-        let json_path = file_path.with_extension(format!(
-            "{}.json",
-            file_path.extension().and_then(|e| e.to_str()).unwrap_or("")
-        ));
-
-        if !json_path.exists() {
-            return None;
-        }
-
-        let contents = std::fs::read_to_string(&json_path).ok()?;
-        self.parse_metadata(&contents)
-    }
-
-    /// read a files metadata from an NTFS ADS stream
+    /// the other backends are still tried, so libraries with metadata in more than one place
+    /// (like ADS and sidecars on windows) are read in full
     ///
     /// # Arguments
     ///
-    /// * `file_path` - the path to the file being read
-    #[cfg(target_os = "windows")]
-    fn read_metadata_from_ads(&self, file_path: &Path) -> Option<PostMetadata> {
-        let ads_path = format!("{}:metadata", file_path.display());
-        let mut file = OpenOptions::new().read(true).open(&ads_path).ok()?;
-
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).ok()?;
-
-        self.parse_metadata(&contents)
-    }
-
-    /// parse a JSON string into PostMetadata
-    ///
-    /// # Arguments
-    ///
-    /// * `contents` - the JSON data to parse
-    fn parse_metadata(&self, contents: &str) -> Option<PostMetadata> {
-        let post = serde_json::from_str::<crate::models::E6Post>(contents).ok()?;
-
-        Some(PostMetadata {
-            id: post.id,
-            rating: post.rating,
-            score: post.score.total,
-            fav_count: post.fav_count,
-            artists: post.tags.artist,
-            tags: post.tags.general,
-            character_tags: post.tags.character,
-            species_tags: post.tags.species,
-            created_at: post.created_at,
-            pools: post.pools,
-        })
+    /// * `backend` - the backend to read first
+    pub fn with_backend(mut self, backend: MetadataBackend) -> Self {
+        self.backend = backend;
+        self
     }
 }
 
@@ -122,8 +91,14 @@ impl MediaScanner for FsScanner {
         let directory = directory.to_path_buf();
         let load_meta = self.load_metadata;
         let num_threads = self.num_threads;
+        let backend = self.backend;
 
         let media_items = tokio::task::spawn_blocking(move || {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(num_threads.max(1))
+                .build()
+                .map_err(std::io::Error::other)?;
+
             let entries: Vec<_> = WalkDir::new(&directory)
                 .skip_hidden(false)
                 .parallelism(jwalk::Parallelism::RayonNewPool(num_threads))
@@ -132,61 +107,36 @@ impl MediaScanner for FsScanner {
                 .filter(|e| e.file_type().is_file())
                 .collect();
 
-            entries
-                .par_iter()
-                .filter_map(|entry| {
-                    let path = entry.path();
-                    let ext = path.extension()?;
-                    let media_type = MediaType::from_extension(&ext.to_string_lossy())?;
-                    let rel_path = path.strip_prefix(&directory).ok()?;
-                    let size = entry.metadata().ok()?.len();
-
-                    let mut item = MediaItem::new(
-                        format!("/files/{}", rel_path.display()),
-                        path.file_name()?.to_string_lossy().to_string(),
-                        media_type,
-                        size,
-                    );
-
-                    if load_meta && let Some(metadata) = Self::read_metadata_static(path.as_path())
-                    {
-                        item = item.with_metadata(metadata);
-                        info!("Found item: {}", item.name());
-                    }
-
-                    Some(item)
-                })
-                .collect()
+            Ok::<Vec<MediaItem>, std::io::Error>(pool.install(|| {
+                entries
+                    .par_iter()
+                    .filter_map(|entry| {
+                        let path = entry.path();
+                        let ext = path.extension()?;
+                        let media_type = MediaType::from_extension(&ext.to_string_lossy())?;
+                        let rel_path = path.strip_prefix(&directory).ok()?;
+                        let size = entry.metadata().ok()?.len();
+
+                        let mut item = MediaItem::new(
+                            format!("/files/{}", rel_path.display()),
+                            path.file_name()?.to_string_lossy().to_string(),
+                            media_type,
+                            size,
+                        );
+
+                        if load_meta && let Ok(post) = read_metadata(path.as_path(), backend) {
+                            item = item.with_metadata(PostMetadata::from(post));
+                            info!("Found item: {}", item.name());
+                        }
+
+                        Some(item)
+                    })
+                    .collect()
+            }))
         })
         .await
-        .map_err(std::io::Error::other)?;
+        .map_err(std::io::Error::other)??;
 
         Ok(media_items)
     }
 }
-
-impl FsScanner {
-    #[cfg(target_os = "windows")]
-    /// read a files metadata into a PostMetadata
-    ///
-    /// # Arguments
-    ///
-    /// * `file_path` - the path to the file to read
-    fn read_metadata_static(file_path: &Path) -> Option<PostMetadata> {
-        let scanner = Self::new(true);
-        scanner
-            .read_metadata_from_ads(file_path)
-            .or_else(|| scanner.read_metadata_from_json(file_path))
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    /// read a files metadata into a PostMetadata
-    ///
-    /// # Arguments
-    ///
-    /// * `file_path` - the path to the file to read
-    fn read_metadata_static(file_path: &Path) -> Option<PostMetadata> {
-        let scanner = Self::new(true);
-        scanner.read_metadata_from_json(file_path)
-    }
-}
@@ -1,6 +1,9 @@
 //! media statistics stuff
 use {
-    crate::serve::media::{item::MediaItem, types::MediaType},
+    crate::{
+        models::Rating,
+        serve::media::{item::MediaItem, types::MediaType},
+    },
     hashbrown::{HashMap, HashSet},
     serde::Serialize,
 };
@@ -38,11 +41,11 @@ impl FilterStats {
             }
 
             if let Some(meta) = &item.metadata() {
-                match meta.rating.as_str() {
-                    "s" => stats.safe_count += 1,
-                    "q" => stats.questionable_count += 1,
-                    "e" => stats.explicit_count += 1,
-                    _ => {}
+                match Rating::from(meta.rating.as_str()) {
+                    Rating::Safe => stats.safe_count += 1,
+                    Rating::Questionable => stats.questionable_count += 1,
+                    Rating::Explicit => stats.explicit_count += 1,
+                    Rating::Unknown(_) => {}
                 }
 
                 for artist in &meta.artists {
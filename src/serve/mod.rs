@@ -1,6 +1,8 @@
 //! the browser frontend service
+pub mod auth;
 pub mod cfg;
 pub mod media;
 pub mod routes;
 pub mod server;
 pub mod theme;
+pub mod watch;
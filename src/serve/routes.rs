@@ -1,22 +1,37 @@
 //! routes for the gallery
 use {
     crate::{
+        config::options::MetadataBackend,
+        data::{metadata::read_metadata, thumbs::ThumbnailCache},
         getopt,
+        models::E6Post,
         serve::{
+            auth::Authorized,
             media::{
                 filter::MediaFilter, gallery::MediaGallery, item::MediaItem, stats::FilterStats,
             },
             theme::registry::ThemeRegistry,
+            watch::MediaEvent,
         },
     },
+    futures::{SinkExt, StreamExt},
     rocket::{
-        State, get,
+        State,
+        fs::NamedFile,
+        get,
         http::Status,
+        post,
         response::content::{RawCss, RawHtml, RawJavaScript},
         serde::json::Json,
     },
-    std::sync::Arc,
-    tokio::sync::RwLock,
+    rocket_ws::{Channel, Message, WebSocket},
+    serde::Serialize,
+    std::{path::PathBuf, sync::Arc, time::Instant},
+    tokio::sync::{
+        Mutex, RwLock,
+        broadcast::{self, error::RecvError},
+    },
+    tracing::{info, warn},
 };
 
 /// the HTML code of the media gallery
@@ -26,10 +41,47 @@ const CSS: &str = include_str!("../../resources/gallery/styles.css");
 /// the JS for the html template
 const JS: &str = include_str!("../../resources/gallery/script.js");
 
+/// the size of a thumbnail when a request doesn't ask for one, in pixels
+const DEFAULT_THUMBNAIL_SIZE: u32 = 384;
+
+/// the smallest and biggest thumbnails that can be asked for, in pixels
+const THUMBNAIL_SIZE_RANGE: (u32, u32) = (32, 1024);
+
 /// the state of the app
 pub struct AppState {
     /// the gallery
     pub gallery: Arc<RwLock<MediaGallery>>,
+    /// the media directory (kept outside the gallery lock so files can be served mid-rescan)
+    pub directory: PathBuf,
+    /// when the last rescan finished
+    ///
+    /// also serializes rescans, so only one walks the media directory at a time
+    last_rescan: Mutex<Option<Instant>>,
+    /// changes to the media directory, forwarded to every `/api/events` subscriber
+    pub events: broadcast::Sender<MediaEvent>,
+    /// the thumbnails served by `/thumbnails/...`
+    pub thumbnails: ThumbnailCache,
+    /// the metadata backend `/post/...` reads first
+    pub metadata_backend: MetadataBackend,
+}
+
+/// the settings the gallery page starts with, served by `/api/config`
+#[derive(Debug, Clone, Serialize)]
+pub struct GallerySettings {
+    /// whether posts can be filtered by their metadata
+    pub metadata_filtering: bool,
+    /// the rating code shown when the page is opened (`None` shows everything)
+    pub default_rating: Option<String>,
+}
+
+/// the result of a rescan
+#[derive(Debug, Serialize)]
+pub struct RescanResult {
+    /// how many posts are in the gallery after the rescan
+    pub posts: usize,
+    /// whether this request started the rescan (false if it reused one that finished while it
+    /// was waiting)
+    pub rescanned: bool,
 }
 
 impl AppState {
@@ -38,22 +90,64 @@ impl AppState {
     /// # Arguments
     ///
     /// * `gallery` - a loaded gallery
-    pub fn new(gallery: MediaGallery) -> Self {
+    /// * `metadata_backend` - the metadata backend to read first
+    pub fn new(gallery: MediaGallery, metadata_backend: MetadataBackend) -> Self {
         Self {
+            directory: gallery.directory().to_path_buf(),
             gallery: Arc::new(RwLock::new(gallery)),
+            last_rescan: Mutex::new(None),
+            events: broadcast::channel(16).0,
+            thumbnails: ThumbnailCache::configured(),
+            metadata_backend,
+        }
+    }
+
+    /// re-walk the media directory and reload its metadata
+    ///
+    /// rescans are debounced: a request that arrives while another rescan is running waits for
+    /// it and reuses its result instead of walking the directory again
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the media directory can't be scanned
+    pub async fn rescan(&self) -> Result<RescanResult, std::io::Error> {
+        let requested = Instant::now();
+        let mut last_rescan = self.last_rescan.lock().await;
+
+        if last_rescan.is_some_and(|finished| finished >= requested) {
+            let posts = self.gallery.read().await.len();
+            return Ok(RescanResult {
+                posts,
+                rescanned: false,
+            });
         }
+
+        let started = Instant::now();
+        let posts = self.gallery.write().await.refresh().await?.len();
+        *last_rescan = Some(Instant::now());
+
+        info!(
+            "Rescanned {} posts in {:.2}s",
+            posts,
+            started.elapsed().as_secs_f64()
+        );
+
+        Ok(RescanResult {
+            posts,
+            rescanned: true,
+        })
     }
 }
 
 #[get("/")]
 /// the main gallery page
-pub async fn index_handler() -> RawHtml<String> {
+pub async fn index_handler(_auth: Authorized) -> RawHtml<String> {
     RawHtml(HTML_TEMPLATE.to_string())
 }
 
 #[get("/styles.css")]
 /// serve the dynamically themed CSS
-pub async fn css_handler() -> RawCss<String> {
+pub async fn css_handler(_auth: Authorized) -> RawCss<String> {
     let configured_theme = getopt!(gallery.theme);
     let registry = ThemeRegistry::new();
     let css_vars = registry
@@ -65,10 +159,71 @@ pub async fn css_handler() -> RawCss<String> {
 
 #[get("/script.js")]
 /// serve the JS
-pub async fn js_handler() -> RawJavaScript<String> {
+pub async fn js_handler(_auth: Authorized) -> RawJavaScript<String> {
     RawJavaScript(JS.to_string())
 }
 
+#[get("/files/<path..>")]
+/// serve a downloaded file
+///
+/// # Arguments
+///
+/// * `path` - the path of the file, relative to the media directory
+/// * `state` - the current state of the app
+pub async fn files_handler(
+    _auth: Authorized,
+    path: PathBuf,
+    state: &State<Arc<AppState>>,
+) -> Option<NamedFile> {
+    NamedFile::open(state.directory.join(path)).await.ok()
+}
+
+#[get("/thumbnails/<path..>?<size>")]
+/// serve a downsized copy of a downloaded image, from the thumbnail cache
+///
+/// the thumbnail is made (and cached) the first time it's asked for, and again whenever the
+/// image changes
+///
+/// # Arguments
+///
+/// * `path` - the path of the image, relative to the media directory
+/// * `size` - the most pixels the thumbnail has on either side
+/// * `state` - the current state of the app
+pub async fn thumbnail_handler(
+    _auth: Authorized,
+    path: PathBuf,
+    size: Option<u32>,
+    state: &State<Arc<AppState>>,
+) -> Option<NamedFile> {
+    let size = size
+        .unwrap_or(DEFAULT_THUMBNAIL_SIZE)
+        .clamp(THUMBNAIL_SIZE_RANGE.0, THUMBNAIL_SIZE_RANGE.1);
+    let md5 = state
+        .gallery
+        .read()
+        .await
+        .get_item(&format!("/files/{}", path.display()))
+        .and_then(|item| item.metadata())
+        .map(|meta| meta.md5.clone())
+        .filter(|md5| !md5.is_empty());
+
+    let source = state.directory.join(path);
+    let thumbnails = state.thumbnails.clone();
+    let thumb = tokio::task::spawn_blocking(move || {
+        thumbnails.get_or_create(&source, md5.as_deref(), size)
+    })
+    .await
+    .ok()?;
+
+    match thumb {
+        Ok(thumb) => NamedFile::open(thumb).await.ok(),
+        Err(e) => {
+            warn!("Couldn't make a thumbnail: {}", e);
+            None
+        }
+    }
+}
+
 #[get("/api/media?<filter..>")]
 /// handler for listing media
 ///
@@ -77,6 +232,7 @@ pub async fn js_handler() -> RawJavaScript<String> {
 /// * `state` - the current state of the app
 /// * `filter` - any active filters
 pub async fn list_media_handler(
+    _auth: Authorized,
     state: &State<Arc<AppState>>,
     filter: Option<MediaFilter>,
 ) -> Result<Json<Vec<MediaItem>>, Status> {
@@ -91,9 +247,124 @@ pub async fn list_media_handler(
     Ok(Json(items))
 }
 
+#[get("/post/<key>")]
+/// handler for getting the full stored metadata of a single post
+///
+/// the post is looked up among the loaded items, then its metadata is read in full. 404s if
+/// no loaded item has metadata for it
+///
+/// # Arguments
+///
+/// * `key` - the post id, or the md5 of the file
+/// * `state` - the current state of the app
+pub async fn post_handler(
+    _auth: Authorized,
+    key: &str,
+    state: &State<Arc<AppState>>,
+) -> Result<Json<E6Post>, Status> {
+    let path = state
+        .gallery
+        .read()
+        .await
+        .find_post(key)
+        .and_then(|item| item.path().strip_prefix("/files/"))
+        .map(|path| state.directory.join(path))
+        .ok_or(Status::NotFound)?;
+
+    let backend = state.metadata_backend;
+    let post = tokio::task::spawn_blocking(move || read_metadata(&path, backend))
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    match post {
+        Ok(post) => Ok(Json(post)),
+        Err(e) => {
+            warn!("Couldn't read the metadata of post {}: {}", key, e);
+            Err(Status::NotFound)
+        }
+    }
+}
+
+#[post("/api/rescan")]
+/// handler for rescanning the media directory
+///
+/// # Arguments
+///
+/// * `state` - the current state of the app
+pub async fn rescan_handler(
+    _auth: Authorized,
+    state: &State<Arc<AppState>>,
+) -> Result<Json<RescanResult>, Status> {
+    match state.rescan().await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => {
+            warn!("Rescan failed: {}", e);
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+#[get("/api/events")]
+/// websocket that pushes a [`MediaEvent`] whenever the media directory changes
+///
+/// only mounted when `gallery.watch` is on
+///
+/// # Arguments
+///
+/// * `ws` - the websocket upgrade
+/// * `state` - the current state of the app
+pub fn events_handler(
+    _auth: Authorized,
+    ws: WebSocket,
+    state: &State<Arc<AppState>>,
+) -> Channel<'static> {
+    let mut events = state.events.subscribe();
+
+    ws.channel(move |mut stream| {
+        Box::pin(async move {
+            loop {
+                tokio::select! {
+                    event = events.recv() => match event {
+                        Ok(event) => {
+                            let Ok(json) = serde_json::to_string(&event) else {
+                                continue;
+                            };
+                            stream.send(Message::Text(json)).await?;
+                        }
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    },
+                    message = stream.next() => match message {
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        Some(Ok(_)) => continue,
+                    },
+                }
+            }
+
+            Ok(())
+        })
+    })
+}
+
+#[get("/api/config")]
+/// handler for getting the settings the gallery page starts with
+///
+/// # Arguments
+///
+/// * `settings` - the gallery's settings
+pub async fn config_handler(
+    _auth: Authorized,
+    settings: &State<GallerySettings>,
+) -> Json<GallerySettings> {
+    Json(settings.inner().clone())
+}
+
 #[get("/api/stats")]
 /// handler for getting current filter stats
-pub async fn stats_handler(state: &State<Arc<AppState>>) -> Result<Json<FilterStats>, Status> {
+pub async fn stats_handler(
+    _auth: Authorized,
+    state: &State<Arc<AppState>>,
+) -> Result<Json<FilterStats>, Status> {
     let gallery = state.gallery.read().await;
     let stats = gallery.get_filter_stats();
     Ok(Json(stats))
@@ -1,16 +1,20 @@
 //! the actual media server
 use {
     crate::serve::{
+        auth::unauthorized_catcher,
         cfg::ServerConfig,
         media::gallery::MediaGallery,
         routes::{
-            AppState, css_handler, index_handler, js_handler, list_media_handler, stats_handler,
+            AppState, GallerySettings, config_handler, css_handler, events_handler, files_handler,
+            index_handler, js_handler, list_media_handler, post_handler, rescan_handler,
+            stats_handler, thumbnail_handler,
         },
+        watch::watch_media,
     },
     color_eyre::eyre::Result,
-    rocket::{Config, figment::Figment, fs::FileServer, routes},
-    std::sync::Arc,
-    tracing::info,
+    rocket::{Config, catchers, figment::Figment, routes},
+    std::{sync::Arc, time::Instant},
+    tracing::{info, warn},
 };
 
 #[derive(Clone)]
@@ -28,11 +32,36 @@ impl MediaServer {
 
     /// serve the media server
     pub async fn serve(self) -> Result<()> {
-        let gallery = MediaGallery::new(
+        let mut gallery = MediaGallery::new(
             self.config.media_directory.clone(),
             self.config.enable_metadata_filtering,
+            self.config.num_threads,
+            self.config.metadata_backend,
         );
-        let state = Arc::new(AppState::new(gallery));
+
+        info!(
+            "Indexing downloads with up to {} threads",
+            self.config.num_threads
+        );
+        let started = Instant::now();
+        let indexed = gallery.get_items().await?.len();
+        info!(
+            "Indexed {} posts in {:.2}s",
+            indexed,
+            started.elapsed().as_secs_f64()
+        );
+
+        let state = Arc::new(AppState::new(gallery, self.config.metadata_backend));
+
+        let _watcher = if self.config.watch {
+            info!("Watching for new downloads");
+            Some(watch_media(
+                Arc::clone(&state),
+                self.config.media_directory.clone(),
+            )?)
+        } else {
+            None
+        };
 
         info!("e6srv running at http://{}", self.config.bind_address);
         info!(
@@ -47,6 +76,31 @@ impl MediaServer {
                 "disabled"
             }
         );
+        if let Some(rating) = &self.config.default_rating {
+            info!("Default rating: {}", rating);
+        }
+        info!(
+            "Authentication: {}",
+            if self.config.auth.is_enabled() {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+
+        if !self.config.bind_address.ip().is_loopback() {
+            if self.config.auth.is_enabled() {
+                warn!(
+                    "Binding to {}, the gallery is reachable from other devices on your network",
+                    self.config.bind_address.ip()
+                );
+            } else {
+                warn!(
+                    "Binding to {} WITHOUT authentication, anyone on your network can browse your downloads (set gallery.username/password or gallery.token)",
+                    self.config.bind_address.ip()
+                );
+            }
+        }
 
         let figment = Figment::from(Config::default())
             .merge(("address", self.config.bind_address.ip()))
@@ -58,12 +112,26 @@ impl MediaServer {
                 routes![
                     index_handler,
                     list_media_handler,
+                    post_handler,
+                    rescan_handler,
                     stats_handler,
+                    config_handler,
                     css_handler,
-                    js_handler
+                    js_handler,
+                    files_handler,
+                    thumbnail_handler
                 ],
             )
-            .mount("/files", FileServer::from(&self.config.media_directory));
+            .register("/", catchers![unauthorized_catcher])
+            .manage(self.config.auth.clone())
+            .manage(GallerySettings {
+                metadata_filtering: self.config.enable_metadata_filtering,
+                default_rating: self.config.default_rating.clone(),
+            });
+
+        if self.config.watch {
+            rocket = rocket.mount("/", routes![events_handler]);
+        }
 
         rocket = rocket.manage(state);
         rocket.launch().await?;
@@ -0,0 +1,132 @@
+//! live reloading of the gallery when downloads change
+//!
+//! a filesystem watcher rescans the gallery whenever media files are added or removed and
+//! broadcasts a [`MediaEvent`] to every browser subscribed to `/api/events`
+use {
+    crate::serve::{media::types::MediaType, routes::AppState},
+    color_eyre::eyre::{Context, Result},
+    notify_debouncer_mini::{
+        DebounceEventResult, Debouncer, new_debouncer,
+        notify::{RecommendedWatcher, RecursiveMode},
+    },
+    serde::Serialize,
+    std::{
+        collections::BTreeSet,
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::Duration,
+    },
+    tokio::sync::mpsc,
+    tracing::{debug, warn},
+};
+
+/// how long a file has to stay quiet before its change is reported
+const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// how long to keep collecting changes after the first one before rescanning
+///
+/// a big download writes files over several seconds, this turns that into a single rescan
+const SETTLE: Duration = Duration::from_secs(2);
+
+/// a change to the media directory, sent to the browser as json
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaEvent {
+    /// the urls of media files that were added or changed
+    pub added: Vec<String>,
+    /// the urls of media files that were removed
+    pub removed: Vec<String>,
+    /// how many posts are in the gallery after the change
+    pub posts: usize,
+}
+
+/// get the `/files/...` url of a media file, if it is one
+fn media_url(root: &Path, path: &Path) -> Option<String> {
+    MediaType::from_extension(&path.extension()?.to_string_lossy())?;
+    let rel = path.strip_prefix(root).ok()?;
+
+    Some(format!(
+        "/files/{}",
+        rel.components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    ))
+}
+
+/// start watching the media directory
+///
+/// the returned debouncer stops watching when dropped, so keep it alive for as long as the
+/// server runs
+///
+/// # Arguments
+///
+/// * `state` - the app state to rescan and broadcast events through
+/// * `directory` - the media directory
+///
+/// # Errors
+///
+/// returns an error if the directory can't be watched
+pub fn watch_media(
+    state: Arc<AppState>,
+    directory: PathBuf,
+) -> Result<Debouncer<RecommendedWatcher>> {
+    let directory = std::path::absolute(&directory).unwrap_or(directory);
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+
+    let mut debouncer = new_debouncer(DEBOUNCE, move |res: DebounceEventResult| match res {
+        Ok(events) => {
+            let _ = tx.send(events.into_iter().map(|e| e.path).collect());
+        }
+        Err(e) => warn!("Media watcher error: {}", e),
+    })
+    .context("failed to start the media watcher")?;
+
+    debouncer
+        .watcher()
+        .watch(&directory, RecursiveMode::Recursive)
+        .with_context(|| format!("failed to watch '{}'", directory.display()))?;
+
+    tokio::spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut changed: BTreeSet<PathBuf> = first.into_iter().collect();
+
+            tokio::time::sleep(SETTLE).await;
+            while let Ok(more) = rx.try_recv() {
+                changed.extend(more);
+            }
+
+            let (added, removed): (Vec<_>, Vec<_>) = changed
+                .iter()
+                .filter_map(|path| Some((media_url(&directory, path)?, path.exists())))
+                .partition(|(_, exists)| *exists);
+
+            if added.is_empty() && removed.is_empty() {
+                continue;
+            }
+
+            let posts = match state.rescan().await {
+                Ok(result) => result.posts,
+                Err(e) => {
+                    warn!("Rescan after a media change failed: {}", e);
+                    continue;
+                }
+            };
+
+            let event = MediaEvent {
+                added: added.into_iter().map(|(url, _)| url).collect(),
+                removed: removed.into_iter().map(|(url, _)| url).collect(),
+                posts,
+            };
+
+            debug!(
+                added = event.added.len(),
+                removed = event.removed.len(),
+                "media changed"
+            );
+
+            let _ = state.events.send(event);
+        }
+    });
+
+    Ok(debouncer)
+}
@@ -340,3 +340,59 @@ impl Autocomplete for PoolAutocompleter {
             .get_completion_impl(input, highlighted_suggestion))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    /// load a tag db with `wolf` as a tag and `wolfie` aliased to it
+    fn alias_db() -> (TempDir, TagDb) {
+        let dir = TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name).display().to_string();
+
+        std::fs::write(
+            path("tags.csv"),
+            "id,name,category,post_count\n1,wolf,5,1000\n2,fox,5,900\n",
+        )
+        .unwrap();
+        std::fs::write(
+            path("aliases.csv"),
+            "id,antecedent_name,consequent_name,created_at,status\n\
+             1,wolfie,wolf,2020-01-01,active\n",
+        )
+        .unwrap();
+        std::fs::write(
+            path("impls.csv"),
+            "id,antecedent_name,consequent_name,created_at,status\n",
+        )
+        .unwrap();
+
+        let db = TagDb::load_from(
+            &path("tags.csv"),
+            &path("aliases.csv"),
+            &path("impls.csv"),
+            0,
+            true,
+            false,
+        )
+        .unwrap();
+
+        (dir, db)
+    }
+
+    #[test]
+    fn test_alias_suggestion_shows_canonical() {
+        let (_dir, db) = alias_db();
+        let completer = GenericAutocompleter::new(Arc::new(db), 10);
+
+        let suggestions: Vec<String> = completer
+            .get_suggestions_impl("solo -wolfi")
+            .iter()
+            .map(|s| strip_ansi(s))
+            .collect();
+        assert_eq!(suggestions, vec!["-wolfie → wolf".to_string()]);
+
+        let completion = completer.get_completion_impl("solo -wolfi", Some(suggestions[0].clone()));
+        assert_eq!(completion.as_deref(), Some("solo -wolf "));
+    }
+}
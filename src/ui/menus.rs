@@ -84,6 +84,22 @@ crate::menu! {
             online: false
         },
 
+        /// Copy the post into the staging folder
+        CopyToStaging => {
+            label: {
+                english => "Copy to staging",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Copy the file and its metadata into the staging folder, leaving the original",
+                japanese => "",
+                spanish => ""
+            },
+
+            online: false
+        },
+
         /// Print the full post metadata to your terminal
         ShowFullMetadata => {
             label: {
@@ -92,7 +108,23 @@ crate::menu! {
                 spanish => ""
             },
             desc: {
-                english => "Print full metadata as formatted JSON",
+                english => "Print the file, score, tags, relationships, and dates as a table",
+                japanese => "",
+                spanish => ""
+            },
+
+            online: false
+        },
+
+        /// Print the post metadata to your terminal as json
+        ShowMetadataJson => {
+            label: {
+                english => "Show the post metadata as JSON",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Print every field as formatted JSON",
                 japanese => "",
                 spanish => ""
             },
@@ -280,6 +312,48 @@ crate::menu! {
             },
             online: true
         },
+        /// Add or remove the post's tags on the blacklist
+        EditBlacklist => {
+            label: {
+                english => "Blacklist tags from this post",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Pick tags from this post to add to (or remove from) your blacklist",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Show the post's parent/child tree
+        Family => {
+            label: {
+                english => "Show relationship tree",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Show every parent and child of the post as a tree",
+                japanese => "",
+                spanish => ""
+            },
+            online: true
+        },
+        /// Flag the post for the moderators
+        Flag => {
+            label: {
+                english => "Flag post",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Flag the post for the moderators (needs login)",
+                japanese => "",
+                spanish => ""
+            },
+            online: true
+        },
         /// Go back
         Back => {
             label: {
@@ -342,6 +416,20 @@ crate::menu! {
             },
             online: true
         },
+        /// Download only the pages missing from the pools folder
+        DownloadNew => {
+            label: {
+                english => "Download new pages to pools dir",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Only download the pages of this pool that aren't in your pools folder yet",
+                japanese => "",
+                spanish => ""
+            },
+            online: true
+        },
         /// Create BBF file from pool
         CreateBBF => {
             label: {
@@ -448,6 +536,20 @@ crate::menu! {
             },
             online: true
         },
+        /// Export the tag lists of all selected posts
+        ExportTags => {
+            label: {
+                english => "Export tag list of all selected posts",
+                japanese => "",
+                spanish => "Exportar la lista de etiquetas de las publicaciones seleccionadas"
+            },
+            desc: {
+                english => "Write an `md5 tag1 tag2 ...` file for re-importing tags elsewhere",
+                japanese => "",
+                spanish => "Escribe un archivo `md5 etiqueta1 etiqueta2 ...` para reimportar las etiquetas en otro sitio"
+            },
+            online: false
+        },
         /// Go back
         Back => {
             label: {
@@ -512,6 +614,34 @@ crate::menu! {
             },
             online: true
         },
+        /// Filter pools by category
+        ByCategory => {
+            label: {
+                english => "Filter by category",
+                japanese => "",
+                spanish => "Filtrar por categoría"
+            },
+            desc: {
+                english => "List pools of a single category, like series or collection",
+                japanese => "",
+                spanish => "Muestra los grupos de una sola categoría, como serie o colección"
+            },
+            online: true
+        },
+        /// Filter pools by how many posts they have
+        ByPostCount => {
+            label: {
+                english => "Filter by post count",
+                japanese => "",
+                spanish => "Filtrar por cantidad de publicaciones"
+            },
+            desc: {
+                english => "List pools with a number of posts within a range",
+                japanese => "",
+                spanish => "Muestra los grupos cuya cantidad de publicaciones está dentro de un rango"
+            },
+            online: true
+        },
         /// Browse the latest pools
         BrowseLatest => {
             label: {
@@ -668,6 +798,20 @@ crate::menu! {
             },
             online: true
         },
+        /// Browse recently viewed posts
+        RecentlyViewed => {
+            label: {
+                english => "View recently viewed posts",
+                japanese => "",
+                spanish => "Ver publicaciones vistas recientemente"
+            },
+            desc: {
+                english => "Revisit posts you've looked at, whether or not you downloaded them",
+                japanese => "",
+                spanish => "Vuelve a las publicaciones que has visto, las hayas descargado o no"
+            },
+            online: true
+        },
         /// Explore downloads
         ExploreDownloads => {
             label: {
@@ -724,6 +868,20 @@ crate::menu! {
             },
             online: false
         },
+        /// Remove empty folders from the download directories
+        CleanEmptyFolders => {
+            label: {
+                english => "Clean empty folders",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Remove folders left empty in your download directories by moves and deletes",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
         /// Update currently downloaded files based on already downloaded artists
         UpdateDownloads => {
             label: {
@@ -738,6 +896,20 @@ crate::menu! {
             },
             online: true
         },
+        /// Count the new posts from already downloaded artists without downloading them
+        WhatsNew => {
+            label: {
+                english => "What's new from your artists",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "See how many new posts the artists you've downloaded have, without downloading them",
+                japanese => "",
+                spanish => ""
+            },
+            online: true
+        },
         /// Manage the configuration file
         ManageConfig => {
             label: {
@@ -788,6 +960,20 @@ crate::menu! {
             },
             online: true
         },
+        /// Search posts by tags picked from each category
+        BrowseTags => {
+            label: {
+                english => "Posts (browse tags by category)",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Pick artist, character, species, and other tags from the tag database to search with",
+                japanese => "",
+                spanish => ""
+            },
+            online: true
+        },
         /// Search pools
         Pools => {
             label: {
@@ -888,6 +1074,20 @@ crate::menu! {
             },
             online: false
         },
+        /// Filter posts with a boolean tag expression
+        TagSearch => {
+            label: {
+                english => "Search by tag expression",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Filter posts with tag logic like `canine -feral` or `cat | dog`",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
         /// Filter posts by rating
         FilterByRating => {
             label: {
@@ -902,6 +1102,34 @@ crate::menu! {
             },
             online: false
         },
+        /// Filter posts by when they were downloaded
+        FilterByDownloadDate => {
+            label: {
+                english => "Filter by download date",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Only show files downloaded within a date range (uses the file's modified time)",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Filter posts by favorite count
+        FilterByFavCount => {
+            label: {
+                english => "Filter by favorite count",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Only show posts with at least a given number of favorites",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
         /// Sort posts
         SortBy => {
             label: {
@@ -958,6 +1186,104 @@ crate::menu! {
             },
             online: false
         },
+        /// Fix the case and spacing of folder and file names
+        NormalizeNames => {
+            label: {
+                english => "Normalize folder names",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Fix names that only differ from the current format by case or spaces vs underscores",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Check the shown posts' files for corruption
+        VerifyIntegrity => {
+            label: {
+                english => "Scan for corruption",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Re-hash files, compare them to their metadata, and re-download bad ones",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Export the tag lists of the shown posts
+        ExportTags => {
+            label: {
+                english => "Export tag list",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Write an `md5 tag1 tag2 ...` file of the currently shown posts",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Export how often each tag shows up in the shown posts
+        ExportTagCounts => {
+            label: {
+                english => "Export tag frequencies",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Write a `tag<TAB>count` TSV (or JSON) of the shown posts' tags for a wordcloud generator",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Save a contact sheet image of the shown posts
+        ContactSheet => {
+            label: {
+                english => "Build a contact sheet",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Save a single PNG with a thumbnail of every shown post laid out in a grid",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Copy the shown posts into the staging folder
+        CopyToStaging => {
+            label: {
+                english => "Copy to staging",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Copy every shown post and its metadata into the staging folder, leaving the originals",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Play the shown videos as a playlist
+        PlayVideos => {
+            label: {
+                english => "Play videos as a playlist",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Open every shown video in your video player (mpv by default) as one playlist",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
         /// Go back
         Back => {
             label: {
@@ -1091,6 +1417,34 @@ crate::menu! {
                 spanish => ""
             },
             online: false
+        },
+        /// Sort by download date (newest)
+        DownloadedNewest => {
+            label: {
+                english => "Sort by download date (newest first)",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Sort posts with the most recently downloaded files first",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
+        /// Sort by download date (oldest)
+        DownloadedOldest => {
+            label: {
+                english => "Sort by download date (oldest first)",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Sort posts with the earliest downloaded files first",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
         }
     }
 }
@@ -1157,6 +1511,21 @@ crate::menu! {
             },
             online: false
         },
+
+        /// posts with any other rating
+        Other => {
+            label: {
+                english => "Other ratings",
+                japanese => "",
+                spanish => ""
+            },
+            desc: {
+                english => "Pick from the ratings of loaded posts that aren't safe, questionable, or explicit",
+                japanese => "",
+                spanish => ""
+            },
+            online: false
+        },
     }
 }
 
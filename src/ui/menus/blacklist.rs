@@ -1,15 +1,17 @@
 //! blacklist manager ui
 use {
     crate::{
-        config::blacklist::{add_to_blacklist, clear_blacklist, remove_from_blacklist},
+        config::blacklist::{
+            BlacklistSite, add_to_blacklist, clear_blacklist, remove_from_blacklist,
+        },
         error::{Report, Result},
         getopt,
+        models::E6Post,
         ui::{E6Ui, autocomplete::TagAutocompleter, menus::BlacklistManager},
     },
     bearask::{AskOption, Confirm, MultiSelect, Select, TextInput},
     color_eyre::eyre::Context,
     hashbrown::HashSet,
-    std::sync::Arc,
 };
 
 /// functions for blacklist management
@@ -58,6 +60,16 @@ pub trait BlacklistMenu {
 
     /// import tags from a search to the blacklist
     fn import_tags_to_blacklist(&self) -> impl Future<Output = Result<()>>;
+
+    /// add or remove a post's tags on the blacklist
+    ///
+    /// lists every tag on the post. selected tags are added to the blacklist, or removed if
+    /// they're already on it
+    ///
+    /// # Arguments
+    ///
+    /// * `post` - the post to pick tags from
+    fn edit_blacklist_from_post(&self, post: &E6Post) -> Result<()>;
 }
 
 impl BlacklistMenu for E6Ui {
@@ -77,6 +89,22 @@ impl BlacklistMenu for E6Ui {
         for (i, tag) in blacklist.iter().enumerate() {
             println!("  {}. {}", i + 1, tag);
         }
+
+        let (site, additions) = match BlacklistSite::active() {
+            BlacklistSite::E621 => ("e621", getopt!(search.blacklist_e621)),
+            BlacklistSite::E926 => ("e926", getopt!(search.blacklist_e926)),
+        };
+        if !additions.is_empty() {
+            println!(
+                "\nAlso blacklisted on {} ({} total):",
+                site,
+                additions.len()
+            );
+            for (i, tag) in additions.iter().enumerate() {
+                println!("  {}. {}", i + 1, tag);
+            }
+        }
+
         println!(
             "\nNote: Posts with these tags will be filtered out unless explicitly searched for."
         );
@@ -142,8 +170,8 @@ impl BlacklistMenu for E6Ui {
 
     /// add a tag to the blacklist
     async fn add_tag_to_blacklist(&self) -> Result<()> {
-        let tag_db = Arc::clone(&self.tag_db);
-        let completer = TagAutocompleter::new(tag_db);
+        let tag_db = self.tag_db();
+        let completer = TagAutocompleter::new(tag_db.clone());
 
         let tag = miette::Context::wrap_err(
             TextInput::new("Enter a tag to add to the blacklist:")
@@ -167,7 +195,7 @@ impl BlacklistMenu for E6Ui {
             return Ok(());
         }
 
-        if !self.tag_db.exists(&tag) && !self.prompt_add_unknown_tag(&tag).await? {
+        if !tag_db.exists(&tag) && !self.prompt_add_unknown_tag(&tag).await? {
             return Ok(());
         }
 
@@ -194,7 +222,7 @@ impl BlacklistMenu for E6Ui {
         }
 
         let suggestions = self
-            .tag_db
+            .tag_db()
             .search(tag, 5)
             .iter()
             .map(|s| AskOption::with_name(s.clone(), s.clone()))
@@ -441,4 +469,97 @@ impl BlacklistMenu for E6Ui {
 
         Ok(())
     }
+
+    /// add or remove a post's tags on the blacklist
+    fn edit_blacklist_from_post(&self, post: &E6Post) -> Result<()> {
+        let blacklist = getopt!(search.blacklist);
+
+        let mut tags: Vec<String> = [
+            &post.tags.artist,
+            &post.tags.copyright,
+            &post.tags.character,
+            &post.tags.species,
+            &post.tags.general,
+            &post.tags.lore,
+            &post.tags.meta,
+        ]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+        tags.sort();
+        tags.dedup();
+
+        if tags.is_empty() {
+            println!("This post has no tags.");
+            return Ok(());
+        }
+
+        let options = tags
+            .iter()
+            .map(|tag| {
+                let name = if blacklist.contains(tag) {
+                    format!("{} (blacklisted, select to remove)", tag)
+                } else {
+                    tag.clone()
+                };
+
+                AskOption::with_name(name, tag.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let selected_tags = miette::Context::wrap_err(
+            MultiSelect::new(format!(
+                "Select tags to blacklist ({} on this post):",
+                tags.len()
+            ))
+            .with_options(options)
+            .with_help_message("Space to select/deselect, Enter to confirm, Esc to cancel")
+            .ask(),
+            "Failed to display tag selection",
+        )?;
+
+        if selected_tags.is_empty() {
+            println!("No tags selected.");
+            return Ok(());
+        }
+
+        let (to_remove, to_add): (Vec<String>, Vec<String>) = selected_tags
+            .into_iter()
+            .map(|tag| tag.value)
+            .partition(|tag| blacklist.contains(tag));
+
+        let confirm = miette::Context::wrap_err(
+            Confirm::new(format!(
+                "Add {} and remove {} tags on the blacklist?",
+                to_add.len(),
+                to_remove.len()
+            ))
+            .ask(),
+            "Failed to get user confirmation",
+        )?;
+
+        if !confirm {
+            return Ok(());
+        }
+
+        for tag in &to_add {
+            add_to_blacklist(tag.clone())
+                .wrap_err_with(|| format!("Failed to add '{}' to blacklist", tag))?;
+        }
+
+        for tag in &to_remove {
+            remove_from_blacklist(tag)
+                .wrap_err_with(|| format!("Failed to remove '{}' from blacklist", tag))?;
+        }
+
+        println!(
+            "Blacklist updated (added {}, removed {}), now {} tags.",
+            to_add.len(),
+            to_remove.len(),
+            getopt!(search.blacklist).len()
+        );
+
+        Ok(())
+    }
 }
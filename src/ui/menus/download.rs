@@ -8,25 +8,37 @@
 use {
     crate::{
         bail,
-        config::format::FormatTemplate,
+        client::{is_retryable, retry_delay},
+        config::{
+            format::FormatTemplate,
+            options::{DownloadRoute, MetadataBackend, OnConflict, OnDnp},
+        },
+        data::{
+            manifest::hash_file,
+            metadata,
+            queue::{DownloadQueue, QueuedDownload},
+        },
         error::*,
         getopt,
-        models::E6Post,
-        ui::progress::ProgressManager,
-        utils::{self, MutableStatic as MutStatic},
+        models::{E6Post, Rating},
+        ui::{menus::explore::expr::TagExpr, progress::ProgressManager},
+        utils::MutableStatic as MutStatic,
     },
+    bearask::Confirm,
     color_eyre::eyre::Context,
     futures::StreamExt,
-    hashbrown::HashMap,
+    hashbrown::{HashMap, HashSet},
     indicatif::ProgressBar,
     miette::Context as _,
+    owo_colors::OwoColorize,
     reqwest::Client,
     std::{
         path::{Path, PathBuf},
-        sync::Arc,
+        sync::{Arc, LazyLock, Mutex},
+        time::Duration,
     },
     tokio::{fs::File, io::AsyncWriteExt},
-    tracing::warn,
+    tracing::{debug, warn},
     url::Url,
 };
 
@@ -47,6 +59,98 @@ pub struct DownloadProgress {
 pub static IN_PROGRESS_DOWNLOADS: MutStatic<Vec<DownloadProgress>> = MutStatic::new(Vec::new());
 /// atomic counter for unique dl ids
 static DOWNLOAD_ID_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+/// downloads that haven't finished yet, shared by every downloader and persisted to disk
+static DOWNLOAD_QUEUE: LazyLock<Mutex<DownloadQueue>> =
+    LazyLock::new(|| Mutex::new(DownloadQueue::load().unwrap_or_default()));
+/// paths claimed by downloads in this process that haven't finished yet, see [`try_claim`]
+static ACTIVE_CLAIMS: LazyLock<Mutex<HashSet<PathBuf>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// the least time between two saves of the download queue while downloads finish
+const QUEUE_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// update the persistent download queue and write it back to disk
+///
+/// failing to save the queue is logged rather than failing the download
+fn update_queue(f: impl FnOnce(&mut DownloadQueue)) {
+    update_queue_with(f, DownloadQueue::save);
+}
+
+/// update the persistent download queue, and save it with `save`
+///
+/// failing to save the queue is logged rather than failing the download
+fn update_queue_with(
+    f: impl FnOnce(&mut DownloadQueue),
+    save: impl FnOnce(&mut DownloadQueue) -> color_eyre::Result<()>,
+) {
+    let Ok(mut queue) = DOWNLOAD_QUEUE.lock() else {
+        warn!("download queue lock poisoned, not updating it");
+        return;
+    };
+
+    f(&mut queue);
+
+    if let Err(e) = save(&mut queue) {
+        warn!("failed to save download queue: {}", e);
+    }
+}
+
+/// get the downloads left in the queue from a previous run
+pub fn queued_downloads() -> Vec<QueuedDownload> {
+    DOWNLOAD_QUEUE
+        .lock()
+        .map(|queue| queue.entries().to_vec())
+        .unwrap_or_default()
+}
+
+/// remove a single post from the download queue
+///
+/// # Arguments
+///
+/// * `id` - the id of the post to remove
+pub fn dequeue_download(id: i64) {
+    update_queue(|queue| queue.dequeue(id));
+}
+
+/// remove posts from the download queue, saving it once
+///
+/// # Arguments
+///
+/// * `ids` - the ids of the posts to remove
+pub fn dequeue_downloads(ids: &[i64]) {
+    if ids.is_empty() {
+        return;
+    }
+
+    update_queue(|queue| {
+        for id in ids {
+            queue.dequeue(*id);
+        }
+    });
+}
+
+/// remove a finished download from the queue without saving it more than once per
+/// [`QUEUE_SAVE_INTERVAL`] (see [`flush_download_queue`])
+///
+/// # Arguments
+///
+/// * `id` - the id of the post to remove
+fn dequeue_finished(id: i64) {
+    update_queue_with(
+        |queue| queue.dequeue(id),
+        |queue| queue.save_debounced(QUEUE_SAVE_INTERVAL),
+    );
+}
+
+/// save any download queue changes [`dequeue_finished`] left unsaved
+fn flush_download_queue() {
+    update_queue_with(|_| {}, DownloadQueue::flush);
+}
+
+/// forget every queued download
+pub fn clear_queued_downloads() {
+    update_queue(DownloadQueue::clear);
+}
 
 #[ctor::dtor]
 unsafe fn terminate() {
@@ -128,6 +232,7 @@ impl DownloadGuard {
 impl Drop for DownloadGuard {
     fn drop(&mut self) {
         self.cleanup_if_failed();
+        release_claim(&self.path);
     }
 }
 
@@ -138,9 +243,12 @@ impl Drop for DownloadGuard {
 pub struct PostDownloader {
     /// the http client
     ///
-    /// used for making requests to the e6 api
+    /// used for downloading files, see [`download_client`]
     pub client: Client,
 
+    /// how many times to retry a file download that fails
+    pub max_retries: u32,
+
     /// the path to download to
     ///
     /// specifies the base directory where downloaded files are stored
@@ -155,6 +263,40 @@ pub struct PostDownloader {
     ///
     /// manages and displays progress bars for download operations
     pub progress_manager: Arc<ProgressManager>,
+
+    /// rules that send matching posts somewhere other than `download_dir`
+    ///
+    /// see [`route_dir`]
+    pub routes: Vec<DownloadRoute>,
+
+    /// whether to ask before downloading a big batch
+    ///
+    /// see [`exceeds_download_guard`]
+    pub confirm_large: bool,
+
+    /// whether someone is at the terminal to answer prompts
+    ///
+    /// when off, nothing is asked, and questions fall back to the safe answer
+    pub interactive: bool,
+}
+
+/// build the http client used for downloading files
+///
+/// it has its own timeout (`download.timeout`) since a big video can take far longer to come
+/// down than any api request
+pub fn download_client() -> Client {
+    Client::builder()
+        .user_agent(getopt!(http.user_agent))
+        .timeout(Duration::from_secs(getopt!(download.timeout)))
+        .connect_timeout(Duration::from_secs(getopt!(http.connect_timeout)))
+        .build()
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to build the download client, using the defaults: {}",
+                e
+            );
+            Client::new()
+        })
 }
 
 /// sanitize a value for use in filenames (before template substitution)
@@ -244,6 +386,498 @@ pub fn sanitize_path<S: AsRef<str>>(
     }
 }
 
+/// check whether a post matches a download route
+fn route_matches(route: &DownloadRoute, post: &E6Post) -> bool {
+    let rating_ok = route
+        .rating
+        .as_deref()
+        .is_none_or(|r| Rating::from(r) == post.content_rating());
+    let tags_ok = route
+        .tags
+        .as_deref()
+        .is_none_or(|t| TagExpr::parse(t).is_ok_and(|expr| expr.matches(post)));
+
+    rating_ok && tags_ok
+}
+
+/// pick the directory a post is downloaded to from `download.routes`
+///
+/// the first route that matches wins
+#[bearive::argdoc]
+#[returns = "the route's directory, or `None` if no route matches"]
+pub fn route_dir(
+    /// the routes to check, in order
+    routes: &[DownloadRoute],
+    /// the post being downloaded
+    post: &E6Post,
+) -> Option<PathBuf> {
+    routes
+        .iter()
+        .find(|route| route_matches(route, post))
+        .map(|route| PathBuf::from(&route.dir))
+}
+
+/// get every directory downloads can end up in
+///
+/// `download.path` followed by the directory of each `download.routes` entry, without
+/// duplicates or directories inside another one (see [`outermost_dirs`])
+pub fn download_dirs() -> Vec<PathBuf> {
+    let dirs = std::iter::once(PathBuf::from(getopt!(download.path)))
+        .chain(
+            getopt!(download.routes)
+                .into_iter()
+                .map(|route| PathBuf::from(route.dir)),
+        )
+        .collect();
+
+    outermost_dirs(dirs)
+}
+
+/// leave out directories that are the same as, or inside, another one in the list
+///
+/// scanning a directory already scans everything inside it, so nested ones would be scanned
+/// twice. paths are compared after resolving them, so `./downloads` and `downloads` are the
+/// same. the order is kept, and the first of two identical directories is the one kept
+#[bearive::argdoc]
+pub fn outermost_dirs(
+    /// the directories
+    dirs: Vec<PathBuf>,
+) -> Vec<PathBuf> {
+    let resolved: Vec<PathBuf> = dirs
+        .iter()
+        .map(|dir| {
+            std::fs::canonicalize(dir)
+                .or_else(|_| std::path::absolute(dir))
+                .unwrap_or_else(|_| dir.clone())
+        })
+        .collect();
+
+    dirs.into_iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            !resolved.iter().enumerate().any(|(j, other)| {
+                j != *i && resolved[*i].starts_with(other) && (resolved[*i] != *other || j < *i)
+            })
+        })
+        .map(|(_, dir)| dir)
+        .collect()
+}
+
+/// put a formatted filename inside a folder named after the post's rating
+///
+/// used for `download.separate_by_rating`. a filename that already starts with the rating
+/// folder (e.g. from a format beginning with `$rating/`) is returned unchanged so the folder
+/// isn't doubled up. a `$rating` anywhere else in the format is kept as is, so
+/// `$artist/$rating/$id.$ext` becomes `explicit/artist/explicit/123.png`
+#[bearive::argdoc]
+pub fn prefix_rating_dir(
+    /// the post the filename was formatted for
+    post: &E6Post,
+    /// the formatted filename
+    filename: &str,
+) -> String {
+    let rating = sanitize_value(post.content_rating().name());
+    let first = filename.split(['/', '\\']).next().unwrap_or_default();
+
+    if first == rating {
+        filename.to_string()
+    } else {
+        format!("{}/{}", rating, filename)
+    }
+}
+
+/// split off posts that have no file url to download
+///
+/// deleted posts, and posts hidden from the current user (like DNP or login-only posts), come
+/// back from the api without a file url
+#[bearive::argdoc]
+#[returns = "the downloadable posts, and the ids of the unavailable ones"]
+pub fn split_unavailable(
+    /// the posts to check
+    posts: Vec<E6Post>,
+) -> (Vec<E6Post>, Vec<i64>) {
+    let (available, unavailable): (Vec<_>, Vec<_>) =
+        posts.into_iter().partition(|post| post.file.url.is_some());
+
+    (
+        available,
+        unavailable.into_iter().map(|post| post.id).collect(),
+    )
+}
+
+/// the tags e621 puts on posts by artists on the avoid posting list
+pub const DNP_TAGS: &[&str] = &["avoid_posting", "conditional_dnp"];
+
+/// check whether a post is by an artist on the avoid posting list
+#[bearive::argdoc]
+#[returns = "whether the post has any of the [`DNP_TAGS`]"]
+pub fn is_dnp(
+    /// the post to check
+    post: &E6Post,
+) -> bool {
+    post.tags
+        .artist
+        .iter()
+        .chain(&post.tags.invalid)
+        .any(|tag| DNP_TAGS.contains(&tag.to_lowercase().replace('-', "_").as_str()))
+}
+
+/// find which of a pool's posts are already downloaded to its directory
+///
+/// a file counts as a post if its stored metadata has the post's id, or (for files without
+/// metadata) if its md5 matches the post's
+#[bearive::argdoc]
+#[returns = "the ids of the downloaded posts"]
+pub fn downloaded_pool_posts(
+    /// the directory the pool is downloaded to
+    pool_dir: &Path,
+    /// the posts in the pool
+    posts: &[E6Post],
+    /// the metadata backend to check first
+    backend: MetadataBackend,
+) -> HashSet<i64> {
+    let ids: HashSet<i64> = posts.iter().map(|post| post.id).collect();
+    let md5s: HashMap<String, i64> = posts
+        .iter()
+        .filter(|post| !post.file.md5.is_empty())
+        .map(|post| (post.file.md5.to_lowercase(), post.id))
+        .collect();
+
+    let Ok(entries) = std::fs::read_dir(pool_dir) else {
+        return HashSet::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && !metadata::is_metadata_file(path))
+        .filter_map(|path| match metadata::read_metadata(&path, backend) {
+            Ok(post) => Some(post.id),
+            Err(_) => md5s.get(&hash_file(&path).ok()?).copied(),
+        })
+        .filter(|id| ids.contains(id))
+        .collect()
+}
+
+/// check whether a batch is big enough to ask before downloading it
+#[bearive::argdoc]
+#[returns = "whether the batch is over either limit (a limit of 0 is never exceeded)"]
+pub fn exceeds_download_guard(
+    /// the number of posts in the batch
+    count: usize,
+    /// the estimated size of the batch in bytes
+    bytes: u64,
+    /// the most posts a batch can have without asking
+    max_posts: usize,
+    /// the most megabytes a batch can be without asking
+    max_mb: u64,
+) -> bool {
+    (max_posts > 0 && count > max_posts) || (max_mb > 0 && bytes > max_mb * 1024 * 1024)
+}
+
+/// print how many posts were skipped because they can't be downloaded
+fn report_unavailable(ids: &[i64]) {
+    if ids.is_empty() {
+        return;
+    }
+
+    let shown: Vec<String> = ids.iter().take(20).map(|id| format!("#{}", id)).collect();
+    println!(
+        "{} posts unavailable (deleted or hidden), skipped: {}{}",
+        ids.len(),
+        shown.join(", "),
+        if ids.len() > shown.len() { ", ..." } else { "" }
+    );
+}
+
+/// a download path claimed by a single download task
+///
+/// claiming creates an empty file at the path, so no other task can pick the same name. the
+/// claim is released when the download finishes or fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimedPath {
+    /// where the download will be saved
+    pub path: PathBuf,
+    /// whether the file was created by the claim, rather than being an existing file to
+    /// overwrite
+    pub created: bool,
+}
+
+/// atomically create an empty file at a path if nothing is there yet
+///
+/// an empty file that no download in this process has claimed was left behind by a download
+/// that never finished (e.g. the process was killed), so it's claimed again instead of being
+/// treated as taken
+#[bearive::argdoc]
+#[error = "the file can't be created for a reason other than already existing"]
+#[returns = "whether the file was created"]
+fn try_claim(
+    /// the path to claim
+    path: &Path,
+) -> Result<bool> {
+    let mut claims = ACTIVE_CLAIMS.lock().unwrap_or_else(|e| e.into_inner());
+
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+    {
+        Ok(_) => {
+            claims.insert(path.to_path_buf());
+            Ok(true)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            let unfinished = !claims.contains(path)
+                && std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() == 0);
+
+            if unfinished {
+                debug!(
+                    "'{}' is an unfinished download, claiming it again",
+                    path.display()
+                );
+                claims.insert(path.to_path_buf());
+            }
+
+            Ok(unfinished)
+        }
+        Err(e) => Err(e)
+            .with_context(|| format!("failed to create '{}'", path.display()))
+            .map_err(Report::new),
+    }
+}
+
+/// release a path claimed by [`try_claim`] once its download is done with it
+fn release_claim(path: &Path) {
+    ACTIVE_CLAIMS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(path);
+}
+
+/// find and claim a free `<name>_<n>.<ext>` path next to an existing file
+#[bearive::argdoc]
+#[error = "no free name was found"]
+fn unique_path(
+    /// the path that's already taken
+    path: &Path,
+) -> Result<PathBuf> {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("file");
+    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let parent = path.parent().unwrap_or(Path::new("."));
+
+    for i in 1..10000 {
+        let new_name = if extension.is_empty() {
+            format!("{}_{}", stem, i)
+        } else {
+            format!("{}_{}.{}", stem, i, extension)
+        };
+
+        let new_path = parent.join(new_name);
+        if try_claim(&new_path)? {
+            return Ok(new_path);
+        }
+    }
+
+    bail!("Could not find unique filename for {}", path.display())
+}
+
+/// make a download directory and any of its parents that are missing
+///
+/// with `nomedia`, every directory that gets made also gets an empty
+/// [`metadata::NOMEDIA_FILE`]. directories that already existed are left alone, and failing to
+/// write the file is only logged
+#[bearive::argdoc]
+#[error = "the directory can't be made"]
+fn create_download_dir(
+    /// the directory to make
+    dir: &Path,
+    /// whether to put a `.nomedia` file in every directory that's made
+    nomedia: bool,
+) -> Result<()> {
+    let missing: Vec<&Path> = dir
+        .ancestors()
+        .take_while(|d| !d.as_os_str().is_empty() && !d.exists())
+        .collect();
+
+    std::fs::create_dir_all(dir)
+        .context(format!("Failed to create directory: {}", dir.display()))?;
+
+    if nomedia {
+        for created in missing {
+            let marker = created.join(metadata::NOMEDIA_FILE);
+            if let Err(e) = std::fs::write(&marker, b"") {
+                warn!("Failed to create '{}': {}", marker.display(), e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// claim a path to download to
+///
+/// makes the parent dirs if needed (see [`create_download_dir`]), then claims the path so
+/// concurrent downloads can't pick the same one. if something already exists at the path
+/// (including a file claimed by another download), `on_conflict` decides whether the download is
+/// skipped, overwrites it, or is renamed
+#[bearive::argdoc]
+#[error = "parent directories can't be made"]
+#[error = "the file exists and no free name was found to rename it to"]
+#[returns = "the claimed path, or `None` if the download should be skipped"]
+fn prepare_filepath(
+    /// the path the file will be saved to
+    path: &Path,
+    /// what to do if the path is taken
+    on_conflict: OnConflict,
+    /// whether new parent dirs get a `.nomedia` file
+    nomedia: bool,
+) -> Result<Option<ClaimedPath>> {
+    if let Some(parent) = path.parent()
+        && !parent.exists()
+    {
+        create_download_dir(parent, nomedia)?;
+    }
+
+    if try_claim(path)? {
+        return Ok(Some(ClaimedPath {
+            path: path.to_path_buf(),
+            created: true,
+        }));
+    }
+
+    match on_conflict {
+        OnConflict::Skip => {
+            debug!("'{}' already exists, skipping", path.display());
+            Ok(None)
+        }
+        OnConflict::Overwrite => Ok(Some(ClaimedPath {
+            path: path.to_path_buf(),
+            created: false,
+        })),
+        OnConflict::Rename => unique_path(path).map(|path| {
+            Some(ClaimedPath {
+                path,
+                created: true,
+            })
+        }),
+    }
+}
+
+/// get a temp file to stream a download into before moving it into place
+///
+/// every call gets its own name, so downloads overwriting the same file don't share one
+fn temp_path(filepath: &Path) -> PathBuf {
+    let id = DOWNLOAD_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let mut name = filepath.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", id));
+    filepath.with_file_name(name)
+}
+
+/// stream an http response into a temp file, updating a progress bar as it goes
+///
+/// a response that breaks off partway is returned as the inner error, after removing the temp
+/// file, so it can be told apart from failing to write the file
+#[bearive::argdoc]
+#[error = "the temp file can't be made or written"]
+#[returns = "the error the response broke off with, if it did"]
+async fn stream_to_temp(
+    /// the http response to stream from
+    response: reqwest::Response,
+    /// the temp file to write to
+    temp_path: &Path,
+    /// progress bar for download progress tracking
+    pb: &ProgressBar,
+) -> Result<std::result::Result<(), reqwest::Error>> {
+    let mut file = File::create(temp_path)
+        .await
+        .context(format!(
+            "failed to create temp file '{}'",
+            temp_path.display()
+        ))
+        .map_err(Report::new)?;
+
+    let mut stream = response.bytes_stream();
+    let mut downloaded = 0u64;
+    let mut last_update = 0u64;
+    const UPDATE_THRESHOLD: u64 = 8192;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                drop(file);
+                if let Err(e) = tokio::fs::remove_file(temp_path).await {
+                    warn!(
+                        "Failed to remove temp file '{}': {}",
+                        temp_path.display(),
+                        e
+                    );
+                }
+                return Ok(Err(e));
+            }
+        };
+        downloaded += chunk.len() as u64;
+
+        file.write_all(&chunk)
+            .await
+            .with_context(|| format!("Error writing to temp file '{}'", temp_path.display()))?;
+
+        if downloaded - last_update >= UPDATE_THRESHOLD {
+            pb.set_position(downloaded);
+            last_update = downloaded;
+        }
+    }
+
+    file.flush().await.context("Failed to flush file to disk")?;
+    file.sync_all()
+        .await
+        .context("Failed to sync file to disk")?;
+
+    Ok(Ok(()))
+}
+
+/// move a finished download into place, and save its metadata if `download.save_metadata` is on
+///
+/// failing to save the metadata is only logged
+#[bearive::argdoc]
+#[error = "the temp file can't be moved into place"]
+async fn finish_download(
+    /// the temp file the download was streamed into
+    temp_path: &Path,
+    /// the path where the file will be saved
+    filepath: &Path,
+    /// post metadata to save alongside the file
+    post: &E6Post,
+) -> Result<()> {
+    tokio::fs::rename(temp_path, filepath)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to move temp file '{}' to '{}'",
+                temp_path.display(),
+                filepath.display()
+            )
+        })
+        .map_err(Report::new)?;
+
+    if getopt!(download.save_metadata)
+        && let Err(e) = metadata::save_metadata(
+            filepath,
+            post,
+            getopt!(download.metadata_backend),
+            getopt!(download.metadata_pretty),
+        )
+    {
+        warn!(
+            "Failed to write metadata for '{}': {}",
+            filepath.display(),
+            e
+        );
+    }
+
+    Ok(())
+}
+
 impl PostDownloader {
     /// make a new post downloader with a given download dir and output format
     ///
@@ -261,45 +895,210 @@ impl PostDownloader {
         PathBuf: From<T>,
     {
         Self {
-            client: Client::new(),
+            client: download_client(),
+            max_retries: getopt!(download.max_retries),
             download_dir: Some(download_dir.into()),
             output_format,
             progress_manager: Arc::new(ProgressManager::new()),
+            routes: getopt!(download.routes),
+            confirm_large: true,
+            interactive: true,
+        }
+    }
+
+    /// never ask before downloading a big batch
+    pub fn without_confirmation(mut self) -> Self {
+        self.confirm_large = false;
+        self
+    }
+
+    /// never ask anything
+    ///
+    /// for downloads that run without anyone at the terminal, like the daemon. big batches are
+    /// downloaded without asking, and avoid posting list posts are skipped if `download.on_dnp`
+    /// is `ask`
+    pub fn non_interactive(mut self) -> Self {
+        self.interactive = false;
+        self.without_confirmation()
+    }
+
+    /// ask whether to go ahead with a batch that's over `download.confirm_over_posts` posts or
+    /// `download.confirm_over_mb` megabytes
+    #[bearive::argdoc]
+    #[returns = "whether to download the posts (always true for small batches)"]
+    #[error = "user interaction fails"]
+    fn confirm_download(
+        &self,
+        /// the posts about to be downloaded
+        posts: &[E6Post],
+    ) -> Result<bool> {
+        let bytes: u64 = posts.iter().map(|post| post.file.size.max(0) as u64).sum();
+
+        if !self.confirm_large
+            || !self.interactive
+            || !exceeds_download_guard(
+                posts.len(),
+                bytes,
+                getopt!(download.confirm_over_posts),
+                getopt!(download.confirm_over_mb),
+            )
+        {
+            return Ok(true);
         }
+
+        Ok(Confirm::new(format!(
+            "Download {} posts (about {})?",
+            posts.len(),
+            getopt!(ui.progress.format).format_size(bytes).trim()
+        ))
+        .ask()?)
+    }
+
+    /// flag posts by artists on the avoid posting list, and decide which to leave out
+    ///
+    /// `download.on_dnp` decides whether they're skipped, downloaded, or the user is asked.
+    /// downloaders made [`PostDownloader::non_interactive`] skip instead of asking
+    #[bearive::argdoc]
+    #[returns = "the ids of the posts to leave out"]
+    #[error = "user interaction fails"]
+    fn dnp_skipped(
+        &self,
+        /// the posts about to be downloaded
+        posts: &[E6Post],
+    ) -> Result<HashSet<i64>> {
+        let dnp: Vec<&E6Post> = posts.iter().filter(|post| is_dnp(post)).collect();
+        if dnp.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let shown: Vec<String> = dnp
+            .iter()
+            .take(20)
+            .map(|post| format!("#{}", post.id))
+            .collect();
+        println!(
+            "{} posts are by artists on the avoid posting list: {}{}",
+            dnp.len(),
+            shown.join(", "),
+            if dnp.len() > shown.len() { ", ..." } else { "" }
+        );
+
+        let skip = match getopt!(download.on_dnp) {
+            OnDnp::Download => false,
+            OnDnp::Skip => true,
+            OnDnp::Ask if !self.interactive => true,
+            OnDnp::Ask => !Confirm::new("Download them anyway?").ask()?,
+        };
+
+        if !skip {
+            return Ok(HashSet::new());
+        }
+
+        println!("Skipping {} avoid posting list posts", dnp.len());
+        Ok(dnp.iter().map(|post| post.id).collect())
     }
 
     /// download multiple posts
     ///
-    /// concurrently downloads a list of posts with a configurable thread limit. creates a progress
-    /// bar to track overall progress and handles errs for individual downloads without stopping
+    /// resolves where each post will be saved, adds them all to the persistent download queue, then
+    /// downloads them (see [`PostDownloader::download_queued`]). posts whose filename can't be
+    /// formatted are skipped with a warning, posts without a file url are reported as
+    /// unavailable instead of being queued, and posts by artists on the avoid posting list are
+    /// handled per `download.on_dnp`
+    ///
+    /// an interactive downloader may ask about the batch, so gather posts from parallel tasks
+    /// and download them in one call rather than calling this from each task
     #[bearive::argdoc]
     #[error = "the progress bar cannot be created (individual download failures are logged as \
                warnings)"]
+    #[returns = "the ids of the posts whose download failed"]
     pub async fn download_posts(
         self: Arc<Self>,
         /// the posts to download
         posts: Vec<E6Post>,
-    ) -> Result<()> {
+    ) -> Result<Vec<i64>> {
+        let (mut posts, unavailable) = split_unavailable(posts);
+        report_unavailable(&unavailable);
+
+        let dnp = self.dnp_skipped(&posts)?;
+        posts.retain(|post| !dnp.contains(&post.id));
+
+        if !self.confirm_download(&posts)? {
+            println!("Download cancelled");
+            return Ok(Vec::new());
+        }
+
+        let jobs: Vec<(E6Post, PathBuf)> = posts
+            .into_iter()
+            .filter_map(|post| match self.format_filename(&post) {
+                Ok(filename) => {
+                    let path = self.resolve_filepath(&post, &filename);
+                    Some((post, path))
+                }
+                Err(e) => {
+                    warn!("skipping post {}: {}", post.id, e);
+                    None
+                }
+            })
+            .collect();
+
+        update_queue(|queue| {
+            queue.enqueue(jobs.iter().map(|(post, path)| QueuedDownload {
+                id: post.id,
+                path: path.clone(),
+            }))
+        });
+
+        self.download_queued(jobs).await
+    }
+
+    /// download posts to already resolved paths
+    ///
+    /// concurrently downloads a list of posts with a configurable thread limit. creates a progress
+    /// bar to track overall progress and handles errs for individual downloads without stopping.
+    /// each post is removed from the persistent download queue once its download finishes. posts
+    /// without a file url are reported as unavailable and dropped from the queue
+    #[bearive::argdoc]
+    #[error = "the progress bar cannot be created (individual download failures are logged as \
+               warnings)"]
+    #[returns = "the ids of the posts whose download failed"]
+    pub async fn download_queued(
+        self: Arc<Self>,
+        /// the posts to download and where to save each of them
+        jobs: Vec<(E6Post, PathBuf)>,
+    ) -> Result<Vec<i64>> {
+        let (jobs, unavailable): (Vec<_>, Vec<_>) = jobs
+            .into_iter()
+            .partition(|(post, _)| post.file.url.is_some());
+        let unavailable: Vec<i64> = unavailable.into_iter().map(|(post, _)| post.id).collect();
+        if !unavailable.is_empty() {
+            report_unavailable(&unavailable);
+            dequeue_downloads(&unavailable);
+        }
+
         let concurrent_limit = getopt!(download.threads);
 
         let total_pb = self
             .progress_manager
-            .create_count_bar("total", posts.len() as u64, "Total Downloads")
+            .create_count_bar("total", jobs.len() as u64, "Total Downloads", false)
             .await?;
 
         let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrent_limit));
+        let ids: Vec<i64> = jobs.iter().map(|(post, _)| post.id).collect();
 
-        let tasks: Vec<_> = posts
+        let tasks: Vec<_> = jobs
             .into_iter()
             .enumerate()
-            .map(|(i, post)| {
+            .map(|(i, (post, path))| {
                 let downloader = Arc::clone(&self);
                 let semaphore = Arc::clone(&semaphore);
                 let total_pb = total_pb.clone();
 
                 tokio::spawn(async move {
                     let _permit = semaphore.acquire().await.unwrap();
-                    let result = downloader.download_post(post, i).await;
+                    let id = post.id;
+                    let result = downloader.download_post_to(post, path, i).await;
+                    dequeue_finished(id);
                     total_pb.inc(1);
                     result
                 })
@@ -307,18 +1106,30 @@ impl PostDownloader {
             .collect();
 
         let results = futures_util::future::join_all(tasks).await;
+        flush_download_queue();
 
         total_pb.finish_with_message("✓ All downloads completed");
 
-        for (i, result) in results.into_iter().enumerate() {
+        let mut failed = Vec::new();
+        for (id, result) in ids.into_iter().zip(results) {
             match result {
                 Ok(Ok(_)) => {}
-                Ok(Err(_)) => {}
-                Err(e) => warn!("task {} failed: {}", i, e),
+                Ok(Err(e)) => {
+                    failed.push(id);
+                    warn!("download of post {} failed: {}", id, e);
+                }
+                Err(e) => {
+                    failed.push(id);
+                    warn!("task for post {} failed: {}", id, e);
+                }
             }
         }
 
-        Ok(())
+        if !failed.is_empty() {
+            println!("{} downloads failed", failed.len());
+        }
+
+        Ok(failed)
     }
 
     /// download an individual post
@@ -336,6 +1147,32 @@ impl PostDownloader {
         post: E6Post,
         /// the index of this post in a batch download
         index: usize,
+    ) -> Result<()> {
+        let filename = self.format_filename(&post)?;
+        let filepath = self.resolve_filepath(&post, &filename);
+        self.download_post_to(post, filepath, index).await
+    }
+
+    /// download an individual post to a given path
+    ///
+    /// like [`PostDownloader::download_post`], but saves to `filepath` instead of formatting a
+    /// filename from the post
+    ///
+    /// if the file already exists, `download.on_conflict` decides what happens. skipped downloads
+    /// return `Ok`
+    #[bearive::argdoc]
+    #[error = "the post has no downloadable url"]
+    #[error = "the parent dirs can't be made, or the file exists and can't be renamed"]
+    #[error = "the http request fails"]
+    #[error = "the file cannot be saved"]
+    pub async fn download_post_to(
+        &self,
+        /// the post to download
+        post: E6Post,
+        /// where to save the post
+        filepath: PathBuf,
+        /// the index of this post in a batch download
+        index: usize,
     ) -> Result<()> {
         let url = post
             .file
@@ -344,9 +1181,23 @@ impl PostDownloader {
             .context("post has no downloadable file url")
             .map_err(Report::new)?;
 
-        let filename = self.format_filename(&post)?;
-        let filepath = self.get_filepath(&filename)?;
-        let guard = DownloadGuard::new(filepath.clone());
+        let claimed = prepare_filepath(
+            &filepath,
+            getopt!(download.on_conflict),
+            getopt!(download.write_nomedia),
+        )?;
+        let Some(claimed) = claimed else {
+            return Ok(());
+        };
+        let filepath = claimed.path;
+        let filename = filepath
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| post.id.to_string());
+        // a file being overwritten shouldn't be cleaned up if the new download fails
+        let guard = claimed
+            .created
+            .then(|| DownloadGuard::new(filepath.clone()));
 
         let prog_message = match getopt!(ui.progress.message).as_str() {
             "id" => post.id.to_string(),
@@ -360,39 +1211,17 @@ impl PostDownloader {
             .mk_dl_bar(&pb_key, 0, &format!("Downloading {}", prog_message))
             .await?;
 
-        let response = match self.client.get(&url).send().await {
-            Ok(r) => r,
-            Err(e) => {
-                pb.finish_with_message(format!("✗ Failed: {}", filename));
-                self.progress_manager.remove_bar(&pb_key).await;
-                return Err(e.into());
-            }
-        };
-
-        let total_size = response.content_length().unwrap_or(0);
-        pb.set_length(total_size);
-
-        let response = match response.error_for_status() {
-            Ok(r) => r,
-            Err(e) => {
-                pb.finish_with_message(format!("✗ Server error: {}", filename));
-                self.progress_manager.remove_bar(&pb_key).await;
-                return Err(e.into());
-            }
-        };
-
-        match self
-            .save_to_file(response, &filepath, pb.clone(), &post)
-            .await
-        {
+        match self.fetch_to_file(&url, &filepath, &pb, &post).await {
             Ok(_) => {
-                guard.mark_success();
+                if let Some(guard) = &guard {
+                    guard.mark_success();
+                }
                 pb.finish_with_message(format!("✓ Downloaded {}", filename));
                 self.progress_manager.remove_bar(&pb_key).await;
                 Ok(())
             }
             Err(e) => {
-                pb.finish_with_message(format!("✗ Save failed: {}", filename));
+                pb.finish_with_message(format!("✗ Failed: {}", filename));
                 self.progress_manager.remove_bar(&pb_key).await;
                 Err(e)
             }
@@ -402,9 +1231,9 @@ impl PostDownloader {
     /// save a post to a file
     ///
     /// streams the http response to disk while updating a progress bar, optionally saves metadata
-    /// to an ADS (Windows) or JSON file (Unix)
+    /// using the configured metadata backend (see [`metadata::save_metadata`])
     #[bearive::argdoc]
-    #[error = "returns an error if"]
+    #[error = "the response breaks off, or the file can't be written or moved into place"]
     pub async fn save_to_file(
         &self,
         /// the http response to stream from
@@ -416,83 +1245,18 @@ impl PostDownloader {
         /// post metadata to save alongside the file
         post: &E6Post,
     ) -> Result<()> {
-        let temp_path = filepath.with_extension("tmp");
-        let mut file = File::create(&temp_path)
-            .await
-            .context(format!(
-                "failed to create temp file '{}'",
-                temp_path.display()
-            ))
-            .map_err(Report::new)?;
-
-        let mut stream = response.bytes_stream();
-        let mut downloaded = 0u64;
-        let mut last_update = 0u64;
-        const UPDATE_THRESHOLD: u64 = 8192;
-
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.context("Error reading chunk from response")?;
-            downloaded += chunk.len() as u64;
-
-            file.write_all(&chunk)
-                .await
-                .with_context(|| format!("Error writing to temp file '{}'", temp_path.display()))?;
-
-            if downloaded - last_update >= UPDATE_THRESHOLD {
-                pb.set_position(downloaded);
-                last_update = downloaded;
-            }
-        }
-
-        file.flush().await.context("Failed to flush file to disk")?;
-        file.sync_all()
-            .await
-            .context("Failed to sync file to disk")?;
-
-        drop(file);
-
-        tokio::fs::rename(&temp_path, filepath)
-            .await
-            .with_context(|| {
-                format!(
-                    "Failed to move temp file '{}' to '{}'",
-                    temp_path.display(),
-                    filepath.display()
-                )
-            })
-            .map_err(Report::new)?;
-
-        if getopt!(download.save_metadata) {
-            #[cfg(target_os = "windows")]
-            {
-                if let Err(e) = utils::write_to_ads(filepath, "metadata", post) {
-                    warn!(
-                        "Failed to write metadata ADS for '{}': {}",
-                        filepath.display(),
-                        e
-                    );
-                }
-            }
-
-            #[cfg(not(target_os = "windows"))]
-            {
-                if let Err(e) = utils::write_to_json(filepath, post) {
-                    warn!(
-                        "Failed to write metadata JSON for '{}': {}",
-                        filepath.display(),
-                        e
-                    );
-                }
-            }
-        }
-
-        Ok(())
+        let temp_path = temp_path(filepath);
+        stream_to_temp(response, &temp_path, &pb)
+            .await?
+            .context("Error reading chunk from response")?;
+        finish_download(&temp_path, filepath, post).await
     }
 
     /// format the filename based on the post metadata
     ///
     /// applies a format template to post metadata to gen a filename. uses the configured output
-    /// format or a default if non is specified
+    /// format or a default if non is specified. the filename is put in a rating folder when
+    /// `download.separate_by_rating` is on
     #[bearive::argdoc]
     #[error = "the format template can't be parsed"]
     #[error = "the template can't be rendered with the post data"]
@@ -508,41 +1272,54 @@ impl PostDownloader {
             .render_with_arrays(&simple_context, &array_context)
             .context("Failed to render filename template")?;
 
+        if getopt!(download.separate_by_rating) {
+            return Ok(prefix_rating_dir(post, &formatted));
+        }
+
         Ok(formatted)
     }
 
     /// get the path to a file
     ///
-    /// constructs the full path for a file, making parent dirs as needed. checks if the file
-    /// already exists and returns an error if it does
+    /// constructs the full path for a file, making parent dirs as needed, and claims it so no
+    /// other download can use it. if the file already exists, `download.on_conflict` decides
+    /// whether it's skipped, overwritten, or renamed
     #[bearive::argdoc]
     #[error = "parent directories can't be made"]
-    #[error = "the file already exists"]
+    #[error = "the file exists and no free name was found to rename it to"]
+    #[returns = "the claimed path, or `None` if the download should be skipped"]
     pub fn get_filepath(
         &self,
+        /// the post the file is for, used to pick a directory from the routes
+        post: &E6Post,
         /// the filename to construct a path for
         filename: &str,
-    ) -> Result<PathBuf> {
-        let filename = sanitize_path(filename);
-
-        let path = if let Some(ref dir) = self.download_dir {
-            dir.join(filename)
-        } else {
-            filename
-        };
+    ) -> Result<Option<ClaimedPath>> {
+        prepare_filepath(
+            &self.resolve_filepath(post, filename),
+            getopt!(download.on_conflict),
+            getopt!(download.write_nomedia),
+        )
+    }
 
-        if let Some(parent) = path.parent()
-            && !parent.exists()
-        {
-            std::fs::create_dir_all(parent)
-                .context(format!("Failed to create directory: {}", parent.display()))?;
-        }
+    /// resolve the full path a file would be saved to, without touching the filesystem
+    ///
+    /// the file goes in the directory of the first route the post matches, or `download_dir` if
+    /// none do
+    #[bearive::argdoc]
+    pub fn resolve_filepath(
+        &self,
+        /// the post the file is for, used to pick a directory from the routes
+        post: &E6Post,
+        /// the filename to construct a path for
+        filename: &str,
+    ) -> PathBuf {
+        let filename = sanitize_path(filename);
 
-        if path.exists() {
-            bail!("File '{}' already exists", path.display());
+        match route_dir(&self.routes, post).or_else(|| self.download_dir.clone()) {
+            Some(dir) => dir.join(filename),
+            None => filename,
         }
-
-        Ok(path)
     }
 
     /// make a new downloader for a pool, saving to `<download_dir>/<pool_name>`
@@ -564,36 +1341,89 @@ impl PostDownloader {
         let pool_dir = base_download_dir.as_ref().join(sanitized_name);
 
         Self {
-            client: Client::new(),
+            client: download_client(),
+            max_retries: getopt!(download.max_retries),
             download_dir: Some(pool_dir),
             output_format: None,
             progress_manager: Arc::new(ProgressManager::new()),
+            routes: Vec::new(),
+            confirm_large: true,
+            interactive: true,
         }
     }
 
     /// download pool posts with sequential naming based on pool order
     ///
     /// download posts from a pool using seq numbering (001, 002, 003, etc.) to preserve the pool's
-    /// intended order. handles concurrent downloads while maintaining filename order
+    /// intended order. handles concurrent downloads while maintaining filename order. posts
+    /// without a file url are reported as unavailable, and keep their number so the rest of the
+    /// pool isn't renumbered. so do posts left out by `download.on_dnp`. like
+    /// [`PostDownloader::download_posts`], a big batch is confirmed first
     #[bearive::argdoc]
-    #[error = "progress bar creation fails"]
+    #[error = "progress bar creation or user interaction fails"]
     pub async fn download_pool_posts(
         self: Arc<Self>,
         /// the posts to download in seq order
         posts: Vec<E6Post>,
     ) -> Result<()> {
+        self.download_missing_pool_posts(posts, &HashSet::new())
+            .await
+            .map(|_| ())
+    }
+
+    /// download the pages of a pool that aren't downloaded yet
+    ///
+    /// like [`PostDownloader::download_pool_posts`], but posts in `downloaded` are left out.
+    /// they still keep their number, so new pages are named by their place in the whole pool
+    #[bearive::argdoc]
+    #[error = "progress bar creation or user interaction fails"]
+    #[returns = "how many pages were downloaded (0 if the download was cancelled)"]
+    pub async fn download_missing_pool_posts(
+        self: Arc<Self>,
+        /// every post in the pool, in seq order
+        posts: Vec<E6Post>,
+        /// the ids of the posts that are already downloaded
+        downloaded: &HashSet<i64>,
+    ) -> Result<usize> {
         let concurrent_limit = getopt!(download.threads);
         let total = posts.len();
         let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrent_limit));
         let pad_width = total.to_string().len().max(3);
+
+        let missing: Vec<&E6Post> = posts
+            .iter()
+            .filter(|post| !downloaded.contains(&post.id))
+            .collect();
+        let unavailable: Vec<i64> = missing
+            .iter()
+            .filter(|post| post.file.url.is_none())
+            .map(|post| post.id)
+            .collect();
+        report_unavailable(&unavailable);
+
+        let missing: Vec<E6Post> = missing.into_iter().cloned().collect();
+        let dnp = self.dnp_skipped(&missing)?;
+        let queued: Vec<E6Post> = missing
+            .into_iter()
+            .filter(|post| post.file.url.is_some() && !dnp.contains(&post.id))
+            .collect();
+
+        if !self.confirm_download(&queued)? {
+            println!("Download cancelled");
+            return Ok(0);
+        }
+
         let total_pb = self
             .progress_manager
-            .create_count_bar("total", total as u64, "Total Downloads")
+            .create_count_bar("total", queued.len() as u64, "Total Downloads", false)
             .await?;
 
         let tasks: Vec<_> = posts
             .into_iter()
             .enumerate()
+            .filter(|(_, post)| {
+                post.file.url.is_some() && !dnp.contains(&post.id) && !downloaded.contains(&post.id)
+            })
             .map(|(i, post)| {
                 let downloader = Arc::clone(&self);
                 let semaphore = Arc::clone(&semaphore);
@@ -612,18 +1442,30 @@ impl PostDownloader {
             .collect();
 
         let results = futures_util::future::join_all(tasks).await;
+        let attempted = results.len();
 
         total_pb.finish_with_message("✓ All downloads completed");
 
+        let mut failed = 0;
         for (i, result) in results.into_iter().enumerate() {
             match result {
                 Ok(Ok(_)) => {}
-                Ok(Err(_)) => {}
-                Err(e) => warn!("task {} failed: {}", i + 1, e),
+                Ok(Err(e)) => {
+                    failed += 1;
+                    warn!("download {} failed: {}", i + 1, e);
+                }
+                Err(e) => {
+                    failed += 1;
+                    warn!("task {} failed: {}", i + 1, e);
+                }
             }
         }
 
-        Ok(())
+        if failed > 0 {
+            println!("{} downloads failed", failed);
+        }
+
+        Ok(attempted - failed)
     }
 
     /// download a single pool post with sequential naming
@@ -655,53 +1497,95 @@ impl PostDownloader {
             post.file.ext,
             width = pad_width
         );
-        let filepath = self.get_filepath(&filename)?;
+        let Some(claimed) = self.get_filepath(&post, &filename)? else {
+            return Ok(());
+        };
+        let filepath = claimed.path;
 
-        let guard = DownloadGuard::new(filepath.clone());
+        // a file being overwritten shouldn't be cleaned up if the new download fails
+        let guard = claimed
+            .created
+            .then(|| DownloadGuard::new(filepath.clone()));
         let pb_key = format!("download_{}", sequence_num);
         let pb = self
             .progress_manager
             .mk_dl_bar(&pb_key, 0, &format!("Downloading {}", filename))
             .await?;
 
-        let response = match self.client.get(&url).send().await {
-            Ok(r) => r,
-            Err(e) => {
-                pb.finish_with_message(format!("✗ Failed: {}", filename));
-                self.progress_manager.remove_bar(&pb_key).await;
-                return Err(e.into());
-            }
-        };
-
-        let total_size = response.content_length().unwrap_or(0);
-        pb.set_length(total_size);
-
-        let response = match response.error_for_status() {
-            Ok(r) => r,
-            Err(e) => {
-                pb.finish_with_message(format!("✗ Server error: {}", filename));
-                self.progress_manager.remove_bar(&pb_key).await;
-                return Err(e.into());
-            }
-        };
-
-        match self
-            .save_to_file(response, &filepath, pb.clone(), &post)
-            .await
-        {
+        match self.fetch_to_file(&url, &filepath, &pb, &post).await {
             Ok(_) => {
-                guard.mark_success();
+                if let Some(guard) = &guard {
+                    guard.mark_success();
+                }
                 pb.finish_with_message(format!("✓ Downloaded {}", filename));
                 self.progress_manager.remove_bar(&pb_key).await;
                 Ok(())
             }
             Err(e) => {
-                pb.finish_with_message(format!("✗ Save failed: {}", filename));
+                pb.finish_with_message(format!("✗ Failed: {}", filename));
                 self.progress_manager.remove_bar(&pb_key).await;
                 Err(e)
             }
         }
     }
+
+    /// download a file, retrying up to `max_retries` times if it fails
+    ///
+    /// connection failures, server errors, rate limiting, and downloads that break off partway
+    /// are retried, each retry starting the file over. other error statuses (like a 404), and
+    /// failing to write the file, aren't
+    #[bearive::argdoc]
+    #[error = "every attempt fails, or the server answers with an error that isn't worth retrying"]
+    #[error = "the file can't be written or moved into place"]
+    async fn fetch_to_file(
+        &self,
+        /// the url of the file
+        url: &str,
+        /// the path where the file will be saved
+        filepath: &Path,
+        /// progress bar for download progress tracking
+        pb: &ProgressBar,
+        /// post metadata to save alongside the file
+        post: &E6Post,
+    ) -> Result<()> {
+        let mut attempt = 0;
+
+        loop {
+            let error = match self
+                .client
+                .get(url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+            {
+                Ok(response) => {
+                    pb.set_length(response.content_length().unwrap_or(0));
+                    let temp_path = temp_path(filepath);
+                    match stream_to_temp(response, &temp_path, pb).await? {
+                        Ok(()) => return finish_download(&temp_path, filepath, post).await,
+                        Err(e) => e,
+                    }
+                }
+                Err(e) if e.status().is_some_and(|status| !is_retryable(status)) => {
+                    return Err(e.into());
+                }
+                Err(e) => e,
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error.into());
+            }
+
+            attempt += 1;
+            let delay = retry_delay(attempt);
+            warn!(
+                "Downloading post {} failed, retrying in {:?} ({}/{}): {}",
+                post.id, delay, attempt, self.max_retries, error
+            );
+            pb.set_position(0);
+            tokio::time::sleep(delay).await;
+        }
+    }
 }
 
 /// build template context based on post metadata
@@ -852,21 +1736,11 @@ pub fn build_context_from_post(
     );
     insert("size_kb", format!("{:.2}", post.file.size as f64 / 1024.0));
 
-    let rating_full = match post.rating.as_str() {
-        "e" => "explicit",
-        "q" => "questionable",
-        "s" => "safe",
-        _ => "unknown",
-    };
-    insert("rating", rating_full.to_string());
+    let rating = post.content_rating();
+    insert("rating", rating.name().to_string());
     insert(
         "rating_first",
-        post.rating
-            .chars()
-            .next()
-            .unwrap_or('u')
-            .to_lowercase()
-            .to_string(),
+        rating.code().chars().next().unwrap_or('u').to_string(),
     );
 
     insert("score", post.score.total.to_string());
@@ -1081,6 +1955,101 @@ pub fn build_context_from_post(
     (simple, arrays)
 }
 
+/// parse the configured post list format (`ui.post_list_format`)
+///
+/// falls back to the default format if the configured one can't be parsed
+pub fn post_list_template() -> FormatTemplate {
+    let fmt: String = getopt!(ui.post_list_format);
+
+    FormatTemplate::parse(&fmt).unwrap_or_else(|e| {
+        warn!("invalid post list format '{}': {}", fmt, e);
+        let default_fmt = crate::config::instance::defaults()
+            .ui
+            .as_ref()
+            .and_then(|ui| ui.post_list_format.clone())
+            .unwrap_or_default();
+
+        FormatTemplate::parse(&default_fmt).expect("the default post list format is invalid")
+    })
+}
+
+/// format a post as an entry in a post selection list
+///
+/// uses the same placeholders as filenames (see [`build_context_from_post`]). falls back to just
+/// the post id if the template can't be rendered. posts in a parent/child set are marked (see
+/// [`relationship_marker`]) when `ui.relationship_markers` is on
+#[bearive::argdoc]
+pub fn format_post_list_entry(
+    /// the list format, usually from [`post_list_template`]
+    template: &FormatTemplate,
+    /// the post to format
+    post: &E6Post,
+) -> String {
+    let (simple_context, array_context) = build_context_from_post(post);
+
+    let entry = template
+        .render_with_arrays(&simple_context, &array_context)
+        .unwrap_or_else(|_| format!("ID: {}", post.id));
+
+    match relationship_marker(post) {
+        Some(marker) if getopt!(ui.relationship_markers) => format!("{} {}", entry, marker),
+        _ => entry,
+    }
+}
+
+/// get the marker for a post that's part of a parent/child set
+#[bearive::argdoc]
+#[returns = "`[set]` for posts with a parent and children, `[child]` or `[parent]` for posts with \
+             only one of them, or `None` for posts outside a set"]
+pub fn relationship_marker(
+    /// the post to check
+    post: &E6Post,
+) -> Option<&'static str> {
+    let has_parent = post.relationships.parent_id.is_some();
+    let has_children = post.relationships.has_children;
+
+    match (has_parent, has_children) {
+        (true, true) => Some("[set]"),
+        (true, false) => Some("[child]"),
+        (false, true) => Some("[parent]"),
+        (false, false) => None,
+    }
+}
+
+/// the score at which a post's list entry is shown in bold
+const HIGH_SCORE: i64 = 100;
+
+/// format a post as a color coded entry in a post selection list
+///
+/// the entry is colored by rating (safe is green, questionable is yellow, explicit is red), bold
+/// if the post scores at least 100, and dimmed if it scores below 0. the entry is left plain
+/// when `ui.colored_output` is off
+#[bearive::argdoc]
+pub fn format_colored_post_list_entry(
+    /// the list format, usually from [`post_list_template`]
+    template: &FormatTemplate,
+    /// the post to format
+    post: &E6Post,
+) -> String {
+    let entry = format_post_list_entry(template, post);
+    if !getopt!(ui.colored_output) {
+        return entry;
+    }
+
+    let colored = match post.content_rating() {
+        Rating::Safe => entry.green().to_string(),
+        Rating::Questionable => entry.yellow().to_string(),
+        Rating::Explicit => entry.red().to_string(),
+        Rating::Unknown(_) => entry,
+    };
+
+    match post.score.total {
+        score if score >= HIGH_SCORE => colored.bold().to_string(),
+        score if score < 0 => colored.dimmed().to_string(),
+        _ => colored,
+    }
+}
+
 /// sanitize a pool name for use as a directory name
 ///
 /// removes/replaces chars that're problematic in dir names. collapses multiple consecutive
@@ -1129,3 +2098,286 @@ pub fn sanitize_pool_name<S: AsRef<str>>(
 
     result.trim_matches('_').to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_relationship_marker() {
+        let mut post = E6Post::default();
+        assert_eq!(relationship_marker(&post), None);
+
+        post.relationships.has_children = true;
+        assert_eq!(relationship_marker(&post), Some("[parent]"));
+
+        post.relationships.parent_id = Some(1);
+        assert_eq!(relationship_marker(&post), Some("[set]"));
+
+        post.relationships.has_children = false;
+        assert_eq!(relationship_marker(&post), Some("[child]"));
+    }
+
+    #[test]
+    fn test_exceeds_download_guard() {
+        let mb = 1024 * 1024;
+
+        assert!(!exceeds_download_guard(500, 10 * mb, 500, 2048));
+        assert!(exceeds_download_guard(501, 10 * mb, 500, 2048));
+        assert!(exceeds_download_guard(3, 2049 * mb, 500, 2048));
+        assert!(!exceeds_download_guard(5000, 2049 * mb, 0, 0));
+        assert!(exceeds_download_guard(5000, 0, 500, 0));
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let dir = TempDir::new().unwrap();
+        let taken = dir.path().join("123.png");
+        std::fs::write(&taken, b"one").unwrap();
+        std::fs::write(dir.path().join("123_1.png"), b"two").unwrap();
+
+        assert_eq!(unique_path(&taken).unwrap(), dir.path().join("123_2.png"));
+        assert_eq!(
+            unique_path(&dir.path().join("notes")).unwrap(),
+            dir.path().join("notes_1")
+        );
+    }
+
+    #[test]
+    fn test_create_download_dir_nomedia() {
+        let dir = TempDir::new().unwrap();
+        let existing = dir.path().join("existing");
+        std::fs::create_dir(&existing).unwrap();
+
+        let nested = existing.join("artist").join("explicit");
+        create_download_dir(&nested, true).unwrap();
+        assert!(nested.join(metadata::NOMEDIA_FILE).is_file());
+        assert!(
+            existing
+                .join("artist")
+                .join(metadata::NOMEDIA_FILE)
+                .is_file()
+        );
+        assert!(!existing.join(metadata::NOMEDIA_FILE).exists());
+
+        let plain = dir.path().join("plain");
+        create_download_dir(&plain, false).unwrap();
+        assert!(plain.is_dir());
+        assert!(!plain.join(metadata::NOMEDIA_FILE).exists());
+    }
+
+    #[test]
+    fn test_outermost_dirs() {
+        let dir = TempDir::new().unwrap();
+        let downloads = dir.path().join("downloads");
+        let pools = downloads.join("pools");
+        let other = dir.path().join("other");
+        std::fs::create_dir_all(&pools).unwrap();
+        std::fs::create_dir_all(&other).unwrap();
+
+        assert_eq!(
+            outermost_dirs(vec![
+                downloads.clone(),
+                pools.clone(),
+                other.clone(),
+                downloads.join("."),
+                dir.path().join("missing"),
+            ]),
+            vec![downloads.clone(), other.clone(), dir.path().join("missing")]
+        );
+        assert_eq!(
+            outermost_dirs(vec![pools, downloads.clone()]),
+            vec![downloads]
+        );
+    }
+
+    #[test]
+    fn test_concurrent_claims() {
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("nested").join("123.png");
+
+        let claim_all = |on_conflict: OnConflict| -> Vec<Option<ClaimedPath>> {
+            let handles: Vec<_> = (0..32)
+                .map(|_| {
+                    let target = target.clone();
+                    std::thread::spawn(move || {
+                        prepare_filepath(&target, on_conflict, false).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        };
+
+        let renamed = claim_all(OnConflict::Rename);
+        let paths: std::collections::HashSet<_> =
+            renamed.iter().flatten().map(|c| c.path.clone()).collect();
+        assert_eq!(paths.len(), 32);
+        assert!(renamed.iter().flatten().all(|c| c.created));
+        assert!(paths.contains(&target));
+
+        std::fs::remove_dir_all(dir.path().join("nested")).unwrap();
+        let skipped = claim_all(OnConflict::Skip);
+        assert_eq!(skipped.iter().flatten().count(), 1);
+
+        let overwritten = claim_all(OnConflict::Overwrite);
+        assert!(
+            overwritten
+                .iter()
+                .flatten()
+                .all(|c| c.path == target && !c.created)
+        );
+    }
+
+    #[test]
+    fn test_reclaim_unfinished() {
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("123.png");
+
+        std::fs::write(&target, b"").unwrap();
+        let claimed = prepare_filepath(&target, OnConflict::Skip, false).unwrap();
+        assert_eq!(
+            claimed,
+            Some(ClaimedPath {
+                path: target.clone(),
+                created: true,
+            })
+        );
+        assert!(
+            prepare_filepath(&target, OnConflict::Skip, false)
+                .unwrap()
+                .is_none()
+        );
+
+        release_claim(&target);
+        std::fs::write(&target, b"done").unwrap();
+        assert!(
+            prepare_filepath(&target, OnConflict::Skip, false)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_split_unavailable() {
+        let post = |id, url: Option<&str>| {
+            let mut post = E6Post {
+                id,
+                ..Default::default()
+            };
+            post.file.url = url.map(str::to_string);
+            post
+        };
+
+        let (available, unavailable) = split_unavailable(vec![
+            post(1, Some("https://static1.e621.net/1.png")),
+            post(2, None),
+            post(3, Some("https://static1.e621.net/3.png")),
+            post(4, None),
+        ]);
+        assert_eq!(
+            available.iter().map(|p| p.id).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(unavailable, vec![2, 4]);
+    }
+
+    #[test]
+    fn test_is_dnp() {
+        let mut post = E6Post::default();
+        post.tags.artist = vec!["someone".to_string()];
+        assert!(!is_dnp(&post));
+
+        post.tags.artist.push("conditional_dnp".to_string());
+        assert!(is_dnp(&post));
+
+        post.tags.artist = vec!["Avoid-Posting".to_string()];
+        assert!(is_dnp(&post));
+    }
+
+    #[test]
+    fn test_downloaded_pool_posts() {
+        let dir = TempDir::new().unwrap();
+        let page = |id: i64, md5: &str| {
+            let mut post = E6Post {
+                id,
+                ..Default::default()
+            };
+            post.file.md5 = md5.to_string();
+            post
+        };
+        let posts = vec![
+            page(1, ""),
+            page(2, "b8a9f715dbb64fd5c56e7783c6820a61"),
+            page(3, ""),
+        ];
+
+        let first = dir.path().join("001.png");
+        std::fs::write(&first, b"one").unwrap();
+        metadata::save_metadata(&first, &posts[0], MetadataBackend::Sidecar, true).unwrap();
+        std::fs::write(dir.path().join("002.png"), b"two").unwrap();
+
+        let downloaded = downloaded_pool_posts(dir.path(), &posts, MetadataBackend::Sidecar);
+        assert_eq!(downloaded, HashSet::from([1, 2]));
+        assert!(
+            downloaded_pool_posts(
+                &dir.path().join("missing"),
+                &posts,
+                MetadataBackend::Sidecar
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_route_dir() {
+        let routes = vec![
+            DownloadRoute {
+                rating: Some("safe".to_string()),
+                tags: None,
+                dir: "sfw".to_string(),
+            },
+            DownloadRoute {
+                rating: Some("e".to_string()),
+                tags: Some("canine -feral".to_string()),
+                dir: "canines".to_string(),
+            },
+        ];
+
+        let mut post = E6Post {
+            rating: "e".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(route_dir(&routes, &post), None);
+
+        post.tags.species = vec!["canine".to_string()];
+        assert_eq!(route_dir(&routes, &post), Some(PathBuf::from("canines")));
+
+        post.tags.general = vec!["feral".to_string()];
+        assert_eq!(route_dir(&routes, &post), None);
+
+        post.rating = "s".to_string();
+        assert_eq!(route_dir(&routes, &post), Some(PathBuf::from("sfw")));
+    }
+
+    #[test]
+    fn test_prefix_rating_dir() {
+        let post = E6Post {
+            rating: "e".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(prefix_rating_dir(&post, "123.png"), "explicit/123.png");
+        assert_eq!(
+            prefix_rating_dir(&post, "explicit/artist/123.png"),
+            "explicit/artist/123.png"
+        );
+        assert_eq!(
+            prefix_rating_dir(&post, "artist/explicit/123.png"),
+            "explicit/artist/explicit/123.png"
+        );
+        assert_eq!(
+            prefix_rating_dir(&post, "explicit_art/123.png"),
+            "explicit/explicit_art/123.png"
+        );
+    }
+}
@@ -4,36 +4,70 @@
 use {
     crate::{
         bail,
+        config::options::MetadataBackend,
+        data::{
+            manifest::hash_file,
+            metadata,
+            scan::{DirScan, SCAN_CACHE_FILE, ScanCache},
+            thumbs::ThumbnailCache,
+            trash,
+        },
         error::Result,
         getopt,
-        models::E6Post,
+        models::{E6Post, Rating, TagCategory},
+        serve::media::types::MediaType,
         ui::{
             E6Ui,
             menus::{
-                ExplorerFilterBy, ExplorerMenu, ExplorerSortBy, LocalPostInteractionMenu,
-                view::{ViewMenu, print_dl_to_terminal, print_post_to_terminal},
+                ConflictMenu, ExplorerFilterBy, ExplorerMenu, ExplorerSortBy,
+                LocalPostInteractionMenu,
+                download::{download_dirs, format_colored_post_list_entry, post_list_template},
+                explore::{
+                    expr::TagExpr,
+                    sheet::{SheetFile, build_contact_sheet},
+                },
+                reorganize::{FileReorganizer, ReorganizeOptions},
+                view::{
+                    ViewMenu, encode_dl_sixel, format_post_metadata, print_dl_to_terminal,
+                    print_post_to_terminal,
+                },
             },
             progress::ProgressManager,
         },
     },
     bearask::{AskOption, Confirm, Select, TextInput},
+    chrono::{DateTime, Days, Local, NaiveDate},
     color_eyre::eyre::Context,
-    crossterm::event::{Event, KeyCode, KeyEventKind},
+    crossterm::{
+        cursor::{MoveTo, RestorePosition, SavePosition},
+        event::{Event, KeyCode, KeyEventKind},
+        style::Print,
+        terminal::{Clear, ClearType},
+    },
     futures::lock::Mutex,
     hashbrown::HashMap,
+    indicatif::ProgressBar,
     jwalk::WalkDir,
     owo_colors::OwoColorize,
     qrcode::QrCode,
+    rand::seq::SliceRandom,
+    rayon::prelude::*,
     std::{
-        fs::OpenOptions,
-        io::Read,
+        collections::BTreeMap,
         path::{Path, PathBuf},
-        sync::Arc,
-        time::Duration,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::{Duration, Instant},
     },
+    tokio::{sync::Semaphore, task::JoinHandle},
     tracing::warn,
 };
 
+pub mod expr;
+pub mod sheet;
+
 lazy_static::lazy_static! {
     /// the metadata cache for the explorer
     ///
@@ -42,6 +76,11 @@ lazy_static::lazy_static! {
     static ref METADATA_CACHE: Arc<Mutex<HashMap<PathBuf, E6Post>>> = Arc::new(Mutex::new(HashMap::new()));
 }
 
+/// ignore the record of the last scan and read every file's metadata again
+///
+/// set by `--full-rescan`
+pub static FULL_RESCAN: AtomicBool = AtomicBool::new(false);
+
 #[derive(Debug, Clone)]
 /// a local post
 ///
@@ -56,22 +95,35 @@ pub struct LocalPost {
     ///
     /// the location of the downloaded file on the filesystem
     pub file_path: PathBuf,
+
+    /// when the post was downloaded
+    ///
+    /// the modified time of the downloaded file, or `None` if it couldn't be read
+    pub downloaded_at: Option<DateTime<Local>>,
 }
 
 impl LocalPost {
     /// read a file into a [`LocalPost`]
     ///
     /// loads a file from disk and parses its associated metadata into a [`LocalPost`]. metadata is
-    /// read from alternate data streams (Windows) or JSON sidecar files (Unix)
+    /// read from the given backend first, then from any other backend that has it
     #[bearive::argdoc]
     #[error = "the metadata can't be read"]
     #[error = "the metadata is malformed"]
     pub fn from_file(
         /// the path to the downloaded file
         file_path: PathBuf,
+        /// the metadata backend to check first
+        backend: MetadataBackend,
     ) -> Result<Self> {
-        let post = Self::read_metadata(&file_path)?;
-        Ok(Self { post, file_path })
+        let post = metadata::read_metadata(&file_path, backend)?;
+        let downloaded_at = file_mtime(&file_path);
+
+        Ok(Self {
+            post,
+            file_path,
+            downloaded_at,
+        })
     }
 
     /// view a post
@@ -82,52 +134,170 @@ impl LocalPost {
     pub fn view(&self) -> Result<()> {
         crate::ui::menus::view::print_dl_to_terminal(&self.file_path)
     }
+}
 
-    #[cfg(target_os = "windows")]
-    /// read post metadata (windows)
-    ///
-    /// reads metadata from an NTFS alternate data stream named `metadata`. the metadata is stored
-    /// as JSON in the ADS
-    #[bearive::argdoc]
-    #[error = "the ads can't be opened"]
-    #[error = "the ads can't be parsed"]
-    pub fn read_metadata(
-        /// the path to the file containing the alternate data stream
-        file_path: &Path,
-    ) -> Result<E6Post> {
-        let ads_path = format!("{}:metadata", file_path.display());
-        let mut contents = String::new();
-        let mut file = OpenOptions::new()
-            .read(true)
-            .open(&ads_path)
-            .with_context(|| format!("Failed to open ADS metadata for {}", file_path.display()))?;
-
-        file.read_to_string(&mut contents)
-            .with_context(|| format!("Failed to read ADS metadata for {}", file_path.display()))?;
-
-        serde_json::from_str(&contents)
-            .with_context(|| format!("Failed to parse metadata for {}", file_path.display()))
-            .map_err(|e| e.into())
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    /// read post metadata (non-windows)
-    pub fn read_metadata(file_path: &Path) -> Result<E6Post> {
-        let json_path = file_path.with_extension(format!(
-            "{}.json",
-            file_path.extension().and_then(|e| e.to_str()).unwrap_or("")
-        ));
+/// an entry picked in the local post browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BrowseChoice {
+    /// the post at this index of the current page
+    Post(usize),
+    /// the page indicator, which does nothing
+    PageInfo,
+    /// go to the next page
+    NextPage,
+    /// go to the previous page
+    PreviousPage,
+    /// go back to the explorer menu
+    Back,
+}
+
+/// what the slideshow is currently doing, shown in its status line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SlideshowStatus {
+    /// the 1-indexed position of the current slide
+    position: usize,
+    /// the number of slides
+    total: usize,
+    /// time until the next auto-advance
+    remaining: Duration,
+    /// whether auto-advance is paused
+    paused: bool,
+    /// whether the slides are in a random order
+    shuffle: bool,
+    /// whether the slideshow starts over after the last slide
+    looping: bool,
+}
+
+impl std::fmt::Display for SlideshowStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flag = |on: bool| if on { "on" } else { "off" };
+
+        write!(f, "[{}/{}] ", self.position, self.total)?;
+        if self.paused {
+            write!(f, "paused")?;
+        } else {
+            write!(f, "next in {}s", self.remaining.as_secs_f64().ceil() as u64)?;
+        }
+
+        write!(
+            f,
+            " | shuffle: {} | loop: {}",
+            flag(self.shuffle),
+            flag(self.looping)
+        )
+    }
+}
+
+/// decodes upcoming slideshow images in the background so advancing doesn't stall
+///
+/// honors `performance.preload_images`, `performance.prefetch_batch_size` (how many slides to
+/// decode ahead), and `performance.max_preload_size_mb` (bigger files are decoded when shown)
+struct SlidePreloader {
+    /// slides being decoded (or already decoded), keyed by file path
+    slides: Vec<(PathBuf, JoinHandle<Option<String>>)>,
+    /// how many slides to decode ahead (0 = off)
+    ahead: usize,
+    /// files bigger than this many bytes aren't preloaded
+    max_bytes: u64,
+}
+
+impl SlidePreloader {
+    /// make a preloader from the performance config
+    fn from_cfg() -> Self {
+        Self {
+            slides: Vec::new(),
+            ahead: if getopt!(performance.preload_images) {
+                getopt!(performance.prefetch_batch_size)
+            } else {
+                0
+            },
+            max_bytes: getopt!(performance.max_preload_size_mb) * 1024 * 1024,
+        }
+    }
+
+    /// take the encoded sixel of a slide, if it was preloaded
+    async fn take(&mut self, path: &Path) -> Option<String> {
+        let idx = self.slides.iter().position(|(p, _)| p == path)?;
+        let (_, handle) = self.slides.swap_remove(idx);
+        handle.await.ok().flatten()
+    }
+
+    /// start decoding the next slides, dropping any that are no longer coming up
+    fn preload<'a>(&mut self, upcoming: impl Iterator<Item = &'a Path>) {
+        let upcoming: Vec<&Path> = upcoming.take(self.ahead).collect();
+
+        self.slides.retain(|(path, handle)| {
+            let keep = upcoming.contains(&path.as_path());
+            if !keep {
+                handle.abort();
+            }
+            keep
+        });
 
-        if !json_path.exists() {
-            bail!("Metadata file not found: {}", json_path.display());
+        for path in upcoming {
+            let too_big = std::fs::metadata(path).is_ok_and(|m| m.len() > self.max_bytes);
+            if too_big || self.slides.iter().any(|(p, _)| p == path) {
+                continue;
+            }
+
+            let owned = path.to_path_buf();
+            let handle =
+                tokio::task::spawn_blocking(move || encode_dl_sixel(&owned).ok().flatten());
+            self.slides.push((path.to_path_buf(), handle));
         }
+    }
+}
+
+/// draw the slideshow's status line over the first line of the terminal
+fn draw_slideshow_status(status: &SlideshowStatus) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    crossterm::queue!(
+        stdout,
+        SavePosition,
+        MoveTo(0, 0),
+        Clear(ClearType::CurrentLine),
+        Print(status),
+        RestorePosition
+    )?;
+    std::io::Write::flush(&mut stdout)?;
+
+    Ok(())
+}
+
+/// get the modified time of a file
+fn file_mtime(path: &Path) -> Option<DateTime<Local>> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::<Local>::from)
+}
+
+/// parse one end of a download date range
+///
+/// accepts a date (`2024-06-01`), a number of days ago (`7d`), or nothing for an open end
+#[bearive::argdoc]
+#[error = "the input isn't a valid date or day count"]
+pub fn parse_date_bound(
+    /// the user's input
+    input: &str,
+) -> Result<Option<NaiveDate>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
 
-        let contents = std::fs::read_to_string(&json_path)
-            .with_context(|| format!("Failed to read metadata file {}", json_path.display()))?;
+    if let Some(days) = input.strip_suffix('d')
+        && let Ok(days) = days.parse::<u64>()
+    {
+        return Ok(Local::now().date_naive().checked_sub_days(Days::new(days)));
+    }
 
-        serde_json::from_str(&contents)
-            .with_context(|| format!("Failed to parse metadata for {}", file_path.display()))
-            .map_err(|e| e.into())
+    match NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        Ok(date) => Ok(Some(date)),
+        Err(_) => bail!(
+            "'{}' isn't a date (YYYY-MM-DD) or day count (e.g. 7d)",
+            input
+        ),
     }
 }
 
@@ -156,10 +326,50 @@ pub struct ExplorerState {
     /// text that posts must match in tags, id, desc, file ext, or uploader
     pub search_query: Option<String>,
 
+    /// the current tag expression (if any)
+    ///
+    /// boolean tag logic that posts must satisfy, see [`expr`] for the syntax
+    pub tag_expr: Option<TagExpr>,
+
     /// the current content rating filter (if any)
     ///
-    /// if set, only posts with this rating (`s`/`q`/`e`) will be shown
-    pub rating_filter: Option<String>,
+    /// if set, only posts with this rating will be shown
+    pub rating_filter: Option<Rating>,
+
+    /// the lowest score a post can have and still be shown
+    ///
+    /// posts scoring below this are always left out of the filtered posts
+    pub hide_below_score: i64,
+
+    /// posts hidden by the score filter
+    ///
+    /// the number of loaded posts left out because of `hide_below_score`
+    pub hidden_by_score: usize,
+
+    /// the smallest width a post's file can have and still be shown
+    ///
+    /// 0 shows posts of any width
+    pub min_width: u64,
+
+    /// the smallest height a post's file can have and still be shown
+    ///
+    /// 0 shows posts of any height
+    pub min_height: u64,
+
+    /// the fewest favorites a post can have and still be shown
+    ///
+    /// 0 shows posts with any number of favorites
+    pub min_fav_count: u64,
+
+    /// the earliest download date to show (if any)
+    ///
+    /// posts whose files were modified before this day are left out
+    pub downloaded_after: Option<NaiveDate>,
+
+    /// the latest download date to show (if any)
+    ///
+    /// posts whose files were modified after this day are left out
+    pub downloaded_before: Option<NaiveDate>,
 }
 
 impl ExplorerState {
@@ -178,10 +388,56 @@ impl ExplorerState {
             filtered_posts,
             current_sort: ExplorerSortBy::DateNewest,
             search_query: None,
+            tag_expr: None,
             rating_filter: None,
+            hide_below_score: i64::MIN,
+            hidden_by_score: 0,
+            min_width: 0,
+            min_height: 0,
+            min_fav_count: 0,
+            downloaded_after: None,
+            downloaded_before: None,
         }
     }
 
+    /// set the minimum score a post needs to be shown
+    ///
+    /// re-applies the filters so the hidden post count stays up to date
+    #[bearive::argdoc]
+    pub fn set_hide_below_score(
+        &mut self,
+        /// the lowest total score a post can have and still be shown
+        min_score: i64,
+    ) {
+        self.hide_below_score = min_score;
+        self.apply_filters();
+    }
+
+    /// set the minimum resolution a post needs to be shown
+    #[bearive::argdoc]
+    pub fn set_min_resolution(
+        &mut self,
+        /// the smallest width in pixels, 0 for any
+        min_width: u64,
+        /// the smallest height in pixels, 0 for any
+        min_height: u64,
+    ) {
+        self.min_width = min_width;
+        self.min_height = min_height;
+        self.apply_filters();
+    }
+
+    /// set the minimum number of favorites a post needs to be shown
+    #[bearive::argdoc]
+    pub fn set_min_fav_count(
+        &mut self,
+        /// the fewest favorites a post can have, 0 for any
+        min_fav_count: u64,
+    ) {
+        self.min_fav_count = min_fav_count;
+        self.apply_filters();
+    }
+
     /// sort the current loaded downloads
     ///
     /// applies the specified sort order to the filtered posts. does not affect the full posts list
@@ -222,6 +478,13 @@ impl ExplorerState {
                 self.filtered_posts
                     .sort_by_key(|b| std::cmp::Reverse(b.post.id));
             }
+            ExplorerSortBy::DownloadedNewest => {
+                self.filtered_posts
+                    .sort_by_key(|b| std::cmp::Reverse(b.downloaded_at));
+            }
+            ExplorerSortBy::DownloadedOldest => {
+                self.filtered_posts.sort_by_key(|a| a.downloaded_at);
+            }
         }
     }
 
@@ -232,10 +495,27 @@ impl ExplorerState {
     #[bearive::argdoc]
     pub fn filter_by_rating(
         &mut self,
-        /// the rating to filter for (`s`/`q`/`e`) or None to show all
-        rating: Option<String>,
+        /// the rating to filter for, or None to show all
+        rating: Option<Rating>,
     ) {
-        self.rating_filter = rating.clone();
+        self.rating_filter = rating;
+        self.apply_filters();
+    }
+
+    /// filter posts by when they were downloaded
+    ///
+    /// only shows posts whose files were modified within the range (inclusive). this is the
+    /// download date, not the upload date. using `None` for both ends removes the filter
+    #[bearive::argdoc]
+    pub fn filter_by_downloaded(
+        &mut self,
+        /// the earliest day to show, or `None` for no lower bound
+        after: Option<NaiveDate>,
+        /// the latest day to show, or `None` for no upper bound
+        before: Option<NaiveDate>,
+    ) {
+        self.downloaded_after = after;
+        self.downloaded_before = before;
         self.apply_filters();
     }
 
@@ -253,17 +533,71 @@ impl ExplorerState {
         self.apply_filters();
     }
 
+    /// filter posts with a boolean tag expression
+    ///
+    /// unlike [`ExplorerState::search`], tags are matched exactly and can be combined with
+    /// include/exclude/OR logic. using `None` removes the tag filter
+    #[bearive::argdoc]
+    pub fn search_tags(
+        &mut self,
+        /// the parsed tag expression, or `None` to clear it
+        expr: Option<TagExpr>,
+    ) {
+        self.tag_expr = expr;
+        self.apply_filters();
+    }
+
     /// apply the selected filters
     ///
-    /// rebuilds the filtered posts list by applying all active filters (rating + search).
-    /// automatically re-sorts after filtering
+    /// rebuilds the filtered posts list by applying all active filters (score + resolution +
+    /// favorites + rating + download date + tags + search). automatically re-sorts after
+    /// filtering
     pub fn apply_filters(&mut self) {
+        self.hidden_by_score = self
+            .posts
+            .iter()
+            .filter(|local_post| local_post.post.score.total < self.hide_below_score)
+            .count();
+
         self.filtered_posts = self
             .posts
             .iter()
             .filter(|local_post| {
+                if local_post.post.score.total < self.hide_below_score {
+                    return false;
+                }
+
+                if !local_post
+                    .post
+                    .meets_resolution_with(self.min_width, self.min_height)
+                {
+                    return false;
+                }
+
+                if (local_post.post.fav_count.max(0) as u64) < self.min_fav_count {
+                    return false;
+                }
+
                 if let Some(ref rating) = self.rating_filter
-                    && &local_post.post.rating != rating
+                    && &local_post.post.content_rating() != rating
+                {
+                    return false;
+                }
+
+                if self.downloaded_after.is_some() || self.downloaded_before.is_some() {
+                    let Some(date) = local_post.downloaded_at.map(|d| d.date_naive()) else {
+                        return false;
+                    };
+
+                    if self.downloaded_after.is_some_and(|after| date < after)
+                        || self.downloaded_before.is_some_and(|before| date > before)
+                    {
+                        return false;
+                    }
+                }
+
+                if let Some(ref expr) = self.tag_expr
+                    && !expr.matches(&local_post.post)
                 {
                     return false;
                 }
@@ -334,23 +668,26 @@ impl ExplorerState {
     ///
     /// calculates stats about both the full post collection and the curr filtered subset. includes
     /// counts by rating, avg score, and total faves
+    #[bearive::argdoc]
+    #[returns = "the collection's [`ExplorerStatistics`]"]
     pub fn get_statistics(&self) -> ExplorerStatistics {
         let total_posts = self.posts.len();
         let filtered_posts = self.filtered_posts.len();
 
-        let rating_counts = self.posts.iter().fold(
-            (0, 0, 0, 0),
-            |(safe, questionable, explicit, unknown), local_post| match local_post
-                .post
-                .rating
-                .as_str()
-            {
-                "s" => (safe + 1, questionable, explicit, unknown),
-                "q" => (safe, questionable + 1, explicit, unknown),
-                "e" => (safe, questionable, explicit + 1, unknown),
-                _ => (safe, questionable, explicit, unknown + 1),
-            },
-        );
+        let (mut safe, mut questionable, mut explicit) = (0, 0, 0);
+        let mut unknown_ratings = BTreeMap::new();
+        for local_post in &self.posts {
+            match local_post.post.content_rating() {
+                Rating::Safe => safe += 1,
+                Rating::Questionable => questionable += 1,
+                Rating::Explicit => explicit += 1,
+                unknown @ Rating::Unknown(_) => {
+                    *unknown_ratings
+                        .entry(unknown.name().to_string())
+                        .or_insert(0) += 1;
+                }
+            }
+        }
 
         let avg_score = if total_posts > 0 {
             self.posts.iter().map(|lp| lp.post.score.total).sum::<i64>() as f64 / total_posts as f64
@@ -363,14 +700,41 @@ impl ExplorerState {
         ExplorerStatistics {
             total_posts,
             filtered_posts,
-            safe: rating_counts.0,
-            questionable: rating_counts.1,
-            explicit: rating_counts.2,
-            unknown: rating_counts.3,
+            hidden_by_score: self.hidden_by_score,
+            safe,
+            questionable,
+            explicit,
+            unknown: unknown_ratings.values().sum(),
+            unknown_ratings,
             avg_score,
             total_favorites,
         }
     }
+
+    /// count how many of the filtered posts have each tag
+    #[bearive::argdoc]
+    #[returns = "every tag and its count, most frequent first (ties by name)"]
+    pub fn tag_frequencies(
+        &self,
+        /// the tag categories to count
+        categories: &[TagCategory],
+    ) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for local_post in &self.filtered_posts {
+            for category in categories {
+                for tag in local_post.post.tags.category(*category) {
+                    *counts.entry(tag.as_str()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut counts: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(tag, count)| (tag.to_string(), count))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
 }
 
 /// statistics for the explorer
@@ -391,6 +755,11 @@ pub struct ExplorerStatistics {
     /// the number of posts matching curr filters
     pub filtered_posts: usize,
 
+    /// total posts hidden by score
+    ///
+    /// the number of posts scoring below `hide_below_score`
+    pub hidden_by_score: usize,
+
     /// total safe posts
     ///
     /// count of posts with safe rating
@@ -411,6 +780,11 @@ pub struct ExplorerStatistics {
     /// count of posts with unrecognized rating
     pub unknown: usize,
 
+    /// unknown posts by rating
+    ///
+    /// count of posts with each unrecognized rating, keyed by its raw value
+    pub unknown_ratings: BTreeMap<String, usize>,
+
     /// the average score of loaded posts
     ///
     /// mean total scores across all loaded posts
@@ -422,6 +796,401 @@ pub struct ExplorerStatistics {
     pub total_favorites: i64,
 }
 
+/// check whether a downloaded file has metadata stored alongside it
+///
+/// looks in every metadata backend, starting with the given one
+#[bearive::argdoc]
+pub fn has_metadata(
+    /// the path to the downloaded file
+    path: &Path,
+    /// the metadata backend to check first
+    backend: MetadataBackend,
+) -> bool {
+    metadata::has_metadata(path, backend)
+}
+
+/// read a single local post, going through the metadata cache if enabled
+///
+/// returns `None` if the file has no metadata at all
+async fn read_local_post(
+    path: PathBuf,
+    cache_enabled: bool,
+    backend: MetadataBackend,
+) -> Option<Result<LocalPost>> {
+    let check_path = path.clone();
+    if !tokio::task::spawn_blocking(move || has_metadata(&check_path, backend))
+        .await
+        .unwrap_or(false)
+    {
+        return None;
+    }
+
+    if cache_enabled && let Some(post) = METADATA_CACHE.lock().await.get(&path).cloned() {
+        return Some(Ok(LocalPost {
+            post,
+            downloaded_at: file_mtime(&path),
+            file_path: path,
+        }));
+    }
+
+    let read_path = path.clone();
+    let local_post =
+        match tokio::task::spawn_blocking(move || LocalPost::from_file(read_path, backend)).await {
+            Ok(Ok(local_post)) => local_post,
+            Ok(Err(e)) => {
+                warn!("failed to load metadata for {}: {}", path.display(), e);
+                return Some(Err(e));
+            }
+            Err(e) => return Some(Err(e.to_string().into())),
+        };
+
+    if cache_enabled {
+        METADATA_CACHE
+            .lock()
+            .await
+            .insert(path, local_post.post.clone());
+    }
+
+    Some(Ok(local_post))
+}
+
+/// load local posts from a list of files, reading metadata concurrently
+///
+/// files without metadata are ignored. the returned posts keep the order of `files`, along with
+/// the number of files whose metadata couldn't be read
+#[bearive::argdoc]
+pub async fn load_local_posts(
+    /// the files to load
+    files: Vec<PathBuf>,
+    /// how many files to read at once
+    threads: usize,
+    /// whether to read from and populate the in-memory metadata cache
+    cache_enabled: bool,
+    /// the metadata backend to check first
+    backend: MetadataBackend,
+    /// a progress bar to advance once per file
+    pb: Option<ProgressBar>,
+) -> (Vec<LocalPost>, usize) {
+    let semaphore = Arc::new(Semaphore::new(threads.max(1)));
+    let total_files = files.len() as u64;
+
+    let tasks: Vec<_> = files
+        .into_iter()
+        .map(|path| {
+            let semaphore = Arc::clone(&semaphore);
+            let pb = pb.clone();
+
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire().await.ok()?;
+                let result = read_local_post(path, cache_enabled, backend).await;
+
+                if let Some(ref pb) = pb {
+                    pb.inc(1);
+                    let pos = pb.position();
+                    if pos.is_multiple_of(25) || pos == total_files {
+                        pb.set_message(format!(
+                            "Scanning files for metadata ({}/{})",
+                            pos, total_files
+                        ));
+                    }
+                }
+
+                result
+            })
+        })
+        .collect();
+
+    let mut local_posts = Vec::new();
+    let mut skipped_count = 0;
+
+    for result in futures_util::future::join_all(tasks).await {
+        match result {
+            Ok(Some(Ok(local_post))) => local_posts.push(local_post),
+            Ok(Some(Err(_))) => skipped_count += 1,
+            Ok(None) => {}
+            Err(e) => {
+                warn!("metadata scan task failed: {}", e);
+                skipped_count += 1;
+            }
+        }
+    }
+
+    (local_posts, skipped_count)
+}
+
+/// why a downloaded file doesn't match its metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Corruption {
+    /// the file's size differs from the post's
+    Size {
+        /// the size of the post on e6
+        expected: u64,
+        /// the size of the file on disk
+        actual: u64,
+    },
+    /// the file's md5 differs from the post's
+    Md5 {
+        /// the md5 of the file on disk
+        actual: String,
+    },
+    /// the file couldn't be read
+    Unreadable(String),
+}
+
+impl std::fmt::Display for Corruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Size { expected, actual } => {
+                write!(f, "size is {} bytes, expected {}", actual, expected)
+            }
+            Self::Md5 { actual } => write!(f, "md5 is {}, which doesn't match", actual),
+            Self::Unreadable(e) => write!(f, "unreadable: {}", e),
+        }
+    }
+}
+
+/// whether a downloaded file is a video, going by its extension
+///
+/// # Arguments
+///
+/// * `path` - the downloaded file
+pub fn is_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(MediaType::from_extension)
+        == Some(MediaType::Video)
+}
+
+/// write an m3u playlist of files
+///
+/// # Arguments
+///
+/// * `path` - where to write the playlist
+/// * `files` - the files to put in it, in order
+///
+/// # Errors
+///
+/// returns an error if the playlist can't be written
+pub fn write_playlist(path: &Path, files: &[PathBuf]) -> Result<()> {
+    let mut playlist = String::from("#EXTM3U\n");
+    for file in files {
+        playlist.push_str(&file.display().to_string());
+        playlist.push('\n');
+    }
+
+    std::fs::write(path, playlist)
+        .with_context(|| format!("failed to write playlist '{}'", path.display()))?;
+
+    Ok(())
+}
+
+/// write tag frequencies for a wordcloud generator
+///
+/// a path ending in `.json` gets a json array of `{"tag": ..., "count": ...}` objects, anything
+/// else gets one `tag\tcount` line per tag
+///
+/// # Arguments
+///
+/// * `path` - where to write the frequencies
+/// * `counts` - every tag and its count, in the order to write them
+///
+/// # Errors
+///
+/// returns an error if the file can't be written
+pub fn write_tag_frequencies(path: &Path, counts: &[(String, usize)]) -> Result<()> {
+    let contents = if path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+    {
+        let entries: Vec<serde_json::Value> = counts
+            .iter()
+            .map(|(tag, count)| serde_json::json!({ "tag": tag, "count": count }))
+            .collect();
+        serde_json::to_string_pretty(&entries)?
+    } else {
+        counts
+            .iter()
+            .map(|(tag, count)| format!("{}\t{}\n", tag, count))
+            .collect()
+    };
+
+    std::fs::write(path, contents)
+        .with_context(|| format!("failed to write tag frequencies '{}'", path.display()))?;
+
+    Ok(())
+}
+
+/// copy a download and its metadata into a staging folder, keeping its file name
+///
+/// returns where the copy went, or `None` if it was skipped because a file with its name is
+/// already staged (with [`ConflictMenu::Skip`]) or it's the staged file itself
+///
+/// # Arguments
+///
+/// * `reorganizer` - the reorganizer to copy with
+/// * `source` - the downloaded file
+/// * `staging` - the staging folder
+/// * `conflict_resolution` - what to do if a file with the same name is already staged
+///
+/// # Errors
+///
+/// returns an error if the file or its metadata can't be copied
+pub fn stage_file(
+    reorganizer: &FileReorganizer,
+    source: &Path,
+    staging: &Path,
+    conflict_resolution: ConflictMenu,
+) -> Result<Option<PathBuf>> {
+    let Some(name) = source.file_name() else {
+        bail!("'{}' has no file name", source.display());
+    };
+    let target = staging.join(name);
+
+    if target.exists() {
+        let same_file = std::fs::canonicalize(&target).ok() == std::fs::canonicalize(source).ok();
+        if same_file || conflict_resolution == ConflictMenu::Skip {
+            return Ok(None);
+        }
+    }
+
+    reorganizer
+        .copy_file_with_metadata(source, &target, conflict_resolution)
+        .map(Some)
+}
+
+/// rename a downloaded file (and its metadata) for [`ExploreMenu::normalize_names`]
+///
+/// on case-insensitive filesystems a rename that only changes case points at the file itself,
+/// which would be seen as a conflict, so those go through a temporary name first. the same goes
+/// for folders on the way to the file whose name only changes case, which are renamed as a whole
+///
+/// # Arguments
+///
+/// * `reorganizer` - the reorganizer to move with
+/// * `old` - the downloaded file
+/// * `new` - its normalized path
+///
+/// # Errors
+///
+/// returns an error if a folder, the file, or its metadata can't be moved, or something else is
+/// already at `new`
+pub fn rename_normalized(reorganizer: &FileReorganizer, old: &Path, new: &Path) -> Result<PathBuf> {
+    let is_same = |a: &Path, b: &Path| {
+        b.exists() && std::fs::canonicalize(a).ok() == std::fs::canonicalize(b).ok()
+    };
+
+    let old_abs = std::path::absolute(old)?;
+    let new_abs = std::path::absolute(new)?;
+    let mut old_dir = PathBuf::new();
+    let mut new_dir = PathBuf::new();
+
+    if let (Some(old_parent), Some(new_parent)) = (old_abs.parent(), new_abs.parent()) {
+        for (old_part, new_part) in old_parent.components().zip(new_parent.components()) {
+            old_dir.push(old_part);
+            new_dir.push(new_part);
+
+            if old_part != new_part && is_same(&old_dir, &new_dir) {
+                let mut temp_name = old_dir.file_name().unwrap_or_default().to_os_string();
+                temp_name.push(".renaming");
+                let temp = old_dir.with_file_name(temp_name);
+
+                std::fs::rename(&old_dir, &temp)
+                    .and_then(|_| std::fs::rename(&temp, &new_dir))
+                    .with_context(|| {
+                        format!(
+                            "Failed to rename {} to {}",
+                            old_dir.display(),
+                            new_dir.display()
+                        )
+                    })?;
+                old_dir.clone_from(&new_dir);
+            }
+        }
+    }
+
+    let old = old_abs
+        .file_name()
+        .map(|name| old_dir.join(name))
+        .unwrap_or_else(|| old_abs.clone());
+
+    if !is_same(&old, new) {
+        return reorganizer.move_file_with_metadata(&old, new, ConflictMenu::Skip);
+    }
+
+    let mut temp_name = old.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".renaming");
+    let temp = old.with_file_name(temp_name);
+
+    reorganizer.move_file_with_metadata(&old, &temp, ConflictMenu::Skip)?;
+    reorganizer.move_file_with_metadata(&temp, new, ConflictMenu::Skip)
+}
+
+/// get where a file is re-downloaded to before it replaces the original
+/// (`1234.png` -> `1234.redownload.png`)
+///
+/// # Arguments
+///
+/// * `path` - the file being replaced
+fn redownload_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{}.redownload.{}", stem, ext.to_string_lossy())),
+        None => path.with_file_name(format!("{}.redownload", stem)),
+    }
+}
+
+/// swap a re-downloaded file in for the one it replaces, discarding the old one along with its
+/// metadata (see [`trash::delete_download`])
+///
+/// # Arguments
+///
+/// * `path` - the file being replaced
+/// * `replacement` - the re-downloaded file
+/// * `permanent` - delete the old file for good instead of moving it to the trash
+///
+/// # Errors
+///
+/// returns an error if the old file can't be discarded or the new one can't be moved
+fn replace_download(path: &Path, replacement: &Path, permanent: bool) -> Result<()> {
+    trash::delete_download(path, permanent)?;
+    std::fs::rename(replacement, path).with_context(|| {
+        format!(
+            "failed to move '{}' to '{}'",
+            replacement.display(),
+            path.display()
+        )
+    })?;
+    metadata::move_metadata(replacement, path)
+}
+
+/// check a downloaded file against its post's size and md5
+///
+/// the size is checked first so truncated files aren't hashed. posts without a size or md5
+/// (like ones saved before metadata had them) only get the checks they have data for
+fn check_integrity(path: &Path, post: &E6Post) -> Option<Corruption> {
+    let actual = match path.metadata() {
+        Ok(meta) => meta.len(),
+        Err(e) => return Some(Corruption::Unreadable(e.to_string())),
+    };
+
+    if let Ok(expected) = u64::try_from(post.file.size)
+        && expected > 0
+        && expected != actual
+    {
+        return Some(Corruption::Size { expected, actual });
+    }
+
+    if post.file.md5.is_empty() {
+        return None;
+    }
+
+    match hash_file(path) {
+        Ok(md5) if md5.eq_ignore_ascii_case(&post.file.md5) => None,
+        Ok(md5) => Some(Corruption::Md5 { actual: md5 }),
+        Err(e) => Some(Corruption::Unreadable(e.to_string())),
+    }
+}
+
 /// functions for the explorer menu
 ///
 /// provides the ui for the downloads browser (TUI | For the web gallery, see [`crate::serve`])
@@ -442,14 +1211,11 @@ pub trait ExploreMenu {
 
     /// scan the downloads directory for posts
     ///
-    /// # Returns
-    ///
-    /// a list of local posts found in the dl dir
-    ///
     /// # Errors
     ///
     /// returns an error if:
     /// - dir traversal fails
+    #[returns = "a list of local posts found in the dl dir"]
     fn scan_downloads_directory(
         &self,
         /// the directory to scan
@@ -459,13 +1225,16 @@ pub trait ExploreMenu {
     /// show a slideshow of filtered posts
     ///
     /// displays posts sequentially in the term with configurable timing. supports pausing,
-    /// navigation, and exit controls via the kb
+    /// navigation, and exit controls via the kb. the top line shows the current position, the
+    /// countdown to the next slide, and which modes are on
     ///
     /// # Keyboard controls
     ///
     /// - Space: pause/resume
     /// - Left/H: prev post
     /// - Right/L: next post
+    /// - S: shuffle on/off
+    /// - R: loop on/off
     /// - Q/Esc: exit slideshow
     ///
     /// # Errors
@@ -513,8 +1282,8 @@ pub trait ExploreMenu {
 
     /// filter posts by content rating
     ///
-    /// prompts the user to select a rating filter (`all`/`safe`/`questionable`/`explicit`) and
-    /// applies it to the explorer state
+    /// prompts the user to select a rating filter (`all`/`safe`/`questionable`/`explicit`, or
+    /// any other rating the loaded posts have) and applies it to the explorer state
     ///
     /// # Errors
     ///
@@ -526,6 +1295,36 @@ pub trait ExploreMenu {
         state: &mut ExplorerState,
     ) -> Result<()>;
 
+    /// filter posts by download date
+    ///
+    /// prompts the user for the start and end of a date range and applies it to the explorer
+    /// state
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - user interaction fails
+    fn filter_by_download_date(
+        &self,
+        /// the explorer state to update
+        state: &mut ExplorerState,
+    ) -> Result<()>;
+
+    /// filter posts by favorite count
+    ///
+    /// prompts the user for the fewest favorites a post needs and applies it to the explorer
+    /// state
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - user interaction fails
+    fn filter_by_fav_count(
+        &self,
+        /// the explorer state to update
+        state: &mut ExplorerState,
+    ) -> Result<()>;
+
     /// sort posts
     ///
     /// prompts the user to select a sort mode and applies it to the explorer state
@@ -540,6 +1339,106 @@ pub trait ExploreMenu {
         state: &mut ExplorerState,
     ) -> Result<()>;
 
+    /// scan posts for corrupted files
+    ///
+    /// re-hashes every file in parallel and compares its size and md5 to the post's metadata,
+    /// lists the mismatches, and offers to re-download them. each file is re-downloaded next
+    /// to the original, which is only discarded once its replacement is in
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - the progress bar can't be made
+    /// - user interaction fails
+    /// - the posts to re-download can't be fetched
+    fn verify_integrity(
+        &self,
+        /// the posts to check
+        posts: &[LocalPost],
+    ) -> impl Future<Output = Result<()>>;
+
+    /// normalize the case and spacing of folder and file names
+    ///
+    /// re-derives every post's path with the current format and lists the files whose path only
+    /// differs by case or spaces vs underscores, then offers to move them
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - user interaction fails
+    fn normalize_names(
+        &self,
+        /// the explorer state whose posts are moved
+        state: &mut ExplorerState,
+    ) -> Result<()>;
+
+    /// save a contact sheet of posts
+    ///
+    /// asks for the number of columns, whether to label posts with their ids, and where to save,
+    /// then writes a png with a thumbnail of every post that's an image. the sheet is kept within
+    /// `explorer.contact_sheet_max_size` pixels on either side, with posts that don't fit going
+    /// on more sheets numbered after the first. a path without an extension is saved as a png,
+    /// and a taken path is never overwritten, the sheet gets the next free numbered name instead
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - user interaction fails
+    /// - a sheet can't be saved
+    fn contact_sheet(
+        &self,
+        /// the posts to put on the sheet
+        posts: &[LocalPost],
+    ) -> Result<()>;
+
+    /// copy posts into the staging folder
+    ///
+    /// every file and its metadata is copied into `explorer.staging_dir`, leaving the originals
+    /// where they are (see [`stage_file`]). if any of them are already staged, asks whether to
+    /// skip, overwrite, or rename them
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - user interaction fails
+    fn copy_to_staging(
+        &self,
+        /// the posts to copy
+        posts: &[LocalPost],
+    ) -> Result<()>;
+
+    /// export how often each tag shows up in the filtered posts
+    ///
+    /// asks which tag categories to count and where to save (see [`write_tag_frequencies`])
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - user interaction fails
+    /// - the file can't be written
+    fn export_tag_frequencies(
+        &self,
+        /// the current explorer state
+        state: &ExplorerState,
+    ) -> Result<()>;
+
+    /// play the videos among posts as a playlist
+    ///
+    /// asks whether to shuffle and loop, writes the videos to a temporary m3u playlist, and opens
+    /// it with `explorer.video_player`. posts that aren't videos are skipped
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - user interaction fails
+    /// - the playlist can't be written
+    /// - the player can't be started
+    fn play_videos(
+        &self,
+        /// the posts to play
+        posts: &[LocalPost],
+    ) -> Result<()>;
+
     /// display explorer stats
     ///
     /// prints stats about the post collection including:
@@ -559,17 +1458,27 @@ impl ExploreMenu for E6Ui {
     async fn explore_downloads(&self) -> Result<()> {
         println!("\n{0} Downloads Explorer {0}\n", "===".green());
 
-        let download_dir: String = getopt!(download.path);
-        let directory = Path::new(&download_dir);
+        let directories = download_dirs();
+        let main_dir = &directories[0];
 
-        if !directory.exists() {
-            bail!("Download directory does not exist: {}", directory.display());
+        if !main_dir.exists() {
+            bail!("Download directory does not exist: {}", main_dir.display());
         }
 
-        let local_posts = self.scan_downloads_directory(directory).await?;
+        let mut local_posts = Vec::new();
+        for directory in &directories {
+            if directory.exists() {
+                local_posts.extend(self.scan_downloads_directory(directory).await?);
+            } else {
+                warn!(
+                    "download route directory '{}' doesn't exist",
+                    directory.display()
+                );
+            }
+        }
 
         if local_posts.is_empty() {
-            println!("No posts with metadata found in {}", directory.display());
+            println!("No posts with metadata found in {}", main_dir.display());
             return Ok(());
         }
 
@@ -586,9 +1495,18 @@ impl ExploreMenu for E6Ui {
             "favorites_highest" => ExplorerSortBy::FavoritesHighest,
             "id_ascending" => ExplorerSortBy::IDAscending,
             "id_descending" => ExplorerSortBy::IDDescending,
+            "downloaded_newest" => ExplorerSortBy::DownloadedNewest,
+            "downloaded_oldest" => ExplorerSortBy::DownloadedOldest,
             _ => ExplorerSortBy::DateNewest,
         };
         state.sort(default_sort);
+        state.set_min_resolution(getopt!(search.min_width), getopt!(search.min_height));
+        state.set_min_fav_count(getopt!(search.min_fav_count));
+        state.set_hide_below_score(getopt!(ui.hide_below_score));
+
+        if state.hidden_by_score > 0 {
+            println!("{} posts hidden by score filter", state.hidden_by_score);
+        }
 
         loop {
             let action = ExplorerMenu::select(&format!(
@@ -615,10 +1533,33 @@ impl ExploreMenu for E6Ui {
                     println!("Found {} matching posts", state.filtered_posts.len());
                     false
                 }
+                ExplorerMenu::TagSearch => {
+                    let query = TextInput::new(
+                        "Enter tag expression (e.g. `canine -feral` or `cat | dog`):",
+                    )
+                    .ask()?;
+
+                    match TagExpr::parse(&query) {
+                        Ok(expr) => {
+                            state.search_tags(Some(expr));
+                            println!("Found {} matching posts", state.filtered_posts.len());
+                        }
+                        Err(e) => println!("Invalid tag expression: {}", e),
+                    }
+                    false
+                }
                 ExplorerMenu::FilterByRating => {
                     self.filter_by_rating(&mut state)?;
                     false
                 }
+                ExplorerMenu::FilterByDownloadDate => {
+                    self.filter_by_download_date(&mut state)?;
+                    false
+                }
+                ExplorerMenu::FilterByFavCount => {
+                    self.filter_by_fav_count(&mut state)?;
+                    false
+                }
                 ExplorerMenu::SortBy => {
                     self.sort_posts(&mut state)?;
                     false
@@ -629,7 +1570,10 @@ impl ExploreMenu for E6Ui {
                 }
                 ExplorerMenu::ClearFilters => {
                     state.search(None);
+                    state.search_tags(None);
                     state.filter_by_rating(None);
+                    state.filter_by_downloaded(None, None);
+                    state.set_min_fav_count(0);
                     println!(
                         "Filters cleared. Showing all {} posts",
                         state.filtered_posts.len()
@@ -640,6 +1584,51 @@ impl ExploreMenu for E6Ui {
                     self.slideshow(&state.filtered_posts).await?;
                     false
                 }
+                ExplorerMenu::VerifyIntegrity => {
+                    self.verify_integrity(&state.filtered_posts).await?;
+                    false
+                }
+                ExplorerMenu::NormalizeNames => {
+                    self.normalize_names(&mut state)?;
+                    false
+                }
+                ExplorerMenu::ExportTags => {
+                    let posts: Vec<E6Post> = state
+                        .filtered_posts
+                        .iter()
+                        .map(|lp| lp.post.clone())
+                        .collect();
+                    self.export_tag_list(&posts)?;
+                    false
+                }
+                ExplorerMenu::ExportTagCounts => {
+                    if state.filtered_posts.is_empty() {
+                        println!("No posts match the current filters.");
+                    } else {
+                        self.export_tag_frequencies(&state)?;
+                    }
+                    false
+                }
+                ExplorerMenu::ContactSheet => {
+                    if state.filtered_posts.is_empty() {
+                        println!("No posts match the current filters.");
+                    } else {
+                        self.contact_sheet(&state.filtered_posts)?;
+                    }
+                    false
+                }
+                ExplorerMenu::CopyToStaging => {
+                    if state.filtered_posts.is_empty() {
+                        println!("No posts match the current filters.");
+                    } else {
+                        self.copy_to_staging(&state.filtered_posts)?;
+                    }
+                    false
+                }
+                ExplorerMenu::PlayVideos => {
+                    self.play_videos(&state.filtered_posts)?;
+                    false
+                }
                 ExplorerMenu::Back => true,
             };
 
@@ -657,13 +1646,12 @@ impl ExploreMenu for E6Ui {
 
     /// scan the downloads directory for posts
     async fn scan_downloads_directory(&self, directory: &Path) -> Result<Vec<LocalPost>> {
-        let mut local_posts = Vec::new();
-        let mut skipped_count = 0;
-
         let recursive: bool = getopt!(explorer.recursive);
         let show_progress: bool = getopt!(explorer.show_progress);
         let progress_threshold: usize = getopt!(explorer.progress_threshold);
         let cache_enabled: bool = getopt!(explorer.cache_metadata);
+        let scan_threads: usize = getopt!(explorer.scan_threads);
+        let backend: MetadataBackend = getopt!(download.metadata_backend);
 
         let walker = if recursive {
             WalkDir::new(directory).follow_links(false)
@@ -671,11 +1659,40 @@ impl ExploreMenu for E6Ui {
             WalkDir::new(directory).max_depth(1).follow_links(false)
         };
 
-        let total_files: usize = walker
+        let files: Vec<PathBuf> = walker
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_file())
-            .count();
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && !metadata::is_metadata_file(p))
+            .collect();
+
+        let incremental: bool = getopt!(explorer.incremental_scan);
+        let scan_cache_path = getopt!(cache).resolved_cache_dir().join(SCAN_CACHE_FILE);
+        let mut scan_cache = if incremental {
+            ScanCache::load(&scan_cache_path)
+        } else {
+            ScanCache::default()
+        };
+        let scan_key = std::path::absolute(directory).unwrap_or(directory.to_path_buf());
+        let mut scan = DirScan::start();
+
+        let previous = scan_cache
+            .get(&scan_key)
+            .filter(|_| !FULL_RESCAN.load(Ordering::Relaxed));
+        let mut reused = Vec::new();
+        let mut files_to_read = Vec::new();
+        for path in files {
+            match previous.and_then(|prev| prev.unchanged(&path)) {
+                Some(post) => reused.push(LocalPost {
+                    post: post.clone(),
+                    downloaded_at: file_mtime(&path),
+                    file_path: path,
+                }),
+                None => files_to_read.push(path),
+            }
+        }
+        let files = files_to_read;
+        let total_files = files.len();
 
         let show_progress_bar = show_progress && total_files >= progress_threshold;
         let progress_manager = Arc::new(ProgressManager::new());
@@ -687,6 +1704,7 @@ impl ExploreMenu for E6Ui {
                         "explorer_scan",
                         total_files as u64,
                         "Scanning files for metadata",
+                        false,
                     )
                     .await?,
             )
@@ -694,83 +1712,18 @@ impl ExploreMenu for E6Ui {
             None
         };
 
-        let walker = if recursive {
-            WalkDir::new(directory).follow_links(false)
-        } else {
-            WalkDir::new(directory).max_depth(1).follow_links(false)
-        };
-
-        for entry in walker.into_iter().filter_map(|e| e.ok()) {
-            let path = entry.path();
-
-            if !path.is_file() {
-                continue;
-            }
-
-            if let Some(ref pb) = pb {
-                pb.inc(1);
-                let pos = pb.position();
-                if pos.is_multiple_of(25) || pos == total_files as u64 {
-                    pb.set_message(format!(
-                        "Scanning files for metadata ({}/{})",
-                        pos, total_files
-                    ));
-                }
-            }
-
-            let has_metadata = {
-                #[cfg(target_os = "windows")]
-                {
-                    use std::fs::OpenOptions;
-                    let ads_path = format!("{}:metadata", path.display());
-                    OpenOptions::new().read(true).open(&ads_path).is_ok()
-                }
-
-                #[cfg(not(target_os = "windows"))]
-                {
-                    let json_path = path.with_extension(format!(
-                        "{}.json",
-                        path.extension().and_then(|e| e.to_str()).unwrap_or("")
-                    ));
-                    json_path.exists()
-                }
-            };
-
-            if has_metadata {
-                let cached_post = if cache_enabled {
-                    let cache = METADATA_CACHE.lock().await;
-                    cache.get(&path).cloned()
-                } else {
-                    None
-                };
-
-                let local_post = if let Some(post) = cached_post {
-                    LocalPost {
-                        post,
-                        file_path: path.to_path_buf(),
-                    }
-                } else {
-                    match LocalPost::from_file(path.to_path_buf()) {
-                        Ok(local_post) => {
-                            if cache_enabled {
-                                let mut cache_map = METADATA_CACHE.lock().await;
-                                cache_map.insert(path.to_path_buf(), local_post.post.clone());
-                            }
-
-                            local_post
-                        }
-
-                        Err(e) => {
-                            warn!("failed to load metadata for {}: {}", path.display(), e);
-                            skipped_count += 1;
-                            continue;
-                        }
-                    }
-                };
+        let (mut local_posts, skipped_count) =
+            load_local_posts(files, scan_threads, cache_enabled, backend, pb.clone()).await;
+        let read_count = local_posts.len();
+        let reused_count = reused.len();
 
-                local_posts.push(local_post);
+        if cache_enabled {
+            let mut cache = METADATA_CACHE.lock().await;
+            for lp in &reused {
+                cache.insert(lp.file_path.clone(), lp.post.clone());
             }
         }
+        local_posts.extend(reused);
 
         if let Some(pb) = pb {
             pb.finish_with_message(format!(
@@ -787,28 +1740,102 @@ impl ExploreMenu for E6Ui {
             );
         }
 
+        if incremental {
+            println!(
+                "Read {} new or changed files, reused {} from the last scan",
+                read_count, reused_count
+            );
+
+            scan.posts = local_posts
+                .iter()
+                .map(|lp| (lp.file_path.clone(), lp.post.clone()))
+                .collect();
+            scan_cache.insert(scan_key, scan);
+            if let Err(e) = scan_cache.save(&scan_cache_path) {
+                warn!("failed to save the scan cache: {}", e);
+            }
+        }
+
         Ok(local_posts)
     }
 
     /// show a slideshow of filtered posts
     async fn slideshow(&self, posts: &[LocalPost]) -> Result<()> {
         let mut paused = false;
+        let mut shuffle = false;
+        let mut looping = false;
         let sleep_seconds: u64 = getopt!(explorer.slideshow_delay);
         let slide_duration = Duration::from_secs(sleep_seconds);
         let tick_rate = Duration::from_millis(100);
-        let mut i = 0;
+        let mut order: Vec<usize> = (0..posts.len()).collect();
+        let mut pos = 0;
+        let mut preloader = SlidePreloader::from_cfg();
 
-        while i < posts.len() {
+        while pos < order.len() {
             print!("\x1B[2J\x1B[3J\x1B[H");
             std::io::Write::flush(&mut std::io::stdout()).unwrap();
 
-            println!("Space to pause | Left/H - Go back | Right/L - Go forward");
+            println!();
+            println!(
+                "Space - Pause | Left/H - Back | Right/L - Forward | S - Shuffle | R - Loop | Q - Quit"
+            );
+
+            let current = &posts[order[pos]];
+            let preloaded = preloader.take(&current.file_path).await;
+
+            let remaining_slides = if looping {
+                order.len() - 1
+            } else {
+                order.len() - pos - 1
+            };
+            preloader.preload(
+                order
+                    .iter()
+                    .cycle()
+                    .skip(pos + 1)
+                    .take(remaining_slides)
+                    .map(|&i| posts[i].file_path.as_path()),
+            );
 
-            posts[i].view().ok();
+            match preloaded {
+                Some(sixel) => {
+                    print!("{}", sixel);
+                    println!();
+                }
+                None => {
+                    current.view().ok();
+                }
+            }
 
-            let mut time_elapsed = Duration::ZERO;
+            let mut remaining = slide_duration;
+            let mut last_tick = Instant::now();
+            let mut last_status = None;
 
             loop {
+                let now = Instant::now();
+                if !paused {
+                    remaining = remaining.saturating_sub(now - last_tick);
+                }
+                last_tick = now;
+
+                if remaining.is_zero() {
+                    pos += 1;
+                    break;
+                }
+
+                let status = SlideshowStatus {
+                    position: pos + 1,
+                    total: order.len(),
+                    remaining,
+                    paused,
+                    shuffle,
+                    looping,
+                };
+                if last_status.as_ref() != Some(&status) {
+                    draw_slideshow_status(&status)?;
+                    last_status = Some(status);
+                }
+
                 if crossterm::event::poll(tick_rate)?
                     && let Event::Key(key) = crossterm::event::read()?
                     && key.kind == KeyEventKind::Press
@@ -816,31 +1843,43 @@ impl ExploreMenu for E6Ui {
                     match key.code {
                         KeyCode::Char(' ') => paused = !paused,
                         KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                        KeyCode::Char('r') => looping = !looping,
+
+                        KeyCode::Char('s') => {
+                            shuffle = !shuffle;
+                            let current = order[pos];
+
+                            if shuffle {
+                                order.shuffle(&mut rand::rng());
+                            } else {
+                                order.sort_unstable();
+                            }
+
+                            pos = order.iter().position(|&i| i == current).unwrap_or(0);
+                        }
 
                         KeyCode::Right | KeyCode::Char('l') => {
-                            i += 1;
+                            pos += 1;
                             break;
                         }
 
                         KeyCode::Left | KeyCode::Char('h') => {
-                            i = i.saturating_sub(1);
+                            pos = if pos == 0 && looping {
+                                order.len() - 1
+                            } else {
+                                pos.saturating_sub(1)
+                            };
                             break;
                         }
 
                         _ => {}
                     }
-
-                    if paused {
-                        continue;
-                    }
-
-                    time_elapsed += tick_rate;
-                    if time_elapsed >= slide_duration {
-                        i += 1;
-                        break;
-                    }
                 }
             }
+
+            if pos >= order.len() && looping {
+                pos = 0;
+            }
         }
 
         Ok(())
@@ -849,6 +1888,7 @@ impl ExploreMenu for E6Ui {
     /// browse local downloads
     async fn browse_local_posts(&self, posts: &[LocalPost]) -> Result<()> {
         let posts_per_page: usize = getopt!(explorer.posts_per_page);
+        let template = post_list_template();
         let mut current_page = 0;
         let total_pages = posts.len().div_ceil(posts_per_page);
 
@@ -857,89 +1897,56 @@ impl ExploreMenu for E6Ui {
             let end = (start + posts_per_page).min(posts.len());
             let page_posts = &posts[start..end];
 
-            let mut options: Vec<AskOption<_>> = page_posts
+            let mut options: Vec<AskOption<BrowseChoice>> = page_posts
                 .iter()
-                .map(|local_post| {
-                    let post = &local_post.post;
+                .enumerate()
+                .map(|(idx, local_post)| {
                     AskOption::with_name(
-                        format!(
-                            "ID: {} | Score: {} | Rating: {} | Favs: {} | {}",
-                            post.id,
-                            post.score.total,
-                            post.rating,
-                            post.fav_count,
-                            post.tags.artist.first().unwrap_or(&"unknown".to_string())
-                        ),
-                        post,
+                        format_colored_post_list_entry(&template, &local_post.post),
+                        BrowseChoice::Post(idx),
                     )
                 })
                 .collect();
 
-            let default_post = E6Post::default();
-
             if total_pages > 1 {
                 options.push(AskOption::with_name(
                     format!("--- Page {}/{} ---", current_page + 1, total_pages),
-                    &default_post,
+                    BrowseChoice::PageInfo,
                 ));
 
                 if current_page > 0 {
                     options.push(AskOption::with_name(
                         "󰁍 Previous page".to_string(),
-                        &default_post,
+                        BrowseChoice::PreviousPage,
                     ));
                 }
                 if current_page < total_pages - 1 {
                     options.push(AskOption::with_name(
                         "Next page 󰁔".to_string(),
-                        &default_post,
+                        BrowseChoice::NextPage,
                     ));
                 }
             }
             options.push(AskOption::with_name(
                 "󰓕 Back to explorer menu".to_string(),
-                &default_post,
+                BrowseChoice::Back,
             ));
 
-            let selection = Some(
-                Select::new("Select a post to view:")
-                    .with_options(options)
-                    .with_help_message("Use arrow keys to navigate, Enter to select, Esc to cancel")
-                    .ask()?,
-            );
+            let selected = Select::new("Select a post to view:")
+                .with_options(options)
+                .with_help_message("Use arrow keys to navigate, Enter to select, Esc to cancel")
+                .ask()?;
 
-            if let Some(selected) = selection {
-                if selected.name.starts_with("Next Page") {
+            match selected.value {
+                BrowseChoice::Post(idx) => self.view_local_post(&page_posts[idx]).await?,
+                BrowseChoice::PageInfo => {}
+                BrowseChoice::NextPage => {
                     current_page = (current_page + 1).min(total_pages - 1);
-                    continue;
-                } else if selected.name.starts_with("◄ Previous Page") {
-                    current_page = current_page.saturating_sub(1);
-                    continue;
-                } else if selected.name.starts_with("◄ Back") || selected.name.starts_with("---")
-                {
-                    break;
                 }
-
-                let index = page_posts.iter().position(|lp| {
-                    format!(
-                        "ID: {} | Score: {} | Rating: {} | Favs: {} | {}",
-                        lp.post.id,
-                        lp.post.score.total,
-                        lp.post.rating,
-                        lp.post.fav_count,
-                        lp.post
-                            .tags
-                            .artist
-                            .first()
-                            .unwrap_or(&"unknown".to_string())
-                    ) == selected.name
-                });
-
-                if let Some(idx) = index {
-                    self.view_local_post(&page_posts[idx]).await?;
+                BrowseChoice::PreviousPage => {
+                    current_page = current_page.saturating_sub(1);
                 }
-            } else {
-                break;
+                BrowseChoice::Back => break,
             }
         }
 
@@ -968,7 +1975,7 @@ impl ExploreMenu for E6Ui {
                             eprintln!("Failed to display local image: {}", e);
                             if let Some(ref _url) = local_post.post.file.url {
                                 println!("Trying to fetch from URL instead...");
-                                print_post_to_terminal(local_post.post.clone())
+                                print_post_to_terminal(&self.client, local_post.post.clone())
                                     .await
                                     .context("Failed to view image from URL")?;
                             }
@@ -983,6 +1990,9 @@ impl ExploreMenu for E6Ui {
                     open::that(parent).context("Failed to open file location")?;
                     println!("Opened: {}", parent.display());
                 }
+                LocalPostInteractionMenu::CopyToStaging => {
+                    self.copy_to_staging(std::slice::from_ref(local_post))?;
+                }
                 LocalPostInteractionMenu::MakeQR => {
                     let url = format!("https://e621.net/posts/{}", local_post.post.id);
                     let qr = QrCode::with_version(
@@ -1000,6 +2010,11 @@ impl ExploreMenu for E6Ui {
                     println!("{}", str);
                 }
                 LocalPostInteractionMenu::ShowFullMetadata => {
+                    println!("\n{}", "=".repeat(70));
+                    print!("{}", format_post_metadata(&local_post.post));
+                    println!("{}", "=".repeat(70));
+                }
+                LocalPostInteractionMenu::ShowMetadataJson => {
                     println!("\n{}", "=".repeat(70));
                     println!(
                         "{}",
@@ -1029,15 +2044,78 @@ impl ExploreMenu for E6Ui {
 
         match selection {
             ExplorerFilterBy::AllRatings => state.filter_by_rating(None),
-            ExplorerFilterBy::Safe => state.filter_by_rating(Some("s".to_string())),
-            ExplorerFilterBy::Questionable => state.filter_by_rating(Some("q".to_string())),
-            ExplorerFilterBy::Explicit => state.filter_by_rating(Some("e".to_string())),
+            ExplorerFilterBy::Safe => state.filter_by_rating(Some(Rating::Safe)),
+            ExplorerFilterBy::Questionable => state.filter_by_rating(Some(Rating::Questionable)),
+            ExplorerFilterBy::Explicit => state.filter_by_rating(Some(Rating::Explicit)),
+            ExplorerFilterBy::Other => {
+                let unknown = state.get_statistics().unknown_ratings;
+                if unknown.is_empty() {
+                    println!("Every post is rated safe, questionable, or explicit");
+                    return Ok(());
+                }
+
+                let options = unknown
+                    .into_iter()
+                    .map(|(rating, count)| {
+                        AskOption::with_name(format!("{} ({} posts)", rating, count), rating)
+                    })
+                    .collect();
+                let rating = Select::new("Filter by rating:")
+                    .with_options(options)
+                    .ask()?
+                    .value;
+                state.filter_by_rating(Some(Rating::from(rating.as_str())));
+            }
         }
 
         println!("Showing {} posts", state.filtered_posts.len());
         Ok(())
     }
 
+    /// filter posts by download date
+    fn filter_by_download_date(&self, state: &mut ExplorerState) -> Result<()> {
+        let after = loop {
+            let input =
+                TextInput::new("Downloaded on or after (YYYY-MM-DD or e.g. 7d, empty for any):")
+                    .ask()?;
+            match parse_date_bound(&input) {
+                Ok(date) => break date,
+                Err(e) => println!("{}", e),
+            }
+        };
+
+        let before = loop {
+            let input =
+                TextInput::new("Downloaded on or before (YYYY-MM-DD or e.g. 1d, empty for any):")
+                    .ask()?;
+            match parse_date_bound(&input) {
+                Ok(date) => break date,
+                Err(e) => println!("{}", e),
+            }
+        };
+
+        state.filter_by_downloaded(after, before);
+        println!("Showing {} posts", state.filtered_posts.len());
+        Ok(())
+    }
+
+    /// filter posts by favorite count
+    fn filter_by_fav_count(&self, state: &mut ExplorerState) -> Result<()> {
+        let min_fav_count = loop {
+            let input = TextInput::new("Minimum favorites (0 for any):")
+                .with_default(state.min_fav_count.to_string())
+                .ask()?;
+            match input.trim().parse::<u64>() {
+                Ok(min) => break min,
+                Err(_) => println!("'{}' isn't a number of favorites", input.trim()),
+            }
+        };
+
+        state.set_min_fav_count(min_fav_count);
+        println!("Showing {} posts", state.filtered_posts.len());
+        Ok(())
+    }
+
     /// sort posts
     fn sort_posts(&self, state: &mut ExplorerState) -> Result<()> {
         let sort_by = ExplorerSortBy::select("Sort posts by:").ask()?.value;
@@ -1046,6 +2124,384 @@ impl ExploreMenu for E6Ui {
         Ok(())
     }
 
+    /// scan posts for corrupted files
+    async fn verify_integrity(&self, posts: &[LocalPost]) -> Result<()> {
+        let progress_manager = ProgressManager::new();
+        let pb = progress_manager
+            .create_count_bar(
+                "explorer_verify",
+                posts.len() as u64,
+                "Checking files for corruption",
+                false,
+            )
+            .await?;
+
+        let checks: Vec<(PathBuf, E6Post)> = posts
+            .iter()
+            .map(|lp| (lp.file_path.clone(), lp.post.clone()))
+            .collect();
+        let task_pb = pb.clone();
+        let corrupted: Vec<(PathBuf, E6Post, Corruption)> =
+            tokio::task::spawn_blocking(move || {
+                checks
+                    .into_par_iter()
+                    .filter_map(|(path, post)| {
+                        let result = check_integrity(&path, &post);
+                        task_pb.inc(1);
+                        result.map(|corruption| (path, post, corruption))
+                    })
+                    .collect()
+            })
+            .await
+            .context("integrity check failed")?;
+
+        pb.finish_with_message(format!("Checked {} files", posts.len()));
+        progress_manager.remove_bar("explorer_verify").await;
+
+        if corrupted.is_empty() {
+            println!(
+                "{} All {} files match their metadata",
+                "✓".green(),
+                posts.len()
+            );
+            return Ok(());
+        }
+
+        for (path, post, corruption) in &corrupted {
+            println!(
+                "{} {} (post #{}): {}",
+                "✗".red(),
+                path.display(),
+                post.id,
+                corruption
+            );
+        }
+        println!(
+            "\n{} of {} files don't match their metadata",
+            corrupted.len(),
+            posts.len()
+        );
+
+        if !Confirm::new(format!("Re-download {} files?", corrupted.len())).ask()? {
+            return Ok(());
+        }
+
+        let ids: Vec<i64> = corrupted.iter().map(|(_, post, _)| post.id).collect();
+        let mut fresh: HashMap<i64, E6Post> = self
+            .client
+            .get_posts_by_ids(&ids)
+            .await?
+            .into_iter()
+            .map(|res| (res.post.id, res.post))
+            .collect();
+
+        let replacements: Vec<(E6Post, PathBuf, PathBuf)> = corrupted
+            .into_iter()
+            .map(|(path, post, _)| {
+                let temp = redownload_path(&path);
+                (fresh.remove(&post.id).unwrap_or(post), path, temp)
+            })
+            .collect();
+        let jobs: Vec<(E6Post, PathBuf)> = replacements
+            .iter()
+            .map(|(post, _, temp)| (post.clone(), temp.clone()))
+            .collect();
+
+        let failed = self.downloader.clone().download_queued(jobs).await?;
+
+        let permanent = getopt!(explorer.permanent_delete);
+        for (post, path, temp) in replacements {
+            if failed.contains(&post.id) || !temp.is_file() {
+                println!(
+                    "{} Kept {}, its re-download failed",
+                    "✗".red(),
+                    path.display()
+                );
+                continue;
+            }
+
+            if let Err(e) = replace_download(&path, &temp, permanent) {
+                warn!("{}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// normalize the case and spacing of folder and file names
+    fn normalize_names(&self, state: &mut ExplorerState) -> Result<()> {
+        let reorganizer = FileReorganizer::new();
+        let options = ReorganizeOptions {
+            separate_by_rating: getopt!(download.separate_by_rating),
+            routes: getopt!(download.routes),
+            ..Default::default()
+        };
+
+        let moves = reorganizer.find_unnormalized(
+            state
+                .posts
+                .iter()
+                .map(|lp| (lp.file_path.as_path(), &lp.post)),
+            &options,
+        );
+
+        if moves.is_empty() {
+            println!(
+                "{} All {} files already match the current format's names",
+                "✓".green(),
+                state.posts.len()
+            );
+            return Ok(());
+        }
+
+        println!("\nDry run, these files would be renamed:");
+        for (old, new) in &moves {
+            println!("  {} -> {}", old.display(), new.display());
+        }
+
+        if !Confirm::new(format!("Rename {} files?", moves.len())).ask()? {
+            return Ok(());
+        }
+
+        let mut renamed: HashMap<PathBuf, PathBuf> = HashMap::new();
+        for (old, new) in moves {
+            match rename_normalized(&reorganizer, &old, &new) {
+                Ok(final_path) => {
+                    if let Some(parent) = old.parent() {
+                        let _ = std::fs::remove_dir(parent);
+                    }
+                    renamed.insert(old, final_path);
+                }
+                Err(e) => println!("{} {}: {}", "✗".red(), old.display(), e),
+            }
+        }
+
+        for lp in state
+            .posts
+            .iter_mut()
+            .chain(state.filtered_posts.iter_mut())
+        {
+            if let Some(new) = renamed.get(&lp.file_path) {
+                lp.file_path = new.clone();
+            }
+        }
+
+        println!("{} Renamed {} files", "✓".green(), renamed.len());
+        Ok(())
+    }
+
+    /// save a contact sheet of posts
+    fn contact_sheet(&self, posts: &[LocalPost]) -> Result<()> {
+        let default_columns = getopt!(explorer.contact_sheet_columns);
+        let columns = loop {
+            let input = TextInput::new("Columns:")
+                .with_default(default_columns.to_string())
+                .ask()?;
+            match input.trim().parse::<u32>() {
+                Ok(columns) if columns > 0 => break columns,
+                _ => println!("'{}' isn't a number of columns", input.trim()),
+            }
+        };
+
+        let labels = Confirm::new("Label posts with their ids?").ask()?;
+        let path = TextInput::new("Save contact sheet to:")
+            .with_default("contact-sheet.png")
+            .ask()?;
+
+        println!("Building a contact sheet of {} posts...", posts.len());
+        let files: Vec<SheetFile> = posts
+            .iter()
+            .map(|lp| SheetFile {
+                id: lp.post.id,
+                md5: Some(lp.post.file.md5.clone()).filter(|md5| !md5.is_empty()),
+                path: lp.file_path.clone(),
+            })
+            .collect();
+        let (sheets, skipped) = build_contact_sheet(
+            &files,
+            columns,
+            getopt!(explorer.contact_sheet_max_size),
+            labels,
+            Some(&ThumbnailCache::configured()),
+        );
+
+        if sheets.is_empty() {
+            println!("None of the shown posts are images, nothing to put on a contact sheet.");
+            return Ok(());
+        }
+
+        let mut path = PathBuf::from(path.trim());
+        if path.extension().is_none() {
+            path.set_extension("png");
+        }
+
+        let reorganizer = FileReorganizer::new();
+        let count = sheets.len();
+        for sheet in &sheets {
+            let sheet_path = if path.exists() {
+                reorganizer.find_unique_path(&path)?
+            } else {
+                path.clone()
+            };
+
+            sheet.save(&sheet_path).with_context(|| {
+                format!("failed to save contact sheet '{}'", sheet_path.display())
+            })?;
+
+            println!(
+                "{} Saved a {}x{} contact sheet to {}",
+                "✓".green(),
+                sheet.width(),
+                sheet.height(),
+                sheet_path.display()
+            );
+        }
+
+        println!(
+            "{} {} posts on {} sheet{}",
+            "✓".green(),
+            files.len() - skipped,
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+        if skipped > 0 {
+            println!("{} posts were left out because they aren't images", skipped);
+        }
+
+        Ok(())
+    }
+
+    /// copy posts into the staging folder
+    fn copy_to_staging(&self, posts: &[LocalPost]) -> Result<()> {
+        let staging = PathBuf::from(getopt!(explorer.staging_dir));
+        let staged = posts
+            .iter()
+            .filter(|lp| {
+                lp.file_path
+                    .file_name()
+                    .is_some_and(|name| staging.join(name).exists())
+            })
+            .count();
+
+        let conflict_resolution = if staged > 0 {
+            println!(
+                "{} of these files are already in {}",
+                staged,
+                staging.display()
+            );
+            ConflictMenu::select("Choose what to do when target file already exists")
+                .with_default(2)
+                .ask()?
+                .value
+        } else {
+            ConflictMenu::AutoRename
+        };
+
+        let reorganizer = FileReorganizer::new();
+        let mut copied = 0;
+        let mut skipped = 0;
+        for lp in posts {
+            match stage_file(&reorganizer, &lp.file_path, &staging, conflict_resolution) {
+                Ok(Some(_)) => copied += 1,
+                Ok(None) => skipped += 1,
+                Err(e) => println!("{} {}: {}", "✗".red(), lp.file_path.display(), e),
+            }
+        }
+
+        println!(
+            "{} Copied {} of {} posts to {}",
+            "✓".green(),
+            copied,
+            posts.len(),
+            staging.display()
+        );
+        if skipped > 0 {
+            println!("{} posts were already staged and left alone", skipped);
+        }
+
+        Ok(())
+    }
+
+    /// export how often each tag shows up in the filtered posts
+    fn export_tag_frequencies(&self, state: &ExplorerState) -> Result<()> {
+        let categories = self.select_tag_categories()?;
+        if categories.is_empty() {
+            println!("No tag categories selected.");
+            return Ok(());
+        }
+
+        let path = TextInput::new("Save tag frequencies to:")
+            .with_default("tag-frequencies.tsv")
+            .with_help_message("End the name with .json to save json instead of tsv")
+            .ask()?;
+
+        let counts = state.tag_frequencies(&categories);
+        write_tag_frequencies(Path::new(&path), &counts)?;
+
+        println!(
+            "{} Exported the frequencies of {} tags across {} posts to {}",
+            "✓".green(),
+            counts.len(),
+            state.filtered_posts.len(),
+            path
+        );
+
+        Ok(())
+    }
+
+    /// play the videos among posts as a playlist
+    fn play_videos(&self, posts: &[LocalPost]) -> Result<()> {
+        let mut videos: Vec<PathBuf> = posts
+            .iter()
+            .map(|lp| &lp.file_path)
+            .filter(|path| is_video(path))
+            .map(|path| std::path::absolute(path).unwrap_or(path.clone()))
+            .collect();
+
+        if videos.is_empty() {
+            println!("None of the shown posts are videos.");
+            return Ok(());
+        }
+
+        let shuffle = Confirm::new("Shuffle?").ask()?;
+        let repeat = Confirm::new("Loop the playlist?").ask()?;
+
+        if shuffle {
+            videos.shuffle(&mut rand::rng());
+        }
+
+        let playlist = std::env::temp_dir().join(format!("e62rs-{}.m3u", std::process::id()));
+        write_playlist(&playlist, &videos)?;
+
+        let player = getopt!(explorer.video_player);
+        let mut player_args = player.split_whitespace();
+        let player_cmd = player_args.next().unwrap_or("mpv");
+        let mut command = std::process::Command::new(player_cmd);
+        command.args(player_args);
+        if repeat {
+            command.args(getopt!(explorer.video_player_loop_args).split_whitespace());
+        }
+
+        println!(
+            "Playing {} videos with {} ({} skipped because they aren't videos)",
+            videos.len(),
+            player_cmd,
+            posts.len() - videos.len()
+        );
+
+        let status = command
+            .arg(&playlist)
+            .status()
+            .with_context(|| format!("failed to start '{}'", player_cmd));
+        let _ = std::fs::remove_file(&playlist);
+
+        if !status?.success() {
+            warn!("{} exited with an error", player_cmd);
+        }
+
+        Ok(())
+    }
+
     /// display explorer stats
     fn display_statistics(&self, state: &ExplorerState) {
         let stats = state.get_statistics();
@@ -1055,12 +2511,18 @@ impl ExploreMenu for E6Ui {
         println!("{}", "=".repeat(50));
         println!("Total posts: {}", stats.total_posts);
         println!("Filtered posts: {}", stats.filtered_posts);
+        if stats.hidden_by_score > 0 {
+            println!(
+                "Hidden by score filter: {} (below {})",
+                stats.hidden_by_score, state.hide_below_score
+            );
+        }
         println!("\nBy Rating:");
         println!("  Safe: {}", stats.safe);
         println!("  Questionable: {}", stats.questionable);
         println!("  Explicit: {}", stats.explicit);
-        if stats.unknown > 0 {
-            println!("  Unknown: {}", stats.unknown);
+        for (rating, count) in &stats.unknown_ratings {
+            println!("  Other ({}): {}", rating, count);
         }
         println!("\nStatistics:");
         println!("  Average score: {:.2}", stats.avg_score);
@@ -1069,18 +2531,449 @@ impl ExploreMenu for E6Ui {
         if let Some(ref query) = state.search_query {
             println!("\nCurrent search: \"{}\"", query);
         }
-        if let Some(ref rating) = state.rating_filter {
+        if let Some(ref expr) = state.tag_expr {
+            println!("Current tag expression: {}", expr);
+        }
+        if state.downloaded_after.is_some() || state.downloaded_before.is_some() {
             println!(
-                "Current rating filter: {}",
-                match rating.as_str() {
-                    "s" => "Safe",
-                    "q" => "Questionable",
-                    "e" => "Explicit",
-                    _ => "Unknown",
-                }
+                "Current download date filter: {} to {}",
+                state
+                    .downloaded_after
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "any".to_string()),
+                state
+                    .downloaded_before
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "any".to_string())
             );
         }
+        if state.min_fav_count > 0 {
+            println!("Current favorites filter: at least {}", state.min_fav_count);
+        }
+        if let Some(ref rating) = state.rating_filter {
+            println!("Current rating filter: {}", rating);
+        }
         println!("Current sort: {:?}", state.current_sort);
         println!("{}", "=".repeat(50));
     }
 }
+
+#[cfg(test)]
+#[cfg(not(target_os = "windows"))]
+mod tests {
+    use {super::*, hashbrown::HashSet, tempfile::TempDir};
+
+    /// write an empty download with the given metadata sidecar contents
+    fn write_download(dir: &Path, name: &str, metadata: Option<&str>) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, b"").unwrap();
+
+        if let Some(metadata) = metadata {
+            std::fs::write(path.with_extension("png.json"), metadata).unwrap();
+        }
+
+        path
+    }
+
+    #[test]
+    fn test_replace_download() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("1.png");
+        std::fs::write(&path, b"corrupt").unwrap();
+
+        let temp = redownload_path(&path);
+        assert_eq!(temp, dir.path().join("1.redownload.png"));
+        std::fs::write(&temp, b"fresh").unwrap();
+        std::fs::write(metadata::sidecar_path(&path), b"old").unwrap();
+        std::fs::write(metadata::sidecar_path(&temp), b"new").unwrap();
+
+        replace_download(&path, &temp, true).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"fresh");
+        assert_eq!(
+            std::fs::read(metadata::sidecar_path(&path)).unwrap(),
+            b"new"
+        );
+        assert!(!temp.exists());
+        assert!(!metadata::sidecar_path(&temp).exists());
+    }
+
+    #[test]
+    fn test_rename_normalized_case_only() {
+        let dir = TempDir::new().unwrap();
+        let old = write_download(dir.path(), "Fox.png", None);
+        let new = dir.path().join("fox.png");
+
+        let renamed = rename_normalized(&FileReorganizer::new(), &old, &new).unwrap();
+        assert_eq!(renamed, new);
+
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("fox.png")]);
+    }
+
+    #[test]
+    fn test_rename_normalized_folder_case() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("Fox")).unwrap();
+        let old = write_download(&dir.path().join("Fox"), "1.png", None);
+        let new = dir.path().join("fox").join("1.png");
+
+        let renamed = rename_normalized(&FileReorganizer::new(), &old, &new).unwrap();
+        assert_eq!(renamed, new);
+        assert!(new.is_file());
+
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let entry = entry.unwrap();
+            if entry.file_name() != "fox" {
+                assert_eq!(std::fs::read_dir(entry.path()).unwrap().count(), 0);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_local_posts_order_independent() {
+        let dir = TempDir::new().unwrap();
+        let mut files: Vec<PathBuf> = (1..=50)
+            .map(|id| {
+                write_download(
+                    dir.path(),
+                    &format!("{}.png", id),
+                    Some(&format!("{{\"id\": {}}}", id)),
+                )
+            })
+            .collect();
+        files.push(write_download(dir.path(), "no_metadata.png", None));
+        files.push(write_download(dir.path(), "broken.png", Some("{")));
+
+        let ids = |posts: &[LocalPost]| posts.iter().map(|lp| lp.post.id).collect::<HashSet<_>>();
+
+        let (sequential, seq_skipped) =
+            load_local_posts(files.clone(), 1, false, MetadataBackend::Sidecar, None).await;
+        files.reverse();
+        let (parallel, par_skipped) =
+            load_local_posts(files, 16, false, MetadataBackend::Db, None).await;
+
+        assert_eq!(sequential.len(), 50);
+        assert_eq!(ids(&sequential), ids(&parallel));
+        assert_eq!(ids(&sequential), (1..=50).collect::<HashSet<i64>>());
+        assert_eq!((seq_skipped, par_skipped), (1, 1));
+    }
+
+    #[test]
+    fn test_check_integrity() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("1.png");
+        std::fs::write(&path, b"one").unwrap();
+
+        let mut post = E6Post::default();
+        assert_eq!(check_integrity(&path, &post), None);
+
+        post.file.size = 3;
+        post.file.md5 = "F97C5D29941BFB1B2FDAB0874906AB82".to_string();
+        assert_eq!(check_integrity(&path, &post), None);
+
+        post.file.size = 4;
+        assert_eq!(
+            check_integrity(&path, &post),
+            Some(Corruption::Size {
+                expected: 4,
+                actual: 3
+            })
+        );
+
+        std::fs::write(&path, b"uno").unwrap();
+        post.file.size = 3;
+        assert!(matches!(
+            check_integrity(&path, &post),
+            Some(Corruption::Md5 { .. })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            check_integrity(&path, &post),
+            Some(Corruption::Unreadable(_))
+        ));
+    }
+
+    #[test]
+    fn test_hide_below_score() {
+        let posts = [-10, 0, 5, 20]
+            .into_iter()
+            .map(|score| {
+                let mut post = E6Post::default();
+                post.score.total = score;
+                LocalPost {
+                    post,
+                    file_path: PathBuf::new(),
+                    downloaded_at: None,
+                }
+            })
+            .collect();
+
+        let mut state = ExplorerState::new(posts);
+        state.set_hide_below_score(5);
+        assert_eq!(state.filtered_posts.len(), 2);
+        assert_eq!(state.get_statistics().hidden_by_score, 2);
+
+        state.filter_by_rating(None);
+        assert_eq!(state.hidden_by_score, 2);
+        assert!(
+            state
+                .filtered_posts
+                .iter()
+                .all(|lp| lp.post.score.total >= 5)
+        );
+    }
+
+    #[test]
+    fn test_min_resolution() {
+        let posts = [(800, 600), (1920, 1080), (1080, 1920)]
+            .into_iter()
+            .map(|(width, height)| {
+                let mut post = E6Post::default();
+                post.file.width = width;
+                post.file.height = height;
+                LocalPost {
+                    post,
+                    file_path: PathBuf::new(),
+                    downloaded_at: None,
+                }
+            })
+            .collect();
+
+        let mut state = ExplorerState::new(posts);
+        state.set_min_resolution(1000, 0);
+        assert_eq!(state.filtered_posts.len(), 2);
+
+        state.set_min_resolution(1000, 1500);
+        assert_eq!(state.filtered_posts.len(), 1);
+        assert_eq!(state.filtered_posts[0].post.file.height, 1920);
+
+        state.set_min_resolution(0, 0);
+        assert_eq!(state.filtered_posts.len(), 3);
+    }
+
+    #[test]
+    fn test_min_fav_count() {
+        let posts = [3, 50, 120]
+            .into_iter()
+            .map(|fav_count| LocalPost {
+                post: E6Post {
+                    fav_count,
+                    ..Default::default()
+                },
+                file_path: PathBuf::new(),
+                downloaded_at: None,
+            })
+            .collect();
+
+        let mut state = ExplorerState::new(posts);
+        state.set_min_fav_count(50);
+        assert_eq!(state.filtered_posts.len(), 2);
+
+        state.set_min_fav_count(100);
+        assert_eq!(state.filtered_posts.len(), 1);
+        assert_eq!(state.filtered_posts[0].post.fav_count, 120);
+
+        state.set_min_fav_count(0);
+        assert_eq!(state.filtered_posts.len(), 3);
+    }
+
+    #[test]
+    fn test_unknown_ratings() {
+        let posts = ["s", "E", "g", "g", ""]
+            .into_iter()
+            .map(|rating| LocalPost {
+                post: E6Post {
+                    rating: rating.to_string(),
+                    ..Default::default()
+                },
+                file_path: PathBuf::new(),
+                downloaded_at: None,
+            })
+            .collect();
+
+        let mut state = ExplorerState::new(posts);
+        let stats = state.get_statistics();
+        assert_eq!((stats.safe, stats.explicit, stats.unknown), (1, 1, 3));
+        assert_eq!(
+            stats.unknown_ratings.into_iter().collect::<Vec<_>>(),
+            vec![("g".to_string(), 2), ("unknown".to_string(), 1)]
+        );
+
+        state.filter_by_rating(Some(Rating::from("explicit")));
+        assert_eq!(state.filtered_posts.len(), 1);
+
+        state.filter_by_rating(Some(Rating::from(" G ")));
+        assert_eq!(state.filtered_posts.len(), 2);
+        assert!(
+            state
+                .filtered_posts
+                .iter()
+                .all(|lp| lp.post.content_rating() == Rating::Unknown("g".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_write_playlist() {
+        let dir = TempDir::new().unwrap();
+        let files = [
+            dir.path().join("1.webm"),
+            dir.path().join("2.png"),
+            dir.path().join("3.MP4"),
+        ];
+        let videos: Vec<PathBuf> = files.iter().filter(|f| is_video(f)).cloned().collect();
+        assert_eq!(videos, vec![files[0].clone(), files[2].clone()]);
+
+        let playlist = dir.path().join("playlist.m3u");
+        write_playlist(&playlist, &videos).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&playlist).unwrap(),
+            format!("#EXTM3U\n{}\n{}\n", files[0].display(), files[2].display())
+        );
+    }
+
+    #[test]
+    fn test_stage_file() {
+        let dir = TempDir::new().unwrap();
+        let source = write_download(dir.path(), "1.png", Some("{\"id\": 1}"));
+        let staging = dir.path().join("staging");
+        let reorganizer = FileReorganizer::new();
+        let stage = |path: &Path, conflict| stage_file(&reorganizer, path, &staging, conflict);
+
+        let staged = staging.join("1.png");
+        assert_eq!(
+            stage(&source, ConflictMenu::AutoRename).unwrap(),
+            Some(staged.clone())
+        );
+        assert!(source.is_file());
+        assert!(staging.join("1.png.json").is_file());
+
+        assert_eq!(stage(&source, ConflictMenu::Skip).unwrap(), None);
+        let renamed = stage(&source, ConflictMenu::AutoRename).unwrap();
+        assert!(renamed.is_some_and(|path| path != staged && path.is_file()));
+
+        assert_eq!(stage(&staged, ConflictMenu::Overwrite).unwrap(), None);
+        assert!(staged.is_file());
+    }
+
+    #[test]
+    fn test_tag_frequencies() {
+        let post = |general: &[&str], species: &[&str]| {
+            let mut post = E6Post::default();
+            post.tags.general = general.iter().map(|t| t.to_string()).collect();
+            post.tags.species = species.iter().map(|t| t.to_string()).collect();
+            LocalPost {
+                post,
+                file_path: PathBuf::new(),
+                downloaded_at: None,
+            }
+        };
+
+        let state = ExplorerState::new(vec![
+            post(&["solo", "smile"], &["fox"]),
+            post(&["solo"], &["wolf"]),
+            post(&["duo", "smile"], &["fox"]),
+        ]);
+
+        let counts = state.tag_frequencies(&[TagCategory::General, TagCategory::Species]);
+        assert_eq!(
+            counts,
+            vec![
+                ("fox".to_string(), 2),
+                ("smile".to_string(), 2),
+                ("solo".to_string(), 2),
+                ("duo".to_string(), 1),
+                ("wolf".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            state.tag_frequencies(&[TagCategory::Species]),
+            vec![("fox".to_string(), 2), ("wolf".to_string(), 1)]
+        );
+
+        let dir = TempDir::new().unwrap();
+        let tsv = dir.path().join("tags.tsv");
+        write_tag_frequencies(&tsv, &counts[..2]).unwrap();
+        assert_eq!(std::fs::read_to_string(&tsv).unwrap(), "fox\t2\nsmile\t2\n");
+
+        let json = dir.path().join("tags.json");
+        write_tag_frequencies(&json, &counts[..1]).unwrap();
+        let parsed: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+        assert_eq!(parsed, serde_json::json!([{ "tag": "fox", "count": 2 }]));
+    }
+
+    #[test]
+    fn test_filter_and_sort_by_downloaded() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 6, d).expect("valid date");
+        let posts = [(1, Some(10)), (2, Some(3)), (3, Some(20)), (4, None)]
+            .into_iter()
+            .map(|(id, downloaded)| LocalPost {
+                post: E6Post {
+                    id,
+                    ..Default::default()
+                },
+                file_path: PathBuf::new(),
+                downloaded_at: downloaded.map(|d: u32| {
+                    day(d)
+                        .and_hms_opt(12, 0, 0)
+                        .expect("valid time")
+                        .and_local_timezone(Local)
+                        .unwrap()
+                }),
+            })
+            .collect();
+
+        let mut state = ExplorerState::new(posts);
+        state.sort(ExplorerSortBy::DownloadedNewest);
+        let ids: Vec<i64> = state.filtered_posts.iter().map(|lp| lp.post.id).collect();
+        assert_eq!(ids, [3, 1, 2, 4]);
+
+        state.filter_by_downloaded(Some(day(3)), Some(day(10)));
+        state.sort(ExplorerSortBy::DownloadedOldest);
+        let ids: Vec<i64> = state.filtered_posts.iter().map(|lp| lp.post.id).collect();
+        assert_eq!(ids, [2, 1]);
+
+        state.filter_by_downloaded(None, None);
+        assert_eq!(state.filtered_posts.len(), 4);
+    }
+
+    #[test]
+    fn test_slideshow_status() {
+        let mut status = SlideshowStatus {
+            position: 12,
+            total: 340,
+            remaining: Duration::from_millis(3200),
+            paused: false,
+            shuffle: true,
+            looping: false,
+        };
+        assert_eq!(
+            status.to_string(),
+            "[12/340] next in 4s | shuffle: on | loop: off"
+        );
+
+        status.paused = true;
+        assert_eq!(
+            status.to_string(),
+            "[12/340] paused | shuffle: on | loop: off"
+        );
+    }
+
+    #[test]
+    fn test_parse_date_bound() {
+        assert_eq!(parse_date_bound("").unwrap(), None);
+        assert_eq!(
+            parse_date_bound("2024-06-01").unwrap(),
+            NaiveDate::from_ymd_opt(2024, 6, 1)
+        );
+        assert_eq!(
+            parse_date_bound("7d").unwrap(),
+            Local::now().date_naive().checked_sub_days(Days::new(7))
+        );
+        assert!(parse_date_bound("last week").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! boolean tag expressions for the downloads explorer
+//!
+//! an expression is a whitespace separated list of terms that must all match:
+//! * `tag` - the post must have the tag
+//! * `-tag` - the post must not have the tag
+//! * `a|b` or `a | b` - the post must have at least one of the tags
+//! * `~a ~b` - e6-style OR, the post must have at least one of the `~` tags
+//!
+//! tags are matched case-insensitively against every tag category on the post
+use {
+    crate::{bail, error::Result, models::E6Post},
+    hashbrown::HashSet,
+};
+
+/// a single term in a tag expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagTerm {
+    /// the post must have this tag
+    Include(String),
+    /// the post must not have this tag
+    Exclude(String),
+    /// the post must have at least one of these tags
+    AnyOf(Vec<String>),
+}
+
+/// a parsed tag expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagExpr {
+    /// the terms that all have to match
+    terms: Vec<TagTerm>,
+}
+
+impl TagExpr {
+    /// parse a tag expression
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - the expression to parse (e.g. `canine -feral` or `cat | dog`)
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the expression is empty or has a dangling `-`, `~`, or `|`
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut tokens: Vec<String> = Vec::new();
+        let mut join_next = false;
+
+        for token in query.split_whitespace() {
+            let token = token.to_lowercase();
+            let starts_with_pipe = token.starts_with('|');
+            let ends_with_pipe = token.ends_with('|');
+
+            if (join_next || starts_with_pipe)
+                && let Some(last) = tokens.last_mut()
+            {
+                last.push('|');
+                last.push_str(token.trim_start_matches('|'));
+            } else if starts_with_pipe {
+                bail!("'|' needs a tag on both sides");
+            } else {
+                tokens.push(token);
+            }
+
+            join_next = ends_with_pipe;
+            if join_next && let Some(last) = tokens.last_mut() {
+                last.truncate(last.trim_end_matches('|').len());
+            }
+        }
+
+        if join_next {
+            bail!("'|' needs a tag on both sides");
+        }
+
+        let mut terms = Vec::new();
+        let mut tilde_group = Vec::new();
+
+        for token in tokens {
+            if token.contains('|') {
+                let options: Vec<String> = token.split('|').map(str::to_string).collect();
+                if options.iter().any(|o| o.is_empty() || o.starts_with('-')) {
+                    bail!("invalid OR group '{}'", token);
+                }
+
+                terms.push(TagTerm::AnyOf(options));
+            } else if let Some(tag) = token.strip_prefix('-') {
+                if tag.is_empty() {
+                    bail!("'-' needs a tag to exclude");
+                }
+
+                terms.push(TagTerm::Exclude(tag.to_string()));
+            } else if let Some(tag) = token.strip_prefix('~') {
+                if tag.is_empty() {
+                    bail!("'~' needs a tag");
+                }
+
+                tilde_group.push(tag.to_string());
+            } else {
+                terms.push(TagTerm::Include(token));
+            }
+        }
+
+        if !tilde_group.is_empty() {
+            terms.push(TagTerm::AnyOf(tilde_group));
+        }
+
+        if terms.is_empty() {
+            bail!("tag expression is empty");
+        }
+
+        Ok(Self { terms })
+    }
+
+    /// get the terms of this expression
+    pub fn terms(&self) -> &[TagTerm] {
+        &self.terms
+    }
+
+    /// check whether a post matches this expression
+    ///
+    /// # Arguments
+    ///
+    /// * `post` - the post to check
+    pub fn matches(&self, post: &E6Post) -> bool {
+        let tags: HashSet<String> = [
+            &post.tags.general,
+            &post.tags.artist,
+            &post.tags.contributor,
+            &post.tags.copyright,
+            &post.tags.character,
+            &post.tags.species,
+            &post.tags.invalid,
+            &post.tags.meta,
+            &post.tags.lore,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|t| t.to_lowercase())
+        .collect();
+
+        self.terms.iter().all(|term| match term {
+            TagTerm::Include(tag) => tags.contains(tag),
+            TagTerm::Exclude(tag) => !tags.contains(tag),
+            TagTerm::AnyOf(options) => options.iter().any(|o| tags.contains(o)),
+        })
+    }
+}
+
+impl std::fmt::Display for TagExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let terms: Vec<String> = self
+            .terms
+            .iter()
+            .map(|term| match term {
+                TagTerm::Include(tag) => tag.clone(),
+                TagTerm::Exclude(tag) => format!("-{}", tag),
+                TagTerm::AnyOf(options) => options.join(" | "),
+            })
+            .collect();
+
+        write!(f, "{}", terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// make a post with the given general and species tags
+    fn post_with(general: &[&str], species: &[&str]) -> E6Post {
+        let mut post = E6Post::default();
+        post.tags.general = general.iter().map(|t| t.to_string()).collect();
+        post.tags.species = species.iter().map(|t| t.to_string()).collect();
+        post
+    }
+
+    #[test]
+    fn test_include() {
+        let expr = TagExpr::parse("canine solo").unwrap();
+        assert!(expr.matches(&post_with(&["solo"], &["canine"])));
+        assert!(!expr.matches(&post_with(&["duo"], &["canine"])));
+    }
+
+    #[test]
+    fn test_exclude() {
+        let expr = TagExpr::parse("canine -feral").unwrap();
+        assert!(expr.matches(&post_with(&["anthro"], &["canine"])));
+        assert!(!expr.matches(&post_with(&["feral"], &["canine"])));
+    }
+
+    #[test]
+    fn test_or() {
+        for query in ["cat|dog", "cat | dog", "cat |dog", "cat| dog", "~cat ~dog"] {
+            let expr = TagExpr::parse(query).unwrap();
+            assert_eq!(
+                expr.terms(),
+                &[TagTerm::AnyOf(vec!["cat".to_string(), "dog".to_string()])],
+                "{query}"
+            );
+            assert!(expr.matches(&post_with(&[], &["dog"])), "{query}");
+            assert!(!expr.matches(&post_with(&[], &["fox"])), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_combined() {
+        let expr = TagExpr::parse("Solo cat | dog -Feral").unwrap();
+        assert!(expr.matches(&post_with(&["solo"], &["cat"])));
+        assert!(!expr.matches(&post_with(&["solo", "feral"], &["cat"])));
+        assert!(!expr.matches(&post_with(&["duo"], &["dog"])));
+    }
+
+    #[test]
+    fn test_invalid() {
+        for query in [
+            "", "   ", "-", "~", "cat |", "| cat", "cat|-dog", "cat||dog",
+        ] {
+            assert!(TagExpr::parse(query).is_err(), "{query}");
+        }
+    }
+}
@@ -0,0 +1,394 @@
+//! contact sheets for the downloads explorer
+//!
+//! a contact sheet is a single png with a downsized copy of every shown post laid out in a grid,
+//! optionally with each post's id drawn under it. files that can't be decoded as images (videos,
+//! flash) are left out. posts that don't fit on one sheet go on more sheets
+use {
+    crate::data::thumbs::ThumbnailCache,
+    image::{ImageReader, Rgba, RgbaImage, imageops},
+    rayon::prelude::*,
+    std::path::{Path, PathBuf},
+};
+
+/// the biggest a single cell can be, in pixels
+const MAX_CELL_SIZE: u32 = 256;
+
+/// the smallest a cell can be before images spill onto another sheet, in pixels
+const MIN_CELL_SIZE: u32 = 32;
+
+/// how many thumbnails are decoded at once while drawing a sheet
+const DECODE_BATCH: usize = 64;
+
+/// the space between cells, in pixels
+const PADDING: u32 = 4;
+
+/// the sheet's background color
+const BACKGROUND: Rgba<u8> = Rgba([24, 24, 27, 255]);
+
+/// the color of the id labels
+const LABEL_COLOR: Rgba<u8> = Rgba([230, 230, 230, 255]);
+
+/// a 3x5 bitmap of each digit, one row per byte with the leftmost pixel in bit 2
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// a file to put on a contact sheet
+#[derive(Debug, Clone)]
+pub struct SheetFile {
+    /// the id of the post, drawn in its label
+    pub id: i64,
+    /// the md5 of the file, if it's known (used to find its cached thumbnail)
+    pub md5: Option<String>,
+    /// the downloaded file
+    pub path: PathBuf,
+}
+
+/// how a contact sheet is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SheetLayout {
+    /// the number of columns
+    pub columns: u32,
+    /// the number of rows
+    pub rows: u32,
+    /// the width and height of a cell (the space an image gets)
+    pub cell: u32,
+    /// the height of the label under each cell (0 without labels)
+    pub label: u32,
+}
+
+impl SheetLayout {
+    /// fit `count` images into a grid that's at most `max_size` pixels on either side
+    ///
+    /// `count` should be at most [`SheetLayout::capacity`], more than that can't fit
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - the number of images
+    /// * `columns` - the number of columns (capped to `count` and to what fits)
+    /// * `max_size` - the largest the sheet can be on either side
+    /// * `labels` - whether to leave room for id labels
+    pub fn fit(count: usize, columns: u32, max_size: u32, labels: bool) -> Self {
+        let count = count.max(1) as u32;
+        let columns = columns.clamp(1, count.min(Self::max_columns(max_size)));
+        let rows = count.div_ceil(columns);
+
+        let label_of = |cell: u32| if labels { label_scale(cell) * 7 } else { 0 };
+        let cell = (1..=MAX_CELL_SIZE)
+            .rev()
+            .find(|&cell| {
+                columns * (cell + PADDING) + PADDING <= max_size
+                    && rows * (cell + label_of(cell) + PADDING) + PADDING <= max_size
+            })
+            .unwrap_or(1);
+
+        Self {
+            columns,
+            rows,
+            cell,
+            label: label_of(cell),
+        }
+    }
+
+    /// the most columns that fit on a sheet with cells of at least [`MIN_CELL_SIZE`]
+    fn max_columns(max_size: u32) -> u32 {
+        (max_size.saturating_sub(PADDING) / (MIN_CELL_SIZE + PADDING)).max(1)
+    }
+
+    /// the most images that fit on one sheet with cells of at least [`MIN_CELL_SIZE`]
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - the number of columns
+    /// * `max_size` - the largest the sheet can be on either side
+    /// * `labels` - whether to leave room for id labels
+    pub fn capacity(columns: u32, max_size: u32, labels: bool) -> usize {
+        let columns = columns.clamp(1, Self::max_columns(max_size));
+        let label = if labels {
+            label_scale(MIN_CELL_SIZE) * 7
+        } else {
+            0
+        };
+        let rows = (max_size.saturating_sub(PADDING) / (MIN_CELL_SIZE + label + PADDING)).max(1);
+
+        (columns * rows) as usize
+    }
+
+    /// the width and height of the whole sheet
+    pub fn size(&self) -> (u32, u32) {
+        (
+            self.columns * (self.cell + PADDING) + PADDING,
+            self.rows * (self.cell + self.label + PADDING) + PADDING,
+        )
+    }
+
+    /// the top left corner of the cell at `index`
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the position of the image on the sheet
+    pub fn origin(&self, index: u32) -> (u32, u32) {
+        let (col, row) = (index % self.columns, index / self.columns);
+        (
+            PADDING + col * (self.cell + PADDING),
+            PADDING + row * (self.cell + self.label + PADDING),
+        )
+    }
+}
+
+/// how big a pixel of the label font is for a given cell size
+fn label_scale(cell: u32) -> u32 {
+    (cell / 64).max(1)
+}
+
+/// draw a post id in the label font
+///
+/// # Arguments
+///
+/// * `sheet` - the image to draw on
+/// * `id` - the id to draw
+/// * `x` - the left edge of the text
+/// * `y` - the top edge of the text
+/// * `scale` - how big a font pixel is
+/// * `max_width` - the text is cut off past this many pixels
+fn draw_id(sheet: &mut RgbaImage, id: i64, x: u32, y: u32, scale: u32, max_width: u32) {
+    for (i, digit) in id.to_string().bytes().enumerate() {
+        let Some(glyph) = digit.checked_sub(b'0').and_then(|d| DIGITS.get(d as usize)) else {
+            continue;
+        };
+
+        let left = i as u32 * 4 * scale;
+        if left + 3 * scale > max_width {
+            break;
+        }
+
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let (px, py) = (x + left + col * scale + dx, y + row as u32 * scale + dy);
+                        if px < sheet.width() && py < sheet.height() {
+                            sheet.put_pixel(px, py, LABEL_COLOR);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// build contact sheets of the given files
+///
+/// returns the sheets and the number of files that were left out because they couldn't be
+/// decoded. files that don't fit on one sheet (see [`SheetLayout::capacity`]) go on the next,
+/// and there are no sheets if none of them could be decoded. thumbnails are decoded
+/// [`DECODE_BATCH`] at a time and drawn straight onto their sheet, so memory use doesn't grow
+/// with the number of files
+///
+/// # Arguments
+///
+/// * `files` - every file, in the order they go on the sheet
+/// * `columns` - the number of columns
+/// * `max_size` - the largest the sheet can be on either side, in pixels
+/// * `labels` - whether to draw each post's id under it
+/// * `thumbnails` - where to reuse and keep thumbnails (every file is decoded without one)
+pub fn build_contact_sheet(
+    files: &[SheetFile],
+    columns: u32,
+    max_size: u32,
+    labels: bool,
+    thumbnails: Option<&ThumbnailCache>,
+) -> (Vec<RgbaImage>, usize) {
+    let images: Vec<&SheetFile> = files
+        .par_iter()
+        .filter(|file| is_image(&file.path))
+        .collect();
+    let mut skipped = files.len() - images.len();
+
+    let sheets = images
+        .chunks(SheetLayout::capacity(columns, max_size, labels))
+        .map(|chunk| {
+            let (sheet, failed) = draw_sheet(chunk, columns, max_size, labels, thumbnails);
+            skipped += failed;
+            sheet
+        })
+        .collect();
+
+    (sheets, skipped)
+}
+
+/// whether a file looks like an image, going by its header
+fn is_image(path: &Path) -> bool {
+    ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .is_ok_and(|reader| reader.into_dimensions().is_ok())
+}
+
+/// lay out the thumbnails of files on a single sheet
+///
+/// returns the sheet and the number of files that couldn't be decoded after all (their cells
+/// are left empty)
+///
+/// # Arguments
+///
+/// * `files` - the files to draw, at most [`SheetLayout::capacity`] of them
+/// * `columns` - the number of columns
+/// * `max_size` - the largest the sheet can be on either side, in pixels
+/// * `labels` - whether to draw each post's id under it
+/// * `thumbnails` - where to reuse and keep thumbnails
+fn draw_sheet(
+    files: &[&SheetFile],
+    columns: u32,
+    max_size: u32,
+    labels: bool,
+    thumbnails: Option<&ThumbnailCache>,
+) -> (RgbaImage, usize) {
+    let layout = SheetLayout::fit(files.len(), columns, max_size, labels);
+    let (width, height) = layout.size();
+    let mut sheet = RgbaImage::from_pixel(width, height, BACKGROUND);
+    let mut failed = 0;
+
+    for (batch_index, batch) in files.chunks(DECODE_BATCH).enumerate() {
+        let thumbs: Vec<Option<RgbaImage>> = batch
+            .par_iter()
+            .map(|file| {
+                let thumb = match thumbnails {
+                    Some(cache) => cache
+                        .load(&file.path, file.md5.as_deref(), MAX_CELL_SIZE)
+                        .ok()?,
+                    None => image::open(&file.path).ok()?,
+                };
+                Some(thumb.thumbnail(layout.cell, layout.cell).to_rgba8())
+            })
+            .collect();
+
+        for (i, (file, thumb)) in batch.iter().zip(thumbs).enumerate() {
+            let Some(thumb) = thumb else {
+                failed += 1;
+                continue;
+            };
+
+            let (x, y) = layout.origin((batch_index * DECODE_BATCH + i) as u32);
+            let (offset_x, offset_y) = (
+                (layout.cell - thumb.width()) / 2,
+                (layout.cell - thumb.height()) / 2,
+            );
+            imageops::overlay(
+                &mut sheet,
+                &thumb,
+                (x + offset_x) as i64,
+                (y + offset_y) as i64,
+            );
+
+            if layout.label > 0 {
+                let scale = label_scale(layout.cell);
+                draw_id(
+                    &mut sheet,
+                    file.id,
+                    x,
+                    y + layout.cell + scale,
+                    scale,
+                    layout.cell,
+                );
+            }
+        }
+    }
+
+    (sheet, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_layout_respects_max_size() {
+        let layout = SheetLayout::fit(100, 10, 1000, true);
+        assert_eq!((layout.columns, layout.rows), (10, 10));
+        let (width, height) = layout.size();
+        assert!(width <= 1000 && height <= 1000);
+
+        let small = SheetLayout::fit(3, 10, 4096, false);
+        assert_eq!((small.columns, small.rows), (3, 1));
+        assert_eq!(small.cell, MAX_CELL_SIZE);
+        assert_eq!(small.label, 0);
+    }
+
+    #[test]
+    fn test_layout_capacity() {
+        for (columns, max_size, labels) in [(6, 4096, true), (500, 256, false), (1, 256, true)] {
+            let capacity = SheetLayout::capacity(columns, max_size, labels);
+            let layout = SheetLayout::fit(capacity, columns, max_size, labels);
+            let (width, height) = layout.size();
+            assert!(width <= max_size && height <= max_size);
+            assert!(layout.cell >= MIN_CELL_SIZE);
+            assert!((layout.columns * layout.rows) as usize >= capacity);
+        }
+    }
+
+    #[test]
+    fn test_build_contact_sheet() {
+        let dir = TempDir::new().unwrap();
+        let image = dir.path().join("1.png");
+        RgbaImage::from_pixel(40, 20, Rgba([255, 0, 0, 255]))
+            .save(&image)
+            .unwrap();
+        let video = dir.path().join("2.webm");
+        std::fs::write(&video, b"not an image").unwrap();
+
+        let file = |id, path: &PathBuf| SheetFile {
+            id,
+            md5: None,
+            path: path.clone(),
+        };
+        let cache = ThumbnailCache::new(dir.path().join("thumbnails"));
+        let (sheet, skipped) = build_contact_sheet(
+            &[file(1, &image), file(2, &video), file(3, &image)],
+            4,
+            512,
+            true,
+            Some(&cache),
+        );
+        assert_eq!(sheet.len(), 1);
+        let sheet = &sheet[0];
+        assert_eq!(skipped, 1);
+
+        let layout = SheetLayout::fit(2, 4, 512, true);
+        assert_eq!(sheet.dimensions(), layout.size());
+        let (x, y) = layout.origin(1);
+        assert_eq!(
+            sheet.get_pixel(x + layout.cell / 2, y + layout.cell / 2),
+            &Rgba([255, 0, 0, 255])
+        );
+
+        let (none, skipped) = build_contact_sheet(&[], 4, 512, false, None);
+        assert!(none.is_empty());
+        assert_eq!(skipped, 0);
+
+        let capacity = SheetLayout::capacity(4, 256, true);
+        let many: Vec<SheetFile> = (0..capacity as i64 * 2 + 1)
+            .map(|id| file(id, &image))
+            .collect();
+        let (sheets, skipped) = build_contact_sheet(&many, 4, 256, true, None);
+        assert_eq!(sheets.len(), 3);
+        assert_eq!(skipped, 0);
+        assert!(
+            sheets
+                .iter()
+                .all(|sheet| sheet.width() <= 256 && sheet.height() <= 256)
+        );
+    }
+}
@@ -2,19 +2,31 @@
 use {
     crate::{
         bail,
-        config::format::FormatTemplate,
+        config::{
+            format::FormatTemplate,
+            options::{DownloadRoute, MetadataBackend},
+        },
+        data::metadata,
         error::{Report, Result},
         getopt,
         models::E6Post,
-        ui::{E6Ui, menus::ConflictMenu, progress::ProgressManager},
+        ui::{
+            E6Ui,
+            menus::{
+                ConflictMenu,
+                download::{download_dirs, prefix_rating_dir, route_dir},
+            },
+            print_hidden_count,
+            progress::ProgressManager,
+            summary_slice,
+        },
     },
     bearask::{Confirm, TextInput},
     color_eyre::eyre::Context,
     hashbrown::HashMap,
     smart_default::SmartDefault,
     std::{
-        fs::{self, OpenOptions},
-        io::Read,
+        fs,
         path::{Path, PathBuf},
         sync::Arc,
     },
@@ -47,6 +59,23 @@ pub struct ReorganizeOptions {
     #[default(None)]
     /// the output format to use
     pub output_format: Option<String>,
+
+    #[default(false)]
+    /// whether to put files in a folder named after their rating
+    pub separate_by_rating: bool,
+
+    #[default(Vec::new())]
+    /// rules that move matching posts to another directory (see [`route_dir`])
+    pub routes: Vec<DownloadRoute>,
+
+    #[default(None)]
+    /// copy files into a new tree rooted here instead of moving them, leaving the originals
+    /// untouched. routed posts go into a folder of this tree named after their route's directory
+    pub copy_to: Option<PathBuf>,
+
+    #[default(false)]
+    /// remove directories the moves left empty (see [`prune_empty_dirs`]). ignored when copying
+    pub prune_empty: bool,
 }
 
 #[derive(Debug)]
@@ -62,6 +91,8 @@ pub struct ReorganizeResult {
     pub failed: usize,
     /// errors
     pub errors: Vec<(PathBuf, String)>,
+    /// empty directories that were removed (or would be, in a dry run)
+    pub pruned: Vec<PathBuf>,
 }
 
 #[derive(Default)]
@@ -79,48 +110,10 @@ impl FileReorganizer {
         }
     }
 
-    #[cfg(target_os = "windows")]
-    /// read file metadata into an E6Post
-    pub fn read_metadata_from_ads(&self, file_path: &Path) -> Result<E6Post> {
-        let ads_path = format!("{}:metadata", file_path.display());
-
-        let mut file = OpenOptions::new()
-            .read(true)
-            .open(&ads_path)
-            .with_context(|| format!("Failed to open ADS metadata for {}", file_path.display()))?;
-
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)
-            .with_context(|| format!("Failed to read ADS metadata for {}", file_path.display()))?;
-
-        serde_json::from_str(&contents)
-            .with_context(|| format!("Failed to parse metadata for {}", file_path.display()))
-            .map_err(Report::new)
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    /// read file metadata into an E6Post
-    pub fn read_metadata_from_json(&self, file_path: &Path) -> Result<E6Post> {
-        let json_path = file_path.with_extension(format!(
-            "{}.json",
-            file_path.extension().and_then(|e| e.to_str()).unwrap_or("")
-        ));
-
-        if !json_path.exists() {
-            bail!("Metadata file not found: {}", json_path.display());
-        }
-
-        let contents = fs::read_to_string(&json_path)
-            .with_context(|| format!("Failed to read metadata file {}", json_path.display()))?;
-
-        serde_json::from_str(&contents)
-            .with_context(|| format!("Failed to parse metadata for {}", file_path.display()))
-            .map_err(Report::new)
-    }
-
     /// search a directory for any files with valid metadata
     pub fn find_files_with_metadata(&self, directory: &Path) -> Result<Vec<PathBuf>> {
         let mut files_with_metadata = Vec::new();
+        let backend: MetadataBackend = getopt!(download.metadata_backend);
 
         if !directory.exists() {
             bail!("Directory does not exist: {}", directory.display());
@@ -132,25 +125,11 @@ impl FileReorganizer {
             let entry = entry?;
             let path = entry.path();
 
-            if path.is_file() {
-                #[cfg(target_os = "windows")]
-                {
-                    let ads_path = format!("{}:metadata", path.display());
-                    if OpenOptions::new().read(true).open(&ads_path).is_ok() {
-                        files_with_metadata.push(path);
-                    }
-                }
-
-                #[cfg(not(target_os = "windows"))]
-                {
-                    let json_path = path.with_extension(format!(
-                        "{}.json",
-                        path.extension().and_then(|e| e.to_str()).unwrap_or("")
-                    ));
-                    if json_path.exists() {
-                        files_with_metadata.push(path);
-                    }
-                }
+            if path.is_file()
+                && !metadata::is_metadata_file(&path)
+                && metadata::has_metadata(&path, backend)
+            {
+                files_with_metadata.push(path);
             }
         }
 
@@ -175,6 +154,8 @@ impl FileReorganizer {
         directory: &Path,
         files: &mut Vec<PathBuf>,
     ) -> Result<()> {
+        let backend: MetadataBackend = getopt!(download.metadata_backend);
+
         for entry in fs::read_dir(directory)
             .with_context(|| format!("Failed to read directory {}", directory.display()))?
         {
@@ -183,42 +164,20 @@ impl FileReorganizer {
 
             if path.is_dir() {
                 self.find_files_recursive_impl(&path, files)?;
-            } else if path.is_file() {
-                #[cfg(target_os = "windows")]
-                {
-                    let ads_path = format!("{}:metadata", path.display());
-                    if OpenOptions::new().read(true).open(&ads_path).is_ok() {
-                        files.push(path);
-                    }
-                }
-
-                #[cfg(not(target_os = "windows"))]
-                {
-                    let json_path = path.with_extension(format!(
-                        "{}.json",
-                        path.extension().and_then(|e| e.to_str()).unwrap_or("")
-                    ));
-                    if json_path.exists() {
-                        files.push(path);
-                    }
-                }
+            } else if path.is_file()
+                && !metadata::is_metadata_file(&path)
+                && metadata::has_metadata(&path, backend)
+            {
+                files.push(path);
             }
         }
 
         Ok(())
     }
 
-    /// read a file into an E6Post (cross-plat)
+    /// read a file into an E6Post (checks every metadata backend)
     pub fn read_metadata(&self, file_path: &Path) -> Result<E6Post> {
-        #[cfg(target_os = "windows")]
-        {
-            self.read_metadata_from_ads(file_path)
-        }
-
-        #[cfg(not(target_os = "windows"))]
-        {
-            self.read_metadata_from_json(file_path)
-        }
+        metadata::read_metadata(file_path, getopt!(download.metadata_backend))
     }
 
     /// build placeholder context from post metadata
@@ -297,13 +256,8 @@ impl FileReorganizer {
             + post.tags.lore.len();
 
         let now = chrono::Local::now();
-        let rating_first = post
-            .rating
-            .chars()
-            .next()
-            .unwrap_or('u')
-            .to_lowercase()
-            .to_string();
+        let rating = post.content_rating();
+        let rating_first = rating.code().chars().next().unwrap_or('u').to_string();
 
         let (year, month, day, hour, minute, second, timestamp) =
             if let Ok(created_date) = chrono::DateTime::parse_from_rfc3339(&post.created_at) {
@@ -339,13 +293,6 @@ impl FileReorganizer {
                 (year.clone(), month.clone(), day.clone())
             };
 
-        let rating_full = match post.rating.as_str() {
-            "e" => "explicit",
-            "q" => "questionable",
-            "s" => "safe",
-            _ => "unknown",
-        };
-
         let pool_ids = post
             .pools
             .iter()
@@ -359,7 +306,7 @@ impl FileReorganizer {
             .unwrap_or_else(|| "none".to_string());
 
         simple.insert("id".to_string(), post.id.to_string());
-        simple.insert("rating".to_string(), rating_full.to_string());
+        simple.insert("rating".to_string(), rating.name().to_string());
         simple.insert("rating_first".to_string(), rating_first);
         simple.insert("score".to_string(), post.score.total.to_string());
         simple.insert("score_up".to_string(), post.score.up.to_string());
@@ -515,10 +462,9 @@ impl FileReorganizer {
             .map_err(Report::new)
     }
 
-    /// move a file based on its metadata
-    pub fn move_file_with_metadata(
+    /// make room for a file at `new_path`, returning where it should actually go
+    pub fn resolve_target(
         &self,
-        old_path: &Path,
         new_path: &Path,
         conflict_resolution: ConflictMenu,
     ) -> Result<PathBuf> {
@@ -527,17 +473,27 @@ impl FileReorganizer {
                 .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
         }
 
-        let final_path = if new_path.exists() {
-            match conflict_resolution {
-                ConflictMenu::Skip => {
-                    bail!("File already exists: {}", new_path.display());
-                }
-                ConflictMenu::Overwrite => new_path.to_path_buf(),
-                ConflictMenu::AutoRename => self.find_unique_path(new_path)?,
+        if !new_path.exists() {
+            return Ok(new_path.to_path_buf());
+        }
+
+        match conflict_resolution {
+            ConflictMenu::Skip => {
+                bail!("File already exists: {}", new_path.display());
             }
-        } else {
-            new_path.to_path_buf()
-        };
+            ConflictMenu::Overwrite => Ok(new_path.to_path_buf()),
+            ConflictMenu::AutoRename => self.find_unique_path(new_path),
+        }
+    }
+
+    /// move a file based on its metadata
+    pub fn move_file_with_metadata(
+        &self,
+        old_path: &Path,
+        new_path: &Path,
+        conflict_resolution: ConflictMenu,
+    ) -> Result<PathBuf> {
+        let final_path = self.resolve_target(new_path, conflict_resolution)?;
 
         if fs::rename(old_path, &final_path).is_err() {
             fs::copy(old_path, &final_path).with_context(|| {
@@ -553,38 +509,44 @@ impl FileReorganizer {
             })?;
         }
 
-        #[cfg(target_os = "windows")]
-        {
-            let new_ads_path = format!("{}:metadata", final_path.display());
-            if OpenOptions::new().read(true).open(&new_ads_path).is_err() {
-                warn!(
-                    "Metadata ADS may not have moved with file: {}",
-                    final_path.display()
-                );
-            }
+        metadata::move_metadata(old_path, &final_path)
+            .with_context(|| format!("Failed to move metadata for {}", old_path.display()))?;
+
+        if !metadata::has_metadata(&final_path, getopt!(download.metadata_backend)) {
+            warn!(
+                "Metadata may not have moved with file: {}",
+                final_path.display()
+            );
         }
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            let old_json = old_path.with_extension(format!(
-                "{}.json",
-                old_path.extension().and_then(|e| e.to_str()).unwrap_or("")
-            ));
-
-            let new_json = final_path.with_extension(format!(
-                "{}.json",
-                final_path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("")
-            ));
-
-            if old_json.exists() {
-                if fs::rename(&old_json, &new_json).is_err() {
-                    fs::copy(&old_json, &new_json)?;
-                    fs::remove_file(&old_json)?;
-                }
-            }
+        Ok(final_path)
+    }
+
+    /// copy a file based on its metadata, leaving the original where it is
+    pub fn copy_file_with_metadata(
+        &self,
+        old_path: &Path,
+        new_path: &Path,
+        conflict_resolution: ConflictMenu,
+    ) -> Result<PathBuf> {
+        let final_path = self.resolve_target(new_path, conflict_resolution)?;
+
+        fs::copy(old_path, &final_path).with_context(|| {
+            format!(
+                "Failed to copy {} to {}",
+                old_path.display(),
+                final_path.display()
+            )
+        })?;
+
+        metadata::copy_metadata(old_path, &final_path)
+            .with_context(|| format!("Failed to copy metadata for {}", old_path.display()))?;
+
+        if !metadata::has_metadata(&final_path, getopt!(download.metadata_backend)) {
+            warn!(
+                "Metadata may not have been copied with file: {}",
+                final_path.display()
+            );
         }
 
         Ok(final_path)
@@ -633,6 +595,7 @@ impl FileReorganizer {
                 skipped: 0,
                 failed: 0,
                 errors: Vec::new(),
+                pruned: Vec::new(),
             });
         }
 
@@ -641,11 +604,19 @@ impl FileReorganizer {
         let download_dir: String = getopt!(download.path);
         let default_format: String = getopt!(download.format);
         let output_format = options.output_format.clone().unwrap_or(default_format);
-        let base_path = Path::new(&download_dir);
+        let base_path = options
+            .copy_to
+            .as_deref()
+            .unwrap_or(Path::new(&download_dir));
 
         let pb = self
             .progress_manager
-            .create_count_bar("reorganize", files.len() as u64, "Reorganizing files")
+            .create_count_bar(
+                "reorganize",
+                files.len() as u64,
+                "Reorganizing files",
+                false,
+            )
             .await?;
 
         let mut result = ReorganizeResult {
@@ -654,6 +625,7 @@ impl FileReorganizer {
             skipped: 0,
             failed: 0,
             errors: Vec::new(),
+            pruned: Vec::new(),
         };
 
         for file_path in files {
@@ -685,10 +657,73 @@ impl FileReorganizer {
             result.successful, result.skipped, result.failed
         ));
 
+        if options.prune_empty && options.copy_to.is_none() {
+            result.pruned = prune_empty_dirs(directory, options.dry_run)?;
+        }
+
         Ok(result)
     }
 
-    /// process and move a file
+    /// get where a post's file belongs with the given format and options
+    pub fn target_path(
+        &self,
+        post: &E6Post,
+        base_path: &Path,
+        output_format: &str,
+        options: &ReorganizeOptions,
+    ) -> Result<PathBuf> {
+        let mut new_filename = self.format_filename(post, output_format)?;
+        if options.separate_by_rating {
+            new_filename = prefix_rating_dir(post, &new_filename);
+        }
+
+        let dir = match (route_dir(&options.routes, post), &options.copy_to) {
+            (Some(route), Some(_)) => base_path.join(route.file_name().unwrap_or_default()),
+            (Some(route), None) => route,
+            (None, _) => base_path.to_path_buf(),
+        };
+
+        Ok(dir.join(&new_filename))
+    }
+
+    /// find files whose path only differs from the current format's by case or by spaces vs
+    /// underscores
+    ///
+    /// these are left over from older versions of a format, so unlike a full reorganize, moving
+    /// them never changes how the collection is laid out. returns `(current, normalized)` pairs
+    pub fn find_unnormalized<'a>(
+        &self,
+        files: impl IntoIterator<Item = (&'a Path, &'a E6Post)>,
+        options: &ReorganizeOptions,
+    ) -> Vec<(PathBuf, PathBuf)> {
+        let download_dir: String = getopt!(download.path);
+        let output_format = options
+            .output_format
+            .clone()
+            .unwrap_or_else(|| getopt!(download.format));
+        let base_path = Path::new(&download_dir);
+
+        files
+            .into_iter()
+            .filter_map(|(file_path, post)| {
+                let new_path = match self.target_path(post, base_path, &output_format, options) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        debug!("Skipped {}: {}", file_path.display(), e);
+                        return None;
+                    }
+                };
+
+                let old = std::path::absolute(file_path).unwrap_or(file_path.to_path_buf());
+                let new = std::path::absolute(&new_path).unwrap_or(new_path);
+
+                (old != new && normalized_key(&old) == normalized_key(&new))
+                    .then(|| (file_path.to_path_buf(), new))
+            })
+            .collect()
+    }
+
+    /// process and move (or copy, with [`ReorganizeOptions::copy_to`]) a file
     pub fn process_file(
         &self,
         file_path: &Path,
@@ -697,25 +732,215 @@ impl FileReorganizer {
         options: &ReorganizeOptions,
     ) -> Result<PathBuf> {
         let post = self.read_metadata(file_path)?;
-        let new_filename = self.format_filename(&post, output_format)?;
-        let new_path = base_path.join(&new_filename);
+        let new_path = self.target_path(&post, base_path, output_format, options)?;
 
         if file_path == new_path {
             bail!("File already in correct location");
         }
 
-        if !options.dry_run {
-            self.move_file_with_metadata(file_path, &new_path, options.conflict_resolution)?;
+        if options.dry_run {
+            return Ok(new_path);
+        }
+
+        if options.copy_to.is_some() {
+            self.copy_file_with_metadata(file_path, &new_path, options.conflict_resolution)
+        } else {
+            self.move_file_with_metadata(file_path, &new_path, options.conflict_resolution)
+        }
+    }
+}
+
+/// remove every empty directory under `root`, deepest first
+///
+/// a directory that only holds other empty directories, a metadata db with no entries left, or a
+/// [`metadata::NOMEDIA_FILE`] counts as empty. `root` itself is never removed, and symlinks
+/// aren't followed
+///
+/// returns the removed directories (or the ones that would be removed, in a dry run)
+///
+/// # Arguments
+///
+/// * `root` - the directory to clean up
+/// * `dry_run` - only find the empty directories, don't remove them
+///
+/// # Errors
+///
+/// returns an error if a directory can't be read or removed
+pub fn prune_empty_dirs(root: &Path, dry_run: bool) -> Result<Vec<PathBuf>> {
+    let mut pruned = Vec::new();
+    prune_dir(root, dry_run, &mut pruned)?;
+    Ok(pruned)
+}
+
+/// remove the empty directories under `dir`, returning whether `dir` is (or would be) empty
+/// afterwards
+fn prune_dir(dir: &Path, dry_run: bool, pruned: &mut Vec<PathBuf>) -> Result<bool> {
+    let mut empty = true;
+    let mut has_db = false;
+
+    for entry in
+        fs::read_dir(dir).with_context(|| format!("Failed to read directory {}", dir.display()))?
+    {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            if !prune_dir(&path, dry_run, pruned)? {
+                empty = false;
+                continue;
+            }
+
+            if !dry_run {
+                metadata::remove_db(&path)?;
+                let nomedia = path.join(metadata::NOMEDIA_FILE);
+                if nomedia.exists() {
+                    fs::remove_file(&nomedia)
+                        .with_context(|| format!("Failed to remove {}", nomedia.display()))?;
+                }
+                fs::remove_dir(&path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+            pruned.push(path);
+        } else if entry.file_name() == metadata::METADATA_DB_FILE {
+            has_db = true;
+        } else if entry.file_name() != metadata::NOMEDIA_FILE {
+            empty = false;
         }
+    }
+
+    Ok(empty && (!has_db || metadata::db_is_empty(dir)?))
+}
 
-        Ok(new_path)
+/// print the directories [`prune_empty_dirs`] removed
+///
+/// # Arguments
+///
+/// * `pruned` - the removed directories
+/// * `dry_run` - whether they were only found, not removed
+fn print_pruned(pruned: &[PathBuf], dry_run: bool) {
+    if pruned.is_empty() {
+        return;
     }
+
+    println!(
+        "\n{} {} empty director{}:",
+        if dry_run { "Would remove" } else { "Removed" },
+        pruned.len(),
+        if pruned.len() == 1 { "y" } else { "ies" }
+    );
+    let (shown, hidden) = summary_slice(pruned);
+    for dir in shown {
+        println!("  {}", dir.display());
+    }
+    print_hidden_count(hidden);
+}
+
+/// get a path with case and the spaces vs underscores difference folded away
+fn normalized_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase().replace(' ', "_")
+}
+
+/// resolve a path that might not exist yet, following symlinks in the part that does
+fn resolve_path(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+
+    for ancestor in absolute.ancestors() {
+        if let Ok(resolved) = fs::canonicalize(ancestor) {
+            let rest = absolute.strip_prefix(ancestor).unwrap_or(Path::new(""));
+            return resolved.join(rest);
+        }
+    }
+
+    absolute
+}
+
+/// check whether one path is the same as, or inside, the other, once both are resolved
+///
+/// # Arguments
+///
+/// * `a` - the first path
+/// * `b` - the second path
+pub fn paths_overlap(a: &Path, b: &Path) -> bool {
+    let (a, b) = (resolve_path(a), resolve_path(b));
+    a.starts_with(&b) || b.starts_with(&a)
+}
+
+/// reorganize a single directory, offering to run for real after a dry run
+///
+/// # Arguments
+///
+/// * `reorganizer` - the reorganizer to run
+/// * `directory` - the directory to reorganize
+/// * `options` - the reorganization options
+/// * `recursive` - whether to look in subdirectories
+async fn reorganize_one(
+    reorganizer: &FileReorganizer,
+    directory: &Path,
+    options: &ReorganizeOptions,
+    recursive: bool,
+) -> Result<()> {
+    let result = reorganizer
+        .reorganize_directory(directory, options.clone(), recursive)
+        .await?;
+
+    println!("\n=== Reorganization Summary ===");
+    println!("Total files: {}", result.total_files);
+    println!("Successful: {}", result.successful);
+    println!("Skipped: {}", result.skipped);
+    println!("Failed: {}", result.failed);
+
+    if !result.errors.is_empty() {
+        println!("\nErrors:");
+        for (path, error) in &result.errors {
+            println!("  {}: {}", path.display(), error);
+        }
+    }
+
+    print_pruned(&result.pruned, options.dry_run);
+    if options.dry_run && options.prune_empty && options.copy_to.is_none() {
+        println!("(more directories may be left empty once the files are actually moved)");
+    }
+
+    if options.dry_run && result.successful > 0 {
+        println!(
+            "\nThis was a dry run. No files were actually {}.",
+            if options.copy_to.is_some() {
+                "copied"
+            } else {
+                "moved"
+            }
+        );
+        let proceed =
+            Confirm::new("Would you like to perform the reorganization for real?").ask()?;
+
+        if proceed {
+            let real_options = ReorganizeOptions {
+                dry_run: false,
+                ..options.clone()
+            };
+
+            let final_result = reorganizer
+                .reorganize_directory(directory, real_options, recursive)
+                .await?;
+
+            println!("\n=== Final Summary ===");
+            println!("Successfully reorganized: {}", final_result.successful);
+            println!("Skipped: {}", final_result.skipped);
+            println!("Failed: {}", final_result.failed);
+            print_pruned(&final_result.pruned, false);
+        }
+    }
+
+    Ok(())
 }
 
 /// functions for reorganizing downloaded files
 pub trait RegorganizeMenu {
     /// downloads reorganizer
     fn reorganize_downloads(&self) -> impl Future<Output = Result<()>>;
+
+    /// remove empty directories from the download directories, after previewing them
+    fn clean_empty_folders(&self) -> Result<()>;
 }
 
 impl RegorganizeMenu for E6Ui {
@@ -723,18 +948,30 @@ impl RegorganizeMenu for E6Ui {
     async fn reorganize_downloads(&self) -> Result<()> {
         println!("\n=== Downloads Reorganizer ===\n");
         println!("This will reorganize your downloaded files based on the current output format.");
-        println!("Files will be moved to match the format specified in your config.\n");
+        println!("Files will be moved or copied to match the format specified in your config.\n");
 
         let download_dir: String = getopt!(download.path);
         let default_format: String = getopt!(download.format);
 
-        let directory = TextInput::new("Enter directory to reorganize:")
-            .with_default(&download_dir)
-            .ask()?;
+        let all_dirs = download_dirs();
+        let directories = if all_dirs.len() > 1
+            && Confirm::new(format!(
+                "Reorganize all {} download directories (including routes)?",
+                all_dirs.len()
+            ))
+            .ask()?
+        {
+            all_dirs.into_iter().filter(|dir| dir.exists()).collect()
+        } else {
+            vec![PathBuf::from(
+                TextInput::new("Enter directory to reorganize:")
+                    .with_default(&download_dir)
+                    .ask()?,
+            )]
+        };
 
-        let directory = Path::new(&directory);
-        if !directory.exists() {
-            bail!("Directory does not exist: {}", directory.display());
+        if let Some(missing) = directories.iter().find(|dir| !dir.exists()) {
+            bail!("Directory does not exist: {}", missing.display());
         }
 
         let recursive = Confirm::new("Search subdirectories recursively?").ask()?;
@@ -750,61 +987,203 @@ impl RegorganizeMenu for E6Ui {
             None
         };
 
+        let separate_by_rating = getopt!(download.separate_by_rating)
+            || Confirm::new("Split files into safe/questionable/explicit folders?").ask()?;
+
         let conflict_resolution =
             ConflictMenu::select("Choose what to do when target file already exists")
                 .with_default(2)
                 .ask()?
                 .value;
 
+        let copy_to = if Confirm::new(
+            "Copy into a separate directory instead of moving? (originals are left untouched)",
+        )
+        .ask()?
+        {
+            let target = PathBuf::from(
+                TextInput::new("Enter directory to copy the reorganized files into:").ask()?,
+            );
+
+            if let Some(dir) = directories.iter().find(|dir| paths_overlap(dir, &target)) {
+                bail!(
+                    "The copy target can't be inside, or contain, a directory being reorganized: \
+                     {} ({})",
+                    target.display(),
+                    dir.display()
+                );
+            }
+
+            Some(target)
+        } else {
+            None
+        };
+
+        let prune_empty =
+            copy_to.is_none() && Confirm::new("Remove directories left empty afterwards?").ask()?;
+
         let dry_run =
-            Confirm::new("Perform dry run? (preview changes without moving files)").ask()?;
+            Confirm::new("Perform dry run? (preview changes without touching files)").ask()?;
 
         let options = ReorganizeOptions {
             dry_run,
             conflict_resolution,
             output_format,
+            separate_by_rating,
+            routes: getopt!(download.routes),
+            copy_to,
+            prune_empty,
         };
 
         let reorganizer = FileReorganizer::new();
-        let result = reorganizer
-            .reorganize_directory(directory, options.clone(), recursive)
-            .await?;
-
-        println!("\n=== Reorganization Summary ===");
-        println!("Total files: {}", result.total_files);
-        println!("Successful: {}", result.successful);
-        println!("Skipped: {}", result.skipped);
-        println!("Failed: {}", result.failed);
-
-        if !result.errors.is_empty() {
-            println!("\nErrors:");
-            for (path, error) in &result.errors {
-                println!("  {}: {}", path.display(), error);
+        for directory in &directories {
+            if directories.len() > 1 {
+                println!("\n=== {} ===", directory.display());
             }
+
+            reorganize_one(&reorganizer, directory, &options, recursive).await?;
         }
 
-        if options.dry_run && result.successful > 0 {
-            println!("\nThis was a dry run. No files were actually moved.");
-            let proceed =
-                Confirm::new("Would you like to perform the reorganization for real?").ask()?;
+        Ok(())
+    }
 
-            if proceed {
-                let real_options = ReorganizeOptions {
-                    dry_run: false,
-                    ..options
-                };
+    /// remove empty directories from the download directories, after previewing them
+    fn clean_empty_folders(&self) -> Result<()> {
+        let directories: Vec<PathBuf> = download_dirs()
+            .into_iter()
+            .filter(|dir| dir.exists())
+            .collect();
+
+        let mut empty = Vec::new();
+        for directory in &directories {
+            empty.extend(prune_empty_dirs(directory, true)?);
+        }
 
-                let final_result = reorganizer
-                    .reorganize_directory(directory, real_options, recursive)
-                    .await?;
+        if empty.is_empty() {
+            println!("No empty directories found");
+            return Ok(());
+        }
 
-                println!("\n=== Final Summary ===");
-                println!("Successfully reorganized: {}", final_result.successful);
-                println!("Skipped: {}", final_result.skipped);
-                println!("Failed: {}", final_result.failed);
-            }
+        print_pruned(&empty, true);
+        if !Confirm::new("Remove them?").ask()? {
+            return Ok(());
         }
 
+        let mut removed = Vec::new();
+        for directory in &directories {
+            removed.extend(prune_empty_dirs(directory, false)?);
+        }
+        print_pruned(&removed, false);
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_normalized_key() {
+        assert_eq!(
+            normalized_key(Path::new("Fox Artist/Some Tag.PNG")),
+            "fox_artist/some_tag.png"
+        );
+        assert_eq!(
+            normalized_key(Path::new("fox artist/1.png")),
+            normalized_key(Path::new("Fox_Artist/1.png"))
+        );
+        assert_ne!(
+            normalized_key(Path::new("fox-artist/1.png")),
+            normalized_key(Path::new("fox_artist/1.png"))
+        );
+    }
+
+    #[test]
+    fn test_find_unnormalized() {
+        let base = PathBuf::from(getopt!(download.path));
+        let mut post = E6Post {
+            id: 1,
+            ..Default::default()
+        };
+        post.tags.artist = vec!["fox_artist".to_string()];
+        post.file.ext = "png".to_string();
+
+        let options = ReorganizeOptions {
+            output_format: Some("$artist/$id.$ext".to_string()),
+            ..Default::default()
+        };
+        let spaced = base.join("Fox Artist").join("1.png");
+        let normalized = base.join("fox_artist").join("1.png");
+        let elsewhere = base.join("other").join("1.png");
+        let files = [
+            (spaced.as_path(), &post),
+            (normalized.as_path(), &post),
+            (elsewhere.as_path(), &post),
+        ];
+
+        let found = FileReorganizer::new().find_unnormalized(files, &options);
+        assert_eq!(
+            found,
+            vec![(spaced.clone(), std::path::absolute(&normalized).unwrap())]
+        );
+    }
+
+    #[test]
+    fn test_paths_overlap() {
+        let dir = TempDir::new().unwrap();
+        let downloads = dir.path().join("downloads");
+        std::fs::create_dir_all(&downloads).unwrap();
+
+        assert!(paths_overlap(&downloads, &downloads.join("copy")));
+        assert!(paths_overlap(&downloads, &downloads.join(".")));
+        assert!(paths_overlap(&downloads, dir.path()));
+        assert!(!paths_overlap(&downloads, &dir.path().join("copy")));
+        assert!(!paths_overlap(
+            &downloads,
+            &dir.path().join("downloads-copy")
+        ));
+    }
+
+    #[test]
+    fn test_prune_empty_dirs() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("artist").join("explicit")).unwrap();
+        std::fs::write(root.join("artist").join(metadata::NOMEDIA_FILE), b"").unwrap();
+        std::fs::create_dir_all(root.join("kept").join("empty")).unwrap();
+        std::fs::write(root.join("kept").join("1.png"), b"one").unwrap();
+
+        let moved = root.join("moved").join("2.png");
+        std::fs::create_dir_all(root.join("moved")).unwrap();
+        let post = E6Post {
+            id: 2,
+            ..Default::default()
+        };
+        metadata::save_metadata(&moved, &post, MetadataBackend::Db, true).unwrap();
+        metadata::remove_from_db(&moved).unwrap();
+
+        let mut preview = prune_empty_dirs(root, true).unwrap();
+        preview.sort();
+        assert_eq!(
+            preview,
+            vec![
+                root.join("artist"),
+                root.join("artist").join("explicit"),
+                root.join("kept").join("empty"),
+                root.join("moved"),
+            ]
+        );
+        assert!(root.join("artist").join("explicit").is_dir());
+
+        let mut removed = prune_empty_dirs(root, false).unwrap();
+        removed.sort();
+        assert_eq!(removed, preview);
+        assert!(!root.join("artist").exists());
+        assert!(!root.join("moved").exists());
+        assert!(root.join("kept").join("1.png").is_file());
+        assert!(root.is_dir());
+
+        assert!(prune_empty_dirs(root, false).unwrap().is_empty());
+    }
+}
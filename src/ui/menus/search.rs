@@ -2,6 +2,7 @@
 use {
     crate::{
         bail,
+        client::E6Client,
         display::dtext::parser::format_text,
         error::{Report, Result},
         getopt,
@@ -9,16 +10,236 @@ use {
         ui::{
             E6Ui,
             autocomplete::PoolAutocompleter,
-            menus::{AdvPoolSearch, view::ViewMenu},
+            menus::{
+                AdvPoolSearch,
+                download::{format_colored_post_list_entry, post_list_template},
+                view::{ViewMenu, preload_post_images},
+            },
         },
     },
-    bearask::{AskOption, Confirm, ErrorMessage, Select, TextInput, Validation},
+    bearask::{AskOption, Confirm, ErrorMessage, MultiSelect, Select, TextInput, Validation},
     color_eyre::eyre::Context,
     indicatif::{ProgressBar, ProgressStyle},
     std::{collections::HashSet, sync::Arc, time::Duration},
+    tokio::task::JoinHandle,
     tracing::{debug, warn},
 };
 
+/// the include, or, and exclude tags of a search
+type SearchTags = (Vec<String>, Vec<String>, Vec<String>);
+
+/// a page of search results being fetched in the background, and whether there are more
+type PageFetch = Background<Result<(Vec<E6Post>, bool)>>;
+
+/// how the tags of a post search are entered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagInput {
+    /// typed into a prompt with autocomplete (see [`E6Ui::collect_tags`])
+    Typed,
+    /// picked by category from the tag database (see [`SearchMenu::browse_tag_tree`])
+    Browse,
+}
+
+/// an option in the tag tree browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagTreeChoice {
+    /// pick tags from this category
+    Category(TagCategory),
+    /// search with the picked tags
+    Search,
+    /// forget the picked tags
+    Clear,
+    /// go back without searching
+    Cancel,
+}
+
+/// what to do once the results of a post search have been browsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AfterSearch {
+    /// start a new search
+    Again,
+    /// open the search on the website
+    Open,
+    /// copy the url of the search on the website
+    Copy,
+    /// go back to the search menu
+    Done,
+}
+
+/// get the website url of a post search
+///
+/// # Arguments
+///
+/// * `base` - the base url of the site, like `https://e621.net`
+/// * `tags` - the search tags, with `-` and `~` prefixes already applied
+pub fn search_url(base: &str, tags: &[String]) -> String {
+    format!(
+        "{}/posts?tags={}",
+        base.trim_end_matches('/'),
+        urlencoding::encode(&tags.join(" "))
+    )
+}
+
+/// add `width:>=`/`height:>=` metatags for a minimum resolution
+///
+/// a dimension is skipped when its minimum is 0 or the search already has a tag for it
+///
+/// # Arguments
+///
+/// * `tags` - the search tags
+/// * `min_width` - the minimum width in pixels
+/// * `min_height` - the minimum height in pixels
+pub fn with_resolution_tags(mut tags: Vec<String>, min_width: u64, min_height: u64) -> Vec<String> {
+    for (metatag, min) in [("width", min_width), ("height", min_height)] {
+        let prefix = format!("{}:", metatag);
+        if min > 0
+            && !tags
+                .iter()
+                .any(|tag| tag.trim_start_matches(['-', '~']).starts_with(&prefix))
+        {
+            tags.push(format!("{}>={}", prefix, min));
+        }
+    }
+
+    tags
+}
+
+/// add a `favcount:>=` metatag for a minimum favorite count
+///
+/// skipped when the minimum is 0 or the search already has a `favcount:` tag
+///
+/// # Arguments
+///
+/// * `tags` - the search tags
+/// * `min_fav_count` - the minimum number of favorites
+pub fn with_fav_count_tag(mut tags: Vec<String>, min_fav_count: u64) -> Vec<String> {
+    if min_fav_count > 0
+        && !tags
+            .iter()
+            .any(|tag| tag.trim_start_matches(['-', '~']).starts_with("favcount:"))
+    {
+        tags.push(format!("favcount:>={}", min_fav_count));
+    }
+
+    tags
+}
+
+/// a search tag that turns off `search.always_include` and `search.always_exclude` for that search
+pub const RAW_SEARCH_TAG: &str = "!raw";
+
+/// add the tags from `search.always_include` and `search.always_exclude` to a search
+///
+/// returns the tags and the ones that were added. tags the search already has (either way
+/// around) aren't added, and nothing is added if the search has [`RAW_SEARCH_TAG`] in it,
+/// which is taken out
+///
+/// # Arguments
+///
+/// * `tags` - the search tags
+/// * `include` - the tags to add
+/// * `exclude` - the tags to exclude
+pub fn with_always_tags(
+    mut tags: Vec<String>,
+    include: &[String],
+    exclude: &[String],
+) -> (Vec<String>, Vec<String>) {
+    let before = tags.len();
+    tags.retain(|tag| tag != RAW_SEARCH_TAG);
+    if tags.len() < before {
+        return (tags, Vec::new());
+    }
+
+    let has = |tags: &[String], name: &str| {
+        tags.iter()
+            .any(|tag| tag.trim_start_matches(['-', '~', '+']) == name)
+    };
+
+    let mut added = Vec::new();
+    let always = include
+        .iter()
+        .map(|tag| (tag.trim_start_matches('+'), ""))
+        .chain(exclude.iter().map(|tag| (tag.trim_start_matches('-'), "-")));
+    for (name, prefix) in always {
+        if !name.is_empty() && !has(&tags, name) {
+            let tag = format!("{}{}", prefix, name);
+            tags.push(tag.clone());
+            added.push(tag);
+        }
+    }
+
+    (tags, added)
+}
+
+/// a task running in the background while the user browses results
+///
+/// the task is aborted when this is dropped, so prefetching never outlives the screen that
+/// started it
+struct Background<T>(JoinHandle<T>);
+
+impl<T: Send + 'static> Background<T> {
+    /// spawn a background task
+    fn spawn(task: impl Future<Output = T> + Send + 'static) -> Self {
+        Self(tokio::spawn(task))
+    }
+}
+
+impl<T> Drop for Background<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// quietly fetch a page of search results, for prefetching the next page
+///
+/// returns the posts, and whether `search.max_pages` stopped it before `limit` posts were
+/// fetched, so the note can be printed when the page is shown
+///
+/// # Arguments
+///
+/// * `client` - the client to search with
+/// * `tags` - the search tags
+/// * `limit` - how many posts to fetch
+/// * `before_id` - only fetch posts older than this one
+async fn fetch_page(
+    client: Arc<E6Client>,
+    tags: Vec<String>,
+    limit: u64,
+    mut before_id: Option<i64>,
+) -> Result<(Vec<E6Post>, bool)> {
+    let mut posts: Vec<E6Post> = Vec::new();
+    let mut seen = HashSet::new();
+    let max_pages = getopt!(search.max_pages);
+    let mut pages = 0;
+    let mut capped = false;
+
+    while (posts.len() as u64) < limit {
+        if pages >= max_pages {
+            capped = true;
+            break;
+        }
+        pages += 1;
+        let remaining = limit - posts.len() as u64;
+        let results = client
+            .search_posts(
+                &tags,
+                Some(remaining.min(getopt!(search.results))),
+                before_id,
+            )
+            .await
+            .context("Failed to search posts")?;
+
+        let Some(min_id) = results.posts.iter().map(|p| p.id).min() else {
+            break;
+        };
+        before_id = Some(min_id);
+
+        posts.extend(results.posts.into_iter().filter(|p| seen.insert(p.id)));
+    }
+
+    posts.truncate(limit as usize);
+    Ok((posts, capped))
+}
+
 /// functions for searching posts and pools
 pub trait SearchMenu {
     /// search for pools in an advanced way
@@ -33,6 +254,15 @@ pub trait SearchMenu {
     /// browse the latest pools
     fn browse_latest_pools(&self) -> impl Future<Output = Result<()>>;
 
+    /// list local pools in a category
+    fn perform_pool_category_search(&self) -> impl Future<Output = Result<()>>;
+
+    /// list local pools within a post count range
+    fn perform_pool_post_count_search(&self) -> impl Future<Output = Result<()>>;
+
+    /// ask for an optional post count bound
+    fn get_post_count_bound(&self, prompt: &str) -> Result<Option<usize>>;
+
     /// handle the results of a search
     fn handle_pool_results(&self, pools: Vec<E6Pool>) -> impl Future<Output = Result<()>>;
 
@@ -49,7 +279,7 @@ pub trait SearchMenu {
     fn pool_entry_to_e6pool(&self, entry: &PoolEntry) -> E6Pool;
 
     /// search posts
-    fn search_posts(&self) -> impl Future<Output = Result<()>>;
+    fn search_posts(&self, input: TagInput) -> impl Future<Output = Result<()>>;
 
     /// search pools
     fn search_pools(&self) -> impl Future<Output = Result<()>>;
@@ -58,20 +288,27 @@ pub trait SearchMenu {
     fn perform_pool_search(&self) -> impl Future<Output = Result<bool>>;
 
     /// perform a post search
-    fn perform_search(&self) -> impl Future<Output = Result<bool>>;
+    fn perform_search(&self, input: TagInput) -> impl Future<Output = Result<bool>>;
 
-    /// perform a paginated post search
+    /// pick search tags by drilling into tag categories
+    ///
+    /// returns the include, or, and exclude tags like [`E6Ui::collect_tags`], or `None` if the
+    /// user cancelled
+    fn browse_tag_tree(&self) -> Result<Option<SearchTags>>;
+
+    /// perform a paginated post search, starting after `before_id` if given
     fn fetch_posts_paginated(
         &self,
         all_tags: Vec<String>,
         total_limit: u64,
+        before_id: Option<i64>,
     ) -> impl Future<Output = Result<Vec<E6Post>>>;
 
     /// make a search progress bar
     fn create_search_progress_bar(&self, total: u64) -> Result<ProgressBar>;
 
     /// handle a post interaction
-    fn handle_post_interaction(&self, posts: Vec<E6Post>) -> impl Future<Output = Result<bool>>;
+    fn handle_post_interaction(&self, posts: Vec<E6Post>) -> impl Future<Output = Result<()>>;
 
     /// fetch posts selected in list of results
     fn fetch_selected_posts(
@@ -88,6 +325,11 @@ pub trait SearchMenu {
     /// ask whether to continue
     fn ask_continue(&self, message: &str) -> Result<bool>;
 
+    /// offer to open or copy a finished search on the website, or start another one
+    ///
+    /// returns whether to perform another search
+    fn after_search(&self, tags: &[String]) -> Result<bool>;
+
     /// select a post from a list of posts
     fn select_post<'a>(&self, posts: &'a [E6Post]) -> Result<Option<&'a E6Post>>;
 
@@ -126,6 +368,20 @@ impl SearchMenu for E6Ui {
                     }
                     false
                 }
+                AdvPoolSearch::ByCategory => {
+                    if let Err(e) = self.perform_pool_category_search().await {
+                        warn!("Pool category search error: {:#}", e);
+                        eprintln!("Pool category search error: {}", e);
+                    }
+                    false
+                }
+                AdvPoolSearch::ByPostCount => {
+                    if let Err(e) = self.perform_pool_post_count_search().await {
+                        warn!("Pool post count search error: {:#}", e);
+                        eprintln!("Pool post count search error: {}", e);
+                    }
+                    false
+                }
                 AdvPoolSearch::BrowseLatest => {
                     if let Err(e) = self.browse_latest_pools().await {
                         warn!("Error browsing pools: {:#}", e);
@@ -206,6 +462,96 @@ impl SearchMenu for E6Ui {
         self.handle_pool_results(results.pools).await
     }
 
+    /// list local pools in a category
+    async fn perform_pool_category_search(&self) -> Result<()> {
+        let categories = self.pool_db().categories();
+        if categories.is_empty() {
+            println!("No pools are loaded, try updating the pools database.");
+            return Ok(());
+        }
+
+        let options = categories
+            .into_iter()
+            .map(|c| AskOption::with_name(c.clone(), c))
+            .collect();
+        let category = miette::Context::context(
+            Select::new("Select a pool category:")
+                .with_options(options)
+                .ask(),
+            "Failed to get pool category selection",
+        )?
+        .value;
+
+        let limit = self.get_pool_limit()? as usize;
+        let pools = self
+            .pool_db()
+            .by_category(&category)
+            .take(limit)
+            .map(|p| self.pool_entry_to_e6pool(p))
+            .collect();
+
+        self.handle_pool_results(pools).await
+    }
+
+    /// list local pools within a post count range
+    async fn perform_pool_post_count_search(&self) -> Result<()> {
+        let floor = getopt!(search.min_posts_on_pool) as usize + 1;
+        let min = self
+            .get_post_count_bound(&format!("Minimum posts (at least {}):", floor))?
+            .unwrap_or(floor);
+        let max = self.get_post_count_bound("Maximum posts (leave empty for no limit):")?;
+
+        if max.is_some_and(|max| max < min) {
+            bail!("The maximum post count can't be lower than the minimum");
+        }
+
+        if min < floor {
+            println!(
+                "Pools with fewer than {} posts are hidden by search.min_posts_on_pool",
+                floor
+            );
+        }
+
+        let limit = self.get_pool_limit()? as usize;
+        let pools = self
+            .pool_db()
+            .by_post_count(min, max)
+            .take(limit)
+            .map(|p| self.pool_entry_to_e6pool(p))
+            .collect();
+
+        self.handle_pool_results(pools).await
+    }
+
+    /// ask for an optional post count bound
+    fn get_post_count_bound(&self, prompt: &str) -> Result<Option<usize>> {
+        let input = miette::Context::context(
+            TextInput::new(prompt)
+                .with_validation(|input: &str| {
+                    if input.trim().is_empty() || input.trim().parse::<usize>().is_ok() {
+                        Ok(Validation::Valid)
+                    } else {
+                        Ok(Validation::Invalid(ErrorMessage::Custom(
+                            "Please enter a valid number".to_string(),
+                        )))
+                    }
+                })
+                .ask(),
+            "Failed to get post count input",
+        )?;
+
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            trimmed
+                .parse::<usize>()
+                .context("Failed to parse post count")?,
+        ))
+    }
+
     /// handle the results of a search
     async fn handle_pool_results(&self, pools: Vec<E6Pool>) -> Result<()> {
         if pools.is_empty() {
@@ -230,7 +576,7 @@ impl SearchMenu for E6Ui {
 
     /// get the search query for finding pools
     fn get_pool_search_query(&self) -> Result<String> {
-        let autocompleter = PoolAutocompleter::new(self.pool_db.clone());
+        let autocompleter = PoolAutocompleter::new(self.pool_db());
         let query = miette::Context::context(
             TextInput::new("Enter pool search query (leave empty for latest pools):")
                 .with_autocomplete(autocompleter)
@@ -289,21 +635,20 @@ impl SearchMenu for E6Ui {
 
         let options = pools
             .iter()
-            .map(|pool| AskOption::with_name(pool.name.clone(), pool))
+            .enumerate()
+            .map(|(idx, pool)| AskOption::with_name(pool.name.clone(), idx))
             .collect();
 
-        let selection = match miette::Context::context(
+        let idx = miette::Context::context(
             Select::new("Select a pool to view:")
                 .with_options(options)
                 .with_page_size(getopt!(ui.pagination_size))
                 .ask(),
             "Failed to get pool selection",
-        ) {
-            Ok(pool) => Some(pool.value),
-            Err(e) => return Err(e.into()),
-        };
+        )?
+        .value;
 
-        Ok(selection)
+        Ok(pools.get(idx))
     }
 
     /// convert a PoolEntry to an E6Pool
@@ -324,9 +669,9 @@ impl SearchMenu for E6Ui {
     }
 
     /// search posts
-    async fn search_posts(&self) -> Result<()> {
+    async fn search_posts(&self, input: TagInput) -> Result<()> {
         loop {
-            match self.perform_search().await {
+            match self.perform_search(input).await {
                 Ok(should_continue) => {
                     if !should_continue {
                         break;
@@ -369,21 +714,21 @@ impl SearchMenu for E6Ui {
     async fn perform_pool_search(&self) -> Result<bool> {
         let query = self.get_pool_search_query()?;
         let limit = self.get_pool_limit()? as usize;
+        let pool_db = self.pool_db();
 
         let pools = if query.is_empty() {
-            let local_pools: Vec<PoolEntry> =
-                self.pool_db.iter_pools().take(limit).cloned().collect();
+            let local_pools: Vec<PoolEntry> = pool_db.iter_pools().take(limit).cloned().collect();
 
             local_pools
                 .iter()
                 .map(|entry| self.pool_entry_to_e6pool(entry))
                 .collect()
         } else {
-            let local_matches = self.pool_db.search(&query, limit);
+            let local_matches = pool_db.search(&query, limit);
             if !local_matches.is_empty() {
                 local_matches
                     .iter()
-                    .filter_map(|name| self.pool_db.get_by_name(name))
+                    .filter_map(|name| pool_db.get_by_name(name))
                     .map(|entry| self.pool_entry_to_e6pool(entry))
                     .collect()
             } else {
@@ -412,8 +757,14 @@ impl SearchMenu for E6Ui {
     }
 
     /// perform a post search
-    async fn perform_search(&self) -> Result<bool> {
-        let (include_tags, or_tags, exclude_tags) = self.collect_tags()?;
+    async fn perform_search(&self, input: TagInput) -> Result<bool> {
+        let (include_tags, or_tags, exclude_tags) = match input {
+            TagInput::Typed => self.collect_tags()?,
+            TagInput::Browse => match self.browse_tag_tree()? {
+                Some(tags) => tags,
+                None => return Ok(false),
+            },
+        };
         let total_limit = self.get_post_limit()?;
         if include_tags.is_empty() && or_tags.is_empty() && exclude_tags.is_empty() {
             println!("Please specify at least one search tag.");
@@ -429,21 +780,225 @@ impl SearchMenu for E6Ui {
 
         debug!("Searching with tags: {:?}", all_tags);
 
-        if getopt!(search.search_history) {
-            if let Ok(mut history) = self.history.lock() {
-                history.add(&all_tags.join(" "));
-                let _ = history.save();
-            }
+        if getopt!(search.search_history)
+            && let Ok(mut history) = self.history.lock()
+        {
+            history.add(&all_tags.join(" "));
+            let _ = history.save();
         }
 
-        let posts = self.fetch_posts_paginated(all_tags, total_limit).await?;
+        let (all_tags, always) = with_always_tags(
+            all_tags,
+            &getopt!(search.always_include),
+            &getopt!(search.always_exclude),
+        );
+        if !always.is_empty() {
+            println!(
+                "Also searching with {} (add {} to skip them)",
+                always.join(" "),
+                RAW_SEARCH_TAG
+            );
+        }
 
-        if posts.is_empty() {
-            println!("No posts found matching your search criteria.");
-            return self.ask_continue("Would you like to perform another search?");
+        let all_tags = with_resolution_tags(
+            all_tags,
+            getopt!(search.min_width),
+            getopt!(search.min_height),
+        );
+        let min_fav_count = getopt!(search.min_fav_count);
+        let all_tags = with_fav_count_tag(all_tags, min_fav_count);
+
+        let prefetch = getopt!(performance.prefetch_enabled);
+        let mut next_page: Option<PageFetch> = None;
+        let mut before_id = None;
+
+        loop {
+            let mut posts = match next_page.take() {
+                Some(mut page) => match (&mut page.0).await {
+                    Ok(Ok((posts, capped))) => {
+                        println!("Loaded {} more posts.", posts.len());
+                        if capped {
+                            println!(
+                                "stopped after {} requests (search.max_pages), {} of {} posts \
+                                 fetched.",
+                                getopt!(search.max_pages),
+                                posts.len(),
+                                total_limit
+                            );
+                        }
+                        posts
+                    }
+                    _ => {
+                        self.fetch_posts_paginated(all_tags.clone(), total_limit, before_id)
+                            .await?
+                    }
+                },
+                None => {
+                    self.fetch_posts_paginated(all_tags.clone(), total_limit, before_id)
+                        .await?
+                }
+            };
+
+            let has_more = posts.len() as u64 >= total_limit;
+            before_id = posts.iter().map(|p| p.id).min();
+
+            if has_more && prefetch {
+                next_page = Some(Background::spawn(fetch_page(
+                    self.client.clone(),
+                    all_tags.clone(),
+                    total_limit,
+                    before_id,
+                )));
+            }
+
+            let hidden = E6Post::hide_below_score(&mut posts);
+            if hidden > 0 {
+                println!("{} posts hidden by score filter", hidden);
+            }
+
+            let before = posts.len();
+            posts.retain(|post| post.meets_resolution());
+            if posts.len() < before {
+                println!("{} posts hidden by resolution filter", before - posts.len());
+            }
+
+            let before = posts.len();
+            posts.retain(|post| post.fav_count.max(0) as u64 >= min_fav_count);
+            if posts.len() < before {
+                println!(
+                    "{} posts hidden by favorite count filter",
+                    before - posts.len()
+                );
+            }
+
+            if posts.is_empty() && !has_more {
+                println!("No posts found matching your search criteria.");
+                return self.after_search(&all_tags);
+            }
+
+            if !posts.is_empty() {
+                self.handle_post_interaction(posts).await?;
+            }
+
+            if !has_more || !self.ask_continue("Load the next page of results?")? {
+                return self.after_search(&all_tags);
+            }
         }
+    }
+
+    /// pick search tags by drilling into tag categories
+    fn browse_tag_tree(&self) -> Result<Option<SearchTags>> {
+        let mut includes: Vec<String> = Vec::new();
+        let mut excludes: Vec<String> = Vec::new();
+
+        loop {
+            println!();
+            if !includes.is_empty() {
+                println!("Include: {}", includes.join(" "));
+            }
+            if !excludes.is_empty() {
+                println!("Exclude: -{}", excludes.join(" -"));
+            }
+
+            let mut options: Vec<AskOption<TagTreeChoice>> = TagCategory::ALL
+                .iter()
+                .filter(|category| **category != TagCategory::Invalid)
+                .map(|category| {
+                    AskOption::with_name(
+                        format!("Browse {} tags", category),
+                        TagTreeChoice::Category(*category),
+                    )
+                })
+                .collect();
+
+            if !includes.is_empty() || !excludes.is_empty() {
+                options.push(AskOption::with_name(
+                    "Search with these tags".to_string(),
+                    TagTreeChoice::Search,
+                ));
+                options.push(AskOption::with_name(
+                    "Clear picked tags".to_string(),
+                    TagTreeChoice::Clear,
+                ));
+            }
+            options.push(AskOption::with_name(
+                "Cancel".to_string(),
+                TagTreeChoice::Cancel,
+            ));
+
+            let choice = miette::Context::context(
+                Select::new("Pick a tag category:")
+                    .with_options(options)
+                    .ask(),
+                "Failed to get tag category",
+            )?
+            .value;
+
+            let category = match choice {
+                TagTreeChoice::Category(category) => category,
+                TagTreeChoice::Search => return Ok(Some((includes, Vec::new(), excludes))),
+                TagTreeChoice::Clear => {
+                    includes.clear();
+                    excludes.clear();
+                    continue;
+                }
+                TagTreeChoice::Cancel => return Ok(None),
+            };
+
+            let query = miette::Context::context(
+                TextInput::new(format!("Filter {} tags:", category))
+                    .with_placeholder("leave empty for the most used tags")
+                    .ask(),
+                "Failed to get tag filter",
+            )?;
+
+            let tags: Vec<AskOption<String>> = self
+                .tag_db()
+                .search_category(category, &query, getopt!(ui.pagination_size) * 5)
+                .into_iter()
+                .filter(|tag| !includes.contains(&tag.name) && !excludes.contains(&tag.name))
+                .map(|tag| {
+                    AskOption::with_name(
+                        format!("{} ({} posts)", tag.name, tag.post_count),
+                        tag.name.clone(),
+                    )
+                })
+                .collect();
+
+            if tags.is_empty() {
+                println!("No {} tags match '{}'.", category, query);
+                continue;
+            }
+
+            let picked = miette::Context::context(
+                MultiSelect::new("Pick tags (Space to select, Enter to confirm):")
+                    .with_options(tags)
+                    .ask(),
+                "Failed to get tag selection",
+            )?;
+
+            if picked.is_empty() {
+                continue;
+            }
 
-        self.handle_post_interaction(posts).await
+            let exclude = miette::Context::context(
+                Select::new(format!("Add {} tags as:", picked.len()))
+                    .with_options(vec![
+                        AskOption::with_name("Include".to_string(), false),
+                        AskOption::with_name("Exclude".to_string(), true),
+                    ])
+                    .ask(),
+                "Failed to get tag mode",
+            )?
+            .value;
+
+            let target = if exclude {
+                &mut excludes
+            } else {
+                &mut includes
+            };
+            target.extend(picked.into_iter().map(|tag| tag.value));
+        }
     }
 
     /// perform a paginated post search
@@ -451,16 +1006,30 @@ impl SearchMenu for E6Ui {
         &self,
         all_tags: Vec<String>,
         total_limit: u64,
+        mut before_id: Option<i64>,
     ) -> Result<Vec<E6Post>> {
         let mut all_fetched_posts: Vec<E6Post> = Vec::new();
-        let mut before_id: Option<i64> = None;
         let mut seen = HashSet::new();
         let mut consecutive_empty_batches = 0;
+        let mut blacklisted = BlacklistReport::default();
+        let max_pages = getopt!(search.max_pages);
+        let mut pages = 0;
 
         println!("Fetching up to {} posts...", total_limit);
         let pb = self.create_search_progress_bar(total_limit)?;
 
         while (all_fetched_posts.len() as u64) < total_limit && consecutive_empty_batches < 3 {
+            if pages >= max_pages {
+                pb.println(format!(
+                    "stopped after {} requests (search.max_pages), {} of {} posts fetched.",
+                    pages,
+                    all_fetched_posts.len(),
+                    total_limit
+                ));
+                break;
+            }
+            pages += 1;
+
             let remaining = total_limit.saturating_sub(all_fetched_posts.len() as u64);
             let current_limit = (remaining * 2).min(getopt!(search.results)).max(20);
 
@@ -477,6 +1046,7 @@ impl SearchMenu for E6Ui {
                 .context("Failed to search posts")?;
 
             let batch_size_before_filtering = results.posts.len();
+            let batch_blacklisted = results.blacklisted;
 
             if results.posts.is_empty() {
                 consecutive_empty_batches += 1;
@@ -511,14 +1081,19 @@ impl SearchMenu for E6Ui {
             all_fetched_posts.extend(new_posts);
             pb.inc(fetched_count as u64);
 
-            let filtered = batch_size_before_filtering.saturating_sub(fetched_count);
-            if filtered > 0 {
+            let duplicates = batch_size_before_filtering.saturating_sub(fetched_count);
+            if duplicates > 0 {
                 pb.println(format!(
-                    "filtered out {} blacklisted/duplicate posts from this batch",
-                    filtered
+                    "filtered out {} duplicate posts from this batch",
+                    duplicates
                 ));
             }
 
+            if !batch_blacklisted.is_empty() && getopt!(search.verbose_blacklist) {
+                pb.println(format!("blacklist {} in this batch", batch_blacklisted));
+            }
+            blacklisted.merge(&batch_blacklisted);
+
             if fetched_count < 320 {
                 debug!("small batch received, approaching end of results");
                 pb.println("approaching end of available results.");
@@ -534,6 +1109,10 @@ impl SearchMenu for E6Ui {
         pb.set_length(all_fetched_posts.len() as u64);
         pb.finish_with_message(format!("✓ Fetched {} posts.", all_fetched_posts.len()));
 
+        if !blacklisted.is_empty() {
+            println!("Blacklist {}", blacklisted);
+        }
+
         Ok(all_fetched_posts)
     }
 
@@ -553,17 +1132,17 @@ impl SearchMenu for E6Ui {
     }
 
     /// handle a post interaction
-    async fn handle_post_interaction(&self, posts: Vec<E6Post>) -> Result<bool> {
-        if getopt!(performance.prefetch_enabled) {
+    async fn handle_post_interaction(&self, posts: Vec<E6Post>) -> Result<()> {
+        let _prefetch = getopt!(performance.prefetch_enabled).then(|| {
             let batch_size = getopt!(performance.prefetch_batch_size);
             let client = self.client.clone();
             let prefetch_ids: Vec<i64> = posts.iter().take(batch_size).map(|p| p.id).collect();
-            tokio::spawn(async move {
+            Background::spawn(async move {
                 for id in prefetch_ids {
                     let _ = client.get_post_by_id(id).await;
                 }
-            });
-        }
+            })
+        });
 
         let use_multi_select = miette::Context::context(
             Confirm::new("Select multiple posts?").ask(),
@@ -579,14 +1158,18 @@ impl SearchMenu for E6Ui {
                 let fetched_posts = self.fetch_selected_posts(selected_posts).await?;
 
                 if !fetched_posts.is_empty() {
+                    let to_preload = fetched_posts.clone();
+                    let client = self.client.clone();
+                    let _preload = Background::spawn(async move {
+                        preload_post_images(&client, &to_preload).await;
+                    });
+
                     self.batch_interaction_menu(fetched_posts).await?;
                 } else {
                     warn!("Failed to fetch any of the selected posts");
                     eprintln!("Failed to fetch any posts");
                 }
             }
-
-            self.ask_continue("Would you like to perform another search?")
         } else {
             let selected_post = self.select_post(&posts)?;
 
@@ -597,18 +1180,29 @@ impl SearchMenu for E6Ui {
                     .await
                     .context("Failed to fetch post details")?;
 
+                let to_preload = fetched_post.post.clone();
+                let client = self.client.clone();
+                let _preload = Background::spawn(async move {
+                    preload_post_images(&client, std::slice::from_ref(&to_preload)).await;
+                });
+
                 self.display_post(&fetched_post.post);
                 self.interaction_menu(fetched_post.post).await?;
             }
-
-            self.ask_continue("Would you like to perform another search?")
         }
+
+        Ok(())
     }
 
     /// fetch posts selected in list of results
     async fn fetch_selected_posts(&self, selected_posts: Vec<&E6Post>) -> Result<Vec<E6Post>> {
         let concurrent_limit = getopt!(search.fetch_threads).max(1);
-        let post_ids: Vec<i64> = selected_posts.iter().map(|post| post.id).collect();
+        let mut seen = HashSet::new();
+        let post_ids: Vec<i64> = selected_posts
+            .iter()
+            .map(|post| post.id)
+            .filter(|id| seen.insert(*id))
+            .collect();
         let total_count = post_ids.len();
 
         if total_count == 0 {
@@ -756,37 +1350,64 @@ impl SearchMenu for E6Ui {
         .map_err(Report::new)
     }
 
+    /// offer to open or copy a finished search on the website, or start another one
+    fn after_search(&self, tags: &[String]) -> Result<bool> {
+        let url = search_url(&getopt!(http.api), tags);
+
+        loop {
+            let options = vec![
+                AskOption::with_name("Perform another search".to_string(), AfterSearch::Again),
+                AskOption::with_name("Open this search on e621".to_string(), AfterSearch::Open),
+                AskOption::with_name("Copy this search's e621 URL".to_string(), AfterSearch::Copy),
+                AskOption::with_name("Back".to_string(), AfterSearch::Done),
+            ];
+
+            let choice = miette::Context::context(
+                Select::new("What next?").with_options(options).ask(),
+                "Failed to get next action",
+            )?
+            .value;
+
+            match choice {
+                AfterSearch::Again => return Ok(true),
+                AfterSearch::Done => return Ok(false),
+                AfterSearch::Open => {
+                    open::that(&url).context("Failed to open search in browser")?;
+                    println!("Opened search in browser: {}", url);
+                }
+                AfterSearch::Copy => {
+                    crate::utils::copy_to_clipboard(&url)?;
+                    println!("Copied to clipboard: {}", url);
+                }
+            }
+        }
+    }
+
     /// select a post from a list of posts
     fn select_post<'a>(&self, posts: &'a [E6Post]) -> Result<Option<&'a E6Post>> {
         if posts.is_empty() {
             return Ok(None);
         }
 
+        let template = post_list_template();
         let options: Vec<_> = posts
             .iter()
-            .map(|post| {
-                AskOption::with_name(
-                    format!(
-                        "ID: {} | Score: {} | Rating: {}",
-                        post.id, post.score.total, post.rating
-                    ),
-                    post,
-                )
+            .enumerate()
+            .map(|(idx, post)| {
+                AskOption::with_name(format_colored_post_list_entry(&template, post), idx)
             })
             .collect();
 
-        let selection = match miette::Context::context(
+        let idx = miette::Context::context(
             Select::new("Select a post to view:")
                 .with_options(options)
                 .with_page_size(getopt!(ui.pagination_size))
                 .ask(),
             "Failed to get post selection",
-        ) {
-            Ok(p) => Some(p.value),
-            Err(e) => return Err(e.into()),
-        };
+        )?
+        .value;
 
-        Ok(selection)
+        Ok(posts.get(idx))
     }
 
     /// display a pools info
@@ -806,3 +1427,66 @@ impl SearchMenu for E6Ui {
         println!("{}", "=".repeat(70));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            search_url(
+                "https://e621.net/",
+                &strings(&["fox", "-rating:e", "score:>=10"])
+            ),
+            "https://e621.net/posts?tags=fox%20-rating%3Ae%20score%3A%3E%3D10"
+        );
+        assert_eq!(
+            search_url("https://e926.net", &strings(&["~cat", "~dog_(species)"])),
+            "https://e926.net/posts?tags=~cat%20~dog_%28species%29"
+        );
+        assert_eq!(
+            search_url("https://e621.net", &[]),
+            "https://e621.net/posts?tags="
+        );
+    }
+
+    #[test]
+    fn test_with_fav_count_tag() {
+        assert_eq!(with_fav_count_tag(strings(&["fox"]), 0), strings(&["fox"]));
+        assert_eq!(
+            with_fav_count_tag(strings(&["fox"]), 50),
+            strings(&["fox", "favcount:>=50"])
+        );
+        assert_eq!(
+            with_fav_count_tag(strings(&["fox", "favcount:<10"]), 50),
+            strings(&["fox", "favcount:<10"])
+        );
+        assert_eq!(
+            with_fav_count_tag(strings(&["-favcount:0"]), 50),
+            strings(&["-favcount:0"])
+        );
+    }
+
+    #[test]
+    fn test_with_always_tags() {
+        let include = strings(&["+solo", "canine"]);
+        let exclude = strings(&["-gore", "scat", "solo"]);
+
+        let (tags, added) = with_always_tags(strings(&["fox"]), &include, &exclude);
+        assert_eq!(tags, strings(&["fox", "solo", "canine", "-gore", "-scat"]));
+        assert_eq!(added, strings(&["solo", "canine", "-gore", "-scat"]));
+
+        let (tags, added) = with_always_tags(strings(&["-canine", "~gore"]), &include, &exclude);
+        assert_eq!(tags, strings(&["-canine", "~gore", "solo", "-scat"]));
+        assert_eq!(added, strings(&["solo", "-scat"]));
+
+        let (tags, added) = with_always_tags(strings(&["fox", RAW_SEARCH_TAG]), &include, &exclude);
+        assert_eq!(tags, strings(&["fox"]));
+        assert!(added.is_empty());
+    }
+}
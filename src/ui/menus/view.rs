@@ -2,6 +2,8 @@
 use {
     crate::{
         bail,
+        client::E6Client,
+        data::recent::RecentlyViewed,
         display::{
             dtext::parser::format_text,
             image::{
@@ -14,18 +16,280 @@ use {
         },
         error::{Report, Result},
         getopt,
-        models::E6Post,
+        models::{E6Post, PostTree, TagCategory},
         ui::E6Ui,
     },
+    bearask::{AskOption, Select},
     color_eyre::eyre::Context,
+    crossterm::event::{self, Event, KeyEventKind},
+    owo_colors::OwoColorize,
     std::{
+        collections::VecDeque,
         io::{self, Write},
         path::Path,
+        sync::{LazyLock, Mutex},
         thread,
         time::Duration,
     },
+    tracing::debug,
 };
 
+/// a preloaded image and the url it was fetched from
+type PreloadedImage = (String, Vec<u8>);
+
+/// images fetched ahead of time for posts that are about to be viewed, oldest first
+///
+/// the total size is kept under `performance.max_preload_size_mb` by dropping the oldest images
+static PRELOADED_IMAGES: LazyLock<Mutex<VecDeque<PreloadedImage>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// take a preloaded image out of the cache
+fn take_preloaded(url: &str) -> Option<Vec<u8>> {
+    let mut images = PRELOADED_IMAGES.lock().ok()?;
+    let idx = images.iter().position(|(u, _)| u == url)?;
+    images.remove(idx).map(|(_, bytes)| bytes)
+}
+
+/// render a post's metadata as a table, grouped into sections
+///
+/// covers the file, score, tags by category, relationships, and dates. use
+/// `serde_json::to_string_pretty` when every field is needed
+///
+/// # Arguments
+///
+/// * `post` - the post to render
+pub fn format_post_metadata(post: &E6Post) -> String {
+    /// add a section heading
+    fn section(out: &mut String, title: &str) {
+        out.push_str(&format!("\n{}\n", title.bold().cyan()));
+    }
+
+    /// add a labeled value, skipping empty ones
+    fn row(out: &mut String, label: &str, value: impl std::fmt::Display) {
+        let value = value.to_string();
+        if !value.is_empty() {
+            out.push_str(&format!("  {:<14} {}\n", label.bright_black(), value));
+        }
+    }
+
+    /// join a list of values, or leave it empty if there aren't any
+    fn list<T: ToString>(items: &[T]) -> String {
+        items
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    let mut out = format!("{} #{}\n", "Post".bold(), post.id);
+    row(&mut out, "Rating", post.content_rating().name());
+    row(&mut out, "Uploader", &post.uploader_name);
+
+    section(&mut out, "File");
+    row(&mut out, "Type", &post.file.ext);
+    row(
+        &mut out,
+        "Resolution",
+        format!("{}x{}", post.file.width, post.file.height),
+    );
+    row(
+        &mut out,
+        "Size",
+        getopt!(ui.progress.format).format_size(post.file.size.max(0) as u64),
+    );
+    if let Some(duration) = post.duration {
+        row(&mut out, "Duration", format!("{:.1}s", duration));
+    }
+    row(&mut out, "MD5", &post.file.md5);
+    row(
+        &mut out,
+        "URL",
+        post.file.url.as_deref().unwrap_or_default(),
+    );
+
+    section(&mut out, "Score");
+    row(
+        &mut out,
+        "Votes",
+        format!(
+            "{} ({} / {})",
+            post.score.total,
+            format!("↑{}", post.score.up).green(),
+            format!("↓{}", post.score.down.abs()).red()
+        ),
+    );
+    row(&mut out, "Favorites", post.fav_count);
+    row(&mut out, "Comments", post.comment_count);
+
+    section(&mut out, "Tags");
+    for category in TagCategory::ALL {
+        row(
+            &mut out,
+            category.name(),
+            list(post.tags.category(category)),
+        );
+    }
+
+    section(&mut out, "Relationships");
+    row(
+        &mut out,
+        "Parent",
+        post.relationships
+            .parent_id
+            .map(|id| format!("#{}", id))
+            .unwrap_or_default(),
+    );
+    row(
+        &mut out,
+        "Children",
+        list(post.relationships.children.as_deref().unwrap_or_default()),
+    );
+    row(&mut out, "Pools", list(&post.pools));
+    row(
+        &mut out,
+        "Sources",
+        post.sources.join("\n                 "),
+    );
+
+    section(&mut out, "Dates");
+    row(&mut out, "Created", &post.created_at);
+    row(&mut out, "Updated", &post.updated_at);
+
+    out
+}
+
+/// fetch an image through the client so it's cached, retrying failures up to
+/// `display.fetch_retries` times
+///
+/// gives up after `display.fetch_timeout` seconds and returns `None`, so a slow image can't
+/// hang the ui
+///
+/// # Arguments
+///
+/// * `client` - the client to fetch with
+/// * `url` - the url of the image
+///
+/// # Errors
+///
+/// returns an error if every attempt fails before the timeout
+async fn fetch_image(client: &E6Client, url: &str) -> Result<Option<Vec<u8>>> {
+    let timeout = Duration::from_secs(getopt!(display.fetch_timeout));
+    let fetch = client.execute_with_retry(getopt!(display.fetch_retries), || {
+        client.get_cached_or_fetch_once(url)
+    });
+
+    match tokio::time::timeout(timeout, fetch).await {
+        Ok(bytes) => bytes.map(Some),
+        Err(_) => {
+            debug!(url, ?timeout, "image fetch timed out");
+            Ok(None)
+        }
+    }
+}
+
+/// fetch the images of posts that are about to be viewed
+///
+/// does nothing unless `performance.preload_images` is on. at most
+/// `performance.prefetch_batch_size` images are fetched, and posts bigger than
+/// `performance.max_preload_size_mb` are skipped. failures are ignored, the image is just
+/// fetched again when it's viewed
+///
+/// # Arguments
+///
+/// * `client` - the client to fetch with
+/// * `posts` - the posts to preload, most likely to be viewed first
+pub async fn preload_post_images(client: &E6Client, posts: &[E6Post]) {
+    if !getopt!(performance.preload_images) {
+        return;
+    }
+
+    let budget = getopt!(performance.max_preload_size_mb) * 1024 * 1024;
+    let urls: Vec<String> = posts
+        .iter()
+        .filter(|p| u64::try_from(p.file.size).is_ok_and(|size| size <= budget))
+        .filter_map(|p| p.file.url.clone())
+        .take(getopt!(performance.prefetch_batch_size))
+        .collect();
+
+    for url in urls {
+        let cached = PRELOADED_IMAGES
+            .lock()
+            .is_ok_and(|images| images.iter().any(|(u, _)| *u == url));
+        if cached {
+            continue;
+        }
+
+        let bytes = match fetch_image(client, &url).await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!(url, error = %e, "failed to preload image");
+                continue;
+            }
+        };
+
+        if let Ok(mut images) = PRELOADED_IMAGES.lock() {
+            images.push_back((url, bytes));
+
+            let mut total: u64 = images.iter().map(|(_, b)| b.len() as u64).sum();
+            while total > budget
+                && let Some((_, evicted)) = images.pop_front()
+            {
+                total -= evicted.len() as u64;
+            }
+        }
+    }
+}
+
+/// an option in the recently viewed list
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RecentChoice {
+    /// re-open the post with this id
+    Post(i64),
+    /// forget all recently viewed posts
+    Clear,
+    /// go back
+    Back,
+}
+
+/// the most ids e621 accepts in a single `id:` search
+const MAX_IDS_PER_SEARCH: usize = 100;
+
+/// what to do when more posts are opened in the browser than `ui.max_browser_tabs`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TabChoice {
+    /// open a single `id:` search of all the posts
+    Search,
+    /// only open the first `ui.max_browser_tabs` posts
+    FirstTabs,
+    /// open every post in its own tab anyway
+    AllTabs,
+    /// don't open anything
+    Cancel,
+}
+
+/// get e621 search urls that show all the given posts
+///
+/// e621 only takes up to [`MAX_IDS_PER_SEARCH`] ids per search, so longer lists get one url per
+/// chunk. each url asks for a page big enough to show its whole chunk, since the default page only
+/// has 75 posts
+///
+/// # Arguments
+///
+/// * `ids` - the ids of the posts
+pub fn multi_post_urls(ids: &[i64]) -> Vec<String> {
+    ids.chunks(MAX_IDS_PER_SEARCH)
+        .map(|chunk| {
+            let ids: Vec<String> = chunk.iter().map(i64::to_string).collect();
+            format!(
+                "https://e621.net/posts?tags=id:{}&limit={}",
+                ids.join(","),
+                MAX_IDS_PER_SEARCH
+            )
+        })
+        .collect()
+}
+
 /// load an animation from bytes with explicit extension
 ///
 /// # Arguments
@@ -33,15 +297,30 @@ use {
 /// * `bytes` - the bytes of the animation file
 /// * `ext` - the extension to load as (webp/gif)
 fn load_animated_from_bytes_with_ext(bytes: &[u8], ext: &str) -> Result<AnimatedImage> {
+    let max_frames = frames_to_decode();
     match ext.to_lowercase().as_str() {
-        "gif" => AnimatedImage::from_gif_bytes(bytes).map_err(Report::new),
-        "webp" => AnimatedImage::from_webp_bytes(bytes).map_err(Report::new),
+        "gif" => AnimatedImage::from_gif_bytes(bytes, max_frames).map_err(Report::new),
+        "webp" => AnimatedImage::from_webp_bytes(bytes, max_frames).map_err(Report::new),
         _ => bail!("Unsupported animation format: {}", ext),
     }
 }
 
+/// how many frames of an animation to decode
+///
+/// only the first frame is shown when `display.animate` is off, so only it is decoded
+fn frames_to_decode() -> usize {
+    if getopt!(display.animate) {
+        getopt!(display.max_frames)
+    } else {
+        1
+    }
+}
+
 /// play an animation in the terminal
 ///
+/// plays as many times as the animation asks for, or until Enter is pressed. only the first
+/// frame is shown when `display.animate` is off
+///
 /// # Arguments
 ///
 /// * `animated` - the animated image to play
@@ -52,20 +331,21 @@ fn play_animation(
     processor: &ImageProcessor,
     encoder: &SixelEncoder,
 ) -> Result<()> {
-    if animated.frame_count() == 1 {
-        let frame = animated.get_frame(0).expect("No frame");
+    let processed = processor
+        .process_animated(animated)
+        .context("failed to process animation")?;
+
+    if processed.frame_count() == 1 || !getopt!(display.animate) {
+        let frame = processed.get_frame(0).expect("No frame");
         let sixel_str = encoder
             .encode(&frame.data)
             .context("failed to encode frame to sixel")?;
         print!("{}", sixel_str);
+        println!();
         io::stdout().flush()?;
         return Ok(());
     }
 
-    let processed = processor
-        .process_animated(animated)
-        .context("failed to process animation")?;
-
     #[allow(clippy::manual_div_ceil, reason = "bro")]
     let term_lines = (processed.height + 5) / 6;
 
@@ -77,30 +357,35 @@ fn play_animation(
         encoded_frames.push((sixel_str, frame.delay));
     }
 
+    println!("(press Enter to stop)");
     print!("\x1b[s");
     io::stdout().flush()?;
 
     let is_infinite = processed.is_infinite_loop();
-    let loop_count = if is_infinite {
-        1000
-    } else {
-        processed.loop_count.max(1)
-    };
+    let mut plays = 0;
+    let mut first = true;
 
-    for loop_idx in 0..loop_count {
-        for (frame_idx, (sixel_str, delay)) in encoded_frames.iter().enumerate() {
-            if loop_idx > 0 || frame_idx > 0 {
+    'playback: loop {
+        for (sixel_str, delay) in &encoded_frames {
+            if !first {
                 print!("\x1B[{}A\x1B[G", term_lines);
             }
+            first = false;
 
             print!("{}", sixel_str);
             println!();
             io::stdout().flush()?;
 
-            thread::sleep(*delay);
+            if event::poll(*delay)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                break 'playback;
+            }
         }
 
-        if !is_infinite {
+        plays += 1;
+        if !is_infinite && plays >= processed.loop_count {
             break;
         }
     }
@@ -114,21 +399,44 @@ fn play_animation(
 
 /// fetch a post image and display it in the terminal
 ///
+/// gifs and animated webps are played, videos show the still sample e621 makes for them, and a
+/// placeholder is printed instead if the image takes longer than `display.fetch_timeout` seconds
+///
 /// # Arguments
 ///
+/// * `client` - the client to fetch the image with
 /// * `post` - the post to fetch and display
 #[allow(clippy::await_holding_lock)]
-pub async fn print_post_to_terminal(post: E6Post) -> Result<()> {
-    let post_url = post.file.url.unwrap_or(
+pub async fn print_post_to_terminal(client: &E6Client, post: E6Post) -> Result<()> {
+    let image_url = if matches!(post.file.ext.as_str(), "webm" | "mp4") {
+        post.sample.url.or(post.preview.url)
+    } else {
+        post.file.url
+    };
+    let post_url = image_url.unwrap_or(
         "https://static1.e621.net/data/sample/87/23/872340c066697711a8fe432271ef4768_480p.mp4"
             .to_string(),
     );
     let cfg = crate::config::instance::config()?;
     let processor = ImageProcessor::with_dimensions(ImageDimensions::from_cfg(&cfg)?);
     let encoder = SixelEncoder::new();
-    let source = ImageSource::from_url(&post_url)
-        .await
-        .context("failed to fetch image")?;
+    let source = match take_preloaded(&post_url) {
+        Some(bytes) => ImageSource::from_bytes(bytes),
+        None => match fetch_image(client, &post_url)
+            .await
+            .context("failed to fetch image")?
+        {
+            Some(bytes) => ImageSource::from_bytes(bytes),
+            None => {
+                println!(
+                    "[image took longer than {}s to load: {}]",
+                    getopt!(display.fetch_timeout),
+                    post_url
+                );
+                return Ok(());
+            }
+        },
+    };
 
     let url_path = Path::new(&post_url);
     if let (true, Some(ext)) = (is_animated_format(url_path), url_path.extension())
@@ -162,6 +470,42 @@ pub async fn print_post_to_terminal(post: E6Post) -> Result<()> {
     Ok(())
 }
 
+/// decode a still image and encode it to sixel without printing it
+///
+/// # Arguments
+///
+/// * `path` - the path to the image to encode
+/// * `processor` - resizes the image to the configured dimensions
+/// * `encoder` - the sixel encoder
+fn encode_still(path: &Path, processor: &ImageProcessor, encoder: &SixelEncoder) -> Result<String> {
+    let source = ImageSource::from_path(path).context("failed to load image")?;
+    let image_data = processor
+        .process(source)
+        .context("failed to process image")?;
+
+    Ok(encoder
+        .encode(&image_data)
+        .context("failed to encode to sixel")?)
+}
+
+/// decode a downloaded image and encode it to sixel ahead of time
+///
+/// returns `None` for animations, which are decoded frame by frame as they play
+///
+/// # Arguments
+///
+/// * `path` - the path to the image to encode
+pub fn encode_dl_sixel(path: &Path) -> Result<Option<String>> {
+    if is_animated_format(path) {
+        return Ok(None);
+    }
+
+    let cfg = getopt!()?;
+    let processor = ImageProcessor::with_dimensions(ImageDimensions::from_cfg(&cfg)?);
+
+    encode_still(path, &processor, &SixelEncoder::new()).map(Some)
+}
+
 /// print an image to the terminal
 ///
 /// # Arguments
@@ -173,7 +517,7 @@ pub fn print_dl_to_terminal(path: &Path) -> Result<()> {
     let encoder = SixelEncoder::new();
 
     if is_animated_format(path) {
-        match load_animated(path) {
+        match load_animated(path, frames_to_decode()) {
             Ok(animated) => {
                 return play_animation(animated, &processor, &encoder);
             }
@@ -187,13 +531,7 @@ pub fn print_dl_to_terminal(path: &Path) -> Result<()> {
         }
     }
 
-    let source = ImageSource::from_path(path).context("failed to load image")?;
-    let image_data = processor
-        .process(source)
-        .context("failed to process image")?;
-    let sixel_str = encoder
-        .encode(&image_data)
-        .context("failed to encode to sixel")?;
+    let sixel_str = encode_still(path, &processor, &encoder)?;
 
     print!("{}", sixel_str);
     println!();
@@ -201,14 +539,75 @@ pub fn print_dl_to_terminal(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// render a post tree as an indented list, one post per line
+///
+/// each line has the post's id, score, rating, resolution, and file size, so the versions of a
+/// post can be compared at a glance
+///
+/// # Arguments
+///
+/// * `tree` - the tree to render
+/// * `current` - the post to mark as the one being viewed
+pub fn format_post_tree(tree: &PostTree, current: i64) -> Vec<String> {
+    /// render a post and its children under a prefix (`last` is `None` for the root)
+    fn walk(
+        tree: &PostTree,
+        current: i64,
+        prefix: &str,
+        last: Option<bool>,
+        lines: &mut Vec<String>,
+    ) {
+        let post = &tree.post;
+        let branch = match last {
+            Some(true) => "└── ",
+            Some(false) => "├── ",
+            None => "",
+        };
+        let mut line = format!(
+            "{}{}#{}  score {}  {}  {}x{} {}  {}",
+            prefix,
+            branch,
+            post.id,
+            post.score.total,
+            post.content_rating(),
+            post.file.width,
+            post.file.height,
+            post.file.ext,
+            getopt!(ui.progress.format)
+                .format_size(post.file.size.max(0) as u64)
+                .trim()
+        );
+        if post.id == current {
+            line = format!("{}  {}", line.bold(), "<- this post".green());
+        }
+        lines.push(line);
+
+        let child_prefix = match last {
+            Some(true) => format!("{}    ", prefix),
+            Some(false) => format!("{}│   ", prefix),
+            None => prefix.to_string(),
+        };
+        for (i, child) in tree.children.iter().enumerate() {
+            let last = i + 1 == tree.children.len();
+            walk(child, current, &child_prefix, Some(last), lines);
+        }
+    }
+
+    let mut lines = Vec::with_capacity(tree.count());
+    walk(tree, current, "", None, &mut lines);
+    lines
+}
+
 /// fetch multiple posts and display them in the terminal
 ///
 /// # Arguments
 ///
+/// * `client` - the client to fetch images with
 /// * `posts` - a list of posts to fetch and print
-pub async fn print_posts_to_terminal(posts: Vec<E6Post>) -> Result<()> {
+pub async fn print_posts_to_terminal(client: &E6Client, posts: Vec<E6Post>) -> Result<()> {
     for post in posts {
-        print_post_to_terminal(post).await?;
+        RecentlyViewed::record(&post);
+        print_post_to_terminal(client, post).await?;
     }
 
     Ok(())
@@ -287,12 +686,22 @@ pub trait ViewMenu {
     /// display the latest posts
     fn display_latest_posts(&self) -> impl Future<Output = Result<()>>;
 
+    /// browse recently viewed posts and re-open one of them
+    fn browse_recently_viewed(&self) -> impl Future<Output = Result<()>>;
+
     /// display an individual post
     ///
     /// # Arguments
     ///
     /// * `post` - the post to display
     fn display_post(&self, post: &E6Post);
+
+    /// fetch and print the whole parent/child tree a post is in
+    ///
+    /// # Arguments
+    ///
+    /// * `post` - any post in the tree
+    fn show_post_tree(&self, post: &E6Post) -> impl Future<Output = Result<()>>;
 }
 
 impl ViewMenu for E6Ui {
@@ -314,10 +723,52 @@ impl ViewMenu for E6Ui {
 
     /// open multiple posts in the users default browser
     ///
+    /// more posts than `ui.max_browser_tabs` asks first, offering a single search of all of
+    /// them instead of a tab each
+    ///
     /// # Arguments
     ///
     /// * `posts` - a list of posts to open
     fn open_posts_in_browser(&self, posts: &[E6Post]) -> Result<()> {
+        let max_tabs = getopt!(ui.max_browser_tabs);
+        let posts = if max_tabs > 0 && posts.len() > max_tabs {
+            let choice = Select::new(format!(
+                "{} posts are selected, more than the {} tab limit. How should they be opened?",
+                posts.len(),
+                max_tabs
+            ))
+            .with_options(vec![
+                AskOption::with_name(
+                    "As a single e621 search of all of them".to_string(),
+                    TabChoice::Search,
+                ),
+                AskOption::with_name(
+                    format!("Only the first {} in tabs", max_tabs),
+                    TabChoice::FirstTabs,
+                ),
+                AskOption::with_name(format!("All {} in tabs", posts.len()), TabChoice::AllTabs),
+                AskOption::with_name("Cancel".to_string(), TabChoice::Cancel),
+            ])
+            .ask()?
+            .value;
+
+            match choice {
+                TabChoice::Search => {
+                    let ids: Vec<i64> = posts.iter().map(|post| post.id).collect();
+                    for url in multi_post_urls(&ids) {
+                        open::that(&url).context("Failed to open search in browser")?;
+                        println!("Opened search in browser: {}", url);
+                    }
+                    return Ok(());
+                }
+                TabChoice::FirstTabs => &posts[..max_tabs],
+                TabChoice::AllTabs => posts,
+                TabChoice::Cancel => return Ok(()),
+            }
+        } else {
+            posts
+        };
+
         println!("Opening {} posts in browser...", posts.len());
         for post in posts {
             let url = format!("https://e621.net/posts/{}", post.id);
@@ -340,7 +791,11 @@ impl ViewMenu for E6Ui {
         self.print_posts_field(posts, column_width, |post| format!("ID: {}", post.id));
         self.print_row_separator(posts.len(), column_width, "├", "┼", "┤", "─");
         self.print_posts_field(posts, column_width, |post| {
-            format!("Rating: {} | Score: {}", post.rating, post.score.total)
+            format!(
+                "Rating: {} | Score: {}",
+                post.content_rating(),
+                post.score.total
+            )
         });
         self.print_posts_field(posts, column_width, |post| {
             let uploader = self.truncate_string(&post.uploader_name, 15);
@@ -453,12 +908,17 @@ impl ViewMenu for E6Ui {
 
     /// display the latest posts
     async fn display_latest_posts(&self) -> Result<()> {
-        let results = self
+        let mut results = self
             .client
             .get_latest_posts()
             .await
             .context("Failed to fetch latest posts")?;
 
+        let hidden = E6Post::hide_below_score(&mut results.posts);
+        if hidden > 0 {
+            println!("{} posts hidden by score filter", hidden);
+        }
+
         if results.posts.is_empty() {
             println!("No latest posts found.");
             return Ok(());
@@ -469,6 +929,67 @@ impl ViewMenu for E6Ui {
         Ok(())
     }
 
+    /// browse recently viewed posts and re-open one of them
+    async fn browse_recently_viewed(&self) -> Result<()> {
+        loop {
+            let recent = RecentlyViewed::current().unwrap_or_default();
+
+            if recent.entries().is_empty() {
+                println!("No recently viewed posts.");
+                return Ok(());
+            }
+
+            let mut options: Vec<AskOption<RecentChoice>> = recent
+                .entries()
+                .iter()
+                .map(|entry| {
+                    let viewed_at = chrono::DateTime::parse_from_rfc3339(&entry.viewed_at)
+                        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|_| entry.viewed_at.clone());
+
+                    AskOption::with_name(
+                        format!("ID: {} | Viewed: {}", entry.id, viewed_at),
+                        RecentChoice::Post(entry.id),
+                    )
+                })
+                .collect();
+
+            options.push(AskOption::with_name(
+                "Clear recently viewed".to_string(),
+                RecentChoice::Clear,
+            ));
+            options.push(AskOption::with_name("Back".to_string(), RecentChoice::Back));
+
+            let choice = Select::new(format!(
+                "Recently viewed posts ({}):",
+                recent.entries().len()
+            ))
+            .with_options(options)
+            .ask()?
+            .value;
+
+            match choice {
+                RecentChoice::Post(id) => {
+                    let post = self
+                        .client
+                        .get_post_by_id(id)
+                        .await
+                        .context("Failed to fetch post")?
+                        .post;
+
+                    self.display_post(&post);
+                    self.interaction_menu(post).await?;
+                }
+                RecentChoice::Clear => {
+                    RecentlyViewed::clear_all()?;
+                    println!("Cleared recently viewed posts.");
+                    return Ok(());
+                }
+                RecentChoice::Back => return Ok(()),
+            }
+        }
+    }
+
     /// display an individual post
     ///
     /// # Arguments
@@ -477,12 +998,13 @@ impl ViewMenu for E6Ui {
     fn display_post(&self, post: &E6Post) {
         println!("\n{}", "=".repeat(50));
         println!("Post ID: {}", post.id);
-        println!("Rating: {}", post.rating);
+        println!("Rating: {}", post.content_rating());
         println!(
             "Score: ↑{} ↓{} = {}",
             post.score.up, post.score.down, post.score.total
         );
         println!("Tags: {}", post.tags.general[0..=3].join(", "));
+        println!("Tag counts: {}", post.tags.count_summary());
         println!("Favorites: {}", post.fav_count);
         println!("Uploaded by: {}", post.uploader_name);
 
@@ -496,4 +1018,86 @@ impl ViewMenu for E6Ui {
 
         println!("{}", "=".repeat(50));
     }
+
+    /// fetch and print the whole parent/child tree a post is in
+    async fn show_post_tree(&self, post: &E6Post) -> Result<()> {
+        if post.relationships.parent_id.is_none() && !post.relationships.has_children {
+            println!("Post {} has no parent or children.", post.id);
+            return Ok(());
+        }
+
+        println!("Fetching the relationship tree of post {}...", post.id);
+        let (tree, truncated) = self
+            .client
+            .get_post_tree(post.id)
+            .await
+            .context("failed to fetch the relationship tree")?;
+
+        println!(
+            "\n{} ({} posts)",
+            "Relationship tree".bold().cyan(),
+            tree.count()
+        );
+        for line in format_post_tree(&tree, post.id) {
+            println!("  {}", line);
+        }
+
+        if truncated {
+            println!(
+                "{}",
+                "The tree is too big to show in full, some posts were left out.".yellow()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_post_tree() {
+        let node = |id: i64, children: Vec<PostTree>| PostTree {
+            post: E6Post {
+                id,
+                ..Default::default()
+            },
+            children,
+        };
+        let tree = node(
+            1,
+            vec![node(2, vec![node(4, Vec::new())]), node(3, Vec::new())],
+        );
+
+        let lines = format_post_tree(&tree, 3);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("#1  score"));
+        assert!(lines[1].starts_with("├── #2  score"));
+        assert!(lines[2].starts_with("│   └── #4  score"));
+        assert!(lines[3].contains("└── #3  score"));
+        assert!(lines[3].contains("<- this post"));
+        assert!(!lines[..3].iter().any(|line| line.contains("<- this post")));
+    }
+
+    #[test]
+    fn test_multi_post_urls() {
+        assert!(multi_post_urls(&[]).is_empty());
+        assert_eq!(
+            multi_post_urls(&[1, 2, 3]),
+            vec!["https://e621.net/posts?tags=id:1,2,3&limit=100"]
+        );
+
+        let ids: Vec<i64> = (1..=250).collect();
+        let urls = multi_post_urls(&ids);
+        assert_eq!(urls.len(), 3);
+        assert!(urls[0].starts_with("https://e621.net/posts?tags=id:1,2,"));
+        assert!(urls[0].ends_with(",100&limit=100"));
+        assert!(urls[1].starts_with("https://e621.net/posts?tags=id:101,"));
+        assert_eq!(
+            urls[2].trim_end_matches("&limit=100").matches(',').count(),
+            49
+        );
+    }
 }
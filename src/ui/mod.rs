@@ -1,26 +1,38 @@
 //! tui stuff for e62rs
 use {
     crate::{
+        app::interrupt::InterruptHandler,
         bail,
-        client::E6Client,
-        config::{blacklist::get_blacklist, options::E62Rs},
-        data::{history::SearchHistory, pools::PoolDb, tags::TagDb},
+        client::{E6Client, flags::FlagReason},
+        config::{
+            blacklist::get_blacklist,
+            options::{E62Rs, REDACTED},
+            validate::{Validate, format_validation_errors},
+        },
+        data::{history::SearchHistory, pools::PoolDb, recent::RecentlyViewed, tags::TagDb},
         error::{Report, Result},
         getopt,
-        models::{E6Pool, E6Post},
-        serve::{cfg::ServerConfig, server::MediaServer},
+        models::{E6Pool, E6Post, TagCategory},
+        serve::{auth::ServerAuth, cfg::ServerConfig, server::MediaServer},
         ui::{
             autocomplete::TagAutocompleter,
             menus::{
                 BatchAction, InteractionMenu, PoolInteractionMenu,
-                download::{PostDownloader, sanitize_pool_name},
-                explore::ExploreMenu,
+                blacklist::BlacklistMenu,
+                download::{
+                    PostDownloader, clear_queued_downloads, dequeue_downloads,
+                    downloaded_pool_posts, format_colored_post_list_entry, post_list_template,
+                    queued_downloads, sanitize_pool_name,
+                },
+                explore::{ExploreMenu, LocalPost},
+                search::RAW_SEARCH_TAG,
                 view::{ViewMenu, print_post_to_terminal, print_posts_to_terminal},
             },
             progress::ProgressManager,
         },
+        utils::FileWriter,
     },
-    bearask::{AskOption, Confirm, MultiSelect, Number, TextInput},
+    bearask::{AskOption, Confirm, MultiSelect, Number, Select, TextInput},
     boundbook::BbfBuilder,
     color_eyre::eyre::Context,
     hashbrown::{HashMap, HashSet},
@@ -29,9 +41,13 @@ use {
     qrcode::QrCode,
     serde::{Deserialize, Serialize},
     std::{
+        net::{IpAddr, SocketAddr},
         path::PathBuf,
         str::FromStr,
-        sync::{Arc, Mutex},
+        sync::{
+            Arc, Mutex, PoisonError, RwLock,
+            atomic::{AtomicU64, Ordering},
+        },
         time::Duration,
     },
     tokio::{fs, sync::Semaphore},
@@ -42,6 +58,10 @@ pub mod autocomplete;
 pub mod menus;
 pub mod progress;
 
+/// an artist's new posts and already downloaded and blacklisted counts, or why they couldn't be
+/// checked
+type ArtistScan = Result<(Vec<E6Post>, u64, u64), String>;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 /// a post download
 pub struct Download {
@@ -58,6 +78,81 @@ pub struct DownloadData {
     pub posts: Vec<Download>,
 }
 
+/// order downloaded artists for the artist updater
+///
+/// pinned artists come first, in the order they're pinned, then the rest by download count and
+/// name. pinned names are matched case-insensitively and pinned artists that haven't been
+/// downloaded are left out. returns each artist's name, download count, and whether it's pinned
+///
+/// # Arguments
+///
+/// * `counts` - the number of downloaded posts of each artist
+/// * `pinned` - the artists to list first
+pub fn sort_artists_for_update<'a>(
+    counts: &'a HashMap<String, usize>,
+    pinned: &[String],
+) -> Vec<(&'a String, usize, bool)> {
+    let pin_rank = |artist: &str| {
+        pinned
+            .iter()
+            .position(|p| p.trim().eq_ignore_ascii_case(artist))
+    };
+
+    let mut sorted: Vec<(&String, usize, Option<usize>)> = counts
+        .iter()
+        .map(|(artist, count)| (artist, *count, pin_rank(artist)))
+        .collect();
+    sorted.sort_by(|a, b| match (a.2, b.2) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)),
+    });
+
+    sorted
+        .into_iter()
+        .map(|(artist, count, rank)| (artist, count, rank.is_some()))
+        .collect()
+}
+
+/// split off the part of a summary list to print, per `ui.summary_list_limit`
+///
+/// returns the entries to show and how many were left out. a limit of 0 shows the whole list
+///
+/// # Arguments
+///
+/// * `items` - the whole list
+pub fn summary_slice<T>(items: &[T]) -> (&[T], usize) {
+    summary_slice_with(items, getopt!(ui.summary_list_limit))
+}
+
+/// split off the first `limit` entries of a summary list (all of them if `limit` is 0)
+///
+/// # Arguments
+///
+/// * `items` - the whole list
+/// * `limit` - the most entries to show
+fn summary_slice_with<T>(items: &[T], limit: usize) -> (&[T], usize) {
+    let shown = if limit == 0 {
+        items.len()
+    } else {
+        limit.min(items.len())
+    };
+
+    (&items[..shown], items.len() - shown)
+}
+
+/// print the `... and N more` line under a summary list cut short by [`summary_slice`]
+///
+/// # Arguments
+///
+/// * `hidden` - how many entries were left out
+pub fn print_hidden_count(hidden: usize) {
+    if hidden > 0 {
+        println!("  ... and {} more", hidden);
+    }
+}
+
 /// the ui for e62rs
 #[derive(Clone)]
 pub struct E6Ui {
@@ -65,12 +160,14 @@ pub struct E6Ui {
     pub client: Arc<E6Client>,
     /// the post downloader
     pub downloader: Arc<PostDownloader>,
-    /// the tags db
-    pub tag_db: Arc<TagDb>,
-    /// the pools db
-    pub pool_db: Arc<PoolDb>,
+    /// the tags db, see [`E6Ui::tag_db`]
+    tag_db: Arc<RwLock<Arc<TagDb>>>,
+    /// the pools db, see [`E6Ui::pool_db`]
+    pool_db: Arc<RwLock<Arc<PoolDb>>>,
     /// search history
     pub history: Arc<Mutex<SearchHistory>>,
+    /// the ctrl+c handler, used to cancel long-running fetches
+    pub interrupt: InterruptHandler,
 }
 
 impl E6Ui {
@@ -81,7 +178,13 @@ impl E6Ui {
     /// * `client` - an e621 api client (see [`E6Client`])
     /// * `tag_db` - a loaded tag database
     /// * `pool_db` - a loaded pool database
-    pub fn new(client: Arc<E6Client>, tag_db: Arc<TagDb>, pool_db: Arc<PoolDb>) -> Self {
+    /// * `interrupt` - the ctrl+c handler
+    pub fn new(
+        client: Arc<E6Client>,
+        tag_db: Arc<TagDb>,
+        pool_db: Arc<PoolDb>,
+        interrupt: InterruptHandler,
+    ) -> Self {
         let downloader = Arc::new(PostDownloader::with_download_dir_and_format(
             getopt!(download.path),
             Some(getopt!(download.format)),
@@ -97,12 +200,53 @@ impl E6Ui {
         Self {
             client,
             downloader,
-            tag_db,
-            pool_db,
+            tag_db: Arc::new(RwLock::new(tag_db)),
+            pool_db: Arc::new(RwLock::new(pool_db)),
             history,
+            interrupt,
         }
     }
 
+    /// get the tags db
+    ///
+    /// it can be swapped out while the ui is running (see [`E6Ui::set_tag_db`]), so hold on to
+    /// the returned db rather than calling this for every lookup
+    pub fn tag_db(&self) -> Arc<TagDb> {
+        self.tag_db
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// get the pools db
+    ///
+    /// it can be swapped out while the ui is running (see [`E6Ui::set_pool_db`]), so hold on to
+    /// the returned db rather than calling this for every lookup
+    pub fn pool_db(&self) -> Arc<PoolDb> {
+        self.pool_db
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// replace the tags db, like after it's been updated in the background
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_db` - the new tags db
+    pub fn set_tag_db(&self, tag_db: Arc<TagDb>) {
+        *self.tag_db.write().unwrap_or_else(PoisonError::into_inner) = tag_db;
+    }
+
+    /// replace the pools db, like after it's been updated in the background
+    ///
+    /// # Arguments
+    ///
+    /// * `pool_db` - the new pools db
+    pub fn set_pool_db(&self, pool_db: Arc<PoolDb>) {
+        *self.pool_db.write().unwrap_or_else(PoisonError::into_inner) = pool_db;
+    }
+
     /// Create a BBF (Bound Book Format) file from a pool
     ///
     /// # Arguments
@@ -222,6 +366,11 @@ impl E6Ui {
                 "~cat".yellow(),
                 "~dog".yellow()
             );
+            println!(
+                "  {} Add {} to skip the always included/excluded tags",
+                "•".bright_blue(),
+                RAW_SEARCH_TAG.magenta().bold()
+            );
             println!(
                 "  {} Press {} to autocomplete",
                 "•".bright_blue(),
@@ -234,24 +383,25 @@ impl E6Ui {
             );
         }
 
-        if getopt!(search.search_history) {
-            if let Ok(history) = self.history.lock() {
-                let recent = history.suggestions("");
-                if !recent.is_empty() {
-                    println!("{}", "Recent searches:".bright_black());
-                    for (i, entry) in recent.iter().take(5).enumerate() {
-                        println!(
-                            "  {} {}",
-                            format!("{}.", i + 1).bright_black(),
-                            entry.bright_black()
-                        );
-                    }
-                    println!();
+        if getopt!(search.search_history)
+            && let Ok(history) = self.history.lock()
+        {
+            let recent = history.suggestions("");
+            if !recent.is_empty() {
+                println!("{}", "Recent searches:".bright_black());
+                for (i, entry) in recent.iter().take(5).enumerate() {
+                    println!(
+                        "  {} {}",
+                        format!("{}.", i + 1).bright_black(),
+                        entry.bright_black()
+                    );
                 }
+                println!();
             }
         }
 
-        let autocomplete = TagAutocompleter::new(self.tag_db.clone());
+        let tag_db = self.tag_db();
+        let autocomplete = TagAutocompleter::new(tag_db.clone());
         let tags_input = miette::Context::context(
             TextInput::new("Enter tags:")
                 .with_help_message("Space-separated tags. Use - to exclude, ~ for OR.")
@@ -264,6 +414,7 @@ impl E6Ui {
         let mut includes = Vec::new();
         let mut excludes = Vec::new();
         let mut wildcards = Vec::new();
+        let strict = getopt!(completion.strict_tags);
 
         for tag in tags_input.split_whitespace() {
             let tag = tag.trim();
@@ -273,18 +424,18 @@ impl E6Ui {
             }
 
             if let Some(stripped) = tag.strip_prefix('-') {
-                let canonical = self.tag_db.get_canon_name(stripped);
+                let canonical = tag_db.canonicalize(stripped, strict);
                 if !excludes.contains(&canonical) {
                     excludes.push(canonical);
                 }
             } else if let Some(stripped) = tag.strip_prefix('~') {
-                let canonical = self.tag_db.get_canon_name(stripped);
+                let canonical = tag_db.canonicalize(stripped, strict);
                 if !wildcards.contains(&canonical) {
                     wildcards.push(canonical);
                 }
             } else {
                 let stripped = tag.strip_prefix('+').unwrap_or(tag);
-                let canonical = self.tag_db.get_canon_name(stripped);
+                let canonical = tag_db.canonicalize(stripped, strict);
                 if !includes.contains(&canonical) {
                     includes.push(canonical);
                 }
@@ -327,16 +478,12 @@ impl E6Ui {
     ///
     /// * `posts` - the posts to select from
     pub fn select_multiple_posts<'a>(&self, posts: &'a [E6Post]) -> Result<Vec<&'a E6Post>> {
-        let options: Vec<AskOption<&E6Post>> = posts
+        let template = post_list_template();
+        let options: Vec<AskOption<usize>> = posts
             .iter()
-            .map(|post| {
-                AskOption::with_name(
-                    format!(
-                        "ID: {} | Score: {} | Rating: {}",
-                        post.id, post.score.total, post.rating
-                    ),
-                    post,
-                )
+            .enumerate()
+            .map(|(idx, post)| {
+                AskOption::with_name(format_colored_post_list_entry(&template, post), idx)
             })
             .collect();
 
@@ -350,7 +497,10 @@ impl E6Ui {
             "Failed to get post selections",
         )?;
 
-        Ok(selections.clone().iter().map(|p| p.value).collect())
+        Ok(selections
+            .iter()
+            .filter_map(|selected| posts.get(selected.value))
+            .collect())
     }
 
     /// shows the interaction menu for a post
@@ -359,6 +509,8 @@ impl E6Ui {
     ///
     /// * `post` - the post to interact with
     pub async fn interaction_menu(&self, post: E6Post) -> Result<InteractionMenu> {
+        RecentlyViewed::record(&post);
+
         let choice = miette::Context::context(
             InteractionMenu::select("What would you like to do?").ask(),
             "Failed to get interaction choice",
@@ -378,10 +530,19 @@ impl E6Ui {
                 return Ok(InteractionMenu::Back);
             }
             InteractionMenu::View => {
-                print_post_to_terminal(post)
+                print_post_to_terminal(&self.client, post)
                     .await
                     .context("Failed to view image")?;
             }
+            InteractionMenu::EditBlacklist => {
+                self.edit_blacklist_from_post(&post)?;
+            }
+            InteractionMenu::Family => {
+                self.show_post_tree(&post).await?;
+            }
+            InteractionMenu::Flag => {
+                self.flag_post(&post).await?;
+            }
 
             InteractionMenu::MakeQr => {
                 let url = format!("https://e621.net/posts/{}", post.id);
@@ -399,21 +560,182 @@ impl E6Ui {
         Ok(choice.value)
     }
 
+    /// flag a post for the moderators, asking why
+    ///
+    /// only works when logged in. flagging as inferior also asks for the better post. the
+    /// api's answer is printed, including why it rejected the flag
+    ///
+    /// # Arguments
+    ///
+    /// * `post` - the post to flag
+    pub async fn flag_post(&self, post: &E6Post) -> Result<()> {
+        if !getopt!(login.login) {
+            println!("Flagging posts needs you to be logged in (set login.login in your config).");
+            return Ok(());
+        }
+
+        let reason = miette::Context::context(
+            Select::new(format!("Why are you flagging post {}?", post.id))
+                .with_options(
+                    FlagReason::ALL
+                        .iter()
+                        .map(|reason| AskOption::with_name(reason.describe().to_string(), *reason))
+                        .collect(),
+                )
+                .with_help_message("Use arrow keys to navigate, Enter to select, Esc to cancel")
+                .ask(),
+            "Failed to get flag reason",
+        )?
+        .value;
+
+        let reason = match reason {
+            FlagReason::Inferior { .. } => {
+                let parent_id: i64 = miette::Context::context(
+                    Number::new("ID of the better version of this post:").ask(),
+                    "Failed to get parent post id",
+                )?;
+
+                if parent_id <= 0 || parent_id == post.id {
+                    println!("The better version has to be a different post.");
+                    return Ok(());
+                }
+
+                FlagReason::Inferior { parent_id }
+            }
+            reason => reason,
+        };
+
+        let confirm = miette::Context::context(
+            Confirm::new(format!(
+                "Flag post {} ({})?",
+                post.id,
+                reason.describe().to_lowercase()
+            ))
+            .ask(),
+            "Failed to get user confirmation",
+        )?;
+
+        if !confirm {
+            println!("Not flagged.");
+            return Ok(());
+        }
+
+        match self.client.flag_post(post.id, reason).await {
+            Ok(flag) => println!("Flagged post {} (flag #{}).", post.id, flag.id),
+            Err(e) => println!("{}", e),
+        }
+
+        Ok(())
+    }
+
+    /// offer to resume downloads that didn't finish in a previous run
+    ///
+    /// declining clears the queue
+    pub async fn resume_download_queue(&self) -> Result<()> {
+        let queued = queued_downloads();
+        if queued.is_empty() {
+            return Ok(());
+        }
+
+        let resume = miette::Context::context(
+            Confirm::new(format!(
+                "{} downloads didn't finish last time. Resume them?",
+                queued.len()
+            ))
+            .ask(),
+            "Failed to get resume confirmation",
+        )?;
+
+        if !resume {
+            clear_queued_downloads();
+            return Ok(());
+        }
+
+        let ids: Vec<i64> = queued.iter().map(|q| q.id).collect();
+        let mut posts: HashMap<i64, E6Post> = self
+            .client
+            .get_posts_by_ids_with(&ids, getopt!(search.fetch_threads), &[])
+            .await?
+            .into_iter()
+            .map(|res| (res.post.id, res.post))
+            .collect();
+
+        let mut dropped = Vec::new();
+        let jobs: Vec<(E6Post, PathBuf)> = queued
+            .into_iter()
+            .filter_map(|q| match posts.remove(&q.id) {
+                Some(post) => Some((post, q.path)),
+                None => {
+                    warn!("couldn't fetch queued post {}, dropping it", q.id);
+                    dropped.push(q.id);
+                    None
+                }
+            })
+            .collect();
+        dequeue_downloads(&dropped);
+
+        if jobs.is_empty() {
+            return Ok(());
+        }
+
+        println!("Resuming {} downloads...", jobs.len());
+        self.downloader.clone().download_queued(jobs).await?;
+        Ok(())
+    }
+
     /// opens the current configuration in the default editor
+    ///
+    /// uses `$VISUAL`, then `$EDITOR`, then `nano`. if the edited config doesn't parse or
+    /// validate, the error is shown and the editor is re-opened with the edits intact. the config
+    /// file is only written once the edited text is valid
     pub async fn edit_config_file(&self) -> Result<()> {
-        let curr_cfg = toml::to_string_pretty(&E62Rs::load()?)?;
+        let original = E62Rs::load()?;
+        let mut cfg_text = toml::to_string_pretty(&original.redacted(false))?;
 
         println!("Opening config file in your default editor...");
+        println!("(your api key is shown as {REDACTED}, leave it as-is to keep it)");
+
+        let temp_file = crate::utils::write_private_temp("e62rs_config", "toml", &cfg_text)?;
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|e| !e.trim().is_empty())
+            .unwrap_or_else(|| "nano".to_string());
+        let mut editor_args = editor.split_whitespace();
+        let editor_cmd = editor_args.next().unwrap_or("nano");
+        let editor_args: Vec<&str> = editor_args.collect();
+
+        let mut new_cfg = loop {
+            std::process::Command::new(editor_cmd)
+                .args(&editor_args)
+                .arg(&temp_file)
+                .status()?;
+
+            cfg_text = fs::read_to_string(&temp_file).await?;
+
+            let error = match toml::from_str::<E62Rs>(&cfg_text) {
+                Ok(new_cfg) => match new_cfg.validate() {
+                    Ok(()) => break new_cfg,
+                    Err(errors) => format_validation_errors(&errors),
+                },
+                Err(e) => e.to_string(),
+            };
+
+            eprintln!("{}\n{}", "Error validating new config:".red(), error);
+
+            let retry = miette::Context::context(
+                Confirm::new("Re-open the editor to fix it?").ask(),
+                "Failed to get retry confirmation",
+            )?;
+
+            if !retry {
+                println!("Discarded config changes.");
+                let _ = fs::remove_file(&temp_file).await;
+                return Ok(());
+            }
+        };
 
-        let temp_file = std::env::temp_dir().join("e62rs_config.toml");
-        fs::write(&temp_file, &curr_cfg).await?;
-
-        let editor = std::env::var("EDITOR").unwrap_or_else(|_| "nano".to_string());
-        std::process::Command::new(editor)
-            .arg(&temp_file)
-            .status()?;
-
-        let new_cfg_text = fs::read_to_string(&temp_file).await?;
+        new_cfg.restore_secrets(&original);
 
         let config_path = match (
             E62Rs::global_config_path()?.exists(),
@@ -423,13 +745,8 @@ impl E6Ui {
             (true, false) => &E62Rs::global_config_path()?.display().to_string(),
         };
 
-        if let Ok(new_cfg) = toml::from_str::<E62Rs>(new_cfg_text.as_str()) {
-            fs::write(config_path, toml::to_string_pretty(&new_cfg)?).await?;
-            fs::remove_file(&temp_file).await?;
-        } else {
-            eprintln!("Error validating new config text");
-            std::process::exit(1);
-        }
+        fs::write(config_path, toml::to_string_pretty(&new_cfg)?).await?;
+        fs::remove_file(&temp_file).await?;
 
         crate::config::instance::reload_config()?;
 
@@ -468,13 +785,87 @@ impl E6Ui {
             }
             BatchAction::ViewAll => {
                 let posts_clone = posts.clone();
-                print_posts_to_terminal(posts_clone).await?;
+                print_posts_to_terminal(&self.client, posts_clone).await?;
+            }
+            BatchAction::ExportTags => {
+                self.export_tag_list(&posts)?;
             }
         }
 
         Ok(choice.value)
     }
 
+    /// export the tag lists of some posts to an import file
+    ///
+    /// asks which tag categories to include and where to save, then writes one
+    /// `md5 tag1 tag2 ...` line per post
+    ///
+    /// # Arguments
+    ///
+    /// * `posts` - the posts to export the tags of
+    pub fn export_tag_list(&self, posts: &[E6Post]) -> Result<()> {
+        let categories = self.select_tag_categories()?;
+        if categories.is_empty() {
+            println!("No tag categories selected.");
+            return Ok(());
+        }
+
+        let path = miette::Context::context(
+            TextInput::new("Save tag list to:")
+                .with_default("tags.txt")
+                .ask(),
+            "Failed to get export path",
+        )?;
+
+        let mut writer = FileWriter::text(&path)?;
+        let mut written = 0;
+
+        for post in posts {
+            if post.file.md5.is_empty() {
+                warn!(id = post.id, "post has no md5, skipping tag export");
+                continue;
+            }
+
+            writer.write_text(format!("{}\n", post.tag_line(&categories)))?;
+            written += 1;
+        }
+
+        writer.flush()?;
+        println!(
+            "{} Exported tags for {} post{} to {}",
+            "✓".green(),
+            written,
+            if written == 1 { "" } else { "s" },
+            path
+        );
+
+        Ok(())
+    }
+
+    /// ask which tag categories to include, with every category but invalid selected by default
+    pub fn select_tag_categories(&self) -> Result<Vec<TagCategory>> {
+        let options: Vec<AskOption<TagCategory>> = TagCategory::ALL
+            .iter()
+            .map(|category| AskOption::with_name(category.to_string(), *category))
+            .collect();
+        let defaults: Vec<usize> = TagCategory::ALL
+            .iter()
+            .enumerate()
+            .filter(|(_, category)| **category != TagCategory::Invalid)
+            .map(|(i, _)| i)
+            .collect();
+
+        let selected = miette::Context::context(
+            MultiSelect::new("Select tag categories to include:")
+                .with_options(options)
+                .with_default_selections(&defaults)
+                .ask(),
+            "Failed to get tag category selection",
+        )?;
+
+        Ok(selected.iter().map(|o| o.value).collect())
+    }
+
     /// display a menu for interacting with posts
     ///
     /// # Arguments
@@ -488,18 +879,18 @@ impl E6Ui {
 
         match choice.value {
             PoolInteractionMenu::ViewPosts => {
-                let posts = self.client.get_pool_posts(pool.id).await?;
-                if posts.posts.is_empty() {
+                let posts = self.fetch_all_pool_posts(&pool).await?;
+                if posts.is_empty() {
                     println!("No posts found in this pool.");
                 } else {
-                    self.display_posts(&posts.posts);
+                    self.display_posts(&posts);
 
                     let interact =
                         Confirm::new("Would you like to interact with these posts?").ask()?;
 
                     if interact {
-                        let _selected_posts = self.select_multiple_posts(&posts.posts)?;
-                        let selected_posts = self.select_multiple_posts(&posts.posts)?;
+                        let _selected_posts = self.select_multiple_posts(&posts)?;
+                        let selected_posts = self.select_multiple_posts(&posts)?;
                         if !selected_posts.is_empty() {
                             let mut fetched_posts = Vec::new();
                             for post in &selected_posts {
@@ -512,16 +903,16 @@ impl E6Ui {
                 }
             }
             PoolInteractionMenu::Download => {
-                let posts = self.client.get_pool_posts(pool.id).await?;
-                if posts.posts.is_empty() {
+                let posts = self.fetch_all_pool_posts(&pool).await?;
+                if posts.is_empty() {
                     println!("No posts found in this pool.");
                 } else {
                     println!(
                         "Downloading {} posts from pool '{}'...",
-                        posts.posts.len(),
+                        posts.len(),
                         pool.name
                     );
-                    self.downloader.clone().download_posts(posts.posts).await?;
+                    self.downloader.clone().download_posts(posts).await?;
                 }
             }
             PoolInteractionMenu::CreateBBF => {
@@ -530,6 +921,9 @@ impl E6Ui {
             PoolInteractionMenu::DownloadToPoolsFolder => {
                 self.download_pool_to_pools_folder(&pool).await?;
             }
+            PoolInteractionMenu::DownloadNew => {
+                self.update_pool_in_pools_folder(&pool).await?;
+            }
             PoolInteractionMenu::OpenInBrowser => {
                 let url = format!("https://e621.net/pools/{}", pool.id);
                 open::that(&url).context("Failed to open pool in browser")?;
@@ -605,6 +999,124 @@ impl E6Ui {
         Ok(())
     }
 
+    /// download only the pages of a pool that aren't in the pools folder yet
+    ///
+    /// pages are matched to files by their stored metadata, or by md5 for files without any.
+    /// new pages are numbered by their place in the whole pool
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - the pool to update
+    pub async fn update_pool_in_pools_folder(&self, pool: &E6Pool) -> Result<()> {
+        if pool.post_ids.is_empty() {
+            println!("This pool has no posts to download.");
+            return Ok(());
+        }
+
+        let downloader = Arc::new(PostDownloader::for_pool(
+            getopt!(download.pools_path),
+            &pool.name,
+        ));
+        let pool_dir = downloader.download_dir.clone().unwrap_or_default();
+
+        let posts = self.fetch_pool_posts(pool).await?;
+        if posts.is_empty() {
+            println!("Failed to fetch any posts from this pool.");
+            return Ok(());
+        }
+
+        let downloaded =
+            downloaded_pool_posts(&pool_dir, &posts, getopt!(download.metadata_backend));
+        if downloaded.len() == posts.len() {
+            println!(
+                "✓ Pool '{}' is up to date ({} pages in {})",
+                pool.name,
+                posts.len(),
+                pool_dir.display()
+            );
+            return Ok(());
+        }
+
+        println!(
+            "{} of {} pages already downloaded, getting the other {}...",
+            downloaded.len(),
+            posts.len(),
+            posts.len() - downloaded.len()
+        );
+
+        let added = downloader
+            .download_missing_pool_posts(posts, &downloaded)
+            .await?;
+
+        println!(
+            "✓ Added {} new pages to '{}' in {}",
+            added,
+            pool.name,
+            pool_dir.display()
+        );
+
+        Ok(())
+    }
+
+    /// fetch every post in a pool page by page, showing progress
+    ///
+    /// when `ui.ctrlc_handler` is on, pressing ctrl+c stops the fetch after the current page and
+    /// returns whatever loaded so far. posts are returned in pool order
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - the pool to fetch posts from
+    pub async fn fetch_all_pool_posts(&self, pool: &E6Pool) -> Result<Vec<E6Post>> {
+        let total = pool.post_ids.len().max(pool.post_count as usize);
+        let pb = ProgressBar::new(total as u64);
+        pb.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.bright_cyan} [{elapsed_precise}] [{bar:40.bright_cyan/blue}] \
+                 {pos}/{len} {msg}",
+            )
+            .context("Failed to create progress bar template")?
+            .progress_chars("━╸─"),
+        );
+        pb.set_message(if getopt!(ui.ctrlc_handler) {
+            "Fetching pool posts (Ctrl+C to stop)..."
+        } else {
+            "Fetching pool posts..."
+        });
+        pb.enable_steady_tick(Duration::from_millis(100));
+
+        let (mut posts, cancelled) = self
+            .client
+            .get_all_pool_posts(
+                pool.id,
+                |loaded| pb.set_position(loaded as u64),
+                || self.interrupt.check_and_reset(),
+            )
+            .await
+            .inspect_err(|_| pb.abandon())?;
+
+        let order: HashMap<i64, usize> = pool
+            .post_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, &id)| (id, idx))
+            .collect();
+        posts.sort_by_key(|p| order.get(&p.id).copied().unwrap_or(usize::MAX));
+
+        if cancelled {
+            pb.abandon_with_message("Cancelled");
+            println!(
+                "Stopped early: loaded {} of {} posts from pool '{}'",
+                posts.len(),
+                total,
+                pool.name
+            );
+        } else {
+            pb.finish_with_message(format!("✓ Fetched {} pool posts", posts.len()));
+        }
+
+        Ok(posts)
+    }
+
     /// fetch all posts from a pool
     ///
     /// # Arguments
@@ -713,20 +1225,37 @@ impl E6Ui {
         let downloads_dir = getopt!(download.path);
         let enable_metadata = getopt!(gallery.metadata_filtering);
         let cache_metadata = getopt!(gallery.cache_metadata);
+        let bind_host: IpAddr = getopt!(gallery.bind_host)
+            .parse()
+            .context("gallery.bind-host must be an IP address")?;
+        let bind_address = SocketAddr::new(bind_host, getopt!(gallery.port));
 
         let srv_cfg = ServerConfig::builder()
             .media_directory(PathBuf::from_str(&downloads_dir)?)
-            .bind_address(format!("127.0.0.1:{}", getopt!(gallery.port)).parse()?)
+            .bind_address(bind_address)
             .max_file_size(100 * 1024 * 1024)
             .enable_metadata_filtering(enable_metadata)
             .cache_metadata(cache_metadata)
+            .num_threads(getopt!(gallery.load_threads))
+            .watch(getopt!(gallery.watch))
+            .metadata_backend(getopt!(download.metadata_backend))
+            .default_rating(&getopt!(gallery.default_rating))
+            .auth(ServerAuth::new(
+                &getopt!(gallery.username),
+                &getopt!(gallery.password),
+                &getopt!(gallery.token),
+            ))
             .build()
             .expect("Failed to build server config");
 
         let srv = MediaServer::new(srv_cfg);
 
         if getopt!(gallery.auto_open) {
-            let url = format!("http://localhost:{}", getopt!(gallery.port));
+            let url = if bind_host.is_loopback() || bind_host.is_unspecified() {
+                format!("http://localhost:{}", bind_address.port())
+            } else {
+                format!("http://{}", bind_address)
+            };
             let _ = open::that(&url);
             println!("Opening browser at {}", url);
         }
@@ -765,32 +1294,17 @@ impl E6Ui {
         false
     }
 
-    /// update downloads
-    pub async fn redownload_by_artists(&self) -> Result<()> {
-        println!("\n=== Update Downloads by Artists ===\n");
-        println!(
-            "This will scan your downloads, find all artists, and download NEW posts from them."
-        );
-
-        let download_path = getopt!(download.path);
-        let download_dir = std::path::Path::new(&download_path);
-
-        if !download_dir.exists() {
-            bail!(
-                "Download directory does not exist: {}",
-                download_dir.display()
-            );
-        }
-
-        println!("Scanning downloaded posts for artist names and post IDs...\n");
-
-        let local_posts = self.scan_downloads_directory(download_dir).await?;
-
-        if local_posts.is_empty() {
-            println!("No posts with metadata found in {}", download_dir.display());
-            return Ok(());
-        }
-
+    /// count the downloaded posts of every artist, and collect the ids of all downloaded posts
+    ///
+    /// tags that end up in the artist category without naming an artist (like
+    /// `conditional_dnp` or `unknown_artist`) aren't counted
+    ///
+    /// # Arguments
+    ///
+    /// * `local_posts` - the downloaded posts
+    pub fn tally_downloaded_artists(
+        local_posts: &[LocalPost],
+    ) -> (HashMap<String, usize>, HashSet<i64>) {
         let mut artist_post_counts: HashMap<String, usize> = HashMap::new();
         let mut downloaded_post_ids: HashSet<i64> = HashSet::with_capacity(local_posts.len());
         let special_tags: HashSet<&str> = HashSet::from([
@@ -808,7 +1322,7 @@ impl E6Ui {
             "anonymous-artist",
         ]);
 
-        for local_post in &local_posts {
+        for local_post in local_posts {
             downloaded_post_ids.insert(local_post.post.id);
 
             for artist in &local_post.post.tags.artist {
@@ -820,6 +1334,112 @@ impl E6Ui {
             }
         }
 
+        (artist_post_counts, downloaded_post_ids)
+    }
+
+    /// look for new posts from several artists at once, `search.fetch_threads` (at most 4) at a
+    /// time
+    ///
+    /// every finished artist ticks `total_pb`, whose message keeps a running count of the new
+    /// posts found. if a progress manager is given, each artist also gets its own spinner
+    #[bearive::argdoc]
+    #[error = "the scan tasks couldn't be started"]
+    #[returns = "each artist's new posts and already downloaded and blacklisted counts"]
+    async fn scan_artists(
+        &self,
+        /// the artists to check
+        artists: Vec<String>,
+        /// the max number of new posts to find per artist
+        limit: Option<u64>,
+        /// the ids of every downloaded post
+        downloaded_post_ids: HashSet<i64>,
+        /// the current loaded blacklist
+        blacklist: HashSet<String>,
+        /// the bar counting finished artists
+        total_pb: &ProgressBar,
+        /// where to add per-artist spinners
+        spinners: Option<&Arc<ProgressManager>>,
+    ) -> Result<Vec<Result<(String, ArtistScan), tokio::task::JoinError>>> {
+        let concurrent_artists = getopt!(search.fetch_threads).clamp(1, 4);
+        let semaphore = Arc::new(Semaphore::new(concurrent_artists));
+        let downloaded_ids = Arc::new(downloaded_post_ids);
+        let blacklist = Arc::new(blacklist);
+        let base_message = total_pb.message();
+        let found_so_far = Arc::new(AtomicU64::new(0));
+        let mut handles = Vec::with_capacity(artists.len());
+
+        for artist in artists {
+            let permit = semaphore.clone().acquire_owned().await?;
+            let client = self.client.clone();
+            let downloaded_ids = downloaded_ids.clone();
+            let blacklist = blacklist.clone();
+            let total_pb = total_pb.clone();
+            let spinners = spinners.cloned();
+            let base_message = base_message.clone();
+            let found_so_far = found_so_far.clone();
+
+            handles.push(tokio::spawn(async move {
+                let artist_pb = spinners
+                    .map(|manager| manager.create_spinner(&format!("{}: starting", artist)));
+                let result = Self::find_new_artist_posts(
+                    &client,
+                    &artist,
+                    limit,
+                    &downloaded_ids,
+                    &blacklist,
+                    artist_pb.as_ref(),
+                )
+                .await;
+
+                if let Some(artist_pb) = artist_pb {
+                    artist_pb.finish_and_clear();
+                }
+
+                if let Ok((posts, _, _)) = &result {
+                    let new = posts.len() as u64;
+                    let found = found_so_far.fetch_add(new, Ordering::Relaxed) + new;
+                    total_pb.set_message(format!("{} ({} new posts so far)", base_message, found));
+                }
+
+                total_pb.inc(1);
+                drop(permit);
+
+                (artist, result.map_err(|e| e.to_string()))
+            }));
+        }
+
+        Ok(futures_util::future::join_all(handles).await)
+    }
+
+    /// update downloads
+    pub async fn redownload_by_artists(&self) -> Result<()> {
+        println!("\n=== Update Downloads by Artists ===\n");
+        println!(
+            "This will scan your downloads, find all artists, and download NEW posts from them."
+        );
+
+        let download_path = getopt!(download.path);
+        let download_dir = std::path::Path::new(&download_path);
+
+        if !download_dir.exists() {
+            bail!(
+                "Download directory does not exist: {}",
+                download_dir.display()
+            );
+        }
+
+        println!("Scanning downloaded posts for artist names and post IDs...\n");
+
+        let local_posts = self.scan_downloads_directory(download_dir).await?;
+
+        if local_posts.is_empty() {
+            println!("No posts with metadata found in {}", download_dir.display());
+            return Ok(());
+        }
+
+        let (artist_post_counts, downloaded_post_ids) =
+            Self::tally_downloaded_artists(&local_posts);
+
         if artist_post_counts.is_empty() {
             println!("No artist tags found in downloaded posts.");
             return Ok(());
@@ -832,18 +1452,19 @@ impl E6Ui {
             artist_post_counts.len()
         );
 
-        let mut sorted_artists: Vec<_> = artist_post_counts.iter().collect();
-        sorted_artists.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let sorted_artists =
+            sort_artists_for_update(&artist_post_counts, &getopt!(download.pinned_artists));
 
         let artist_options: Vec<AskOption<String>> = sorted_artists
             .into_iter()
-            .map(|(artist, count)| {
+            .map(|(artist, count, pinned)| {
                 AskOption::with_name(
                     format!(
-                        "{} ({} downloaded post{})",
+                        "{}{} ({} downloaded post{})",
+                        if pinned { "[pinned] " } else { "" },
                         artist,
                         count,
-                        if *count == 1 { "" } else { "s" }
+                        if count == 1 { "" } else { "s" }
                     ),
                     artist.clone(),
                 )
@@ -873,19 +1494,16 @@ impl E6Ui {
             if selected_artists.len() == 1 { "" } else { "s" }
         );
 
-        for (i, artist) in selected_artists.iter().map(|o| &o.value).enumerate() {
+        let (shown, hidden) = summary_slice(&selected_artists);
+        for artist in shown.iter().map(|o| &o.value) {
             let count = artist_post_counts.get(artist).unwrap_or(&0);
-            if i < 20 {
-                println!(
-                    "  • {} ({} already downloaded)",
-                    artist.bright_white(),
-                    count,
-                );
-            } else if i == 20 {
-                println!("  ... and {} more", selected_artists.len() - 20);
-                break;
-            }
+            println!(
+                "  • {} ({} already downloaded)",
+                artist.bright_white(),
+                count,
+            );
         }
+        print_hidden_count(hidden);
 
         let blacklist: HashSet<String> = get_blacklist()
             .unwrap_or_default()
@@ -929,57 +1547,29 @@ impl E6Ui {
 
         println!("\n{} Checking for new posts...\n", "→".bright_cyan());
 
-        let concurrent_artists = getopt!(search.fetch_threads).clamp(1, 4);
-        let semaphore = Arc::new(Semaphore::new(concurrent_artists));
-        let client = self.client.clone();
-        let downloader = self.downloader.clone();
-        let downloaded_ids = Arc::new(downloaded_post_ids);
-        let blacklist = Arc::new(blacklist);
         let progress_manager = Arc::new(ProgressManager::new());
         let total_pb = progress_manager
             .create_count_bar(
                 "artists",
                 selected_artists.len() as u64,
                 "Processing artists",
+                true,
             )
             .await
             .map_err(Report::new)?;
 
         type ArtistResult = Result<(u64, u64, u64), String>;
-        let mut handles = Vec::with_capacity(selected_artists.len());
-
-        for artist in selected_artists {
-            let permit = semaphore.clone().acquire_owned().await?;
-            let client = client.clone();
-            let downloader = downloader.clone();
-            let downloaded_ids = downloaded_ids.clone();
-            let blacklist = blacklist.clone();
-            let total_pb = total_pb.clone();
-
-            let handle = tokio::spawn(async move {
-                let result = Self::download_new_artist_posts(
-                    &client,
-                    &downloader,
-                    &artist.value,
-                    limit,
-                    &downloaded_ids,
-                    &blacklist,
-                )
-                .await;
-
-                total_pb.inc(1);
-                drop(permit);
-
-                match result {
-                    Ok((new, skipped, blacklisted)) => (artist, Ok((new, skipped, blacklisted))),
-                    Err(e) => (artist, Err(e.to_string())),
-                }
-            });
-
-            handles.push(handle);
-        }
-
-        let results = futures_util::future::join_all(handles).await;
+        let artists = selected_artists.into_iter().map(|o| o.value).collect();
+        let results = self
+            .scan_artists(
+                artists,
+                limit,
+                downloaded_post_ids,
+                blacklist,
+                &total_pb,
+                Some(&progress_manager),
+            )
+            .await?;
         total_pb.finish_with_message("✓ Processing complete");
 
         let mut total_new_posts = 0u64;
@@ -987,22 +1577,24 @@ impl E6Ui {
         let mut total_blacklisted = 0u64;
         let mut total_errors = 0u64;
         let mut artist_results: Vec<(String, ArtistResult)> = Vec::new();
+        let mut new_posts: Vec<E6Post> = Vec::new();
+        let mut seen_ids: HashSet<i64> = HashSet::new();
 
         for result in results {
             match result {
-                Ok((artist, Ok((new_count, skipped_count, blacklisted_count)))) => {
+                Ok((artist, Ok((posts, skipped_count, blacklisted_count)))) => {
+                    let new_count = posts.len() as u64;
+                    new_posts.extend(posts.into_iter().filter(|post| seen_ids.insert(post.id)));
                     total_new_posts += new_count;
                     total_already_downloaded += skipped_count;
                     total_blacklisted += blacklisted_count;
-                    artist_results.push((
-                        artist.value,
-                        Ok((new_count, skipped_count, blacklisted_count)),
-                    ));
+                    artist_results
+                        .push((artist, Ok((new_count, skipped_count, blacklisted_count))));
                 }
                 Ok((artist, Err(error_msg))) => {
                     total_errors += 1;
-                    artist_results.push((artist.value.clone(), Err(error_msg.clone())));
-                    warn!("Failed to check posts from {}: {}", artist.value, error_msg);
+                    warn!("Failed to check posts from {}: {}", artist, error_msg);
+                    artist_results.push((artist, Err(error_msg)));
                 }
                 Err(e) => {
                     total_errors += 1;
@@ -1016,7 +1608,7 @@ impl E6Ui {
         println!("{}", "=".repeat(70));
         println!("Artists checked: {}", artist_results.len());
         println!(
-            "{} NEW posts downloaded: {}",
+            "{} NEW posts found: {}",
             "✓".green().bold(),
             total_new_posts.to_string().bright_green()
         );
@@ -1063,25 +1655,21 @@ impl E6Ui {
             with_new_posts.sort_by(|a, b| b.1.cmp(a.1));
 
             println!("\n{} Artists with new posts:", "✓".green().bold());
-            for (i, (artist, new_count, skipped, blacklisted)) in with_new_posts.iter().enumerate()
-            {
-                if i < 15 {
-                    let mut extra = format!("{} already had", skipped);
-                    if **blacklisted > 0 {
-                        extra.push_str(&format!(", {} filtered", blacklisted));
-                    }
-                    println!(
-                        "  • {}: {} new post{} ({})",
-                        artist.green(),
-                        new_count.to_string().bright_green().bold(),
-                        if **new_count == 1 { "" } else { "s" },
-                        extra
-                    );
-                } else if i == 15 {
-                    println!("  ... and {} more", with_new_posts.len() - 15);
-                    break;
+            let (shown, hidden) = summary_slice(&with_new_posts);
+            for (artist, new_count, skipped, blacklisted) in shown {
+                let mut extra = format!("{} already had", skipped);
+                if **blacklisted > 0 {
+                    extra.push_str(&format!(", {} filtered", blacklisted));
                 }
+                println!(
+                    "  • {}: {} new post{} ({})",
+                    artist.green(),
+                    new_count.to_string().bright_green().bold(),
+                    if **new_count == 1 { "" } else { "s" },
+                    extra
+                );
             }
+            print_hidden_count(hidden);
         }
 
         let no_new_posts: Vec<_> = artist_results
@@ -1101,31 +1689,29 @@ impl E6Ui {
 
         if !no_new_posts.is_empty() {
             println!("\n{} Artists with no new posts:", "→".bright_black());
-            for (i, (artist, skipped)) in no_new_posts.iter().enumerate() {
-                if i < 10 {
-                    println!(
-                        "  • {}: {} already downloaded",
-                        artist.bright_black(),
-                        skipped
-                    );
-                } else if i == 10 {
-                    println!("  ... and {} more", no_new_posts.len() - 10);
-                    break;
-                }
+            let (shown, hidden) = summary_slice(&no_new_posts);
+            for (artist, skipped) in shown {
+                println!(
+                    "  • {}: {} already downloaded",
+                    artist.bright_black(),
+                    skipped
+                );
             }
+            print_hidden_count(hidden);
         }
 
         println!("{}", "=".repeat(70));
 
         if total_new_posts > 0 {
             println!(
-                "\n{} Successfully downloaded {} new post{} from {} artist{}!",
-                "✓".green().bold(),
-                total_new_posts.to_string().bright_green().bold(),
-                if total_new_posts == 1 { "" } else { "s" },
+                "\n{} Downloading {} new post{} from {} artist{}",
+                "→".bright_cyan(),
+                new_posts.len().to_string().bright_green().bold(),
+                if new_posts.len() == 1 { "" } else { "s" },
                 with_new_posts.len(),
                 if with_new_posts.len() == 1 { "" } else { "s" }
             );
+            self.downloader.clone().download_posts(new_posts).await?;
         } else {
             println!(
                 "\n{} All downloads are up to date! No new posts found.",
@@ -1136,8 +1722,133 @@ impl E6Ui {
         Ok(())
     }
 
+    /// show how many new posts every downloaded artist has, without downloading anything
+    pub async fn whats_new(&self) -> Result<()> {
+        println!("\n=== What's New From Your Artists ===\n");
+
+        let download_path = getopt!(download.path);
+        let download_dir = std::path::Path::new(&download_path);
+
+        if !download_dir.exists() {
+            bail!(
+                "Download directory does not exist: {}",
+                download_dir.display()
+            );
+        }
+
+        let local_posts = self.scan_downloads_directory(download_dir).await?;
+        let (artist_post_counts, downloaded_post_ids) =
+            Self::tally_downloaded_artists(&local_posts);
+
+        if artist_post_counts.is_empty() {
+            println!("No artists found in {}", download_dir.display());
+            return Ok(());
+        }
+
+        let blacklist: HashSet<String> = get_blacklist()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| s.to_lowercase())
+            .collect();
+
+        let progress_manager = ProgressManager::new();
+        let total_pb = progress_manager
+            .create_count_bar(
+                "artists",
+                artist_post_counts.len() as u64,
+                "Checking artists",
+                false,
+            )
+            .await
+            .map_err(Report::new)?;
+
+        let artists = artist_post_counts.into_keys().collect();
+        let results = self
+            .scan_artists(
+                artists,
+                None,
+                downloaded_post_ids,
+                blacklist,
+                &total_pb,
+                None,
+            )
+            .await?;
+        total_pb.finish_and_clear();
+
+        let mut tally: Vec<(String, u64)> = Vec::new();
+        let mut failed = 0usize;
+
+        for result in results {
+            match result {
+                Ok((artist, Ok((posts, _, _)))) => tally.push((artist, posts.len() as u64)),
+                Ok((artist, Err(e))) => {
+                    failed += 1;
+                    warn!("Failed to check posts from {}: {}", artist, e);
+                }
+                Err(e) => {
+                    failed += 1;
+                    warn!("Task join error: {}", e);
+                }
+            }
+        }
+
+        tally.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let checked = tally.len();
+        tally.retain(|(_, new)| *new > 0);
+
+        if tally.is_empty() {
+            println!(
+                "{} Nothing new from the {} artist{} you've downloaded",
+                "✓".green().bold(),
+                checked,
+                if checked == 1 { "" } else { "s" }
+            );
+        } else {
+            let (shown, hidden) = summary_slice(&tally);
+            let width = shown.iter().map(|(a, _)| a.len()).max().unwrap_or(0);
+            for (artist, new) in shown {
+                println!(
+                    "  {:<width$}  {} new post{}",
+                    artist.bright_white(),
+                    new.to_string().bright_green().bold(),
+                    if *new == 1 { "" } else { "s" },
+                );
+            }
+            print_hidden_count(hidden);
+
+            let total: u64 = tally.iter().map(|(_, new)| new).sum();
+            println!(
+                "\n{} {} new post{} from {} of {} artist{}",
+                "→".bright_cyan(),
+                total,
+                if total == 1 { "" } else { "s" },
+                tally.len(),
+                checked,
+                if checked == 1 { "" } else { "s" }
+            );
+            println!("Use \"Update downloaded artists\" to download them");
+        }
+
+        if failed > 0 {
+            println!(
+                "{} {} artist{} couldn't be checked",
+                "✗".red().bold(),
+                failed,
+                if failed == 1 { "" } else { "s" }
+            );
+        }
+
+        Ok(())
+    }
+
     /// download new artist posts based on already downloaded posts
+    ///
+    /// returns the number of new, already downloaded, and blacklisted posts. if a progress bar is
+    /// given, its message shows the pages fetched and new posts found so far. the downloader may
+    /// ask before a big batch, so when checking many artists at once, use
+    /// [`E6Ui::find_new_artist_posts`] and download the combined batch instead
     #[bearive::argdoc]
+    #[allow(clippy::too_many_arguments)]
     pub async fn download_new_artist_posts(
         /// an e621 api client
         client: &Arc<E6Client>,
@@ -1151,7 +1862,54 @@ impl E6Ui {
         downloaded_post_ids: &HashSet<i64>,
         /// the current loaded blacklist
         blacklist: &HashSet<String>,
+        /// only count the new posts, without downloading them
+        check_only: bool,
+        /// a bar to show the artist's progress on
+        progress: Option<&ProgressBar>,
     ) -> Result<(u64, u64, u64)> {
+        let (new_posts, skipped_count, blacklisted_count) = Self::find_new_artist_posts(
+            client,
+            artist,
+            limit,
+            downloaded_post_ids,
+            blacklist,
+            progress,
+        )
+        .await?;
+        let new_count = new_posts.len() as u64;
+
+        if !check_only && !new_posts.is_empty() {
+            if let Some(pb) = progress {
+                pb.set_message(format!("{}: downloading {} new posts", artist, new_count));
+            }
+
+            downloader.clone().download_posts(new_posts).await?;
+        }
+
+        Ok((new_count, skipped_count, blacklisted_count))
+    }
+
+    /// find an artist's posts that haven't been downloaded yet
+    ///
+    /// returns the new posts, and the number of already downloaded and blacklisted posts. if a
+    /// progress bar is given, its message shows the pages fetched and new posts found so far.
+    /// nothing is downloaded here, so callers checking many artists at once can ask about the
+    /// combined batch once before downloading it
+    #[bearive::argdoc]
+    pub async fn find_new_artist_posts(
+        /// an e621 api client
+        client: &Arc<E6Client>,
+        /// the artist to check
+        artist: &str,
+        /// the max number of new posts to find from the artist
+        limit: Option<u64>,
+        /// a list of already downloaded ids from the artist
+        downloaded_post_ids: &HashSet<i64>,
+        /// the current loaded blacklist
+        blacklist: &HashSet<String>,
+        /// a bar to show the artist's progress on
+        progress: Option<&ProgressBar>,
+    ) -> Result<(Vec<E6Post>, u64, u64)> {
         let search_tags = vec![format!("~{}", artist), format!("~{}_(artist)", artist)];
         let mut new_posts: Vec<E6Post> = Vec::new();
         let mut skipped_count = 0u64;
@@ -1163,6 +1921,7 @@ impl E6Ui {
         const MAX_CONSECUTIVE_EMPTY: u32 = 2;
 
         let mut consecutive_empty = 0u32;
+        let mut pages = 0u64;
         let rate_limit_delay = Duration::from_millis(250);
         let mut last_request = std::time::Instant::now();
 
@@ -1177,6 +1936,7 @@ impl E6Ui {
                 .await?;
 
             last_request = std::time::Instant::now();
+            pages += 1;
 
             if results.posts.is_empty() {
                 break;
@@ -1213,6 +1973,17 @@ impl E6Ui {
                 before_id = Some(min_id);
             }
 
+            if let Some(pb) = progress {
+                pb.set_message(format!(
+                    "{}: {} page{} fetched, {} new post{} found",
+                    artist,
+                    pages,
+                    if pages == 1 { "" } else { "s" },
+                    new_posts.len(),
+                    if new_posts.len() == 1 { "" } else { "s" }
+                ));
+            }
+
             if !found_new_in_batch {
                 consecutive_empty += 1;
                 if consecutive_empty >= MAX_CONSECUTIVE_EMPTY {
@@ -1231,12 +2002,55 @@ impl E6Ui {
             new_posts.truncate(lim as usize);
         }
 
-        let new_count = new_posts.len() as u64;
+        Ok((new_posts, skipped_count, blacklisted_count))
+    }
+}
 
-        if !new_posts.is_empty() {
-            downloader.clone().download_posts(new_posts).await?;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Ok((new_count, skipped_count, blacklisted_count))
+    #[test]
+    fn test_summary_slice() {
+        let items = [1, 2, 3, 4, 5];
+
+        assert_eq!(summary_slice_with(&items, 3), (&items[..3], 2));
+        assert_eq!(summary_slice_with(&items, 5), (&items[..], 0));
+        assert_eq!(summary_slice_with(&items, 10), (&items[..], 0));
+        assert_eq!(summary_slice_with(&items, 0), (&items[..], 0));
+        assert_eq!(summary_slice_with::<i32>(&[], 3), (&[][..], 0));
+    }
+
+    #[test]
+    fn test_sort_artists_for_update() {
+        let counts: HashMap<String, usize> = [("fox", 2), ("wolf", 9), ("cat", 2), ("bird", 5)]
+            .into_iter()
+            .map(|(artist, count)| (artist.to_string(), count))
+            .collect();
+        let pinned = vec![
+            "Cat".to_string(),
+            " fox ".to_string(),
+            "missing".to_string(),
+        ];
+
+        let sorted: Vec<(&str, usize, bool)> = sort_artists_for_update(&counts, &pinned)
+            .into_iter()
+            .map(|(artist, count, pinned)| (artist.as_str(), count, pinned))
+            .collect();
+        assert_eq!(
+            sorted,
+            vec![
+                ("cat", 2, true),
+                ("fox", 2, true),
+                ("wolf", 9, false),
+                ("bird", 5, false),
+            ]
+        );
+
+        let unpinned: Vec<&str> = sort_artists_for_update(&counts, &[])
+            .into_iter()
+            .map(|(artist, _, _)| artist.as_str())
+            .collect();
+        assert_eq!(unpinned, vec!["wolf", "bird", "cat", "fox"]);
     }
 }
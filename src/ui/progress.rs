@@ -88,25 +88,38 @@ impl ProgressManager {
         Ok(pb)
     }
 
-    /// make a new progress bar for a countdown
+    /// make a new progress bar that counts up to `len`
     ///
     /// # Arguments
     ///
     /// * `key` - a key for the bar to be able to access it later
     /// * `len` - the length of the bar
-    /// * `msg` - a message to show to the right of the bar
+    /// * `message` - a message to show to the right of the bar
+    /// * `eta` - whether to show how long is left
     pub async fn create_count_bar(
         &self,
         key: &str,
         len: u64,
         message: &str,
+        eta: bool,
     ) -> Result<ProgressBar> {
         let detailed = getopt!(ui.progress.detailed);
-        let template = if detailed {
-            "{spinner:.bright_cyan} [{elapsed_precise}] [{wide_bar:.bright_cyan/blue}] {pos}/{len} \
-             ({percent}%) {msg}"
-        } else {
-            "{spinner:.bright_cyan} [{wide_bar:.bright_cyan/blue}] {pos}/{len} {msg}"
+        let template = match (detailed, eta) {
+            (true, true) => {
+                "{spinner:.bright_cyan} [{elapsed_precise}] [{wide_bar:.bright_cyan/blue}] \
+                 {pos}/{len} ({percent}%, ETA {eta}) {msg}"
+            }
+            (true, false) => {
+                "{spinner:.bright_cyan} [{elapsed_precise}] [{wide_bar:.bright_cyan/blue}] \
+                 {pos}/{len} ({percent}%) {msg}"
+            }
+            (false, true) => {
+                "{spinner:.bright_cyan} [{wide_bar:.bright_cyan/blue}] {pos}/{len} (ETA {eta}) \
+                 {msg}"
+            }
+            (false, false) => {
+                "{spinner:.bright_cyan} [{wide_bar:.bright_cyan/blue}] {pos}/{len} {msg}"
+            }
         };
 
         let style = ProgressStyle::with_template(template)?
@@ -139,7 +152,7 @@ impl ProgressManager {
     /// * `len` - the length of the bar
     /// * `msg` - a message to show to the right of the bar
     pub async fn create_bar(&self, key: &str, len: u64, message: &str) -> Result<ProgressBar> {
-        self.create_count_bar(key, len, message).await
+        self.create_count_bar(key, len, message, false).await
     }
 
     /// get one of the bars in the multi
@@ -149,10 +149,12 @@ pub fn create_auth_header(
 }
 
 /// make an auth header based on the loaded config
+///
+/// the api key is resolved with [`crate::config::options::LoginCfg::resolve_api_key`]
 #[cfg(feature = "cli")]
-#[must_use]
 pub fn create_auth_header_from_config() -> Result<HeaderMap> {
-    create_auth_header(&crate::getopt!(login.username), &crate::getopt!(login.api_key))
+    let api_key = crate::getopt!(login).resolve_api_key()?;
+    create_auth_header(&crate::getopt!(login.username), &api_key)
 }
 
 /// shorten a path to a given length
@@ -246,6 +248,67 @@ pub fn check_for_internet_from_config() -> bool {
     check_for_internet(&crate::getopt!(http.api))
 }
 
+/// copy text to the clipboard through the terminal
+///
+/// sends an OSC 52 escape sequence, which most modern terminals (and tmux with
+/// `set-clipboard on`) turn into a clipboard write, including over ssh. terminals without
+/// OSC 52 support silently ignore it
+#[bearive::argdoc]
+#[error = "it fails to write to stdout"]
+pub fn copy_to_clipboard(
+    /// the text to copy
+    text: &str,
+) -> Result<()> {
+    let encoded = general_purpose::STANDARD.encode(text.as_bytes());
+    let mut stdout = std::io::stdout().lock();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded).wrap_err("failed to write to the terminal")?;
+    stdout.flush().wrap_err("failed to flush the terminal")?;
+
+    Ok(())
+}
+
+/// write a new file in the temp dir that only the current user can read
+///
+/// the file is named `<name>_<pid>_<n>.<ext>` with the first `n` that's free. it's always
+/// created from scratch, so a file someone else put at that path is never reused or followed.
+/// on unix the file gets mode `0600`
+#[bearive::argdoc]
+#[error = "no free name is found, or the file can't be made or written to"]
+#[returns = "the path of the new file"]
+pub fn write_private_temp(
+    /// the start of the file name
+    name: &str,
+    /// the file extension
+    ext: &str,
+    /// what to write to it
+    contents: &str,
+) -> Result<PathBuf> {
+    let dir = std::env::temp_dir();
+
+    for n in 0..100 {
+        let path = dir.join(format!("{}_{}_{}.{}", name, std::process::id(), n, ext));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = match options.open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(e).wrap_err_with(|| format!("failed to create '{}'", path.display()));
+            }
+        };
+
+        file.write_all(contents.as_bytes())
+            .wrap_err_with(|| format!("failed to write '{}'", path.display()))?;
+        return Ok(path);
+    }
+
+    color_eyre::eyre::bail!("couldn't find a free temp file name for '{}'", name)
+}
+
 /// write some json data to a given file
 #[bearive::argdoc]
 #[error = "it fails to open `file_path`"]